@@ -0,0 +1,30 @@
+//! Blockers of a real `.fomap`, with item protos from `items.lst`.
+use fo_map_format::{
+    blockers::{BlockerProto, GridBuilder},
+    verbose_read_file, MapParserSettings,
+};
+use hex_geometry::BlockerGrid;
+use nom_prelude::nom_err_to_string;
+use std::{collections::BTreeMap, path::Path};
+
+pub fn load(path: &Path, items_lst: &Path) -> Result<BlockerGrid, String> {
+    let protos: BTreeMap<u16, BlockerProto> = fo_proto_format::build_btree(items_lst.to_owned())
+        .iter()
+        .map(|(&pid, proto)| (pid, proto.into()))
+        .collect();
+    let mut builder = GridBuilder::new(|pid| protos.get(&pid));
+    let grid = verbose_read_file(
+        path,
+        |text, res| nom_err_to_string(text, res).map(|(_rest, map)| builder.build(&map)),
+        MapParserSettings::default(),
+    )
+    .map_err(|err| format!("{:?}: {:?}", path, err))?
+    .map_err(|err| format!("{:?}: {}", path, err))?;
+    if !builder.missing_protos().is_empty() {
+        eprintln!(
+            "Unknown protos, their objects don't block: {:?}",
+            builder.missing_protos()
+        );
+    }
+    Ok(grid)
+}
@@ -0,0 +1,184 @@
+//! Renders heatmaps of who sees and hears whom for `check_look` configs, side by side.
+//!
+//! `check_look_sim -c ../../dll/server/config_examples/old_forp.ServerConfig.toml
+//!     -c ../../dll/server/config_examples/tnf.ServerConfig.toml --perception 8 --target-running`
+#[cfg(feature = "fomap")]
+mod fomap;
+mod render;
+
+use check_look::{
+    config::CheckLook,
+    sim::{parse_grid, Heatmap, SimCritter, SimMap},
+    Moving, MAX_PERCEPTION, MIN_PERCEPTION,
+};
+use hex_geometry::{dirs_count, BlockerGrid, HexGrid};
+use primitives::Hex;
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "check_look_sim")]
+struct Opt {
+    /// `ServerConfig.toml` files to compare, only `check_look` section is used
+    #[structopt(short, long = "config", parse(from_os_str), required = true)]
+    configs: Vec<PathBuf>,
+    /// Text grid of the map: `.` empty, `#` wall, `o` see-through obstacle, `@` observer
+    #[structopt(short, long, parse(from_os_str))]
+    grid: Option<PathBuf>,
+    /// `.fomap` file, blockers are built from its objects
+    #[cfg(feature = "fomap")]
+    #[structopt(short, long, parse(from_os_str), conflicts_with = "grid")]
+    map: Option<PathBuf>,
+    /// `items.lst` of item protos, for `--map`
+    #[cfg(feature = "fomap")]
+    #[structopt(
+        long,
+        parse(from_os_str),
+        default_value = "../../proto/items/items.lst"
+    )]
+    items: PathBuf,
+    /// Size of open field, used without `--grid` and `--map`
+    #[structopt(long, default_value = "81")]
+    size: u16,
+    /// Square map instead of hexagonal one
+    #[structopt(long)]
+    square: bool,
+    /// Proto id of the map, to check `map_utility_start` rule
+    #[structopt(long, default_value = "0")]
+    map_pid: u16,
+    /// Observer's hex, `@` of the grid or center of the map by default
+    #[structopt(long, number_of_values = 2)]
+    hex: Option<Vec<u16>>,
+    /// Observer's direction
+    #[structopt(long, default_value = "0")]
+    dir: u8,
+    #[structopt(long, default_value = "5")]
+    perception: u32,
+    /// Observer is npc with the proto id
+    #[structopt(long)]
+    npc: Option<u16>,
+    /// `QST_VISION` of the observer
+    #[structopt(long, default_value = "0")]
+    vision: u32,
+    #[structopt(long)]
+    running: bool,
+    /// Target is npc
+    #[structopt(long)]
+    target_npc: bool,
+    /// `QST_INVIS` of the target
+    #[structopt(long, default_value = "0")]
+    target_invis: u32,
+    #[structopt(long)]
+    target_running: bool,
+    /// Output html file
+    #[structopt(short, long, parse(from_os_str), default_value = "check_look.html")]
+    out: PathBuf,
+}
+
+fn load_config(path: &Path) -> Result<CheckLook, String> {
+    let config = server_config::load(path).map_err(|err| format!("{:?}: {}", path, err))?;
+    Ok(config.check_look)
+}
+
+fn moving(running: bool) -> Moving {
+    if running {
+        Moving::Running
+    } else {
+        Moving::Still
+    }
+}
+
+fn run(opt: Opt) -> Result<(), String> {
+    let hexagonal = !opt.square;
+    #[cfg(feature = "fomap")]
+    let fomap = match &opt.map {
+        Some(path) => Some(fomap::load(path, &opt.items)?),
+        None => None,
+    };
+    #[cfg(not(feature = "fomap"))]
+    let fomap = None;
+    let (grid, grid_observer) = match (&opt.grid, fomap) {
+        (Some(path), _) => {
+            let text =
+                std::fs::read_to_string(path).map_err(|err| format!("{:?}: {}", path, err))?;
+            parse_grid(&text, hexagonal).map_err(|err| format!("{:?}: {}", path, err))?
+        }
+        (None, Some(grid)) => (grid, None),
+        (None, None) => {
+            let max = Hex::new(opt.size, opt.size);
+            let grid = if hexagonal {
+                BlockerGrid::new(max)
+            } else {
+                BlockerGrid::square(max)
+            };
+            (grid, None)
+        }
+    };
+    let map = SimMap {
+        grid,
+        proto_id: opt.map_pid,
+    };
+    let max = map.grid.max_hex();
+    let hex = match (&opt.hex, grid_observer) {
+        (Some(hex), _) => Hex::new(hex[0], hex[1]),
+        (None, Some(hex)) => hex,
+        (None, None) => Hex::new(max.x / 2, max.y / 2),
+    };
+    if hex.x >= max.x || hex.y >= max.y {
+        return Err(format!(
+            "Observer {:?} is outside of the map {:?}",
+            hex, max
+        ));
+    }
+    if opt.dir >= dirs_count(hexagonal) {
+        return Err(format!("Invalid direction {}", opt.dir));
+    }
+    if !(MIN_PERCEPTION..=MAX_PERCEPTION).contains(&opt.perception) {
+        return Err(format!("Perception {} is out of range", opt.perception));
+    }
+    let observer = SimCritter {
+        hex,
+        dir: opt.dir,
+        proto_id: opt.npc.unwrap_or(0),
+        npc: opt.npc.is_some(),
+        vision: opt.vision,
+        perception: opt.perception,
+        moving: moving(opt.running),
+        ..Default::default()
+    };
+    let target = SimCritter {
+        npc: opt.target_npc,
+        invis: opt.target_invis,
+        moving: moving(opt.target_running),
+        ..Default::default()
+    };
+
+    let mut panels = Vec::with_capacity(opt.configs.len());
+    for path in &opt.configs {
+        let config = load_config(path)?;
+        let heatmap = Heatmap::scan(&config, &map, &observer, &target);
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        println!("{}", name);
+        println!("{}", render::reach_text(&heatmap.reach));
+        panels.push(render::Panel {
+            name,
+            senses: config.senses.len(),
+            heatmap,
+        });
+    }
+    let html = render::html(&map, &observer, &target, &panels);
+    std::fs::write(&opt.out, html).map_err(|err| format!("{:?}: {}", opt.out, err))?;
+    println!("Written {:?}", opt.out);
+    Ok(())
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    if let Err(err) = run(opt) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
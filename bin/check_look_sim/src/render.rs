@@ -0,0 +1,244 @@
+use check_look::{
+    sim::{Heatmap, Reach, SimCritter, SimMap},
+    Look,
+};
+use hex_geometry::{get_distance_hex, HexGrid};
+use primitives::Hex;
+use std::fmt::Write as _;
+
+const SQRT3: f32 = 1.732_050_8;
+/// Pixels per unit of hex layout, hex radius is 2 units.
+const SCALE: f32 = 4.0;
+const SENSE_COLORS: &[&str] = &["#4caf50", "#ffb300", "#29b6f6", "#ef5350", "#8d6e63"];
+const FORCED_COLOR: &str = "#b070ff";
+const UNNOTICED_COLOR: &str = "#f4f4f4";
+const OBSERVER_COLOR: &str = "#1e40ff";
+const WALL_COLOR: &str = "#404040";
+const OBSTACLE_COLOR: &str = "#9e9e9e";
+
+pub struct Panel {
+    pub name: String,
+    pub senses: usize,
+    pub heatmap: Heatmap,
+}
+
+fn sense_name(index: usize) -> String {
+    // Order of senses in config examples
+    match index {
+        0 => "vision".into(),
+        1 => "hearing".into(),
+        _ => format!("sense {}", index),
+    }
+}
+
+fn sense_color(index: usize) -> &'static str {
+    SENSE_COLORS[index % SENSE_COLORS.len()]
+}
+
+fn look_color(look: Look, senses: usize) -> &'static str {
+    match look {
+        Look::Forced(true) => FORCED_COLOR,
+        Look::Fast(true) => sense_color(0),
+        Look::Senses(_) => (0..senses)
+            .find(|&index| look.sense(index))
+            .map(sense_color)
+            .unwrap_or(UNNOTICED_COLOR),
+        Look::Forced(false) | Look::Fast(false) | Look::Dead => UNNOTICED_COLOR,
+    }
+}
+
+fn look_text(look: Look, senses: usize) -> String {
+    match look {
+        Look::Forced(true) => "noticed by vision/invisibility params".into(),
+        Look::Forced(false) => "hidden by utility map or invisibility params".into(),
+        Look::Dead => "observer is dead".into(),
+        Look::Fast(noticed) => format!("npc fast check: {}", noticed),
+        Look::Senses(_) => {
+            let noticed: Vec<_> = (0..senses)
+                .filter(|&index| look.sense(index))
+                .map(sense_name)
+                .collect();
+            if noticed.is_empty() {
+                "unnoticed".into()
+            } else {
+                noticed.join(", ")
+            }
+        }
+    }
+}
+
+/// Cell outline of the hex, in units.
+fn cell_points(hex: Hex, hexagonal: bool) -> Vec<(f32, f32)> {
+    if hexagonal {
+        // Same layout as `LineTracer`: flat-top hexes, odd columns shifted half a row up
+        let cx = 3.0 * hex.x as f32 + 2.0;
+        let cy = 2.0 * SQRT3 * hex.y as f32 - SQRT3 * (hex.x & 1) as f32 + 2.0 * SQRT3;
+        vec![
+            (cx - 2.0, cy),
+            (cx - 1.0, cy - SQRT3),
+            (cx + 1.0, cy - SQRT3),
+            (cx + 2.0, cy),
+            (cx + 1.0, cy + SQRT3),
+            (cx - 1.0, cy + SQRT3),
+        ]
+    } else {
+        let (x, y) = (4.0 * hex.x as f32, 4.0 * hex.y as f32);
+        vec![(x, y), (x + 4.0, y), (x + 4.0, y + 4.0), (x, y + 4.0)]
+    }
+}
+
+fn svg_size(max: Hex, hexagonal: bool) -> (f32, f32) {
+    if hexagonal {
+        (
+            (3.0 * max.x as f32 + 1.0) * SCALE,
+            (2.0 * SQRT3 * max.y as f32 + SQRT3) * SCALE,
+        )
+    } else {
+        (4.0 * max.x as f32 * SCALE, 4.0 * max.y as f32 * SCALE)
+    }
+}
+
+fn svg(map: &SimMap, panel: &Panel) -> String {
+    let heatmap = &panel.heatmap;
+    let hexagonal = map.is_hexagonal();
+    let (width, height) = svg_size(heatmap.max, hexagonal);
+    let mut out = String::new();
+    writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}">"#,
+        width, height
+    )
+    .unwrap();
+    for y in 0..heatmap.max.y {
+        for x in 0..heatmap.max.x {
+            let hex = Hex::new(x, y);
+            let (color, text) = match heatmap.look(hex) {
+                Some(look) => (
+                    look_color(look, panel.senses),
+                    look_text(look, panel.senses),
+                ),
+                None if hex == heatmap.observer => (OBSERVER_COLOR, "observer".into()),
+                None if map.is_hex_raked(hex) => (OBSTACLE_COLOR, "obstacle".into()),
+                None => (WALL_COLOR, "wall".into()),
+            };
+            let points: Vec<String> = cell_points(hex, hexagonal)
+                .into_iter()
+                .map(|(x, y)| format!("{:.1},{:.1}", x * SCALE, y * SCALE))
+                .collect();
+            writeln!(
+                out,
+                r#"<polygon points="{}" fill="{}"><title>{},{} dist {}: {}</title></polygon>"#,
+                points.join(" "),
+                color,
+                x,
+                y,
+                get_distance_hex(heatmap.observer, hex, hexagonal),
+                text
+            )
+            .unwrap();
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn reach_table(reach: &Reach) -> String {
+    let mut out = String::from("<table><tr><th>look dir</th><th>noticed</th>");
+    for index in 0..reach.senses.len() {
+        write!(out, "<th>{}</th>", sense_name(index)).unwrap();
+    }
+    out.push_str("</tr>\n");
+    for look_dir in 0..4 {
+        write!(
+            out,
+            "<tr><td>{}</td><td>{}</td>",
+            look_dir, reach.noticed[look_dir]
+        )
+        .unwrap();
+        for sense in &reach.senses {
+            write!(out, "<td>{}</td>", sense[look_dir]).unwrap();
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n");
+    out
+}
+
+/// Farthest noticed distances by look direction, for terminal.
+pub fn reach_text(reach: &Reach) -> String {
+    let mut out = String::from("look dir  noticed");
+    for index in 0..reach.senses.len() {
+        write!(out, " {:>9}", sense_name(index)).unwrap();
+    }
+    for look_dir in 0..4 {
+        write!(out, "\n{:>8} {:>8}", look_dir, reach.noticed[look_dir]).unwrap();
+        for sense in &reach.senses {
+            write!(out, " {:>9}", sense[look_dir]).unwrap();
+        }
+    }
+    out
+}
+
+fn critter_text(cr: &SimCritter) -> String {
+    format!(
+        "{}, perception {}, {:?}, vision {}, invis {}",
+        if cr.npc { "npc" } else { "player" },
+        cr.perception,
+        cr.moving,
+        cr.vision,
+        cr.invis
+    )
+}
+
+pub fn html(map: &SimMap, observer: &SimCritter, target: &SimCritter, panels: &[Panel]) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>check_look</title>\n\
+         <style>body{font-family:sans-serif} .panels{display:flex;gap:24px;align-items:flex-start}\
+         table{border-collapse:collapse;margin:8px 0} td,th{border:1px solid #ccc;padding:2px 6px;text-align:right}\
+         .swatch{display:inline-block;width:12px;height:12px;margin:0 4px 0 12px;vertical-align:middle}</style>\n\
+         </head><body>\n",
+    );
+    writeln!(
+        out,
+        "<p>Observer at {},{} facing {}: {}<br>Target: {}</p>",
+        observer.hex.x,
+        observer.hex.y,
+        observer.dir,
+        critter_text(observer),
+        critter_text(target)
+    )
+    .unwrap();
+    let senses = panels.iter().map(|panel| panel.senses).max().unwrap_or(0);
+    out.push_str("<p>");
+    let mut legend = |color: &str, text: &str| {
+        write!(
+            out,
+            r#"<span class="swatch" style="background:{}"></span>{}"#,
+            color, text
+        )
+        .unwrap();
+    };
+    for index in 0..senses {
+        legend(sense_color(index), &sense_name(index));
+    }
+    legend(FORCED_COLOR, "params");
+    legend(UNNOTICED_COLOR, "unnoticed");
+    legend(OBSERVER_COLOR, "observer");
+    legend(WALL_COLOR, "wall");
+    legend(OBSTACLE_COLOR, "obstacle");
+    out.push_str("</p>\n<div class=\"panels\">\n");
+    for panel in panels {
+        writeln!(out, "<div><h3>{}</h3>", escape(&panel.name)).unwrap();
+        out.push_str(&reach_table(&panel.heatmap.reach));
+        out.push_str(&svg(map, panel));
+        out.push_str("</div>\n");
+    }
+    out.push_str("</div>\n</body></html>\n");
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -1,16 +1,17 @@
 use crate::overlay::{OverlayEvent, OverlayEventProxy};
-use bridge::{BridgeMessage, BridgeServerHandle};
+use bridge::{BridgeMessage, BridgeServerHandle, Policy, Protocol};
 pub use protocol::message::client_dll_overlay::{
     Avatar, Char, ClientDllToOverlay as MsgIn, OverlayToClientDll as MsgOut, Position,
 };
-use protocol::message::client_dll_overlay::{HANDSHAKE, VERSION};
+use protocol::message::client_dll_overlay::{HANDSHAKE, MIN_VERSION, VERSION};
 use std::net::SocketAddr;
 
 pub type BridgeOverlayToClient = BridgeServerHandle<MsgIn, MsgOut, WinitChannel>;
 
 pub fn start(proxy: OverlayEventProxy) -> BridgeOverlayToClient {
     let addr: SocketAddr = "127.0.0.1:33741".parse().expect("malformed socket address");
-    BridgeOverlayToClient::start_ext(addr, HANDSHAKE, VERSION, WinitChannel(proxy), ())
+    let protocol = Protocol::new(HANDSHAKE, VERSION).min_version(MIN_VERSION);
+    BridgeOverlayToClient::start_ext(addr, protocol, Policy::default(), WinitChannel(proxy), ())
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,37 @@
+//! Checks `ServerConfig.toml` files of the server dll and dumps the default one.
+//!
+//! `server_config --dump-default-config > ServerConfig.toml`
+//! `server_config ../../dll/server/config_examples/*.toml`
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+#[structopt(name = "server_config")]
+struct Opt {
+    /// Print default config with comments for every key
+    #[structopt(long)]
+    dump_default_config: bool,
+    /// Config files to check: unknown keys, types and values
+    #[structopt(parse(from_os_str))]
+    check: Vec<PathBuf>,
+}
+
+fn main() {
+    let opt = Opt::from_args();
+    if opt.dump_default_config {
+        print!("{}", server_config::default_config_toml());
+    }
+    let mut failed = false;
+    for path in &opt.check {
+        match server_config::load(path) {
+            Ok(_) => eprintln!("{:?}: ok", path),
+            Err(err) => {
+                eprintln!("{:?}: {}", path, err);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
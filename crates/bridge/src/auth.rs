@@ -0,0 +1,139 @@
+//! Shared-secret challenge-response, performed right after protocol handshake.
+//!
+//! Both peers send a random challenge and answer the peer's one with
+//! `HMAC-SHA256(secret, role || challenger nonce || prover nonce)`. The role differs for
+//! the listening and connecting side, so a proof can't be reflected back to its author.
+//! This module doesn't do any IO, transports exchange produced messages themselves.
+use super::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+pub type Nonce = [u8; 32];
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Side that accepts connections.
+    Listener,
+    /// Side that connects.
+    Connector,
+}
+
+impl Role {
+    fn label(self) -> &'static [u8] {
+        match self {
+            Role::Listener => b"bridge-listener",
+            Role::Connector => b"bridge-connector",
+        }
+    }
+    fn peer(self) -> Role {
+        match self {
+            Role::Listener => Role::Connector,
+            Role::Connector => Role::Listener,
+        }
+    }
+}
+
+pub struct Auth {
+    secret: Vec<u8>,
+    role: Role,
+    nonce: Nonce,
+    peer_nonce: Option<Nonce>,
+}
+
+impl Auth {
+    pub fn new(secret: &[u8], role: Role) -> Result<Self, BridgeError> {
+        let mut nonce = [0u8; 32];
+        getrandom::getrandom(&mut nonce).map_err(|err| BridgeError::Io(err.into()))?;
+        Ok(Auth {
+            secret: secret.to_owned(),
+            role,
+            nonce,
+            peer_nonce: None,
+        })
+    }
+    pub fn challenge<T>(&self) -> BridgeMessage<T> {
+        BridgeMessage::Challenge(self.nonce)
+    }
+    /// Answers peer's challenge. Incoming and outgoing data types may differ, just like the
+    /// directions of a bridge.
+    pub fn proof<T, U>(
+        &mut self,
+        challenge: BridgeMessage<T>,
+    ) -> Result<BridgeMessage<U>, BridgeError> {
+        let peer_nonce = match challenge {
+            BridgeMessage::Challenge(nonce) => nonce,
+            _ => return Err(BridgeError::Unauthorized),
+        };
+        if peer_nonce == self.nonce {
+            return Err(BridgeError::Unauthorized);
+        }
+        self.peer_nonce = Some(peer_nonce);
+        let mac = self.mac(self.role, &peer_nonce, &self.nonce);
+        let mut proof = [0u8; 32];
+        proof.copy_from_slice(&mac.finalize().into_bytes());
+        Ok(BridgeMessage::Proof(proof))
+    }
+    /// Checks peer's answer to our challenge.
+    pub fn verify<T>(&self, proof: BridgeMessage<T>) -> Result<(), BridgeError> {
+        let (proof, peer_nonce) = match (proof, &self.peer_nonce) {
+            (BridgeMessage::Proof(proof), Some(peer_nonce)) => (proof, peer_nonce),
+            _ => return Err(BridgeError::Unauthorized),
+        };
+        self.mac(self.role.peer(), &self.nonce, peer_nonce)
+            .verify_slice(&proof)
+            .map_err(|_| BridgeError::Unauthorized)
+    }
+    fn mac(&self, role: Role, challenger: &Nonce, prover: &Nonce) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.secret).expect("HMAC can take key of any size");
+        mac.update(role.label());
+        mac.update(challenger);
+        mac.update(prover);
+        mac
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    type Msg = BridgeMessage<()>;
+
+    fn exchange(listener: &mut Auth, connector: &mut Auth) -> (Msg, Msg) {
+        let listener_challenge: Msg = listener.challenge();
+        let connector_challenge: Msg = connector.challenge();
+        let listener_proof = listener.proof(connector_challenge).unwrap();
+        let connector_proof = connector.proof(listener_challenge).unwrap();
+        (listener_proof, connector_proof)
+    }
+
+    #[test]
+    fn same_secret() {
+        let mut listener = Auth::new(b"secret", Role::Listener).unwrap();
+        let mut connector = Auth::new(b"secret", Role::Connector).unwrap();
+        let (listener_proof, connector_proof) = exchange(&mut listener, &mut connector);
+        listener.verify(connector_proof).unwrap();
+        connector.verify(listener_proof).unwrap();
+    }
+
+    #[test]
+    fn wrong_secret() {
+        let mut listener = Auth::new(b"secret", Role::Listener).unwrap();
+        let mut connector = Auth::new(b"guess", Role::Connector).unwrap();
+        let (listener_proof, connector_proof) = exchange(&mut listener, &mut connector);
+        assert!(listener.verify(connector_proof).is_err());
+        assert!(connector.verify(listener_proof).is_err());
+    }
+
+    #[test]
+    fn reflected_proof() {
+        let mut listener = Auth::new(b"secret", Role::Listener).unwrap();
+        let mut mirror = Auth::new(b"secret", Role::Listener).unwrap();
+        // attacker replays our own challenge and then our own proof
+        mirror.nonce = [7; 32];
+        let proof = listener.proof::<(), ()>(mirror.challenge()).unwrap();
+        assert!(listener.verify(proof).is_err());
+        assert!(listener.proof::<(), ()>(listener.challenge()).is_err());
+    }
+}
@@ -16,13 +16,16 @@ impl<H> BridgeCell<H> {
 }
 
 impl<T: BridgeTask> BridgeCell<BridgeHandle<T>> {
-    pub fn connect(&self, addr: SocketAddr, handshake: u16, version: u16) {
+    pub fn connect(&self, addr: SocketAddr, protocol: Protocol) {
+        self.connect_with(addr, protocol, Policy::default());
+    }
+    pub fn connect_with(&self, addr: SocketAddr, protocol: Protocol, policy: Policy<T::MsgOut>) {
         let inner = self.inner.get_or_init(|| Mutex::new(None));
         let mut guard = inner.lock();
-        if let Some(mut old) = guard.take() {
-            old.finish(true);
+        if let Some(old) = guard.take() {
+            let _ = old.finish(true);
         }
-        *guard = Some(BridgeHandle::<T>::start(addr, handshake, version));
+        *guard = Some(BridgeHandle::<T>::start_with(addr, protocol, policy));
     }
 
     pub fn with_online<O, F>(&self, mut f: F) -> Result<O, BridgeError>
@@ -1,5 +1,4 @@
 use super::*;
-use bincode::{deserialize, deserialize_from, serialize, serialize_into};
 use std::net::Shutdown;
 
 pub struct BridgeClient<MsgIn, MsgOut> {
@@ -31,8 +30,7 @@ impl<MIn: 'static + Send + DeserializeOwned + Debug, MOut: 'static + Send + Seri
                 Ok(stream) => break stream,
                 Err(err) => match err.kind() {
                     std::io::ErrorKind::TimedOut => {
-                        let msg_out = worker.try_receive()?;
-                        if let Some(BridgeMessage::Shutdown) = msg_out {
+                        if worker.is_shutdown() {
                             println!("\nBridge client writer: shutdown");
                             return Ok(());
                         }
@@ -54,8 +52,8 @@ impl<MIn: 'static + Send + DeserializeOwned + Debug, MOut: 'static + Send + Seri
             .map_err(BridgeError::Io)?;
 
         println!("Bridge client: handshake");
-        serialize_into(&mut stream, &worker.handshake()).map_err(BridgeError::BinCode)?;
-        let handshake = deserialize_from(&mut stream).map_err(BridgeError::BinCode)?;
+        frame::write(&mut stream, &worker.handshake())?;
+        let handshake = frame::read_handshake(&mut stream)?;
         worker.check_handshake(handshake)?;
 
         stream.set_nodelay(true).map_err(BridgeError::Io)?;
@@ -65,16 +63,17 @@ impl<MIn: 'static + Send + DeserializeOwned + Debug, MOut: 'static + Send + Seri
         println!("Bridge client: online");
 
         let sender = worker.sender();
-        let mut reader = BufReader::new(stream.try_clone().map_err(BridgeError::Io)?);
-        worker.spawn_reader(move || with_bincode::reader(reader, sender));
+        let outbox = worker.outbox();
+        let reader = BufReader::new(stream.try_clone().map_err(BridgeError::Io)?);
+        worker.spawn_reader(move || with_bincode::reader(reader, sender, outbox));
 
         loop {
-            let msg_out = worker.receive()?;
+            let msg_out = worker.receive();
             if let BridgeMessage::Shutdown = msg_out {
                 println!("Bridge client writer: shutdown");
                 return Ok(());
             }
-            serialize_into(&mut stream, &msg_out).map_err(BridgeError::BinCode)?;
+            frame::write(&mut stream, &msg_out)?;
             if let BridgeMessage::Hang = msg_out {
                 println!("Bridge client writer: hang");
                 worker.task().shutdown();
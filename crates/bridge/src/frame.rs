@@ -0,0 +1,227 @@
+//! Length-prefixed framing of bridge messages.
+//!
+//! Every frame is a big-endian `u32` length followed by bincode-encoded `BridgeMessage`.
+//! Since the length is known upfront, a frame that can't be decoded (i.e. a message variant
+//! added in a newer protocol version) is skipped instead of desynchronizing the stream.
+use super::*;
+use byteorder::{ReadBytesExt, WriteBytesExt, BE, LE};
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+};
+
+pub const MAX_FRAME_LEN: u32 = 16 * 1024 * 1024;
+/// Limit for handshake and authentication frames, the peer isn't trusted yet.
+pub const MAX_HANDSHAKE_LEN: u32 = 4 * 1024;
+const HEADER_LEN: usize = 4;
+// Variant indices of `BridgeMessage` known to this version
+const TAG_DATA: u32 = 0;
+const TAG_LAST: u32 = 7;
+
+#[derive(Debug)]
+pub enum Frame<T> {
+    Message(BridgeMessage<T>),
+    Unknown(UnknownTag),
+}
+
+/// Bincode variant indices of an undecodable frame, for logging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnknownTag {
+    pub message: u32,
+    pub data: Option<u32>,
+}
+
+pub fn encode<T: Serialize>(msg: &BridgeMessage<T>) -> Result<Vec<u8>, BridgeError> {
+    let len = bincode::serialized_size(msg).map_err(BridgeError::BinCode)?;
+    let len = u32::try_from(len)
+        .ok()
+        .filter(|len| *len <= MAX_FRAME_LEN)
+        .ok_or(BridgeError::FrameTooLarge(len))?;
+    let mut buf = Vec::with_capacity(HEADER_LEN + len as usize);
+    buf.write_u32::<BE>(len).map_err(BridgeError::Io)?;
+    bincode::serialize_into(&mut buf, msg).map_err(BridgeError::BinCode)?;
+    Ok(buf)
+}
+
+/// Decodes frame payload, without length prefix.
+pub fn decode<T: DeserializeOwned>(payload: &[u8]) -> Result<Frame<T>, BridgeError> {
+    match bincode::deserialize(payload) {
+        Ok(msg) => Ok(Frame::Message(msg)),
+        Err(err) => {
+            let mut tags = payload;
+            let message = tags.read_u32::<LE>().map_err(BridgeError::Io)?;
+            match message {
+                TAG_DATA => Ok(Frame::Unknown(UnknownTag {
+                    message,
+                    data: tags.read_u32::<LE>().ok(),
+                })),
+                tag if tag > TAG_LAST => Ok(Frame::Unknown(UnknownTag {
+                    message,
+                    data: None,
+                })),
+                _ => Err(BridgeError::BinCode(err)),
+            }
+        }
+    }
+}
+
+/// Reads length prefix from the start of the buffer, `None` if the whole frame isn't there yet.
+pub fn frame_len(buf: &[u8]) -> Result<Option<usize>, BridgeError> {
+    if buf.len() < HEADER_LEN {
+        return Ok(None);
+    }
+    let len = (&buf[..HEADER_LEN])
+        .read_u32::<BE>()
+        .map_err(BridgeError::Io)?;
+    if len > MAX_FRAME_LEN {
+        return Err(BridgeError::FrameTooLarge(len as u64));
+    }
+    let len = HEADER_LEN + len as usize;
+    Ok(if buf.len() < len { None } else { Some(len) })
+}
+
+pub fn payload(frame: &[u8]) -> &[u8] {
+    &frame[HEADER_LEN..]
+}
+
+pub fn write<T: Serialize, W: Write>(
+    mut writer: W,
+    msg: &BridgeMessage<T>,
+) -> Result<(), BridgeError> {
+    let buf = encode(msg)?;
+    writer.write_all(&buf).map_err(BridgeError::Io)?;
+    writer.flush().map_err(BridgeError::Io)
+}
+
+pub fn read<T: DeserializeOwned, R: Read>(reader: R) -> Result<Frame<T>, BridgeError> {
+    read_within(reader, MAX_FRAME_LEN)
+}
+
+/// Reads frame, failing without allocation if it's longer than `limit`.
+pub fn read_within<T: DeserializeOwned, R: Read>(
+    mut reader: R,
+    limit: u32,
+) -> Result<Frame<T>, BridgeError> {
+    let len = reader.read_u32::<BE>().map_err(BridgeError::Io)?;
+    if len > limit {
+        return Err(BridgeError::FrameTooLarge(len as u64));
+    }
+    let mut payload = vec![0u8; len as usize];
+    reader.read_exact(&mut payload).map_err(BridgeError::Io)?;
+    decode(&payload)
+}
+
+/// Reads frames until a known one arrives, unknown frames are skipped.
+pub fn read_known<T: DeserializeOwned, R: Read>(
+    reader: R,
+) -> Result<BridgeMessage<T>, BridgeError> {
+    read_known_within(reader, MAX_FRAME_LEN)
+}
+
+/// Same as `read_known`, but for frames exchanged before the peer is authenticated.
+pub fn read_handshake<T: DeserializeOwned, R: Read>(
+    reader: R,
+) -> Result<BridgeMessage<T>, BridgeError> {
+    read_known_within(reader, MAX_HANDSHAKE_LEN)
+}
+
+fn read_known_within<T: DeserializeOwned, R: Read>(
+    mut reader: R,
+    limit: u32,
+) -> Result<BridgeMessage<T>, BridgeError> {
+    loop {
+        match read_within(&mut reader, limit)? {
+            Frame::Message(msg) => return Ok(msg),
+            Frame::Unknown(tag) => eprintln!("Bridge: skipping unknown frame {:?}", tag),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Cursor;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    enum Old {
+        Foo(u32),
+        Bar(String),
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    enum New {
+        Foo(u32),
+        Bar(String),
+        Baz { x: u8, y: u8 },
+    }
+
+    fn frames(msgs: &[BridgeMessage<New>]) -> Cursor<Vec<u8>> {
+        let mut buf = vec![];
+        for msg in msgs {
+            write(&mut buf, msg).unwrap();
+        }
+        Cursor::new(buf)
+    }
+
+    #[test]
+    fn skip_unknown_variant() {
+        let mut stream = frames(&[
+            BridgeMessage::Data(New::Baz { x: 1, y: 2 }),
+            BridgeMessage::Data(New::Bar("hello".into())),
+            BridgeMessage::Ping,
+        ]);
+        match read::<Old, _>(&mut stream).unwrap() {
+            Frame::Unknown(tag) => assert_eq!(
+                tag,
+                UnknownTag {
+                    message: TAG_DATA,
+                    data: Some(2)
+                }
+            ),
+            frame => panic!("unexpected frame: {:?}", frame),
+        }
+        match read_known::<Old, _>(&mut stream).unwrap() {
+            BridgeMessage::Data(Old::Bar(text)) => assert_eq!(text, "hello"),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+        assert!(matches!(
+            read_known::<Old, _>(&mut stream).unwrap(),
+            BridgeMessage::Ping
+        ));
+    }
+
+    #[test]
+    fn partial_frame() {
+        let buf = encode(&BridgeMessage::Data(New::Foo(42))).unwrap();
+        assert_eq!(frame_len(&buf[..3]).unwrap(), None);
+        assert_eq!(frame_len(&buf[..buf.len() - 1]).unwrap(), None);
+        assert_eq!(frame_len(&buf).unwrap(), Some(buf.len()));
+        match decode::<Old>(payload(&buf)).unwrap() {
+            Frame::Message(BridgeMessage::Data(Old::Foo(42))) => {}
+            frame => panic!("unexpected frame: {:?}", frame),
+        }
+    }
+
+    #[test]
+    fn reject_huge_frame() {
+        let mut buf = vec![];
+        buf.write_u32::<BE>(MAX_FRAME_LEN + 1).unwrap();
+        assert!(matches!(
+            read::<Old, _>(Cursor::new(buf)),
+            Err(BridgeError::FrameTooLarge(_))
+        ));
+
+        let mut buf = vec![];
+        buf.write_u32::<BE>(MAX_HANDSHAKE_LEN + 1).unwrap();
+        buf.resize(buf.len() + MAX_HANDSHAKE_LEN as usize + 1, 0);
+        assert!(matches!(
+            read_handshake::<Old, _>(Cursor::new(buf)),
+            Err(BridgeError::FrameTooLarge(_))
+        ));
+        let mut stream = frames(&[BridgeMessage::Handshake(Protocol::new(1, 1))]);
+        assert!(matches!(
+            read_handshake::<Old, _>(&mut stream),
+            Ok(BridgeMessage::Handshake(_))
+        ));
+    }
+}
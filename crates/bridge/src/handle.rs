@@ -3,10 +3,13 @@ use super::*;
 use std::time::Instant;
 
 impl<T: BridgeTask> BridgeHandle<T> {
-    pub fn start(addr: SocketAddr, handshake: u16, version: u16) -> Self {
+    pub fn start(addr: SocketAddr, protocol: Protocol) -> Self {
+        Self::start_with(addr, protocol, Policy::default())
+    }
+    pub fn start_with(addr: SocketAddr, protocol: Protocol, policy: Policy<T::MsgOut>) -> Self {
         let (sender_in, receiver_in) = channel();
 
-        Self::start_ext(addr, handshake, version, sender_in, receiver_in)
+        Self::start_ext(addr, protocol, policy, sender_in, receiver_in)
     }
 
     pub fn receive<'a>(&'a mut self) -> impl Iterator<Item = T::MsgIn> + 'a {
@@ -16,33 +19,49 @@ impl<T: BridgeTask> BridgeHandle<T> {
             _ => None,
         })
     }
+
+    /// Waits for the next data message, `None` on timeout.
+    pub fn receive_timeout(&mut self, timeout: Duration) -> Option<T::MsgIn> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let left = deadline.checked_duration_since(Instant::now())?;
+            match self.receiver.recv_timeout(left) {
+                Ok(BridgeMessage::Data(data)) => return Some(data),
+                Ok(_) => continue,
+                Err(_) => return None,
+            }
+        }
+    }
 }
 
 impl<T: BridgeTask, S: Channel<T::MsgIn>> BridgeHandle<T, S> {
     pub fn is_online(&self) -> bool {
         self.online.load(Ordering::Relaxed)
     }
+    /// Number of the current or the last connection, grows with every reconnect.
+    pub fn connection(&self) -> u32 {
+        self.connection.load(Ordering::Relaxed)
+    }
+    /// Negotiated protocol version and capabilities of the current connection.
+    pub fn session(&self) -> Option<Session> {
+        self.session.lock().clone()
+    }
     /*pub(super) fn hang(&mut self) {
         let _ = self.sender.send(BridgeMessage::Hang);
     }*/
     pub fn start_ext(
         addr: SocketAddr,
-        handshake: u16,
-        version: u16,
+        protocol: Protocol,
+        policy: Policy<T::MsgOut>,
         sender_in: S,
         receiver_in: S::Receiver,
     ) -> Self {
-        let (sender_out, receiver_out) = channel();
+        let outbox = Arc::new(Outbox::new(policy));
 
-        let inner = BridgeWorker::<T, S>::new(
-            receiver_out,
-            sender_in,
-            sender_out.clone(),
-            addr,
-            handshake,
-            version,
-        );
+        let inner = BridgeWorker::<T, S>::new(Arc::clone(&outbox), sender_in, addr, protocol);
         let online = inner.online_handle();
+        let connection = inner.connection_handle();
+        let session = inner.session_handle();
 
         let thread = std::thread::spawn(move || {
             inner.thread();
@@ -50,20 +69,19 @@ impl<T: BridgeTask, S: Channel<T::MsgIn>> BridgeHandle<T, S> {
         });
 
         BridgeHandle {
-            sender: sender_out,
+            outbox,
             receiver: receiver_in,
             thread,
             online,
+            connection,
+            session,
             last_ping: Instant::now(),
         }
     }
     pub fn finish(self, join: bool) -> Result<(), BridgeError> {
         println!("BridgeHandle: finish");
-        let res = self
-            .sender
-            .send(BridgeMessage::Shutdown)
-            .map_err(|_| BridgeError::ChannelDropped);
-        println!("BridgeHandle: shutdown sent: {:?}", res);
+        self.outbox.push(BridgeMessage::Shutdown);
+        println!("BridgeHandle: shutdown sent");
         if join {
             println!("Thread join...");
             let _res2 = self.thread.join();
@@ -73,19 +91,21 @@ impl<T: BridgeTask, S: Channel<T::MsgIn>> BridgeHandle<T, S> {
         }
         println!("BridgeHandle: finished");
 
-        res
+        Ok(())
     }
 
+    /// Queues message, it will be sent as soon as bridge is online.
     pub fn send(&mut self, msg: T::MsgOut) -> Result<(), BridgeError> {
-        self.sender
-            .send(BridgeMessage::Data(msg))
-            .map_err(|_| BridgeError::ChannelDropped)
+        self.outbox.push(BridgeMessage::Data(msg));
+        Ok(())
+    }
+    /// Number of messages waiting to be sent.
+    pub fn queued(&self) -> usize {
+        self.outbox.data_len()
     }
     pub fn ping(&mut self) -> Result<(), BridgeError> {
         if self.last_ping.elapsed() > Duration::from_millis(1000) {
-            self.sender
-                .send(BridgeMessage::Ping)
-                .map_err(|_| BridgeError::ChannelDropped)?;
+            self.outbox.push(BridgeMessage::Ping);
             self.last_ping = Instant::now();
         }
         Ok(())
@@ -96,9 +116,11 @@ pub struct BridgeHandle<
     T: BridgeTask,
     S: Channel<T::MsgIn> = DefaultSender<<T as BridgeTask>::MsgIn>,
 > {
-    sender: Sender<BridgeMessage<T::MsgOut>>,
+    outbox: Arc<Outbox<T::MsgOut>>,
     receiver: S::Receiver,
     thread: JoinHandle<()>,
     online: Arc<AtomicBool>,
+    connection: Arc<AtomicU32>,
+    session: SessionHandle,
     last_ping: Instant,
 }
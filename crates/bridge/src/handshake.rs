@@ -0,0 +1,105 @@
+use super::*;
+
+/// What one side of the bridge is able to speak.
+///
+/// Sent to the peer as the very first frame, both sides then agree on the highest common
+/// version and on the capabilities supported by both of them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Protocol {
+    pub handshake: u16,
+    pub version: u16,
+    pub min_version: u16,
+    pub capabilities: Vec<String>,
+}
+
+impl Protocol {
+    pub fn new(handshake: u16, version: u16) -> Self {
+        Protocol {
+            handshake,
+            version,
+            min_version: version,
+            capabilities: vec![],
+        }
+    }
+    /// Oldest peer version we still can talk to.
+    pub fn min_version(mut self, min_version: u16) -> Self {
+        self.min_version = min_version.min(self.version);
+        self
+    }
+    pub fn capabilities<I, C>(mut self, capabilities: I) -> Self
+    where
+        I: IntoIterator<Item = C>,
+        C: Into<String>,
+    {
+        self.capabilities = capabilities.into_iter().map(Into::into).collect();
+        self
+    }
+    pub fn negotiate(&self, peer: &Protocol) -> Result<Session, BridgeError> {
+        if peer.handshake != self.handshake {
+            return Err(BridgeError::Handshake(peer.handshake, peer.version));
+        }
+        let version = self.version.min(peer.version);
+        if version < self.min_version || version < peer.min_version {
+            return Err(BridgeError::Handshake(peer.handshake, peer.version));
+        }
+        let capabilities = self
+            .capabilities
+            .iter()
+            .filter(|cap| peer.capabilities.contains(cap))
+            .cloned()
+            .collect();
+        Ok(Session {
+            version,
+            capabilities,
+        })
+    }
+}
+
+/// Outcome of the handshake, valid until the connection is lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub version: u16,
+    pub capabilities: Vec<String>,
+}
+
+impl Session {
+    pub fn supports(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|cap| cap == capability)
+    }
+}
+
+pub type SessionHandle = Arc<Mutex<Option<Session>>>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negotiate_lowest_common_version() {
+        let old = Protocol::new(0xB00B, 6).capabilities(vec!["avatars"]);
+        let new = Protocol::new(0xB00B, 8)
+            .min_version(6)
+            .capabilities(vec!["avatars", "messages"]);
+
+        let session = new.negotiate(&old).unwrap();
+        assert_eq!(session, old.negotiate(&new).unwrap());
+        assert_eq!(session.version, 6);
+        assert!(session.supports("avatars"));
+        assert!(!session.supports("messages"));
+    }
+
+    #[test]
+    fn negotiate_rejects_too_old() {
+        let old = Protocol::new(0xB00B, 6);
+        let new = Protocol::new(0xB00B, 8).min_version(7);
+        assert!(new.negotiate(&old).is_err());
+        assert!(old.negotiate(&new).is_err());
+    }
+
+    #[test]
+    fn negotiate_rejects_foreign_magic() {
+        let ours = Protocol::new(0xB00B, 6);
+        let theirs = Protocol::new(0xBABA, 6);
+        assert!(ours.negotiate(&theirs).is_err());
+    }
+}
@@ -1,3 +1,4 @@
+use parking_lot::Mutex;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
     fmt::Debug,
@@ -6,11 +7,11 @@ use std::{
     marker::PhantomData,
     net::{SocketAddr, TcpStream},
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU32, Ordering},
         mpsc::{channel, Receiver, Sender, TryIter},
         Arc,
     },
-    thread::{sleep, JoinHandle},
+    thread::JoinHandle,
     time::Duration,
 };
 
@@ -34,21 +35,43 @@ pub type BridgeServerHandle<MsgIn, MsgOut, S = Sender<BridgeMessage<MsgIn>>> =
 
 mod with_bincode;
 
+mod outbox;
+use outbox::Outbox;
+
+mod policy;
+pub use policy::{Backoff, BridgeEvent, Overflow, Policy};
+
+pub mod frame;
+
+pub mod rpc;
+pub use rpc::RpcHandle;
+
+pub mod auth;
+
+mod handshake;
+use handshake::SessionHandle;
+pub use handshake::{Protocol, Session};
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum BridgeMessage<T> {
     Data(T),
-    Handshake(u16, u16),
+    Handshake(Protocol),
     Ping,
     Hang,
     Shutdown,
+    Pong,
+    Challenge([u8; 32]),
+    Proof([u8; 32]),
 }
 
 #[derive(Debug)]
 pub enum BridgeError {
     Io(std::io::Error),
     BinCode(bincode::Error),
+    FrameTooLarge(u64),
     Handshake(u16, u16),
     NoHandshake,
+    Unauthorized,
     ChannelDropped,
     EmptyBridgeCell,
     NotOnline,
@@ -83,3 +106,163 @@ impl<T: Send + 'static> Channel<T> for DefaultSender<T> {
         self.send(msg).ok().ok_or(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{thread::sleep, time::Instant};
+
+    mod old {
+        use super::*;
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        pub enum Msg {
+            Status(u32),
+            Text(String),
+        }
+        pub fn protocol() -> Protocol {
+            Protocol::new(0x7E57, 1).capabilities(vec!["status"])
+        }
+    }
+
+    mod new {
+        use super::*;
+        #[derive(Debug, PartialEq, Deserialize, Serialize)]
+        pub enum Msg {
+            Status(u32),
+            Text(String),
+            Statistics { fps: u32, lags: u32 },
+        }
+        pub fn protocol() -> Protocol {
+            Protocol::new(0x7E57, 2)
+                .min_version(1)
+                .capabilities(vec!["status", "statistics"])
+        }
+    }
+
+    pub(crate) fn free_addr() -> SocketAddr {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    }
+
+    pub(crate) fn wait_for<F: FnMut() -> bool>(mut f: F) {
+        let start = Instant::now();
+        while !f() {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "bridge test timed out"
+            );
+            sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn wait_message<T: BridgeTask>(handle: &mut BridgeHandle<T>) -> T::MsgIn {
+        let mut msg = None;
+        wait_for(|| {
+            msg = handle.receive().next();
+            msg.is_some()
+        });
+        msg.unwrap()
+    }
+
+    #[test]
+    fn mixed_versions() {
+        let addr = free_addr();
+        let mut server =
+            BridgeHandle::<BridgeServer<new::Msg, new::Msg>>::start(addr, new::protocol());
+        let mut client =
+            BridgeHandle::<BridgeClient<old::Msg, old::Msg>>::start(addr, old::protocol());
+        wait_for(|| server.is_online() && client.is_online());
+
+        let session = client.session().unwrap();
+        assert_eq!(session.version, 1);
+        assert!(session.supports("status"));
+        assert!(!session.supports("statistics"));
+        assert_eq!(server.session(), Some(session));
+
+        server
+            .send(new::Msg::Statistics { fps: 60, lags: 0 })
+            .unwrap();
+        server.send(new::Msg::Status(3)).unwrap();
+        assert_eq!(wait_message(&mut client), old::Msg::Status(3));
+
+        client.send(old::Msg::Text("hello".into())).unwrap();
+        assert_eq!(wait_message(&mut server), new::Msg::Text("hello".into()));
+
+        client.finish(true).unwrap();
+        server.finish(true).unwrap();
+    }
+
+    #[test]
+    fn buffer_while_offline() {
+        let addr = free_addr();
+        let events = Arc::new(Mutex::new(vec![]));
+        let policy = {
+            let events = events.clone();
+            Policy::default()
+                .backoff(Backoff {
+                    initial: Duration::from_millis(10),
+                    max: Duration::from_millis(100),
+                    factor: 2,
+                })
+                .coalesce(|msg| match msg {
+                    old::Msg::Status(_) => Some(0),
+                    old::Msg::Text(_) => None,
+                })
+                .metrics(move |event| events.lock().push(event))
+        };
+        let mut client = BridgeHandle::<BridgeClient<old::Msg, old::Msg>>::start_with(
+            addr,
+            old::protocol(),
+            policy,
+        );
+        for i in 0..10 {
+            client.send(old::Msg::Status(i)).unwrap();
+        }
+        client.send(old::Msg::Text("offline".into())).unwrap();
+        assert_eq!(client.queued(), 2);
+
+        let mut server =
+            BridgeHandle::<BridgeServer<new::Msg, new::Msg>>::start(addr, new::protocol());
+        assert_eq!(wait_message(&mut server), new::Msg::Status(9));
+        assert_eq!(wait_message(&mut server), new::Msg::Text("offline".into()));
+
+        wait_for(|| {
+            client.ping().unwrap();
+            events
+                .lock()
+                .iter()
+                .any(|event| matches!(event, BridgeEvent::PingRtt(_)))
+        });
+
+        server.finish(true).unwrap();
+        wait_for(|| !client.is_online());
+        client.send(old::Msg::Status(10)).unwrap();
+        let mut server =
+            BridgeHandle::<BridgeServer<new::Msg, new::Msg>>::start(addr, new::protocol());
+        assert_eq!(wait_message(&mut server), new::Msg::Status(10));
+
+        let connects =
+            |kind: BridgeEvent| events.lock().iter().filter(|event| **event == kind).count();
+        assert_eq!(connects(BridgeEvent::Connected), 2);
+        assert_eq!(connects(BridgeEvent::Disconnected), 1);
+
+        client.finish(true).unwrap();
+        server.finish(true).unwrap();
+    }
+
+    #[test]
+    fn incompatible_versions() {
+        let addr = free_addr();
+        let server = BridgeHandle::<BridgeServer<new::Msg, new::Msg>>::start(
+            addr,
+            new::protocol().min_version(2),
+        );
+        let client = BridgeHandle::<BridgeClient<old::Msg, old::Msg>>::start(addr, old::protocol());
+        sleep(Duration::from_millis(500));
+        assert!(!client.is_online());
+        assert!(client.session().is_none());
+
+        client.finish(false).unwrap();
+        server.finish(false).unwrap();
+    }
+}
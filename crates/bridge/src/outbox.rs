@@ -0,0 +1,212 @@
+//! Outbound queue shared between `BridgeHandle` and its worker.
+//!
+//! Control messages always go through, data messages are bounded by `Policy::capacity`
+//! and survive reconnects, so whatever was sent while offline is delivered afterwards.
+use super::*;
+use parking_lot::Condvar;
+use std::{collections::VecDeque, time::Instant};
+
+pub struct Outbox<M> {
+    state: Mutex<State<M>>,
+    ready: Condvar,
+    policy: Policy<M>,
+}
+
+struct State<M> {
+    control: VecDeque<BridgeMessage<M>>,
+    data: VecDeque<M>,
+    shutdown: bool,
+    ping_sent: Option<Instant>,
+}
+
+impl<M> Outbox<M> {
+    pub fn new(policy: Policy<M>) -> Self {
+        Outbox {
+            state: Mutex::new(State {
+                control: VecDeque::new(),
+                data: VecDeque::new(),
+                shutdown: false,
+                ping_sent: None,
+            }),
+            ready: Condvar::new(),
+            policy,
+        }
+    }
+    pub fn policy(&self) -> &Policy<M> {
+        &self.policy
+    }
+
+    pub fn push(&self, msg: BridgeMessage<M>) {
+        let mut state = self.state.lock();
+        match msg {
+            BridgeMessage::Data(data) => {
+                if let Some(dropped) = self.push_data(&mut state, data) {
+                    self.policy.report(BridgeEvent::Dropped(dropped));
+                }
+            }
+            BridgeMessage::Shutdown => state.shutdown = true,
+            BridgeMessage::Ping => {
+                state.ping_sent = Some(Instant::now());
+                state.control.push_back(BridgeMessage::Ping);
+            }
+            msg => state.control.push_back(msg),
+        }
+        self.ready.notify_all();
+    }
+
+    fn push_data(&self, state: &mut State<M>, data: M) -> Option<usize> {
+        if let Some(coalesce) = &self.policy.coalesce {
+            if let Some(key) = coalesce(&data) {
+                let old = state
+                    .data
+                    .iter()
+                    .position(|queued| coalesce(queued) == Some(key));
+                if let Some(old) = old {
+                    state.data.remove(old);
+                    state.data.push_back(data);
+                    return None;
+                }
+            }
+        }
+        if state.data.len() < self.policy.capacity {
+            state.data.push_back(data);
+            return None;
+        }
+        if let Overflow::DropOldest = self.policy.overflow {
+            state.data.pop_front();
+            state.data.push_back(data);
+        }
+        Some(1)
+    }
+
+    /// Blocks until there is something to send, shutdown takes priority.
+    pub fn pop(&self) -> BridgeMessage<M> {
+        let mut state = self.state.lock();
+        loop {
+            if let Some(msg) = Self::take(&mut state) {
+                return msg;
+            }
+            self.ready.wait(&mut state);
+        }
+    }
+    pub fn try_pop(&self) -> Option<BridgeMessage<M>> {
+        Self::take(&mut self.state.lock())
+    }
+    fn take(state: &mut State<M>) -> Option<BridgeMessage<M>> {
+        if state.shutdown {
+            return Some(BridgeMessage::Shutdown);
+        }
+        state
+            .control
+            .pop_front()
+            .or_else(|| state.data.pop_front().map(BridgeMessage::Data))
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.state.lock().shutdown
+    }
+    /// Sleeps for `timeout` unless shutdown is requested, returns `true` on shutdown.
+    pub fn wait_shutdown(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock();
+        while !state.shutdown {
+            if self.ready.wait_until(&mut state, deadline).timed_out() {
+                break;
+            }
+        }
+        state.shutdown
+    }
+    /// Drops control messages left from the previous connection.
+    pub fn reset_control(&self) {
+        let mut state = self.state.lock();
+        state.control.clear();
+        state.ping_sent = None;
+    }
+    pub fn pong_received(&self) {
+        let sent = self.state.lock().ping_sent.take();
+        if let Some(sent) = sent {
+            self.policy.report(BridgeEvent::PingRtt(sent.elapsed()));
+        }
+    }
+    pub fn data_len(&self) -> usize {
+        self.state.lock().data.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Msg {
+        Status(u32),
+        Text(&'static str),
+    }
+
+    fn drain(outbox: &Outbox<Msg>) -> Vec<Msg> {
+        std::iter::from_fn(|| match outbox.try_pop()? {
+            BridgeMessage::Data(msg) => Some(msg),
+            msg => panic!("unexpected message: {:?}", msg),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn drop_oldest() {
+        let dropped = Arc::new(Mutex::new(0));
+        let outbox =
+            {
+                let dropped = dropped.clone();
+                Outbox::new(Policy::default().capacity(2, Overflow::DropOldest).metrics(
+                    move |event| {
+                        if let BridgeEvent::Dropped(count) = event {
+                            *dropped.lock() += count;
+                        }
+                    },
+                ))
+            };
+        for i in 0..4 {
+            outbox.push(BridgeMessage::Data(Msg::Status(i)));
+        }
+        assert_eq!(drain(&outbox), vec![Msg::Status(2), Msg::Status(3)]);
+        assert_eq!(*dropped.lock(), 2);
+    }
+
+    #[test]
+    fn drop_newest() {
+        let outbox = Outbox::new(Policy::default().capacity(2, Overflow::DropNewest));
+        for i in 0..4 {
+            outbox.push(BridgeMessage::Data(Msg::Status(i)));
+        }
+        assert_eq!(drain(&outbox), vec![Msg::Status(0), Msg::Status(1)]);
+    }
+
+    #[test]
+    fn coalesce_by_key() {
+        let outbox = Outbox::new(Policy::default().coalesce(|msg| match msg {
+            Msg::Status(_) => Some(0),
+            Msg::Text(_) => None,
+        }));
+        outbox.push(BridgeMessage::Data(Msg::Status(1)));
+        outbox.push(BridgeMessage::Data(Msg::Text("a")));
+        outbox.push(BridgeMessage::Data(Msg::Status(2)));
+        outbox.push(BridgeMessage::Data(Msg::Text("b")));
+        outbox.push(BridgeMessage::Data(Msg::Status(3)));
+        assert_eq!(
+            drain(&outbox),
+            vec![Msg::Text("a"), Msg::Text("b"), Msg::Status(3)]
+        );
+    }
+
+    #[test]
+    fn shutdown_first() {
+        let outbox = Outbox::new(Policy::default());
+        outbox.push(BridgeMessage::Data(Msg::Status(1)));
+        outbox.push(BridgeMessage::Hang);
+        assert!(matches!(outbox.pop(), BridgeMessage::Hang));
+        outbox.push(BridgeMessage::Shutdown);
+        assert!(matches!(outbox.pop(), BridgeMessage::Shutdown));
+        assert!(outbox.wait_shutdown(Duration::from_secs(10)));
+        assert_eq!(outbox.data_len(), 1);
+    }
+}
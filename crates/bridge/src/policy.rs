@@ -0,0 +1,139 @@
+use super::*;
+
+/// Reconnect, buffering and metrics settings of a bridge worker.
+pub struct Policy<M> {
+    pub backoff: Backoff,
+    /// How often the server side polls for incoming connection.
+    pub accept_interval: Duration,
+    /// Max number of data messages buffered while offline or while writer is busy.
+    pub capacity: usize,
+    pub overflow: Overflow,
+    pub coalesce: Option<CoalesceFn<M>>,
+    pub metrics: Option<MetricsFn>,
+}
+
+/// Messages with the same key replace each other in the outbound queue.
+pub type CoalesceFn<M> = Arc<dyn Fn(&M) -> Option<u64> + Send + Sync>;
+pub type MetricsFn = Arc<dyn Fn(BridgeEvent) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    DropOldest,
+    DropNewest,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeEvent {
+    Connected,
+    Disconnected,
+    Dropped(usize),
+    PingRtt(Duration),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub factor: u32,
+}
+
+impl Backoff {
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let mut delay = self.initial;
+        for _ in 0..attempt {
+            delay = delay.checked_mul(self.factor).unwrap_or(self.max);
+            if delay >= self.max {
+                return self.max;
+            }
+        }
+        delay.min(self.max)
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(500),
+            max: Duration::from_secs(10),
+            factor: 2,
+        }
+    }
+}
+
+impl<M> Policy<M> {
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+    pub fn accept_interval(mut self, interval: Duration) -> Self {
+        self.accept_interval = interval;
+        self
+    }
+    pub fn capacity(mut self, capacity: usize, overflow: Overflow) -> Self {
+        self.capacity = capacity.max(1);
+        self.overflow = overflow;
+        self
+    }
+    pub fn coalesce<F: 'static + Fn(&M) -> Option<u64> + Send + Sync>(mut self, f: F) -> Self {
+        self.coalesce = Some(Arc::new(f));
+        self
+    }
+    pub fn metrics<F: 'static + Fn(BridgeEvent) + Send + Sync>(mut self, f: F) -> Self {
+        self.metrics = Some(Arc::new(f));
+        self
+    }
+    pub(crate) fn report(&self, event: BridgeEvent) {
+        if let Some(metrics) = &self.metrics {
+            metrics(event);
+        }
+    }
+}
+
+impl<M> Default for Policy<M> {
+    fn default() -> Self {
+        Policy {
+            backoff: Backoff::default(),
+            accept_interval: Duration::from_millis(100),
+            capacity: 1024,
+            overflow: Overflow::DropOldest,
+            coalesce: None,
+            metrics: None,
+        }
+    }
+}
+
+impl<M> Clone for Policy<M> {
+    fn clone(&self) -> Self {
+        Policy {
+            backoff: self.backoff.clone(),
+            accept_interval: self.accept_interval,
+            capacity: self.capacity,
+            overflow: self.overflow,
+            coalesce: self.coalesce.clone(),
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_to_max() {
+        let backoff = Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_millis(1000),
+            factor: 3,
+        };
+        let delays: Vec<_> = (0..5).map(|attempt| backoff.delay(attempt)).collect();
+        assert_eq!(
+            delays,
+            [100, 300, 900, 1000, 1000]
+                .iter()
+                .map(|ms| Duration::from_millis(*ms))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_millis(1000));
+    }
+}
@@ -0,0 +1,417 @@
+//! Request/response calls on top of fire-and-forget bridge messages.
+//!
+//! Both sides exchange `Rpc` envelopes: plain messages, requests tagged with a correlation id
+//! and responses carrying the id of the request they answer. `RpcHandle` keeps track of
+//! outstanding requests and buffers everything else for `receive`.
+use super::*;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::Instant,
+};
+
+pub type RequestId = u32;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum Rpc<Msg, Req, Resp> {
+    Message(Msg),
+    Request(RequestId, Req),
+    Response(RequestId, Resp),
+}
+
+/// Lets `RpcHandle` name message, request and response types of `BridgeTask` messages.
+pub trait RpcSet: Sized {
+    type Message;
+    type Request;
+    type Response;
+
+    fn into_rpc(self) -> Rpc<Self::Message, Self::Request, Self::Response>;
+    fn from_rpc(rpc: Rpc<Self::Message, Self::Request, Self::Response>) -> Self;
+}
+
+impl<Msg, Req, Resp> RpcSet for Rpc<Msg, Req, Resp> {
+    type Message = Msg;
+    type Request = Req;
+    type Response = Resp;
+
+    fn into_rpc(self) -> Self {
+        self
+    }
+    fn from_rpc(rpc: Self) -> Self {
+        rpc
+    }
+}
+
+#[derive(Debug)]
+pub enum RpcError {
+    Bridge(BridgeError),
+    Timeout,
+    /// Connection was lost before the response arrived, request is cancelled.
+    Disconnected,
+    UnknownRequest(RequestId),
+}
+
+/// Incoming message or request, requests should be answered with `RpcHandle::respond`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incoming<Msg, Req> {
+    Message(Msg),
+    Request(RequestId, Req),
+}
+
+type In<T> = <T as BridgeTask>::MsgIn;
+type Out<T> = <T as BridgeTask>::MsgOut;
+type InResponse<T> = <In<T> as RpcSet>::Response;
+type InIncoming<T> = Incoming<<In<T> as RpcSet>::Message, <In<T> as RpcSet>::Request>;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Outstanding request, tagged with the connection it was sent on.
+struct Pending<Resp> {
+    connection: u32,
+    state: PendingState<Resp>,
+}
+
+enum PendingState<Resp> {
+    Waiting,
+    Ready(Resp),
+    /// Connection of the request was lost, response will never come.
+    Disconnected,
+}
+
+pub struct RpcHandle<T: BridgeTask>
+where
+    T::MsgIn: RpcSet,
+    T::MsgOut: RpcSet,
+{
+    handle: BridgeHandle<T>,
+    next_id: RequestId,
+    waiting: BTreeMap<RequestId, Pending<InResponse<T>>>,
+    inbox: VecDeque<InIncoming<T>>,
+}
+
+impl<T: BridgeTask> RpcHandle<T>
+where
+    T::MsgIn: RpcSet,
+    T::MsgOut: RpcSet,
+{
+    pub fn new(handle: BridgeHandle<T>) -> Self {
+        RpcHandle {
+            handle,
+            next_id: 0,
+            waiting: BTreeMap::new(),
+            inbox: VecDeque::new(),
+        }
+    }
+    pub fn handle(&mut self) -> &mut BridgeHandle<T> {
+        &mut self.handle
+    }
+    pub fn finish(self, join: bool) -> Result<(), BridgeError> {
+        self.handle.finish(join)
+    }
+
+    pub fn send(&mut self, msg: <Out<T> as RpcSet>::Message) -> Result<(), BridgeError> {
+        self.handle.send(Out::<T>::from_rpc(Rpc::Message(msg)))
+    }
+    pub fn respond(
+        &mut self,
+        id: RequestId,
+        response: <Out<T> as RpcSet>::Response,
+    ) -> Result<(), BridgeError> {
+        self.handle
+            .send(Out::<T>::from_rpc(Rpc::Response(id, response)))
+    }
+
+    /// Sends request without waiting, poll the result with `try_response`.
+    ///
+    /// Request is bound to the current connection: if it's lost before the response arrives,
+    /// the request fails with `RpcError::Disconnected` even if the bridge reconnects.
+    pub fn request(&mut self, request: <Out<T> as RpcSet>::Request) -> Result<RequestId, RpcError> {
+        let connection = self.handle.connection();
+        if !self.handle.is_online() {
+            return Err(RpcError::Bridge(BridgeError::NotOnline));
+        }
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.handle
+            .send(Out::<T>::from_rpc(Rpc::Request(id, request)))
+            .map_err(RpcError::Bridge)?;
+        self.waiting.insert(
+            id,
+            Pending {
+                connection,
+                state: PendingState::Waiting,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Non-blocking check for response, suitable for polling from a game loop.
+    pub fn try_response(&mut self, id: RequestId) -> Result<Option<InResponse<T>>, RpcError> {
+        self.pump();
+        self.take_response(id)
+    }
+
+    /// Sends request and blocks until response arrives, timeout expires or connection is lost.
+    pub fn call(
+        &mut self,
+        request: <Out<T> as RpcSet>::Request,
+        timeout: Duration,
+    ) -> Result<InResponse<T>, RpcError> {
+        let id = self.request(request)?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(response) = self.take_response(id)? {
+                return Ok(response);
+            }
+            let left = match deadline.checked_duration_since(Instant::now()) {
+                Some(left) => left,
+                None => {
+                    self.waiting.remove(&id);
+                    return Err(RpcError::Timeout);
+                }
+            };
+            if let Some(msg) = self.handle.receive_timeout(left.min(POLL_INTERVAL)) {
+                self.dispatch(msg);
+            }
+        }
+    }
+
+    /// Drops outstanding request, late response will be ignored.
+    pub fn cancel(&mut self, id: RequestId) {
+        self.waiting.remove(&id);
+    }
+
+    pub fn receive(&mut self) -> impl Iterator<Item = InIncoming<T>> + '_ {
+        self.pump();
+        self.inbox.drain(..)
+    }
+
+    fn pump(&mut self) {
+        let received: Vec<_> = self.handle.receive().collect();
+        for msg in received {
+            self.dispatch(msg);
+        }
+    }
+
+    fn take_response(&mut self, id: RequestId) -> Result<Option<InResponse<T>>, RpcError> {
+        self.fail_lost();
+        let pending = self.waiting.get(&id).ok_or(RpcError::UnknownRequest(id))?;
+        if let PendingState::Waiting = pending.state {
+            return Ok(None);
+        }
+        match self.waiting.remove(&id).map(|pending| pending.state) {
+            Some(PendingState::Ready(response)) => Ok(Some(response)),
+            _ => Err(RpcError::Disconnected),
+        }
+    }
+
+    /// Fails requests still waiting for responses from lost connections.
+    fn fail_lost(&mut self) {
+        let current = self.handle.connection();
+        let online = self.handle.is_online();
+        for pending in self.waiting.values_mut() {
+            if let PendingState::Waiting = pending.state {
+                if !online || pending.connection != current {
+                    pending.state = PendingState::Disconnected;
+                }
+            }
+        }
+    }
+
+    fn dispatch(&mut self, msg: In<T>) {
+        match msg.into_rpc() {
+            Rpc::Message(msg) => self.inbox.push_back(Incoming::Message(msg)),
+            Rpc::Request(id, request) => self.inbox.push_back(Incoming::Request(id, request)),
+            Rpc::Response(id, response) => match self.waiting.get_mut(&id) {
+                Some(Pending {
+                    state: state @ PendingState::Waiting,
+                    ..
+                }) => *state = PendingState::Ready(response),
+                Some(_) => eprintln!("Bridge RPC: late response to request {}", id),
+                None => eprintln!("Bridge RPC: response to unknown request {}", id),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::{free_addr, wait_for};
+    use std::{sync::atomic::AtomicBool, thread};
+
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+    enum Question {
+        IsOnline(u32),
+    }
+
+    #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+    enum Answer {
+        Online { map_pid: u16 },
+        Offline,
+    }
+
+    type WebMsg = Rpc<String, Question, ()>;
+    type DllMsg = Rpc<String, (), Answer>;
+    type Web = RpcHandle<BridgeClient<DllMsg, WebMsg>>;
+    type Dll = RpcHandle<BridgeServer<WebMsg, DllMsg>>;
+
+    fn protocol() -> Protocol {
+        Protocol::new(0x7E57, 1)
+    }
+
+    fn connect() -> (Web, Dll) {
+        connect_to(free_addr())
+    }
+
+    fn connect_to(addr: SocketAddr) -> (Web, Dll) {
+        let dll = RpcHandle::new(BridgeHandle::start(addr, protocol()));
+        let mut web: Web = RpcHandle::new(BridgeHandle::start(addr, protocol()));
+        wait_for(|| web.handle().is_online());
+        (web, dll)
+    }
+
+    fn answer(dll: &mut Dll, stop: &AtomicBool) {
+        while !stop.load(Ordering::Relaxed) {
+            let incoming: Vec<_> = dll.receive().collect();
+            for msg in incoming {
+                match msg {
+                    Incoming::Request(id, Question::IsOnline(123)) => {
+                        dll.send("answering".into()).unwrap();
+                        dll.respond(id, Answer::Online { map_pid: 170 }).unwrap();
+                    }
+                    Incoming::Request(id, Question::IsOnline(_)) => {
+                        dll.respond(id, Answer::Offline).unwrap();
+                    }
+                    Incoming::Message(_) => {}
+                }
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn call_roundtrip() {
+        let (mut web, mut dll) = connect();
+        let stop = Arc::new(AtomicBool::new(false));
+        let responder = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                answer(&mut dll, &stop);
+                dll
+            })
+        };
+        let timeout = Duration::from_secs(5);
+
+        let answer = web.call(Question::IsOnline(123), timeout).unwrap();
+        assert_eq!(answer, Answer::Online { map_pid: 170 });
+        let answer = web.call(Question::IsOnline(1), timeout).unwrap();
+        assert_eq!(answer, Answer::Offline);
+
+        let first = web.request(Question::IsOnline(123)).unwrap();
+        let second = web.request(Question::IsOnline(2)).unwrap();
+        let mut responses = (None, None);
+        wait_for(|| {
+            if responses.0.is_none() {
+                responses.0 = web.try_response(first).unwrap();
+            }
+            if responses.1.is_none() {
+                responses.1 = web.try_response(second).unwrap();
+            }
+            responses.0.is_some() && responses.1.is_some()
+        });
+        assert_eq!(responses.0, Some(Answer::Online { map_pid: 170 }));
+        assert_eq!(responses.1, Some(Answer::Offline));
+
+        let messages: Vec<_> = web.receive().collect();
+        assert_eq!(
+            messages,
+            vec![Incoming::Message("answering".to_string()); 2]
+        );
+
+        stop.store(true, Ordering::Relaxed);
+        let dll = responder.join().unwrap();
+        web.finish(true).unwrap();
+        dll.finish(true).unwrap();
+    }
+
+    #[test]
+    fn call_timeout() {
+        let (mut web, dll) = connect();
+        match web.call(Question::IsOnline(123), Duration::from_millis(200)) {
+            Err(RpcError::Timeout) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        web.finish(true).unwrap();
+        dll.finish(true).unwrap();
+    }
+
+    #[test]
+    fn cancel_on_disconnect() {
+        let (mut web, dll) = connect();
+        let killer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            dll.finish(true).unwrap();
+        });
+        match web.call(Question::IsOnline(123), Duration::from_secs(10)) {
+            Err(RpcError::Disconnected) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+        killer.join().unwrap();
+        assert!(matches!(
+            web.request(Question::IsOnline(123)),
+            Err(RpcError::Bridge(BridgeError::NotOnline))
+        ));
+        web.finish(true).unwrap();
+    }
+
+    #[test]
+    fn reconnect_fails_only_lost_requests() {
+        let addr = free_addr();
+        let (mut web, dll) = connect_to(addr);
+        let lost = web.request(Question::IsOnline(123)).unwrap();
+        // Asking about an id that was never issued doesn't affect other requests
+        assert!(matches!(
+            web.try_response(lost + 100),
+            Err(RpcError::UnknownRequest(_))
+        ));
+        assert!(matches!(web.try_response(lost), Ok(None)));
+
+        let connection = web.handle().connection();
+        dll.finish(true).unwrap();
+        wait_for(|| !web.handle().is_online());
+        let mut dll: Dll = RpcHandle::new(BridgeHandle::start(addr, protocol()));
+        wait_for(|| web.handle().is_online());
+        assert!(web.handle().connection() > connection);
+
+        let fresh = web.request(Question::IsOnline(1)).unwrap();
+        assert!(matches!(
+            web.try_response(lost),
+            Err(RpcError::Disconnected)
+        ));
+        // Failed request is forgotten afterwards
+        assert!(matches!(
+            web.try_response(lost),
+            Err(RpcError::UnknownRequest(_))
+        ));
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let responder = {
+            let stop = stop.clone();
+            thread::spawn(move || {
+                answer(&mut dll, &stop);
+                dll
+            })
+        };
+        let mut response = None;
+        wait_for(|| {
+            response = web.try_response(fresh).unwrap();
+            response.is_some()
+        });
+        assert_eq!(response, Some(Answer::Offline));
+
+        stop.store(true, Ordering::Relaxed);
+        let dll = responder.join().unwrap();
+        web.finish(true).unwrap();
+        dll.finish(true).unwrap();
+    }
+}
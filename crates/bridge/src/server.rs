@@ -1,5 +1,4 @@
 use super::*;
-use bincode::{deserialize, deserialize_from, serialize, serialize_into};
 use std::net::{Shutdown, TcpListener};
 
 pub struct BridgeServer<MsgIn, MsgOut> {
@@ -34,14 +33,13 @@ impl<MIn: 'static + Send + DeserializeOwned + Debug, MOut: 'static + Send + Seri
         let timeout = Some(Duration::from_millis(2000));
 
         println!("Bridge server: listening...");
+        let accept_interval = worker.outbox().policy().accept_interval;
         let (mut stream, addr) = loop {
-            sleep(Duration::from_millis(100));
             match listener.accept() {
                 Ok(ok) => break ok,
                 Err(err) => match err.kind() {
                     std::io::ErrorKind::WouldBlock => {
-                        let msg_out = worker.try_receive()?;
-                        if let Some(BridgeMessage::Shutdown) = msg_out {
+                        if worker.wait_shutdown(accept_interval) {
                             println!("Bridge server writer: shutdown");
                             return Ok(());
                         }
@@ -58,8 +56,8 @@ impl<MIn: 'static + Send + DeserializeOwned + Debug, MOut: 'static + Send + Seri
         stream.set_write_timeout(timeout).map_err(BridgeError::Io)?;
 
         println!("Bridge server: handshake");
-        serialize_into(&mut stream, &worker.handshake()).map_err(BridgeError::BinCode)?;
-        let handshake = deserialize_from(&mut stream).map_err(BridgeError::BinCode)?;
+        frame::write(&mut stream, &worker.handshake())?;
+        let handshake = frame::read_handshake(&mut stream)?;
         worker.check_handshake(handshake)?;
 
         worker.task().stream = Some(stream.try_clone().map_err(BridgeError::Io)?);
@@ -67,16 +65,17 @@ impl<MIn: 'static + Send + DeserializeOwned + Debug, MOut: 'static + Send + Seri
         println!("Bridge server: online");
 
         let sender = worker.sender();
-        let mut reader = BufReader::new(stream.try_clone().map_err(BridgeError::Io)?);
-        worker.spawn_reader(move || with_bincode::reader(reader, sender));
+        let outbox = worker.outbox();
+        let reader = BufReader::new(stream.try_clone().map_err(BridgeError::Io)?);
+        worker.spawn_reader(move || with_bincode::reader(reader, sender, outbox));
 
         loop {
-            let msg_out = worker.receive()?;
+            let msg_out = worker.receive();
             if let BridgeMessage::Shutdown = msg_out {
                 println!("Bridge server writer: shutdown");
                 return Ok(());
             }
-            serialize_into(&mut stream, &msg_out).map_err(BridgeError::BinCode)?;
+            frame::write(&mut stream, &msg_out)?;
             if let BridgeMessage::Hang = msg_out {
                 println!("Bridge server writer: hang");
                 return Err(BridgeError::Hang);
@@ -1,15 +1,23 @@
 use super::*;
-use bincode::deserialize_from;
-pub fn reader<M: DeserializeOwned + Debug, S: Channel<M>>(
+
+pub fn reader<M: DeserializeOwned + Debug, S: Channel<M>, O>(
     mut stream: BufReader<TcpStream>,
     sender: S,
+    outbox: Arc<Outbox<O>>,
 ) -> Result<(), BridgeError> {
     loop {
-        /*use byteorder::{ReadBytesExt, BE};
-        stream.fill_buf()
-        let len = stream.read_u32::<BE>().map_err(BridgeError::Io)?;*/
-
-        let msg_in = deserialize_from(&mut stream).map_err(BridgeError::BinCode)?;
+        let msg_in = frame::read_known(&mut stream)?;
+        match msg_in {
+            BridgeMessage::Ping => {
+                outbox.push(BridgeMessage::Pong);
+                continue;
+            }
+            BridgeMessage::Pong => {
+                outbox.pong_received();
+                continue;
+            }
+            _ => {}
+        }
         let hang = if let &BridgeMessage::Hang = &msg_in {
             true
         } else {
@@ -4,43 +4,46 @@ pub struct BridgeWorker<
     T: BridgeTask,
     S: Channel<<T as BridgeTask>::MsgIn> = DefaultSender<<T as BridgeTask>::MsgIn>,
 > {
-    receiver: Receiver<BridgeMessage<T::MsgOut>>,
+    outbox: Arc<Outbox<T::MsgOut>>,
     sender: S,
-    service: Sender<BridgeMessage<T::MsgOut>>,
     addr: SocketAddr,
     thread: Option<JoinHandle<Result<(), BridgeError>>>,
     online: Arc<AtomicBool>,
-    handshake: u16,
-    version: u16,
+    connection: Arc<AtomicU32>,
+    protocol: Protocol,
+    session: SessionHandle,
     task: T,
+    attempt: u32,
 }
 
 impl<T: BridgeTask, S: Channel<<T as BridgeTask>::MsgIn>> BridgeWorker<T, S> {
     pub(super) fn new(
-        receiver: Receiver<BridgeMessage<T::MsgOut>>,
+        outbox: Arc<Outbox<T::MsgOut>>,
         sender: S,
-        service: Sender<BridgeMessage<T::MsgOut>>,
         addr: SocketAddr,
-        handshake: u16,
-        version: u16,
+        protocol: Protocol,
     ) -> Self {
         BridgeWorker {
-            receiver,
+            outbox,
             sender,
-            service,
             addr,
             thread: None,
             online: Arc::new(AtomicBool::new(false)),
-            handshake,
-            version,
+            connection: Arc::new(AtomicU32::new(0)),
+            protocol,
+            session: Arc::new(Mutex::new(None)),
             task: T::new(),
+            attempt: 0,
         }
     }
     pub(super) fn thread(mut self) {
         loop {
             println!("Bridge worker thread: starting process");
             let res = T::process(&mut self);
-            self.online.store(false, Ordering::Relaxed);
+            if self.online.swap(false, Ordering::Relaxed) {
+                self.outbox.policy().report(BridgeEvent::Disconnected);
+            }
+            *self.session.lock() = None;
             println!("Bridge worker thread: offline");
             self.task.shutdown();
             println!("Bridge worker thread: task shutted down");
@@ -63,15 +66,13 @@ impl<T: BridgeTask, S: Channel<<T as BridgeTask>::MsgIn>> BridgeWorker<T, S> {
                 }
                 Err(err) => {
                     eprintln!("Bridge worker thread: {:?}", err);
-                    sleep(Duration::from_millis(500));
-                    eprintln!("Bridge worker slept");
-                    for msg in self.receiver.try_iter() {
-                        eprintln!("msg: {:?}", &msg);
-                        if let msg = BridgeMessage::<T>::Shutdown {
-                            eprintln!("Bridge worker returning");
-                            return;
-                        }
+                    let delay = self.outbox.policy().backoff.delay(self.attempt);
+                    self.attempt = self.attempt.saturating_add(1);
+                    if self.outbox.wait_shutdown(delay) {
+                        eprintln!("Bridge worker returning");
+                        return;
                     }
+                    eprintln!("Bridge worker slept {:?}", delay);
                 }
             }
         }
@@ -83,20 +84,24 @@ impl<T: BridgeTask, S: Channel<<T as BridgeTask>::MsgIn>> BridgeWorker<T, S> {
         &mut self,
         mut f: F,
     ) {
-        let service = self.service.clone();
+        let outbox = self.outbox();
         self.thread = Some(std::thread::spawn(move || {
             let res = f();
             if res.is_err() {
                 eprintln!("Reader thread: {:?}", res);
             }
             println!("spawn_reader");
-            service.send(BridgeMessage::Hang);
+            outbox.push(BridgeMessage::Hang);
             println!("reader return");
             res
         }));
     }
     pub fn set_online(&mut self) {
+        self.attempt = 0;
+        self.outbox.reset_control();
+        self.connection.fetch_add(1, Ordering::Relaxed);
         self.online.store(true, Ordering::Relaxed);
+        self.outbox.policy().report(BridgeEvent::Connected);
     }
     pub fn online(&mut self) -> bool {
         self.online.load(Ordering::Relaxed)
@@ -104,36 +109,47 @@ impl<T: BridgeTask, S: Channel<<T as BridgeTask>::MsgIn>> BridgeWorker<T, S> {
     pub fn online_handle(&self) -> Arc<AtomicBool> {
         Arc::clone(&self.online)
     }
+    pub fn connection_handle(&self) -> Arc<AtomicU32> {
+        Arc::clone(&self.connection)
+    }
+
+    pub fn session_handle(&self) -> SessionHandle {
+        Arc::clone(&self.session)
+    }
 
     pub fn handshake(&self) -> BridgeMessage<T::MsgOut> {
-        BridgeMessage::<T::MsgOut>::Handshake(self.handshake, self.version)
+        BridgeMessage::<T::MsgOut>::Handshake(self.protocol.clone())
     }
     pub fn check_handshake(&self, handshake: BridgeMessage<T::MsgIn>) -> Result<(), BridgeError> {
         match handshake {
-            BridgeMessage::<T::MsgIn>::Handshake(handshake, version) => {
-                if handshake != self.handshake || version != self.version {
-                    Err(BridgeError::Handshake(handshake, version))
-                } else {
-                    Ok(())
-                }
+            BridgeMessage::<T::MsgIn>::Handshake(peer) => {
+                let session = self.protocol.negotiate(&peer)?;
+                println!(
+                    "Bridge: negotiated version {}, capabilities: {:?}",
+                    session.version, session.capabilities
+                );
+                *self.session.lock() = Some(session);
+                Ok(())
             }
-            msg => Err(BridgeError::NoHandshake),
+            _ => Err(BridgeError::NoHandshake),
         }
     }
     pub fn sender(&self) -> S {
         self.sender.clone()
     }
-    pub fn receive(&mut self) -> Result<BridgeMessage<T::MsgOut>, BridgeError> {
-        self.receiver
-            .recv()
-            .map_err(|_| BridgeError::ChannelDropped)
+    pub fn outbox(&self) -> Arc<Outbox<T::MsgOut>> {
+        Arc::clone(&self.outbox)
     }
-    pub fn try_receive(&mut self) -> Result<Option<BridgeMessage<T::MsgOut>>, BridgeError> {
-        match self.receiver.try_recv() {
-            Err(std::sync::mpsc::TryRecvError::Empty) => Ok(None),
-            Err(_) => Err(BridgeError::ChannelDropped),
-            Ok(ok) => Ok(Some(ok)),
-        }
+    /// Blocks until there is a message to send.
+    pub fn receive(&mut self) -> BridgeMessage<T::MsgOut> {
+        self.outbox.pop()
+    }
+    pub fn is_shutdown(&self) -> bool {
+        self.outbox.is_shutdown()
+    }
+    /// Sleeps for `timeout` unless shutdown is requested, returns `true` on shutdown.
+    pub fn wait_shutdown(&self, timeout: Duration) -> bool {
+        self.outbox.wait_shutdown(timeout)
     }
     pub fn task(&mut self) -> &mut T {
         &mut self.task
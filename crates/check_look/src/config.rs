@@ -0,0 +1,298 @@
+//! `check_look` section of `ServerConfig.toml`.
+use derivative::Derivative;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub mod defaults {
+    pub const MAP_UTILITY_START: u16 = 92;
+    pub const NPC_FAST_FROM: u16 = 2200;
+    pub const NPC_FAST_TO: u16 = u16::MAX;
+
+    pub const DEFAULT_BONUS: u32 = 10;
+    pub const DEFAULT_PERCEPTION: u32 = 5;
+
+    pub const VIEW_BONUS: u32 = 10;
+    pub const VIEW_PERCEPTION: u32 = 5;
+
+    pub const HEAR_BONUS: u32 = 5;
+    pub const HEAR_BONUS_NPC: u32 = 25;
+    pub const HEAR_PERCEPTION: u32 = 2;
+
+    pub const DIR_RATE_DEFAULT: [f32; 4] = [1.0; 4];
+    //                                    0    1    2    3
+    pub const DIR_RATE_VIEW: [f32; 4] = [1.0, 0.8, 0.5, 0.4];
+    pub const DIR_RATE_HEAR: [f32; 4] = [0.8, 1.0, 0.8, 0.8];
+
+    pub const MOVING_DEFAULT: f32 = 1.0;
+    pub const MOVING_SELF_RUN: f32 = 0.8;
+    pub const MOVING_TARGET_RUN: f32 = 3.0;
+
+    pub const WALL_RATE_DEFAULT: [f32; 10] = [0.0; 10];
+    pub const WALL_RATE_HEAR: [f32; 10] = [
+        0.1, 0.1, 0.1, 0.1, // 1..=4
+        0.3, 0.3, 0.3, 0.3, // 5..=8
+        0.4, 0.4, // 9..=10
+    ];
+}
+use defaults::*;
+
+#[derive(Deserialize, Serialize, Derivative)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct CheckLook {
+    pub npc_fast: NpcFast,
+    #[derivative(Default(value = "vec![SenseRates::default_view(), SenseRates::default_hear()]"))]
+    pub senses: Vec<SenseRates>,
+    #[derivative(Default(value = "MAP_UTILITY_START"))]
+    pub map_utility_start: u16,
+}
+
+impl CheckLook {
+    /// Checks what `check_look` relies on, beyond TOML syntax.
+    pub fn validate(&self) -> Result<(), InvalidConfig> {
+        if self.senses.is_empty() {
+            return Err(InvalidConfig::NoSenses);
+        }
+        let npc_fast = &self.npc_fast;
+        if npc_fast.sense_index >= self.senses.len() {
+            return Err(InvalidConfig::FastSenseIndex {
+                index: npc_fast.sense_index,
+                senses: self.senses.len(),
+            });
+        }
+        if npc_fast.fast_from > npc_fast.fast_to {
+            return Err(InvalidConfig::FastRange {
+                from: npc_fast.fast_from,
+                to: npc_fast.fast_to,
+            });
+        }
+        for (index, sense) in self.senses.iter().enumerate() {
+            sense.validate(&format!("check_look.senses[{}]", index))?;
+        }
+        Ok(())
+    }
+}
+
+impl SenseRates {
+    fn validate(&self, path: &str) -> Result<(), InvalidConfig> {
+        let rate = |name: String, value: f32, max: f32| {
+            if value.is_finite() && (0.0..=max).contains(&value) {
+                Ok(())
+            } else {
+                Err(InvalidConfig::Rate {
+                    path: format!("{}.{}", path, name),
+                    value,
+                    max,
+                })
+            }
+        };
+        for (i, &value) in self.dir_rate.iter().enumerate() {
+            rate(format!("dir_rate[{}]", i), value, f32::MAX)?;
+        }
+        for (name, moving) in &[
+            ("self_moving", &self.self_moving),
+            ("target_moving", &self.target_moving),
+        ] {
+            rate(format!("{}.still", name), moving.still, f32::MAX)?;
+            rate(format!("{}.walking", name), moving.walking, f32::MAX)?;
+            rate(format!("{}.running", name), moving.running, f32::MAX)?;
+        }
+        // Walls can only make things worse
+        for (i, &value) in self.wall_rate.iter().enumerate() {
+            rate(format!("wall_rate[{}]", i), value, 1.0)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InvalidConfig {
+    NoSenses,
+    FastSenseIndex {
+        index: usize,
+        senses: usize,
+    },
+    FastRange {
+        from: u16,
+        to: u16,
+    },
+    /// Rate is negative, not finite or greater than `max`.
+    Rate {
+        path: String,
+        value: f32,
+        max: f32,
+    },
+}
+
+impl fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidConfig::NoSenses => write!(f, "check_look.senses is empty"),
+            InvalidConfig::FastSenseIndex { index, senses } => write!(
+                f,
+                "check_look.npc_fast.sense_index is {}, but there are only {} senses",
+                index, senses
+            ),
+            InvalidConfig::FastRange { from, to } => write!(
+                f,
+                "check_look.npc_fast.fast_from {} is greater than fast_to {}",
+                from, to
+            ),
+            InvalidConfig::Rate { path, value, max } if *max == f32::MAX => {
+                write!(f, "{} is {}, expected non-negative rate", path, value)
+            }
+            InvalidConfig::Rate { path, value, max } => {
+                write!(f, "{} is {}, expected rate in 0..={}", path, value, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidConfig {}
+
+#[derive(Deserialize, Serialize, Derivative)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct SenseRates {
+    pub player: CritterRates,
+    pub npc: CritterRates,
+    #[derivative(Default(value = "DIR_RATE_DEFAULT"))]
+    pub dir_rate: [f32; 4],
+    pub self_moving: MovingRates,
+    pub target_moving: MovingRates,
+    #[derivative(Default(value = "WALL_RATE_DEFAULT"))]
+    pub wall_rate: [f32; 10],
+}
+impl SenseRates {
+    fn default_view() -> Self {
+        Self {
+            dir_rate: DIR_RATE_VIEW,
+            ..Default::default()
+        }
+    }
+    fn default_hear() -> Self {
+        Self {
+            player: CritterRates::default_hear_player(),
+            npc: CritterRates::default_hear_npc(),
+            dir_rate: DIR_RATE_HEAR,
+            self_moving: MovingRates::default_hear_self(),
+            target_moving: MovingRates::default_hear_target(),
+            wall_rate: WALL_RATE_HEAR,
+        }
+    }
+}
+
+/*
+#[derivative(Default(value = "VIEW_BONUS"))]
+    pub view_bonus: u32,
+    #[derivative(Default(value = "VIEW_PERCEPTION"))]
+    pub view_perception: u32,
+    #[derivative(Default(value = "HEAR_BONUS"))]
+    pub hear_bonus: u32,
+    #[derivative(Default(value = "HEAR_PERCEPTION"))]
+    pub hear_perception: u32,
+    */
+
+#[derive(Deserialize, Serialize, Derivative)]
+#[derivative(Default)]
+#[serde(default)]
+pub struct NpcFast {
+    #[derivative(Default(value = "true"))]
+    pub enable: bool,
+    #[derivative(Default(value = "0"))]
+    pub sense_index: usize,
+    #[derivative(Default(value = "NPC_FAST_FROM"))]
+    pub fast_from: u16,
+    #[derivative(Default(value = "NPC_FAST_TO"))]
+    pub fast_to: u16,
+}
+
+#[derive(Deserialize, Serialize, Derivative)]
+#[derivative(Default)]
+pub struct CritterRates {
+    #[derivative(Default(value = "DEFAULT_BONUS"))]
+    pub basic_bonus: u32,
+    #[derivative(Default(value = "DEFAULT_PERCEPTION"))]
+    pub basic_perception_rate: u32,
+}
+impl CritterRates {
+    const fn default_hear_npc() -> Self {
+        Self {
+            basic_bonus: HEAR_BONUS_NPC,
+            basic_perception_rate: HEAR_PERCEPTION,
+        }
+    }
+    const fn default_hear_player() -> Self {
+        Self {
+            basic_bonus: HEAR_BONUS,
+            basic_perception_rate: HEAR_PERCEPTION,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Derivative)]
+#[derivative(Default)]
+pub struct MovingRates {
+    #[derivative(Default(value = "MOVING_DEFAULT"))]
+    pub still: f32,
+    #[derivative(Default(value = "MOVING_DEFAULT"))]
+    pub walking: f32,
+    #[derivative(Default(value = "MOVING_DEFAULT"))]
+    pub running: f32,
+}
+impl MovingRates {
+    fn default_hear_self() -> Self {
+        MovingRates {
+            running: MOVING_SELF_RUN,
+            ..Default::default()
+        }
+    }
+    fn default_hear_target() -> Self {
+        MovingRates {
+            running: MOVING_TARGET_RUN,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let mut config = CheckLook::default();
+        assert_eq!(config.validate(), Ok(()));
+        config.senses[1].wall_rate[9] = 1.5;
+        assert_eq!(
+            config.validate(),
+            Err(InvalidConfig::Rate {
+                path: "check_look.senses[1].wall_rate[9]".into(),
+                value: 1.5,
+                max: 1.0
+            })
+        );
+        config.senses[1].wall_rate[9] = 0.4;
+        config.senses[0].target_moving.running = f32::NAN;
+        assert!(matches!(config.validate(), Err(InvalidConfig::Rate { .. })));
+        config.senses[0].target_moving.running = 1.0;
+        config.npc_fast.fast_to = 100;
+        assert_eq!(
+            config.validate(),
+            Err(InvalidConfig::FastRange {
+                from: 2200,
+                to: 100
+            })
+        );
+        config.npc_fast.sense_index = 2;
+        assert_eq!(
+            config.validate(),
+            Err(InvalidConfig::FastSenseIndex {
+                index: 2,
+                senses: 2
+            })
+        );
+        config.senses.clear();
+        assert_eq!(config.validate(), Err(InvalidConfig::NoSenses));
+    }
+}
@@ -0,0 +1,210 @@
+//! Who sees and hears whom: engine's `check_look` over traits, so it can be run
+//! by the server with engine critters and by tools with simulated ones.
+pub mod config;
+pub mod sim;
+
+use config::{CheckLook, CritterRates, MovingRates, SenseRates};
+use hex_geometry::{dirs_count, get_direction, get_distance_hex, get_hex_in_path, HexGrid};
+use primitives::Hex;
+
+pub const MIN_PERCEPTION: u32 = 1;
+pub const MAX_PERCEPTION: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Moving {
+    Still,
+    Walking,
+    Running,
+}
+
+/// Critter, as `check_look` sees it.
+pub trait LookCritter {
+    fn hex(&self) -> Hex;
+    fn dir(&self) -> u8;
+    fn proto_id(&self) -> u16;
+    fn is_player(&self) -> bool;
+    fn is_npc(&self) -> bool {
+        !self.is_player()
+    }
+    fn is_dead(&self) -> bool;
+    fn have_gm_vision(&self) -> bool;
+    /// `QST_VISION` param.
+    fn vision(&self) -> u32;
+    /// `QST_INVIS` param.
+    fn invis(&self) -> u32;
+    /// Effective perception, `1..=10`.
+    fn perception(&self) -> u32;
+    fn moving(&self) -> Moving;
+}
+
+/// Map, as `check_look` sees it.
+pub trait LookMap: HexGrid {
+    fn proto_id(&self) -> u16;
+}
+
+/// Why `cr` does or doesn't notice `opponent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Look {
+    /// Decided by utility map rule, vision and invisibility params.
+    Forced(bool),
+    /// Dead npcs notice nobody.
+    Dead,
+    /// Decided by distance alone, for npcs in `npc_fast` range.
+    Fast(bool),
+    /// Bit mask of senses, by index in config, that noticed the opponent.
+    Senses(u32),
+}
+
+impl Look {
+    pub fn is_noticed(self) -> bool {
+        match self {
+            Look::Forced(noticed) | Look::Fast(noticed) => noticed,
+            Look::Dead => false,
+            Look::Senses(mask) => mask != 0,
+        }
+    }
+    pub fn sense(self, index: usize) -> bool {
+        match self {
+            Look::Senses(mask) => index < 32 && mask & (1 << index) != 0,
+            _ => false,
+        }
+    }
+}
+
+/// Difference between direction of `cr` and direction towards `target`, `0..=3`.
+/// Square maps have 8 directions, their `0..=4` differences are scaled to the same range.
+pub fn look_dir(cr_hex: Hex, cr_dir: u8, target: Hex, hexagonal: bool) -> usize {
+    let dirs = dirs_count(hexagonal) as i16;
+    let start_dir = get_direction(cr_hex, target, hexagonal);
+    let look_dir = (start_dir as i16 - cr_dir as i16).rem_euclid(dirs);
+    let look_dir = look_dir.min(dirs - look_dir) as usize;
+    if hexagonal {
+        look_dir
+    } else {
+        (look_dir * 3 + 2) / 4
+    }
+}
+
+fn basic_dist(rates: &CritterRates, perception: u32) -> u32 {
+    rates.basic_bonus + perception * rates.basic_perception_rate
+}
+
+fn moving_rate<C: LookCritter + ?Sized>(cr: &C, rates: &MovingRates) -> f32 {
+    match cr.moving() {
+        Moving::Running => rates.running,
+        // Engine doesn't tell walking critters apart yet
+        Moving::Walking | Moving::Still => rates.still,
+    }
+}
+
+/// Clear and behind-the-wall distances of the sense.
+fn sense_dists<C: LookCritter + ?Sized, O: LookCritter + ?Sized>(
+    sense: &SenseRates,
+    cr: &C,
+    opponent: &O,
+    perception: u32,
+    look_dir: usize,
+) -> (f32, f32) {
+    let critter_rates = if cr.is_npc() {
+        &sense.npc
+    } else {
+        &sense.player
+    };
+    let sense_mul = sense.dir_rate[look_dir]
+        * moving_rate(cr, &sense.self_moving)
+        * moving_rate(opponent, &sense.target_moving);
+    let clear_dist = basic_dist(critter_rates, perception) as f32 * sense_mul;
+    let wall_mul = sense.wall_rate[perception as usize - 1];
+    (clear_dist, clear_dist * wall_mul)
+}
+
+/// Port of `check_look_smart`, with the reason of the decision.
+pub fn look<M, C, O>(config: &CheckLook, map: &M, cr: &C, opponent: &O) -> Look
+where
+    M: LookMap + ?Sized,
+    C: LookCritter + ?Sized,
+    O: LookCritter + ?Sized,
+{
+    if map.proto_id() == config.map_utility_start
+        && opponent.is_player()
+        && cr.is_player()
+        && !cr.have_gm_vision()
+    {
+        return Look::Forced(false);
+    }
+
+    let cr_hex = cr.hex();
+    let opp_hex = opponent.hex();
+    let hexagonal = map.is_hexagonal();
+    let dist = get_distance_hex(cr_hex, opp_hex, hexagonal);
+
+    let cr_vision = cr.vision();
+    let opp_invis = opponent.invis();
+
+    if cr_vision >= dist && opp_invis <= dist {
+        return Look::Forced(true);
+    }
+    if opp_invis != 0 && (opp_invis - 1) < dist {
+        return Look::Forced(false);
+    }
+    if opp_invis > dist || cr_vision >= dist {
+        return Look::Forced(true);
+    }
+
+    let perception = cr.perception().clamp(MIN_PERCEPTION, MAX_PERCEPTION);
+
+    if cr.is_npc() {
+        if cr.is_dead() {
+            return Look::Dead;
+        }
+        let npc_fast = &config.npc_fast;
+        let proto_id = cr.proto_id();
+        if npc_fast.enable && proto_id >= npc_fast.fast_from && proto_id <= npc_fast.fast_to {
+            let fast_dist = config
+                .senses
+                .get(npc_fast.sense_index)
+                .map(|sense| basic_dist(&sense.npc, perception))
+                .unwrap_or(0);
+            return Look::Fast(fast_dist >= dist);
+        }
+    }
+
+    let look_dir = look_dir(cr_hex, cr.dir(), opp_hex, hexagonal);
+    let senses: Vec<(f32, f32)> = config
+        .senses
+        .iter()
+        .map(|sense| sense_dists(sense, cr, opponent, perception, look_dir))
+        .collect();
+
+    let max_dist = senses
+        .iter()
+        .map(|&(clear_dist, _wall_dist)| clear_dist as u32)
+        .max()
+        .unwrap_or(0);
+    if dist > max_dist {
+        return Look::Senses(0);
+    }
+
+    let end_hex = get_hex_in_path(map, cr_hex, opp_hex, 0.0, dist);
+    let blocked = dist > get_distance_hex(cr_hex, end_hex, hexagonal);
+    let mask = senses
+        .iter()
+        .enumerate()
+        .take(32)
+        .filter(|(_, &(clear_dist, wall_dist))| {
+            let sense_dist = if blocked { wall_dist } else { clear_dist };
+            sense_dist as u32 >= dist
+        })
+        .fold(0, |mask, (index, _)| mask | 1 << index);
+    Look::Senses(mask)
+}
+
+/// Does `cr` notice `opponent`, port of `check_look_smart`.
+pub fn check_look<M, C, O>(config: &CheckLook, map: &M, cr: &C, opponent: &O) -> bool
+where
+    M: LookMap + ?Sized,
+    C: LookCritter + ?Sized,
+    O: LookCritter + ?Sized,
+{
+    look(config, map, cr, opponent).is_noticed()
+}
@@ -0,0 +1,401 @@
+//! Simulated critters and maps, to see what a config does without running the server.
+use super::{look, Look, LookCritter, LookMap, Moving};
+use crate::config::CheckLook;
+use hex_geometry::{get_distance_hex, grid::*, BlockerGrid, HexGrid};
+use primitives::Hex;
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub struct SimCritter {
+    pub hex: Hex,
+    pub dir: u8,
+    pub proto_id: u16,
+    pub npc: bool,
+    pub dead: bool,
+    pub gm_vision: bool,
+    pub vision: u32,
+    pub invis: u32,
+    pub perception: u32,
+    pub moving: Moving,
+}
+
+impl Default for SimCritter {
+    fn default() -> Self {
+        SimCritter {
+            hex: Hex::new(0, 0),
+            dir: 0,
+            proto_id: 0,
+            npc: false,
+            dead: false,
+            gm_vision: false,
+            vision: 0,
+            invis: 0,
+            perception: 5,
+            moving: Moving::Still,
+        }
+    }
+}
+
+impl LookCritter for SimCritter {
+    fn hex(&self) -> Hex {
+        self.hex
+    }
+    fn dir(&self) -> u8 {
+        self.dir
+    }
+    fn proto_id(&self) -> u16 {
+        self.proto_id
+    }
+    fn is_player(&self) -> bool {
+        !self.npc
+    }
+    fn is_dead(&self) -> bool {
+        self.dead
+    }
+    fn have_gm_vision(&self) -> bool {
+        self.gm_vision
+    }
+    fn vision(&self) -> u32 {
+        self.vision
+    }
+    fn invis(&self) -> u32 {
+        self.invis
+    }
+    fn perception(&self) -> u32 {
+        self.perception
+    }
+    fn moving(&self) -> Moving {
+        self.moving
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimMap {
+    pub grid: BlockerGrid,
+    pub proto_id: u16,
+}
+
+impl HexGrid for SimMap {
+    fn max_hex(&self) -> Hex {
+        self.grid.max_hex()
+    }
+    fn is_hex_passed(&self, hex: Hex) -> bool {
+        self.grid.is_hex_passed(hex)
+    }
+    fn is_hex_raked(&self, hex: Hex) -> bool {
+        self.grid.is_hex_raked(hex)
+    }
+    fn is_hex_critter(&self, hex: Hex) -> bool {
+        self.grid.is_hex_critter(hex)
+    }
+    fn is_hexagonal(&self) -> bool {
+        self.grid.is_hexagonal()
+    }
+}
+
+impl LookMap for SimMap {
+    fn proto_id(&self) -> u16 {
+        self.proto_id
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    UnknownChar {
+        line: usize,
+        column: usize,
+        ch: char,
+    },
+    ManyObservers,
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "grid is empty"),
+            GridError::UnknownChar { line, column, ch } => {
+                write!(f, "unknown char {:?} at {}:{}", ch, line, column)
+            }
+            GridError::ManyObservers => write!(f, "more than one observer `@` in grid"),
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// Text grid, one line per row of hexes:
+/// `.` - empty hex, `#` - wall, `o` - obstacle that can be seen through (fence, window),
+/// `@` - empty hex with the observer.
+pub fn parse_grid(text: &str, hexagonal: bool) -> Result<(BlockerGrid, Option<Hex>), GridError> {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .ok_or(GridError::Empty)?;
+    let max = Hex::new(width as u16, lines.len() as u16);
+    let mut grid = if hexagonal {
+        BlockerGrid::new(max)
+    } else {
+        BlockerGrid::square(max)
+    };
+    let mut observer = None;
+    for (y, line) in lines.iter().enumerate() {
+        for (x, ch) in line.chars().enumerate() {
+            let hex = Hex::new(x as u16, y as u16);
+            match ch {
+                '.' | ' ' => {}
+                '#' => grid.add_flags(hex, FH_WALL | FH_BLOCK | FH_NOTRAKE),
+                'o' => grid.add_flags(hex, FH_SCEN | FH_BLOCK),
+                '@' if observer.is_none() => observer = Some(hex),
+                '@' => return Err(GridError::ManyObservers),
+                ch => {
+                    return Err(GridError::UnknownChar {
+                        line: y + 1,
+                        column: x + 1,
+                        ch,
+                    })
+                }
+            }
+        }
+    }
+    Ok((grid, observer))
+}
+
+/// Farthest distances at which opponents are noticed, by look direction `0..=3`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Reach {
+    pub noticed: [u32; 4],
+    /// By sense index in config.
+    pub senses: Vec<[u32; 4]>,
+}
+
+/// `look` of the observer at the target placed in every hex of the map.
+#[derive(Debug, Clone)]
+pub struct Heatmap {
+    pub observer: Hex,
+    pub max: Hex,
+    /// By `y * max.x + x`, `None` for the observer's hex and hexes where target can't stand.
+    pub looks: Vec<Option<Look>>,
+    pub reach: Reach,
+}
+
+impl Heatmap {
+    pub fn scan(
+        config: &CheckLook,
+        map: &SimMap,
+        observer: &SimCritter,
+        target: &SimCritter,
+    ) -> Self {
+        let max = map.max_hex();
+        let mut looks = Vec::with_capacity(max.x as usize * max.y as usize);
+        let mut reach = Reach {
+            noticed: [0; 4],
+            senses: vec![[0; 4]; config.senses.len()],
+        };
+        let mut target = target.clone();
+        let hexagonal = map.is_hexagonal();
+        for y in 0..max.y {
+            for x in 0..max.x {
+                let hex = Hex::new(x, y);
+                if hex == observer.hex || !map.is_hex_passed(hex) {
+                    looks.push(None);
+                    continue;
+                }
+                target.hex = hex;
+                let look = look(config, map, observer, &target);
+                let dist = get_distance_hex(observer.hex, hex, hexagonal);
+                let look_dir = super::look_dir(observer.hex, observer.dir, hex, hexagonal);
+                if look.is_noticed() {
+                    reach.noticed[look_dir] = reach.noticed[look_dir].max(dist);
+                }
+                for (index, sense) in reach.senses.iter_mut().enumerate() {
+                    if look.sense(index) {
+                        sense[look_dir] = sense[look_dir].max(dist);
+                    }
+                }
+                looks.push(Some(look));
+            }
+        }
+        Heatmap {
+            observer: observer.hex,
+            max,
+            looks,
+            reach,
+        }
+    }
+    pub fn look(&self, hex: Hex) -> Option<Look> {
+        if hex.x < self.max.x && hex.y < self.max.y {
+            self.looks[hex.y as usize * self.max.x as usize + hex.x as usize]
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_geometry::HexExt;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct ServerConfig {
+        check_look: CheckLook,
+    }
+
+    fn example(name: &str) -> CheckLook {
+        let path = format!(
+            "{}/../../dll/server/config_examples/{}.ServerConfig.toml",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        let text = std::fs::read_to_string(path).unwrap();
+        toml::from_str::<ServerConfig>(&text).unwrap().check_look
+    }
+
+    fn open_field(size: u16) -> (SimMap, SimCritter) {
+        let map = SimMap {
+            grid: BlockerGrid::new(Hex::new(size, size)),
+            proto_id: 0,
+        };
+        let observer = SimCritter {
+            hex: Hex::new(size / 2, size / 2),
+            ..Default::default()
+        };
+        (map, observer)
+    }
+
+    #[test]
+    fn parse() {
+        let (grid, observer) = parse_grid("..#\n.@o\n", true).unwrap();
+        assert_eq!(grid.max_hex(), Hex::new(3, 2));
+        assert_eq!(observer, Some(Hex::new(1, 1)));
+        assert!(!grid.is_hex_passed(Hex::new(2, 0)) && !grid.is_hex_raked(Hex::new(2, 0)));
+        assert!(!grid.is_hex_passed(Hex::new(2, 1)) && grid.is_hex_raked(Hex::new(2, 1)));
+        assert_eq!(
+            parse_grid("..\n.x", true).unwrap_err(),
+            GridError::UnknownChar {
+                line: 2,
+                column: 2,
+                ch: 'x'
+            }
+        );
+        assert_eq!(parse_grid("\n\n", true).unwrap_err(), GridError::Empty);
+    }
+
+    #[test]
+    fn open_field_reach() {
+        let (map, observer) = open_field(81);
+        let target = SimCritter::default();
+        // Perception 5: vision 10 + 5 * 5 = 35, hearing 5 + 5 * 2 = 15 (tnf) or 5 + 5 * 3 = 20 (old_forp)
+        let tnf = Heatmap::scan(&example("tnf"), &map, &observer, &target);
+        assert_eq!(tnf.reach.senses[0], [35, 28, 17, 14]);
+        assert_eq!(tnf.reach.senses[1], [12, 15, 12, 12]);
+        assert_eq!(tnf.reach.noticed, [35, 28, 17, 14]);
+
+        let old_forp = Heatmap::scan(&example("old_forp"), &map, &observer, &target);
+        assert_eq!(old_forp.reach.senses[0], tnf.reach.senses[0]);
+        assert_eq!(old_forp.reach.senses[1], [16, 20, 16, 16]);
+        assert_eq!(old_forp.reach.noticed, [35, 28, 17, 16]);
+    }
+
+    #[test]
+    fn square_field_reach() {
+        let map = SimMap {
+            grid: BlockerGrid::square(Hex::new(81, 81)),
+            proto_id: 0,
+        };
+        let target = SimCritter::default();
+        let tnf = example("tnf");
+        for dir in 0..8 {
+            let observer = SimCritter {
+                hex: Hex::new(40, 40),
+                dir,
+                ..Default::default()
+            };
+            let heatmap = Heatmap::scan(&tnf, &map, &observer, &target);
+            assert_eq!(heatmap.reach.noticed, [35, 28, 17, 14], "dir {}", dir);
+        }
+        // Square distance is the larger of coordinate differences, the diagonal is 35 hexes away
+        let corner = Hex::new(75, 75);
+        let observer = SimCritter {
+            hex: Hex::new(40, 40),
+            dir: hex_geometry::get_direction(Hex::new(40, 40), corner, false),
+            ..Default::default()
+        };
+        let target = SimCritter {
+            hex: corner,
+            ..Default::default()
+        };
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Senses(0b01));
+    }
+
+    #[test]
+    fn walls_and_running() {
+        let grid = "\
+.........
+.........
+.........
+.........
+....#....
+....@....
+.........
+";
+        let (grid, observer_hex) = parse_grid(grid, true).unwrap();
+        let map = SimMap { grid, proto_id: 0 };
+        let observer = SimCritter {
+            hex: observer_hex.unwrap(),
+            ..Default::default()
+        };
+        // Behind the wall, straight up
+        let mut target = SimCritter {
+            hex: Hex::new(4, 0),
+            ..Default::default()
+        };
+        let tnf = example("tnf");
+        // Hearing through walls is at most 15 * 0.3 = 4.5 for perception 5
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Senses(0));
+        target.moving = Moving::Running;
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Senses(0b10));
+        assert_eq!(
+            look(&example("old_forp"), &map, &observer, &target),
+            Look::Senses(0b10)
+        );
+        target.hex = Hex::new(4, 6);
+        target.moving = Moving::Still;
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Senses(0b11));
+    }
+
+    #[test]
+    fn npc_fast_and_utility_map() {
+        let (mut map, mut observer) = open_field(81);
+        let target = SimCritter {
+            hex: Hex::new(40, 10),
+            ..Default::default()
+        };
+        let tnf = example("tnf");
+        let old_forp = example("old_forp");
+        // Straight behind, 30 hexes away: out of sight, but not for fast npcs
+        observer.dir = (observer.hex.get_direction(target.hex) + 3) % 6;
+        observer.npc = true;
+        observer.proto_id = 2200;
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Fast(true));
+        assert_eq!(look(&old_forp, &map, &observer, &target), Look::Senses(0));
+        observer.dead = true;
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Dead);
+
+        observer.npc = false;
+        observer.dead = false;
+        map.proto_id = tnf.map_utility_start;
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Forced(false));
+        observer.gm_vision = true;
+        observer.vision = 100;
+        assert_eq!(look(&tnf, &map, &observer, &target), Look::Forced(true));
+    }
+}
@@ -0,0 +1,75 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::{path::Path, time::Duration};
+
+use clients_db::ClientsDb;
+
+#[path = "../src/fixture.rs"]
+mod fixture;
+use fixture::SaveFixture;
+
+const CLIENTS: usize = 5000;
+
+/// Generated save, so every file takes the full parsing path.
+fn template() -> Vec<u8> {
+    SaveFixture {
+        params: vec![(0, 5), (77, 10)],
+        ..Default::default()
+    }
+    .to_bytes()
+}
+
+fn synthetic_folder(count: usize) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().expect("Can't create temp dir");
+    let template = template();
+    for i in 0..count {
+        write_client(dir.path(), i, &template);
+    }
+    dir
+}
+
+fn write_client(dir: &Path, index: usize, data: &[u8]) {
+    let path = dir.join(format!("client_{}.client", index));
+    std::fs::write(path, data).expect("Can't write client file");
+}
+
+fn bench_clients_db(c: &mut Criterion) {
+    let dir = synthetic_folder(CLIENTS);
+    let path = dir.path().to_path_buf();
+    let db = ClientsDb::new(&path);
+    assert_eq!(db.clients().len(), CLIENTS);
+    assert!(
+        db.clients().values().all(|record| record.info.is_some()),
+        "synthetic saves must parse"
+    );
+
+    let mut group = c.benchmark_group("clients_db");
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(10));
+
+    group.bench_function("full_scan", |b| b.iter(|| ClientsDb::new(black_box(&path))));
+
+    group.bench_function("refresh_unchanged", |b| {
+        b.iter(|| db.refreshed(black_box(&path)).unwrap())
+    });
+
+    let template = template();
+    let changed = SaveFixture {
+        time_events: vec![[1, 0, 0, 0]],
+        ..Default::default()
+    }
+    .to_bytes();
+    let mut flip = false;
+    group.bench_function("refresh_one_changed", |b| {
+        b.iter(|| {
+            // alternate size, so change is noticed even within mtime granularity
+            write_client(&path, 0, if flip { &template } else { &changed });
+            flip = !flip;
+            db.refreshed(black_box(&path)).unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_clients_db);
+criterion_main!(benches);
@@ -1,18 +1,22 @@
 use std::{
     collections::{btree_map::Entry, BTreeMap},
+    ffi::OsStr,
     io,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::Arc,
 };
 
 use crate::{
     fix_encoding::{decode_filename, os_str_debug},
-    not_found, ClientRecord, InnerCritter,
+    not_found,
+    query::{ClientsIndex, Query},
+    ClientRecord, InnerCritter,
 };
 
 #[derive(Default, Debug)]
 pub struct ClientsDb {
     clients: BTreeMap<String, ClientRecord>,
+    index: ClientsIndex,
 }
 
 impl ClientsDb {
@@ -23,9 +27,33 @@ impl ClientsDb {
         db.update_clients(&path, true).expect("Can't load clients");
         db
     }
+    #[cfg(test)]
+    pub(crate) fn from_records(records: impl Iterator<Item = (String, ClientRecord)>) -> Self {
+        let clients: BTreeMap<_, _> = records.collect();
+        let index = ClientsIndex::new(&clients);
+        ClientsDb { clients, index }
+    }
     pub fn clients(&self) -> &BTreeMap<String, ClientRecord> {
         &self.clients
     }
+    pub fn index(&self) -> &ClientsIndex {
+        &self.index
+    }
+    /// Loaded clients matching the query, ordered by name.
+    pub fn query(&self, query: &Query) -> Vec<(&str, &InnerCritter)> {
+        let records: Vec<_> = match query.candidates(&self.index) {
+            Some(names) => names
+                .into_iter()
+                .filter_map(|name| self.clients.get_key_value(name))
+                .filter_map(with_info)
+                .collect(),
+            None => self.clients.iter().filter_map(with_info).collect(),
+        };
+        records
+            .into_iter()
+            .filter(|(_, info)| query.matches(info))
+            .collect()
+    }
     pub fn list_names(path: PathBuf) -> BTreeMap<u32, String> {
         let mut db = ClientsDb {
             ..Default::default()
@@ -80,6 +108,7 @@ impl ClientsDb {
             }
         }
     }
+    /// Rescans the folder, only files with changed mtime or size are parsed again.
     pub fn update_clients(&mut self, path: &PathBuf, load_clients_info: bool) -> io::Result<()> {
         let mut clients: BTreeMap<String, ClientRecord> = BTreeMap::new();
 
@@ -90,10 +119,7 @@ impl ClientsDb {
             .filter_map(|path| {
                 path.file_stem().and_then(|stem| {
                     decode_filename(stem).map(|nickname| {
-                        let mut record = ClientRecord::new(stem);
-                        if load_clients_info {
-                            let _ = record.update_info(path.clone(), nickname.clone());
-                        }
+                        let record = self.load_record(&path, stem, &nickname, load_clients_info);
                         (nickname, record)
                     })
                 })
@@ -114,9 +140,39 @@ impl ClientsDb {
                 }
             };
         }
+        self.index = ClientsIndex::new(&clients);
         self.clients = clients;
         Ok(())
     }
+    /// Copy of the database with changes in the folder applied.
+    pub fn refreshed(&self, path: &PathBuf) -> io::Result<Self> {
+        let mut db = ClientsDb {
+            clients: self.clients.clone(),
+            ..Default::default()
+        };
+        db.update_clients(path, true)?;
+        Ok(db)
+    }
+    fn load_record(
+        &self,
+        path: &Path,
+        stem: &OsStr,
+        nickname: &str,
+        load_clients_info: bool,
+    ) -> ClientRecord {
+        if !load_clients_info {
+            return ClientRecord::new(stem);
+        }
+        let old = self.clients.get(nickname);
+        if let (Some(old), Ok(metadata)) = (old, path.metadata()) {
+            if old.is_fresh(stem, &metadata) {
+                return old.clone();
+            }
+        }
+        let mut record = ClientRecord::new(stem);
+        let _ = record.update_info(path.to_owned(), nickname.to_owned());
+        record
+    }
     pub fn client_info(&self, name: &str) -> io::Result<InnerCritter> {
         if let Some(record) = self.clients.get(name) {
             record.info()
@@ -125,3 +181,73 @@ impl ClientsDb {
         }
     }
 }
+
+fn with_info<'a>(
+    (name, record): (&'a String, &'a ClientRecord),
+) -> Option<(&'a str, &'a InnerCritter)> {
+    record.info.as_ref().map(|info| (name.as_str(), info))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixture::SaveFixture;
+
+    fn write(dir: &Path, name: &str, save: SaveFixture) {
+        std::fs::write(dir.join(name).with_extension("client"), save.to_bytes()).unwrap();
+    }
+
+    #[test]
+    fn incremental_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_path_buf();
+        let save = |id, map_pid| SaveFixture {
+            id,
+            map: (1, map_pid),
+            ..Default::default()
+        };
+        write(&path, "Alice", save(1, 10));
+        write(&path, "Bob", save(2, 10));
+        write(&path, "Carol", save(3, 20));
+        std::fs::write(path.join("broken.client"), b"not a save").unwrap();
+
+        let db = ClientsDb::new(&path);
+        assert_eq!(db.clients().len(), 4);
+        assert_eq!(db.client_info("Alice").unwrap().id, 1);
+        assert!(db.client_info("broken").is_err());
+        assert_eq!(
+            db.index().by_map_pid(10).collect::<Vec<_>>(),
+            ["Alice", "Bob"]
+        );
+
+        // Changed size is noticed even if mtime stays the same
+        write(
+            &path,
+            "Alice",
+            SaveFixture {
+                time_events: vec![[1, 60, 0, 0]],
+                ..save(1, 20)
+            },
+        );
+        write(&path, "Dave", save(4, 10));
+        std::fs::remove_file(path.join("Bob.client")).unwrap();
+
+        let refreshed = db.refreshed(&path).unwrap();
+        let names: Vec<_> = refreshed.clients().keys().map(String::as_str).collect();
+        assert_eq!(names, ["Alice", "Carol", "Dave", "broken"]);
+        assert_eq!(refreshed.client_info("Alice").unwrap().map_pid, 20);
+        assert_eq!(refreshed.index().by_id(2), None);
+        assert_eq!(refreshed.index().by_id(4), Some("Dave"));
+        assert_eq!(
+            refreshed.index().by_map_pid(20).collect::<Vec<_>>(),
+            ["Alice", "Carol"]
+        );
+        // Unchanged record is reused, not parsed again
+        assert!(Arc::ptr_eq(
+            &db.client_info("Carol").unwrap(),
+            &refreshed.client_info("Carol").unwrap()
+        ));
+        // Original is left as it was
+        assert_eq!(db.client_info("Bob").unwrap().id, 2);
+    }
+}
@@ -0,0 +1,166 @@
+//! Groups of characters connected through shared play IPs.
+use std::{collections::BTreeMap, net::Ipv4Addr, time::Duration};
+
+use crate::{ClientRecord, ClientsDb, InnerCritter};
+
+#[derive(Debug, Clone)]
+pub struct IpCluster<'a> {
+    /// Characters ordered by name.
+    pub clients: Vec<(&'a str, &'a InnerCritter)>,
+    /// IPs used by at least two characters of the cluster.
+    pub shared_ips: Vec<Ipv4Addr>,
+}
+
+impl ClientsDb {
+    /// Characters sharing IPs, directly or through other characters. Only clients seen within
+    /// `max_age` are considered, if their save time is known. Singletons are skipped, bigger
+    /// clusters go first.
+    pub fn ip_clusters(&self, max_age: Option<Duration>) -> Vec<IpCluster<'_>> {
+        let recent = |record: &ClientRecord| match (max_age, record.modified) {
+            (Some(max_age), Some(modified)) => modified
+                .elapsed()
+                .map_or(true, |elapsed| elapsed <= max_age),
+            _ => true,
+        };
+        let clients: Vec<(&str, &InnerCritter)> = self
+            .clients()
+            .iter()
+            .filter(|(_, record)| recent(record))
+            .filter_map(|(name, record)| record.info.as_ref().map(|info| (name.as_str(), info)))
+            .collect();
+        let positions: BTreeMap<&str, usize> = clients
+            .iter()
+            .enumerate()
+            .map(|(pos, (name, _))| (*name, pos))
+            .collect();
+
+        let mut sets = DisjointSets::new(clients.len());
+        let mut shared_ips = vec![];
+        for (ip, names) in self.index().ips() {
+            let members: Vec<usize> = names
+                .iter()
+                .filter_map(|name| positions.get(name.as_str()).copied())
+                .collect();
+            if members.len() < 2 {
+                continue;
+            }
+            for pair in members.windows(2) {
+                sets.union(pair[0], pair[1]);
+            }
+            shared_ips.push((*ip, members[0]));
+        }
+
+        let mut groups: BTreeMap<usize, IpCluster> = BTreeMap::new();
+        for (pos, client) in clients.iter().enumerate() {
+            groups
+                .entry(sets.find(pos))
+                .or_insert_with(|| IpCluster {
+                    clients: vec![],
+                    shared_ips: vec![],
+                })
+                .clients
+                .push(*client);
+        }
+        for (ip, member) in shared_ips {
+            if let Some(group) = groups.get_mut(&sets.find(member)) {
+                group.shared_ips.push(ip);
+            }
+        }
+        let mut clusters: Vec<_> = groups
+            .into_iter()
+            .map(|(_, cluster)| cluster)
+            .filter(|cluster| cluster.clients.len() > 1)
+            .collect();
+        clusters.sort_by(|a, b| b.clients.len().cmp(&a.clients.len()));
+        clusters
+    }
+}
+
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets {
+            parents: (0..len).collect(),
+        }
+    }
+    fn find(&mut self, mut node: usize) -> usize {
+        while self.parents[node] != node {
+            self.parents[node] = self.parents[self.parents[node]];
+            node = self.parents[node];
+        }
+        node
+    }
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[a.max(b)] = a.min(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::query::test::record;
+    use std::time::SystemTime;
+
+    fn make_db(records: Vec<ClientRecord>) -> ClientsDb {
+        ClientsDb::from_records(
+            records
+                .into_iter()
+                .map(|record| (record.info.as_ref().unwrap().name.clone(), record)),
+        )
+    }
+
+    fn ids(cluster: &IpCluster) -> Vec<u32> {
+        cluster.clients.iter().map(|(_, info)| info.id).collect()
+    }
+
+    #[test]
+    fn transitive_clusters() {
+        let db = make_db(vec![
+            record(1, 0, [10, 0, 0, 1], 1),
+            record(2, 0, [10, 0, 0, 1], 1),
+            record(3, 0, [10, 0, 0, 2], 1),
+            record(4, 0, [10, 0, 0, 3], 1),
+            record(5, 0, [10, 0, 0, 3], 1),
+        ]);
+        let clusters = db.ip_clusters(None);
+        assert_eq!(clusters.len(), 2);
+        assert_eq!(ids(&clusters[0]), vec![1, 2]);
+        assert_eq!(clusters[0].shared_ips, vec![Ipv4Addr::new(10, 0, 0, 1)]);
+        assert_eq!(ids(&clusters[1]), vec![4, 5]);
+
+        // second IP of client 2 links both clusters and client 3
+        let mut linked = record(2, 0, [10, 0, 0, 1], 1);
+        if let Some(info) = linked.info.as_mut().and_then(std::sync::Arc::get_mut) {
+            info.ip.push([10, 0, 0, 2].into());
+            info.ip.push([10, 0, 0, 3].into());
+        }
+        let db = make_db(vec![
+            record(1, 0, [10, 0, 0, 1], 1),
+            linked,
+            record(3, 0, [10, 0, 0, 2], 1),
+            record(4, 0, [10, 0, 0, 3], 1),
+        ]);
+        let clusters = db.ip_clusters(None);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(ids(&clusters[0]), vec![1, 2, 3, 4]);
+        assert_eq!(clusters[0].shared_ips.len(), 3);
+    }
+
+    #[test]
+    fn time_window() {
+        let mut old = record(2, 0, [10, 0, 0, 1], 1);
+        old.modified = Some(SystemTime::now() - Duration::from_secs(10 * 24 * 60 * 60));
+        let mut fresh = record(1, 0, [10, 0, 0, 1], 1);
+        fresh.modified = Some(SystemTime::now());
+        let db = make_db(vec![fresh, old]);
+        assert_eq!(db.ip_clusters(None).len(), 1);
+        let week = Duration::from_secs(7 * 24 * 60 * 60);
+        assert!(db.ip_clusters(Some(week)).is_empty());
+    }
+}
@@ -8,6 +8,7 @@ use tnf_common::engine_types::critter::Critter;
 use arrayvec::ArrayVec;
 use std::net::Ipv4Addr;
 
+#[derive(Clone)]
 pub struct CritterInfo {
     pub id: u32,
     pub hex_x: u16,
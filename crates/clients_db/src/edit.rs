@@ -0,0 +1,605 @@
+//! Offline editing of `.client` saves, to fix a character without starting the server.
+//!
+//! `fo_save_format` can only read saves, so edits are patched in place: the critter data block
+//! is located by its params and checked against the parsed save, both before and after patching.
+use fo_defines_fo4rp::param::Param;
+use fo_save_format::ClientSaveData;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::CritterInfo;
+
+/// Allowed `(min, max)` of param, as declared by `impl_param!` in `fo_param_fo4rp`,
+/// `None` if any value goes.
+///
+/// `ST_CURRENT_AP` is left out: its declared range limits `base / 100`, not the base itself.
+pub fn limits(param: Param) -> Option<(i32, i32)> {
+    if param == Param::ST_CURRENT_AP {
+        return None;
+    }
+    fo_param_fo4rp::param::all_ranges()
+        .into_iter()
+        .find(|&(index, _, _)| usize::from(index) == param as usize)
+        .map(|(_, min, max)| (min, max))
+}
+
+// Offsets of fields inside of packed `CritData`.
+const OFFSET_ID: usize = 0;
+const OFFSET_HEX_X: usize = 4;
+const OFFSET_HEX_Y: usize = 6;
+const OFFSET_DIR: usize = 16;
+const OFFSET_COND: usize = 17;
+const OFFSET_MAP_ID: usize = 64;
+const OFFSET_MAP_PID: usize = 68;
+const OFFSET_PARAMS: usize = 72;
+const PARAMS_LEN: usize = 1000 * 4;
+
+#[derive(Debug)]
+pub enum EditError {
+    Io(io::Error),
+    Parse(io::Error),
+    /// Critter data wasn't found at the expected layout, nothing can be patched safely.
+    UnknownLayout,
+    /// Not an index of a real param, i.e. `PARAMS_COUNT`.
+    InvalidParam(Param),
+    OutOfRange {
+        param: Param,
+        value: i32,
+        min: i32,
+        max: i32,
+    },
+    /// File was changed on disk after it was loaded.
+    Modified,
+    /// Patched save doesn't read back as expected.
+    Verify(Field),
+}
+
+impl From<io::Error> for EditError {
+    fn from(err: io::Error) -> Self {
+        EditError::Io(err)
+    }
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::Io(err) => write!(f, "IO error: {}", err),
+            EditError::Parse(err) => write!(f, "Can't parse save: {}", err),
+            EditError::UnknownLayout => write!(f, "Unknown save layout"),
+            EditError::InvalidParam(param) => write!(f, "{} is not a param", param.name()),
+            EditError::OutOfRange {
+                param,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "{} = {} is out of range {}..={}",
+                param.name(),
+                value,
+                min,
+                max
+            ),
+            EditError::Modified => write!(f, "Save was modified since it was loaded"),
+            EditError::Verify(field) => write!(f, "Patched {} doesn't match", field),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Param(Param),
+    MapId,
+    MapPid,
+    HexX,
+    HexY,
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Field::Param(param) => f.write_str(param.name()),
+            Field::MapId => f.write_str("map_id"),
+            Field::MapPid => f.write_str("map_pid"),
+            Field::HexX => f.write_str("hex_x"),
+            Field::HexY => f.write_str("hex_y"),
+        }
+    }
+}
+
+/// Single changed value, old one is taken from the loaded save.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub field: Field,
+    pub old: i64,
+    pub new: i64,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.field, self.old, self.new)
+    }
+}
+
+/// Set of changes to apply to a save.
+#[derive(Debug, Default, Clone)]
+pub struct SaveEdit {
+    pub params: BTreeMap<Param, i32>,
+    /// `(map_id, map_pid)`, both are changed together to keep them consistent.
+    pub map: Option<(u32, u16)>,
+    pub hex: Option<(u16, u16)>,
+}
+
+impl SaveEdit {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn param(mut self, param: Param, value: i32) -> Self {
+        self.params.insert(param, value);
+        self
+    }
+    pub fn map(mut self, map_id: u32, map_pid: u16) -> Self {
+        self.map = Some((map_id, map_pid));
+        self
+    }
+    pub fn hex(mut self, hex_x: u16, hex_y: u16) -> Self {
+        self.hex = Some((hex_x, hex_y));
+        self
+    }
+    pub fn is_empty(&self) -> bool {
+        self.params.is_empty() && self.map.is_none() && self.hex.is_none()
+    }
+    pub fn validate(&self) -> Result<(), EditError> {
+        for (&param, &value) in &self.params {
+            if param as usize >= Param::PARAMS_COUNT as usize {
+                return Err(EditError::InvalidParam(param));
+            }
+            if let Some((min, max)) = limits(param) {
+                if value < min || value > max {
+                    return Err(EditError::OutOfRange {
+                        param,
+                        value,
+                        min,
+                        max,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Changes against current state of critter, unchanged values are skipped.
+    /// Edit must be validated first.
+    fn diff(&self, info: &CritterInfo) -> Vec<Change> {
+        let mut changes = vec![];
+        let mut push = |field, old: i64, new: i64| {
+            if old != new {
+                changes.push(Change { field, old, new });
+            }
+        };
+        for (&param, &value) in &self.params {
+            push(
+                Field::Param(param),
+                info.params[param as usize].into(),
+                value.into(),
+            );
+        }
+        if let Some((map_id, map_pid)) = self.map {
+            push(Field::MapId, info.map_id.into(), map_id.into());
+            push(Field::MapPid, info.map_pid.into(), map_pid.into());
+        }
+        if let Some((hex_x, hex_y)) = self.hex {
+            push(Field::HexX, info.hex_x.into(), hex_x.into());
+            push(Field::HexY, info.hex_y.into(), hex_y.into());
+        }
+        changes
+    }
+    fn apply_to_info(&self, info: &mut CritterInfo) {
+        for (&param, &value) in &self.params {
+            info.params[param as usize] = value;
+        }
+        if let Some((map_id, map_pid)) = self.map {
+            info.map_id = map_id;
+            info.map_pid = map_pid;
+        }
+        if let Some((hex_x, hex_y)) = self.hex {
+            info.hex_x = hex_x;
+            info.hex_y = hex_y;
+        }
+    }
+    fn apply_to_bytes(&self, data: &mut [u8]) {
+        fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+            data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        }
+        for (&param, &value) in &self.params {
+            put(
+                data,
+                OFFSET_PARAMS + param as usize * 4,
+                &value.to_le_bytes(),
+            );
+        }
+        if let Some((map_id, map_pid)) = self.map {
+            put(data, OFFSET_MAP_ID, &map_id.to_le_bytes());
+            put(data, OFFSET_MAP_PID, &map_pid.to_le_bytes());
+        }
+        if let Some((hex_x, hex_y)) = self.hex {
+            put(data, OFFSET_HEX_X, &hex_x.to_le_bytes());
+            put(data, OFFSET_HEX_Y, &hex_y.to_le_bytes());
+        }
+    }
+}
+
+/// Offset of critter data block, it must be the only one matching parsed critter.
+fn locate(bytes: &[u8], info: &CritterInfo) -> Result<usize, EditError> {
+    let params: Vec<u8> = info.params.iter().flat_map(|p| p.to_le_bytes()).collect();
+    let header_matches = |start: usize| {
+        let data = &bytes[start..];
+        data[OFFSET_ID..OFFSET_ID + 4] == info.id.to_le_bytes()
+            && data[OFFSET_HEX_X..OFFSET_HEX_X + 2] == info.hex_x.to_le_bytes()
+            && data[OFFSET_HEX_Y..OFFSET_HEX_Y + 2] == info.hex_y.to_le_bytes()
+            && data[OFFSET_DIR] == info.dir
+            && data[OFFSET_COND] == info.cond
+            && data[OFFSET_MAP_ID..OFFSET_MAP_ID + 4] == info.map_id.to_le_bytes()
+            && data[OFFSET_MAP_PID..OFFSET_MAP_PID + 2] == info.map_pid.to_le_bytes()
+    };
+    let mut found = bytes
+        .windows(PARAMS_LEN)
+        .enumerate()
+        .skip(OFFSET_PARAMS)
+        .filter(|(_, window)| *window == &params[..])
+        .map(|(pos, _)| pos - OFFSET_PARAMS)
+        .filter(|&start| header_matches(start));
+    match (found.next(), found.next()) {
+        (Some(start), None) => Ok(start),
+        _ => Err(EditError::UnknownLayout),
+    }
+}
+
+fn parse(bytes: &[u8]) -> Result<CritterInfo, EditError> {
+    fn parse_error<E: Into<io::Error>>(err: E) -> EditError {
+        EditError::Parse(err.into())
+    }
+    let save = ClientSaveData::read_bincode(&mut &bytes[..]).map_err(parse_error)?;
+    Ok(CritterInfo::from(&save))
+}
+
+fn verify(expected: &CritterInfo, actual: &CritterInfo) -> Result<(), EditError> {
+    let fields = [
+        (Field::MapId, expected.map_id == actual.map_id),
+        (Field::MapPid, expected.map_pid == actual.map_pid),
+        (Field::HexX, expected.hex_x == actual.hex_x),
+        (Field::HexY, expected.hex_y == actual.hex_y),
+    ];
+    if let Some((field, _)) = fields.iter().find(|(_, ok)| !ok) {
+        return Err(EditError::Verify(*field));
+    }
+    if let Some(index) =
+        (0..expected.params.len()).find(|&i| expected.params[i] != actual.params[i])
+    {
+        // Edits only touch named params, anything else means patch went to a wrong place.
+        return Err(Param::ALL
+            .iter()
+            .find(|&&param| param as usize == index)
+            .map_or(EditError::UnknownLayout, |&param| {
+                EditError::Verify(Field::Param(param))
+            }));
+    }
+    if expected.id != actual.id || expected.dir != actual.dir || expected.cond != actual.cond {
+        return Err(EditError::UnknownLayout);
+    }
+    Ok(())
+}
+
+/// Loaded save, ready to be patched and written back.
+pub struct ClientSave {
+    path: PathBuf,
+    bytes: Vec<u8>,
+    offset: usize,
+    info: CritterInfo,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+impl ClientSave {
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self, EditError> {
+        let path = path.into();
+        let metadata = path.metadata()?;
+        let bytes = fs::read(&path)?;
+        let info = parse(&bytes)?;
+        let offset = locate(&bytes, &info)?;
+        Ok(ClientSave {
+            path,
+            bytes,
+            offset,
+            info,
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    pub fn info(&self) -> &CritterInfo {
+        &self.info
+    }
+    /// Validates edit and lists what would be changed, save itself is left intact.
+    pub fn diff(&self, edit: &SaveEdit) -> Result<Vec<Change>, EditError> {
+        edit.validate()?;
+        Ok(edit.diff(&self.info))
+    }
+    /// Patches save in memory and checks that it reads back with exactly these changes.
+    pub fn apply(&mut self, edit: &SaveEdit) -> Result<Vec<Change>, EditError> {
+        let changes = self.diff(edit)?;
+        let mut bytes = self.bytes.clone();
+        edit.apply_to_bytes(&mut bytes[self.offset..]);
+        let mut expected = self.info.clone();
+        edit.apply_to_info(&mut expected);
+        let mut actual = parse(&bytes)?;
+        verify(&expected, &actual)?;
+        actual.name = std::mem::take(&mut self.info.name);
+        self.bytes = bytes;
+        self.info = actual;
+        Ok(changes)
+    }
+    /// Backs up file on disk and atomically replaces it, returns path of the backup.
+    pub fn save(&self) -> Result<PathBuf, EditError> {
+        let metadata = self.path.metadata()?;
+        if metadata.modified().ok() != self.modified || metadata.len() != self.len {
+            return Err(EditError::Modified);
+        }
+        let backup = self.backup()?;
+
+        let temp = self.sibling("tmp");
+        let write = || -> io::Result<()> {
+            let mut file = File::create(&temp)?;
+            file.write_all(&self.bytes)?;
+            file.sync_all()?;
+            fs::rename(&temp, &self.path)
+        };
+        if let Err(err) = write() {
+            let _ = fs::remove_file(&temp);
+            return Err(err.into());
+        }
+        Ok(backup)
+    }
+    /// Copies save to a new `name.client.<unix secs>.bak`, counter is added if there is one
+    /// already, so an earlier backup is never overwritten.
+    fn backup(&self) -> io::Result<PathBuf> {
+        let time = unix_time();
+        let mut counter = 0;
+        let (backup, mut file) = loop {
+            let backup = match counter {
+                0 => self.sibling(&format!("{}.bak", time)),
+                _ => self.sibling(&format!("{}-{}.bak", time, counter)),
+            };
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&backup)
+            {
+                Ok(file) => break (backup, file),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => counter += 1,
+                Err(err) => return Err(err),
+            }
+        };
+        let mut copy = || -> io::Result<()> {
+            io::copy(&mut File::open(&self.path)?, &mut file)?;
+            file.sync_all()
+        };
+        if let Err(err) = copy() {
+            let _ = fs::remove_file(&backup);
+            return Err(err);
+        }
+        Ok(backup)
+    }
+    /// Path next to the save, i.e. `name.client.tmp`.
+    fn sibling(&self, extension: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_owned();
+        name.push(".");
+        name.push(extension);
+        self.path.with_file_name(name)
+    }
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fixture::SaveFixture;
+
+    fn info() -> CritterInfo {
+        let mut params = [0i32; 1000];
+        params[Param::ST_LEVEL as usize] = 5;
+        params[Param::ST_STRENGTH as usize] = 6;
+        CritterInfo {
+            id: 5_000_001,
+            hex_x: 100,
+            hex_y: 120,
+            dir: 3,
+            cond: 1,
+            map_id: 7,
+            map_pid: 42,
+            params,
+            name: "client".into(),
+            ip: Default::default(),
+        }
+    }
+
+    /// Save-like bytes: some header, critter data and some tail.
+    fn bytes(info: &CritterInfo) -> Vec<u8> {
+        let mut data = vec![0u8; OFFSET_PARAMS];
+        data[OFFSET_ID..OFFSET_ID + 4].copy_from_slice(&info.id.to_le_bytes());
+        data[OFFSET_HEX_X..OFFSET_HEX_X + 2].copy_from_slice(&info.hex_x.to_le_bytes());
+        data[OFFSET_HEX_Y..OFFSET_HEX_Y + 2].copy_from_slice(&info.hex_y.to_le_bytes());
+        data[OFFSET_DIR] = info.dir;
+        data[OFFSET_COND] = info.cond;
+        data[OFFSET_MAP_ID..OFFSET_MAP_ID + 4].copy_from_slice(&info.map_id.to_le_bytes());
+        data[OFFSET_MAP_PID..OFFSET_MAP_PID + 2].copy_from_slice(&info.map_pid.to_le_bytes());
+        data.extend(info.params.iter().flat_map(|p| p.to_le_bytes()));
+
+        let mut bytes = b"FO\0\x01client\0\0".to_vec();
+        bytes.extend(data);
+        bytes.extend(vec![0xAB; 300]);
+        bytes
+    }
+
+    #[test]
+    fn validate_limits() {
+        assert_eq!(limits(Param::ST_STRENGTH), Some((1, 10)));
+        assert_eq!(limits(Param::ST_LEVEL), None);
+        assert!(SaveEdit::new()
+            .param(Param::ST_LEVEL, 99)
+            .validate()
+            .is_ok());
+        match SaveEdit::new().param(Param::ST_LUCK, 11).validate() {
+            Err(EditError::OutOfRange { value: 11, .. }) => {}
+            other => panic!("{:?}", other),
+        }
+        assert!(matches!(
+            SaveEdit::new().param(Param::PARAMS_COUNT, 1).validate(),
+            Err(EditError::InvalidParam(Param::PARAMS_COUNT))
+        ));
+    }
+
+    #[test]
+    fn locate_and_patch() {
+        let info = info();
+        let mut bytes = bytes(&info);
+        let offset = locate(&bytes, &info).unwrap();
+        assert_eq!(offset, 12);
+
+        let edit = SaveEdit::new()
+            .param(Param::ST_LEVEL, 6)
+            .param(Param::ST_STRENGTH, 6)
+            .map(8, 170)
+            .hex(10, 20);
+        let changes = edit.diff(&info);
+        let shown: Vec<_> = changes.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            shown,
+            [
+                "ST_LEVEL: 5 -> 6",
+                "map_id: 7 -> 8",
+                "map_pid: 42 -> 170",
+                "hex_x: 100 -> 10",
+                "hex_y: 120 -> 20"
+            ]
+        );
+
+        edit.apply_to_bytes(&mut bytes[offset..]);
+        let mut expected = info.clone();
+        edit.apply_to_info(&mut expected);
+        assert_eq!(bytes, self::bytes(&expected));
+        assert_eq!(locate(&bytes, &expected).unwrap(), offset);
+        assert!(verify(&expected, &expected).is_ok());
+        assert!(matches!(
+            verify(&expected, &info),
+            Err(EditError::Verify(Field::MapId))
+        ));
+    }
+
+    #[test]
+    fn ambiguous_layout() {
+        let info = info();
+        let mut bytes = bytes(&info);
+        assert!(matches!(
+            locate(&bytes[20..], &info),
+            Err(EditError::UnknownLayout)
+        ));
+        let copy = bytes.clone();
+        bytes.extend(copy);
+        assert!(matches!(
+            locate(&bytes, &info),
+            Err(EditError::UnknownLayout)
+        ));
+    }
+
+    #[test]
+    fn save_with_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("client.client");
+        let info = info();
+        fs::write(&path, bytes(&info)).unwrap();
+        let metadata = path.metadata().unwrap();
+        let mut expected = info.clone();
+        SaveEdit::new()
+            .param(Param::ST_LEVEL, 10)
+            .apply_to_info(&mut expected);
+        let save = ClientSave {
+            path: path.clone(),
+            bytes: bytes(&expected),
+            offset: 12,
+            info: expected,
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        };
+        let backup = save.save().unwrap();
+        assert_eq!(fs::read(&backup).unwrap(), bytes(&info));
+        assert_eq!(fs::read(&path).unwrap(), save.bytes);
+        assert!(!save.sibling("tmp").exists());
+
+        // Backups made within the same second don't overwrite each other
+        let backups = [save.backup().unwrap(), save.backup().unwrap()];
+        assert!(backups[0] != backup && backups[1] != backup && backups[0] != backups[1]);
+        assert_eq!(fs::read(&backup).unwrap(), bytes(&info));
+        assert_eq!(fs::read(&backups[1]).unwrap(), save.bytes);
+
+        fs::write(&path, b"changed by server").unwrap();
+        assert!(matches!(save.save(), Err(EditError::Modified)));
+    }
+
+    #[test]
+    fn open_apply_save() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("client.client");
+        let level = Param::ST_LEVEL as usize;
+        let strength = Param::ST_STRENGTH as usize;
+        let original = SaveFixture {
+            params: vec![(level, 5), (strength, 6)],
+            ..Default::default()
+        };
+        fs::write(&path, original.to_bytes()).unwrap();
+
+        let mut save = ClientSave::open(&path).unwrap();
+        assert_eq!(save.info().params[level], 5);
+        assert_eq!(save.info().map_pid, 42);
+        for edit in [
+            SaveEdit::new().param(Param::PARAMS_COUNT, 1),
+            SaveEdit::new().param(Param::ST_STRENGTH, 11),
+        ]
+        .iter()
+        {
+            assert!(save.apply(edit).is_err());
+        }
+
+        let edit = SaveEdit::new()
+            .param(Param::ST_LEVEL, 6)
+            .map(8, 170)
+            .hex(10, 20);
+        assert_eq!(save.apply(&edit).unwrap().len(), 5);
+        save.save().unwrap();
+        let expected = SaveFixture {
+            params: vec![(level, 6), (strength, 6)],
+            map: (8, 170),
+            hex: (10, 20),
+            ..original
+        };
+        assert_eq!(fs::read(&path).unwrap(), expected.to_bytes());
+
+        let reopened = ClientSave::open(&path).unwrap();
+        assert_eq!(reopened.info().params[level], 6);
+        assert_eq!((reopened.info().hex_x, reopened.info().hex_y), (10, 20));
+    }
+}
@@ -12,154 +12,108 @@ const FORWARD_TABLE: &'static [u16] = &[
     1089, 1090, 1091, 1092, 1093, 1094, 1095, 1096, 1097, 1098, 1099, 1100, 1101, 1102, 1103,
 ]; // 128 entries
 
-#[cfg(windows)]
-fn is_ascii(string: &OsStr) -> bool {
-    use std::os::windows::ffi::OsStrExt;
-    let mut maybe_ascii = false;
-    for wide in string.encode_wide() {
-        if wide < 0x20 || wide > 0xFF {
-            return false;
-        }
-        if wide >= 0x80 {
-            maybe_ascii = true;
-        }
+/// CP1251 byte as char, NUL is dropped.
+fn cp1251_char(code: u8) -> Option<char> {
+    match code {
+        0 => None,
+        1..=0x7F => Some(code as char),
+        _ => std::char::from_u32(FORWARD_TABLE[(code - 0x80) as usize] as u32),
     }
-    maybe_ascii
 }
 
-#[cfg(windows)]
-fn from_ascii(string: &OsStr) -> Option<String> {
-    use std::convert::TryInto;
-    use std::os::windows::ffi::OsStrExt;
-    //let mut vec = Vec::with_capacity(string.len()*2);
+fn from_cp1251(bytes: &[u8]) -> String {
+    bytes.iter().copied().filter_map(cp1251_char).collect()
+}
+
+/// CP1251 bytes that were read as Latin-1 on the way, i.e. "Èâàí" for "Иван".
+fn from_latin1(string: &str) -> Option<String> {
     let mut new_string = String::with_capacity(string.len() * 2);
-    for wide in string.encode_wide() {
-        let code = wide.to_ne_bytes()[0];
-        if code >= 0x80 {
-            let cp1251 = FORWARD_TABLE[(code - 0x80) as usize] as u32;
-            new_string.push(cp1251.try_into().ok()?);
-        } else if code != 0 {
-            new_string.push(code as char);
+    for ch in string.chars() {
+        let code = ch as u32;
+        if code > 0xFF {
+            return None;
         }
+        new_string.extend(cp1251_char(code as u8));
     }
     Some(new_string)
 }
 
-#[cfg(windows)]
-pub fn decode_filename(filename: &OsStr) -> Option<String> {
-    if is_ascii(filename) {
-        from_ascii(filename)
-    } else {
-        filename.to_str().map(String::from)
-    }
+fn is_russian_letter(ch: char) -> bool {
+    matches!(ch, 'А'..='я' | 'Ё' | 'ё')
 }
 
-#[cfg(not(windows))]
-pub fn decode_filename(filename: &OsStr) -> Option<String> {
-    let string = match filename.to_str() {
-        Some(string) => string,
-        None => {
-            return from_ascii(filename);
+/// Number of russian letters, `None` if there are other non-ASCII chars.
+fn russian_letters(string: &str) -> Option<usize> {
+    let mut count = 0;
+    for ch in string.chars().filter(|ch| !ch.is_ascii()) {
+        if !is_russian_letter(ch) {
+            return None;
         }
-    };
-
-    if is_ascii(string) {
-        from_utfish_ascii(string)
-    } else {
-        Some(string.to_owned())
+        count += 1;
     }
+    Some(count)
 }
 
-#[cfg(not(windows))]
-fn from_utfish_ascii(string: &str) -> Option<String> {
-    use std::convert::TryInto;
-    let mut new_string = String::with_capacity(string.len() * 2);
-    for ch in string.chars() {
-        let code = ch as u32;
-        if code >= 0x80 && code <= 0xFF {
-            let cp1251 = FORWARD_TABLE[(code - 0x80) as usize] as u32;
-            new_string.push(cp1251.try_into().ok()?);
-        } else if code != 0 {
-            new_string.push(ch);
-        }
-    }
-    Some(new_string)
+/// Some word has both latin and non-ASCII letters, i.e. "Cafй" that is "Café" read as CP1251.
+fn mixes_scripts(string: &str) -> bool {
+    string
+        .split(|ch: char| !ch.is_alphabetic())
+        .any(|word| word.chars().any(|ch| ch.is_ascii_alphabetic()) && !word.is_ascii())
 }
 
-#[cfg(not(windows))]
-fn from_ascii(string: &OsStr) -> Option<String> {
-    use std::convert::TryInto;
-    use std::os::unix::ffi::OsStrExt;
-
-    let mut new_string = String::with_capacity(string.len() * 2);
-    for &code in string.as_bytes() {
-        if code >= 0x80 {
-            let cp1251 = FORWARD_TABLE[(code - 0x80) as usize] as u32;
-            new_string.push(cp1251.try_into().ok()?);
-        } else if code != 0 {
-            new_string.push(code as char);
-        }
-    }
-    Some(new_string)
+/// CP1251 text read as Latin-1: read back as CP1251 it has more russian letters, and they make
+/// words of their own, i.e. "Mr_Èâàí" for "Mr_Иван".
+fn looks_like_latin1(string: &str) -> bool {
+    let letters = |string: &str| russian_letters(string).unwrap_or(0);
+    !string.is_ascii()
+        && from_latin1(string)
+            .is_some_and(|fixed| letters(&fixed) > letters(string) && !mixes_scripts(&fixed))
 }
 
-#[cfg(not(windows))]
-fn is_ascii(string: &str) -> bool {
-    let mut maybe_ascii = false;
-    for ch in string.chars() {
-        let ch = ch as u32;
-        if ch < 0x20 || ch > 0xFF {
-            return false;
-        }
-        if ch >= 0x80 {
-            maybe_ascii = true;
+/// Valid UTF-8 is taken as is, unless it's CP1251 read as Latin-1. Otherwise picks the reading
+/// with more russian letters, otherwise whatever is readable.
+fn decode(utf8: Option<&str>, raw: Option<&[u8]>) -> Option<String> {
+    if let Some(string) = utf8 {
+        if !looks_like_latin1(string) {
+            return Some(string.to_owned());
         }
     }
-    maybe_ascii
-}
-/*
-#[cfg(not(windows))]
-fn is_true_ascii() {
-    use std::os::unix::ffi::OsStrExt;
-    let mut maybe_ascii = false;
-    println!("{:?}", string);
-    let mut last_cyr = false;
-    let mut maybe_utf8 = false;
-    let mut utf_len = 0;
-    for &ch in string.as_bytes() {
-        if ch < 0x20 {
-            return false;
-        }
-
-        if ch&0b11000000 == 0b11000000 {
-            utf_len+=1;
-        } else {
-            if utf_len > 1 {
-                return true;
+    let candidates = [utf8.and_then(from_latin1), raw.map(from_cp1251)];
+    let mut best: Option<(usize, &String)> = None;
+    for candidate in candidates.iter().flatten() {
+        if let Some(count) = russian_letters(candidate) {
+            let better = match best {
+                Some((best_count, _)) => count > best_count,
+                None => true,
+            };
+            if better {
+                best = Some((count, candidate));
             }
-            utf_len = 0;
         }
+    }
+    match best {
+        Some((_, string)) => Some(string.clone()),
+        None => utf8.map(String::from).or_else(|| raw.map(from_cp1251)),
+    }
+}
 
-        if last_cyr && ch < 0b11000000 {
-            maybe_utf8 = true;
-        }
+/// Decodes name of a save written by the game server, which may be valid UTF-8, raw CP1251
+/// bytes, or CP1251 that went through Latin-1.
+pub fn decode_bytes(bytes: &[u8]) -> String {
+    decode(std::str::from_utf8(bytes).ok(), Some(bytes)).unwrap_or_default()
+}
 
-        last_cyr = if ch == 208 || ch == 209 {
-            true
-        } else {
-            false
-        };
+#[cfg(windows)]
+pub fn decode_filename(filename: &OsStr) -> Option<String> {
+    // Raw CP1251 bytes never reach us here: Windows converts them to UTF-16 on its own.
+    decode(Some(filename.to_str()?), None)
+}
 
-        if ch >= 0x80 {
-            maybe_ascii = true;
-        }
-        print!(" {}", ch)
-    }
-    if maybe_utf8 {
-        return false;
-    }
-    return maybe_ascii
-}*/
+#[cfg(not(windows))]
+pub fn decode_filename(filename: &OsStr) -> Option<String> {
+    use std::os::unix::ffi::OsStrExt;
+    Some(decode_bytes(filename.as_bytes()))
+}
 
 pub fn os_str_debug<'a>(os_str: &'a OsStr) -> Cow<'a, str> {
     match os_str.to_str() {
@@ -179,3 +133,47 @@ fn os_str_debug_inner(os_str: &OsStr) -> String {
     vec.extend(os_str.encode_wide());
     format!("{:X?}", &vec[..])
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn utf8_names() {
+        assert_eq!(decode_bytes(b"John_Doe"), "John_Doe");
+        assert_eq!(decode_bytes("Иван".as_bytes()), "Иван");
+        assert_eq!(decode_bytes("Пётр Ёжиков".as_bytes()), "Пётр Ёжиков");
+        // not russian, but still readable
+        assert_eq!(decode_bytes("Café".as_bytes()), "Café");
+        assert_eq!(decode_bytes("Zoë_Ångström".as_bytes()), "Zoë_Ångström");
+        assert_eq!(decode_bytes("Ōsaka".as_bytes()), "Ōsaka");
+    }
+
+    #[test]
+    fn cp1251_names() {
+        // "Иван" and "Пётр" saved by the Windows server
+        assert_eq!(decode_bytes(b"\xC8\xE2\xE0\xED"), "Иван");
+        assert_eq!(decode_bytes(b"\xCF\xB8\xF2\xF0"), "Пётр");
+        // "Вё" is valid UTF-8 too ("¸")
+        assert_eq!(decode_bytes(b"\xC2\xB8"), "Вё");
+        assert_eq!(decode_bytes(b"\xD1\xE5\xF0\xE3\xE5\xE9\0"), "Сергей");
+    }
+
+    #[test]
+    fn latin1_names() {
+        // CP1251 read as Latin-1 and written back as UTF-8
+        assert_eq!(decode_bytes("Èâàí".as_bytes()), "Иван");
+        assert_eq!(decode_bytes("Ñåðãåé_2".as_bytes()), "Сергей_2");
+        assert_eq!(decode_bytes("Mr_Èâàí".as_bytes()), "Mr_Иван");
+        assert_eq!(decode_bytes("Èâàí Smith".as_bytes()), "Иван Smith");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_filenames() {
+        use std::os::unix::ffi::OsStrExt;
+        let filename = OsStr::from_bytes(b"\xC8\xE2\xE0\xED");
+        assert_eq!(decode_filename(filename).as_deref(), Some("Иван"));
+        assert_eq!(os_str_debug(filename), "[C8, E2, E0, ED]");
+    }
+}
@@ -0,0 +1,73 @@
+//! Generated `.client` saves for tests and benches, laid out the way the server writes them:
+//! signature, password hash, `CritData`, `CritDataExt` and time events.
+//!
+//! Shared with benches via `#[path]`, so it depends on nothing from the crate.
+
+const SIGNATURE: [u8; 4] = *b"FO\0\x01";
+const PASS_HASH_LEN: usize = 32;
+const CRIT_DATA_LEN: usize = 7404;
+const CRIT_DATA_EXT_LEN: usize = 6944;
+const OFFSET_PLAY_IP: usize = 6704;
+
+#[derive(Debug, Clone)]
+pub struct SaveFixture {
+    pub id: u32,
+    pub hex: (u16, u16),
+    pub dir: u8,
+    pub cond: u8,
+    pub map: (u32, u16),
+    pub params: Vec<(usize, i32)>,
+    pub ips: Vec<[u8; 4]>,
+    /// `(func_num, rate, next_time, identifier)` of each time event.
+    pub time_events: Vec<[u32; 4]>,
+}
+
+impl Default for SaveFixture {
+    fn default() -> Self {
+        SaveFixture {
+            id: 5_000_001,
+            hex: (100, 120),
+            dir: 3,
+            cond: 1,
+            map: (7, 42),
+            params: vec![],
+            ips: vec![[127, 0, 0, 1]],
+            time_events: vec![],
+        }
+    }
+}
+
+impl SaveFixture {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0u8; CRIT_DATA_LEN];
+        put(&mut data, 0, &self.id.to_le_bytes());
+        put(&mut data, 4, &self.hex.0.to_le_bytes());
+        put(&mut data, 6, &self.hex.1.to_le_bytes());
+        data[16] = self.dir;
+        data[17] = self.cond;
+        put(&mut data, 64, &self.map.0.to_le_bytes());
+        put(&mut data, 68, &self.map.1.to_le_bytes());
+        for &(index, value) in &self.params {
+            put(&mut data, 72 + index * 4, &value.to_le_bytes());
+        }
+
+        let mut data_ext = vec![0u8; CRIT_DATA_EXT_LEN];
+        for (i, ip) in self.ips.iter().enumerate() {
+            put(&mut data_ext, OFFSET_PLAY_IP + i * 4, ip);
+        }
+
+        let mut bytes = SIGNATURE.to_vec();
+        bytes.extend_from_slice(&[0xA5; PASS_HASH_LEN]);
+        bytes.extend(data);
+        bytes.extend(data_ext);
+        bytes.extend_from_slice(&(self.time_events.len() as u32).to_le_bytes());
+        for event in &self.time_events {
+            bytes.extend(event.iter().flat_map(|field| field.to_le_bytes()));
+        }
+        bytes
+    }
+}
+
+fn put(data: &mut [u8], offset: usize, bytes: &[u8]) {
+    data[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
@@ -1,9 +1,21 @@
 mod clients;
+pub mod clusters;
 mod critter_info;
+pub mod edit;
 pub mod fix_encoding;
+#[cfg(test)]
+mod fixture;
+pub mod query;
 mod record;
 
-pub use crate::{clients::ClientsDb, critter_info::CritterInfo, record::ClientRecord};
+pub use crate::{
+    clients::ClientsDb,
+    clusters::IpCluster,
+    critter_info::CritterInfo,
+    edit::{ClientSave, SaveEdit},
+    query::{Cmp, ParamFilter, Query},
+    record::ClientRecord,
+};
 
 type InnerCritter = std::sync::Arc<CritterInfo>;
 fn not_found() -> std::io::Error {
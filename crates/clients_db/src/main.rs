@@ -1,62 +1,98 @@
-use clients_db::ClientsDb;
+use clients_db::{edit::EditError, ClientSave, ClientsDb, Cmp, ParamFilter, SaveEdit};
 
-use itertools::EitherOrBoth;
-use itertools::Itertools;
-use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+const USAGE: &str = "\
+Usage:
+    clients_db edit <save.client> [--dry-run] [--set PARAM=VALUE]... [--map MAP_ID:MAP_PID] [--hex X,Y]
+    clients_db fix <clients_dir> [--dry-run]
+
+Stop the game server before editing, otherwise it will overwrite the save.
+Original save is kept next to it as <save.client>.<unix time>.bak";
+
 fn main() {
-    /*
-    let ids = ClientsDb::list_ids("../../FO4RP/save/clients/".into());
-    for (id, path) in &ids {
-        let name = path.file_name().expect("file name").to_str().expect("file name str");
-        if name.starts_with("ё") {
-            let mut to = path.clone();
-            to.set_file_name(format!("{}", *id));
-            to.set_extension("client");
-            dbg!(&path);
-            dbg!(&to);
-            std::fs::rename(&path,&to).expect("rename");
-        }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let res = match args.first().map(String::as_str) {
+        Some("edit") => edit(&args[1..]),
+        Some("fix") => fix(&args[1..]),
+        _ => Err(USAGE.into()),
+    };
+    if let Err(err) = res {
+        eprintln!("{}", err);
+        std::process::exit(1);
     }
-    */
-    //let names = ClientsDb::list_names("../../FO4RP_move/save/clients/".into());
-    /*
-    let join: BTreeMap<u32, (&PathBuf, &String)> = ids.iter()
-        .merge_join_by(names.iter(), |(id1, _path), (id2, _name)| id1.cmp(id2))
-        .filter_map(|either| {
-            match either {
-                EitherOrBoth::Both(a, b) => {
-                    Some((*a.0, (a.1, b.1)))
-                },
-                _ => None
-            }
-        })
-        .filter(|(id, (path,name))| {
-            path.file_stem().unwrap().to_str().unwrap() != name.as_str()
-        })
-        .collect();
-    */
-    //println!("Совпадений: {:?}", join.len());
-    //println!("{:#?}", join);
-    /*for (key, (path, name)) in join.iter() {
-        let mut to = (*path).clone();
-        to.set_file_name(name);
-        to.set_extension("client");
-        std::fs::rename(&path,&to).unwrap();
-    }*/
-    //ClientsDb::fix_clients("../../FO4RP_move/save/clients/".into(), false);
 }
-/*
-use tnf_web_server::*;
 
-fn main() -> std::io::Result<()> {
-    let clients = env::expect_clients();
-    env::setup_working_dir()?;
+fn fix(args: &[String]) -> Result<(), String> {
+    let path = args.first().ok_or(USAGE)?;
+    let dry_run = args[1..].iter().any(|arg| arg == "--dry-run");
+    ClientsDb::fix_clients(path.into(), dry_run);
+    Ok(())
+}
 
-    let not_test = std::env::args().nth(1).as_ref().map(AsRef::as_ref) == Some("--notest");
-    critters_db::CrittersDb::fix_clients(clients, !not_test);
+fn edit(args: &[String]) -> Result<(), String> {
+    let path: PathBuf = args.first().ok_or(USAGE)?.into();
+    let mut edit = SaveEdit::new();
+    let mut dry_run = false;
+    let mut args = args[1..].iter();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("No value for {}", arg));
+        match arg.as_str() {
+            "--dry-run" => dry_run = true,
+            "--set" => {
+                let value = value()?;
+                let filter: ParamFilter = value
+                    .parse()
+                    .map_err(|err| format!("Invalid param {:?}: {:?}", value, err))?;
+                if filter.cmp != Cmp::Eq {
+                    return Err(format!("Expected PARAM=VALUE, got {:?}", value));
+                }
+                edit = edit.param(filter.param, filter.value);
+            }
+            "--map" => {
+                let value = value()?;
+                let (map_id, map_pid) = pair(value, ':')
+                    .ok_or_else(|| format!("Expected MAP_ID:MAP_PID, got {:?}", value))?;
+                edit = edit.map(map_id, map_pid);
+            }
+            "--hex" => {
+                let value = value()?;
+                let (hex_x, hex_y) =
+                    pair(value, ',').ok_or_else(|| format!("Expected X,Y, got {:?}", value))?;
+                edit = edit.hex(hex_x, hex_y);
+            }
+            _ => return Err(format!("Unknown argument {:?}\n{}", arg, USAGE)),
+        }
+    }
+    if edit.is_empty() {
+        return Err(USAGE.into());
+    }
 
+    let error = |err: EditError| format!("{}: {}", path.display(), err);
+    let mut save = ClientSave::open(&path).map_err(error)?;
+    let changes = if dry_run {
+        save.diff(&edit)
+    } else {
+        save.apply(&edit)
+    }
+    .map_err(error)?;
+    if changes.is_empty() {
+        println!("Nothing to change");
+        return Ok(());
+    }
+    for change in &changes {
+        println!("{}", change);
+    }
+    if !dry_run {
+        let backup = save.save().map_err(error)?;
+        println!("Saved, backup: {}", backup.display());
+    }
     Ok(())
 }
-*/
+
+fn pair<A: std::str::FromStr, B: std::str::FromStr>(value: &str, sep: char) -> Option<(A, B)> {
+    let mut parts = value.splitn(2, sep);
+    let a = parts.next()?.trim().parse().ok()?;
+    let b = parts.next()?.trim().parse().ok()?;
+    Some((a, b))
+}
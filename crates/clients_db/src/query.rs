@@ -0,0 +1,298 @@
+//! Queries over loaded saves, backed by secondary indexes.
+use fo_defines::CritterParam;
+use fo_defines_fo4rp::param::Param;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    net::Ipv4Addr,
+    str::FromStr,
+};
+
+use crate::{ClientRecord, CritterInfo};
+
+/// Client names by id, map and play IP, rebuilt on every rescan.
+#[derive(Debug, Default, Clone)]
+pub struct ClientsIndex {
+    by_id: BTreeMap<u32, String>,
+    by_map_pid: BTreeMap<u16, BTreeSet<String>>,
+    by_ip: BTreeMap<Ipv4Addr, BTreeSet<String>>,
+}
+
+impl ClientsIndex {
+    pub fn new(clients: &BTreeMap<String, ClientRecord>) -> Self {
+        let mut index = ClientsIndex::default();
+        for (name, record) in clients {
+            let info = match &record.info {
+                Some(info) => info,
+                None => continue,
+            };
+            index.by_id.insert(info.id, name.clone());
+            index
+                .by_map_pid
+                .entry(info.map_pid)
+                .or_default()
+                .insert(name.clone());
+            for ip in &info.ip {
+                index.by_ip.entry(*ip).or_default().insert(name.clone());
+            }
+        }
+        index
+    }
+    pub fn by_id(&self, id: u32) -> Option<&str> {
+        self.by_id.get(&id).map(String::as_str)
+    }
+    pub fn by_map_pid(&self, map_pid: u16) -> impl Iterator<Item = &str> {
+        self.by_map_pid
+            .get(&map_pid)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+    pub fn by_ip(&self, ip: Ipv4Addr) -> impl Iterator<Item = &str> {
+        self.by_ip
+            .get(&ip)
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+    /// All known IPs with names of clients who played from them.
+    pub fn ips(&self) -> impl Iterator<Item = (&Ipv4Addr, &BTreeSet<String>)> {
+        self.by_ip.iter()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cmp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Cmp {
+    // Longer operators first, so `>=` isn't taken for `>`.
+    const ALL: [(&'static str, Cmp); 7] = [
+        ("==", Cmp::Eq),
+        ("!=", Cmp::Ne),
+        ("<=", Cmp::Le),
+        (">=", Cmp::Ge),
+        ("=", Cmp::Eq),
+        ("<", Cmp::Lt),
+        (">", Cmp::Gt),
+    ];
+
+    pub fn test(self, left: i32, right: i32) -> bool {
+        match self {
+            Cmp::Eq => left == right,
+            Cmp::Ne => left != right,
+            Cmp::Lt => left < right,
+            Cmp::Le => left <= right,
+            Cmp::Gt => left > right,
+            Cmp::Ge => left >= right,
+        }
+    }
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Cmp::Eq => "==",
+            Cmp::Ne => "!=",
+            Cmp::Lt => "<",
+            Cmp::Le => "<=",
+            Cmp::Gt => ">",
+            Cmp::Ge => ">=",
+        }
+    }
+}
+
+/// Predicate over single param, i.e. `ST_LEVEL>=10`.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamFilter {
+    pub param: Param,
+    pub cmp: Cmp,
+    pub value: i32,
+}
+
+impl ParamFilter {
+    pub fn new(param: Param, cmp: Cmp, value: i32) -> Self {
+        ParamFilter { param, cmp, value }
+    }
+    pub fn matches<C: CritterParam<Param>>(&self, critter: &C) -> bool {
+        self.cmp.test(critter.param(self.param), self.value)
+    }
+}
+
+impl fmt::Display for ParamFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}",
+            self.param.name(),
+            self.cmp.as_str(),
+            self.value
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseFilterError {
+    NoOperator(String),
+    UnknownParam(String),
+    InvalidValue(String),
+}
+
+impl FromStr for ParamFilter {
+    type Err = ParseFilterError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let (pos, op, cmp) = Cmp::ALL
+            .iter()
+            .filter_map(|&(op, cmp)| string.find(op).map(|pos| (pos, op, cmp)))
+            .min_by_key(|&(pos, op, _)| (pos, usize::MAX - op.len()))
+            .ok_or_else(|| ParseFilterError::NoOperator(string.into()))?;
+        let name = string[..pos].trim();
+        let value = string[pos + op.len()..].trim();
+        let param =
+            Param::from_name(name).ok_or_else(|| ParseFilterError::UnknownParam(name.into()))?;
+        let value = value
+            .parse()
+            .map_err(|_| ParseFilterError::InvalidValue(value.into()))?;
+        Ok(ParamFilter { param, cmp, value })
+    }
+}
+
+/// Conjunction of all set conditions, empty query matches every loaded client.
+#[derive(Debug, Default, Clone)]
+pub struct Query {
+    pub id: Option<u32>,
+    pub map_pid: Option<u16>,
+    pub ip: Option<Ipv4Addr>,
+    pub params: Vec<ParamFilter>,
+}
+
+impl Query {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn id(mut self, id: u32) -> Self {
+        self.id = Some(id);
+        self
+    }
+    pub fn map_pid(mut self, map_pid: u16) -> Self {
+        self.map_pid = Some(map_pid);
+        self
+    }
+    pub fn ip(mut self, ip: Ipv4Addr) -> Self {
+        self.ip = Some(ip);
+        self
+    }
+    pub fn param(mut self, param: Param, cmp: Cmp, value: i32) -> Self {
+        self.params.push(ParamFilter::new(param, cmp, value));
+        self
+    }
+    pub fn matches(&self, info: &CritterInfo) -> bool {
+        self.id.map_or(true, |id| info.id == id)
+            && self.map_pid.map_or(true, |map_pid| info.map_pid == map_pid)
+            && self.ip.map_or(true, |ip| info.ip.contains(&ip))
+            && self.params.iter().all(|filter| filter.matches(info))
+    }
+    /// Names of clients that may match, `None` if query has no indexed condition.
+    pub(crate) fn candidates<'a>(&self, index: &'a ClientsIndex) -> Option<BTreeSet<&'a str>> {
+        if let Some(id) = self.id {
+            Some(index.by_id(id).into_iter().collect())
+        } else if let Some(map_pid) = self.map_pid {
+            Some(index.by_map_pid(map_pid).collect())
+        } else if let Some(ip) = self.ip {
+            Some(index.by_ip(ip).collect())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::ClientsDb;
+    use std::sync::Arc;
+
+    pub(crate) fn record(id: u32, map_pid: u16, ip: [u8; 4], level: i32) -> ClientRecord {
+        let mut params = [0i32; 1000];
+        params[Param::ST_LEVEL as usize] = level;
+        let info = CritterInfo {
+            id,
+            hex_x: 0,
+            hex_y: 0,
+            dir: 0,
+            cond: 1,
+            map_id: 0,
+            map_pid,
+            params,
+            name: format!("client{}", id),
+            ip: std::iter::once(Ipv4Addr::from(ip)).collect(),
+        };
+        let mut record = ClientRecord::new(info.name.as_ref());
+        record.info = Some(Arc::new(info));
+        record
+    }
+
+    fn db() -> ClientsDb {
+        let records = vec![
+            record(1, 170, [10, 0, 0, 1], 5),
+            record(2, 170, [10, 0, 0, 2], 12),
+            record(3, 42, [10, 0, 0, 1], 20),
+        ];
+        ClientsDb::from_records(
+            records
+                .into_iter()
+                .map(|record| (record.info.as_ref().unwrap().name.clone(), record)),
+        )
+    }
+
+    fn ids(db: &ClientsDb, query: &Query) -> Vec<u32> {
+        db.query(query)
+            .into_iter()
+            .map(|(_, info)| info.id)
+            .collect()
+    }
+
+    #[test]
+    fn parse_filter() {
+        let filter: ParamFilter = "ST_LEVEL >= 10".parse().unwrap();
+        assert!(filter.param == Param::ST_LEVEL);
+        assert_eq!(filter.cmp, Cmp::Ge);
+        assert_eq!(filter.value, 10);
+        assert_eq!(filter.to_string(), "ST_LEVEL>=10");
+        let filter: ParamFilter = "ST_ACCESS_LEVEL>0".parse().unwrap();
+        assert_eq!(filter.cmp, Cmp::Gt);
+        let filter: ParamFilter = "ST_GENDER=-1".parse().unwrap();
+        assert_eq!((filter.cmp, filter.value), (Cmp::Eq, -1));
+        assert_eq!(
+            "ST_LEVEL".parse::<ParamFilter>().unwrap_err(),
+            ParseFilterError::NoOperator("ST_LEVEL".into())
+        );
+        assert_eq!(
+            "LEVEL>1".parse::<ParamFilter>().unwrap_err(),
+            ParseFilterError::UnknownParam("LEVEL".into())
+        );
+        // Sentinel is past the end of params array
+        assert_eq!(
+            "PARAMS_COUNT>0".parse::<ParamFilter>().unwrap_err(),
+            ParseFilterError::UnknownParam("PARAMS_COUNT".into())
+        );
+    }
+
+    #[test]
+    fn query_by_indexes() {
+        let db = db();
+        assert_eq!(ids(&db, &Query::new()), vec![1, 2, 3]);
+        assert_eq!(ids(&db, &Query::new().id(2)), vec![2]);
+        assert_eq!(ids(&db, &Query::new().map_pid(170)), vec![1, 2]);
+        assert_eq!(ids(&db, &Query::new().ip([10, 0, 0, 1].into())), vec![1, 3]);
+        let query = Query::new()
+            .map_pid(170)
+            .param(Param::ST_LEVEL, Cmp::Ge, 10);
+        assert_eq!(ids(&db, &query), vec![2]);
+        assert_eq!(ids(&db, &Query::new().id(2).map_pid(42)), Vec::<u32>::new());
+    }
+}
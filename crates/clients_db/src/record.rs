@@ -1,5 +1,6 @@
 use std::{
     ffi::{OsStr, OsString},
+    fs::Metadata,
     io,
     path::PathBuf,
     sync::Arc,
@@ -9,10 +10,11 @@ use std::{
 use crate::{not_found, CritterInfo, InnerCritter};
 use fo_save_format::ClientSaveData;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct ClientRecord {
     pub filename: Box<OsStr>,
     pub modified: Option<SystemTime>,
+    pub size: Option<u64>,
     pub info: Option<InnerCritter>,
 }
 
@@ -21,12 +23,24 @@ impl ClientRecord {
         Self {
             filename: filename.into(),
             modified: None,
+            size: None,
             info: None,
         }
     }
+    /// Was the file already loaded with the same name, mtime and size?
+    pub fn is_fresh(&self, filename: &OsStr, metadata: &Metadata) -> bool {
+        self.modified.is_some()
+            && &*self.filename == filename
+            && self.modified == metadata.modified().ok()
+            && self.size == Some(metadata.len())
+    }
     pub fn update_info(&mut self, path: PathBuf, name: String) -> io::Result<()> {
         //let pathbuf = self.file_path(path);
-        self.modified = path.metadata().and_then(|md| md.modified()).ok();
+        // Remember stamp even if parsing fails, so broken file isn't parsed again until changed.
+        let metadata = path.metadata().ok();
+        self.modified = metadata.as_ref().and_then(|md| md.modified().ok());
+        self.size = metadata.as_ref().map(Metadata::len);
+        self.info = None;
         let data = std::fs::read(&path)?;
         let client_data = ClientSaveData::read_bincode(&mut &data[..])?;
         let mut critter_info = CritterInfo::from(&client_data);
@@ -44,6 +58,10 @@ impl ClientRecord {
         let mut to = path;
         to.push(&name);
         to.set_extension("client");
+        // Unix `rename` silently replaces the target, Windows refuses to; don't replace on both.
+        if to.exists() {
+            return Err(io::ErrorKind::AlreadyExists.into());
+        }
         std::fs::rename(from, to)?;
         self.filename = OsString::from(name).into_boxed_os_str();
         Ok(())
@@ -1,6 +1,36 @@
-#[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
-#[allow(bad_style)]
-pub enum Param {
+macro_rules! params {
+    ($($name:ident = $value:expr,)* ; $count:ident = $count_value:expr,) => {
+        #[derive(PartialOrd, Ord, PartialEq, Eq, Clone, Copy)]
+        #[allow(bad_style)]
+        pub enum Param {
+            $($name = $value,)*
+            $count = $count_value,
+        }
+
+        impl Param {
+            /// All params in declaration order, without the `PARAMS_COUNT` sentinel.
+            pub const ALL: &'static [Param] = &[$(Param::$name,)*];
+
+            /// Name as in `_defines.fos`, i.e. `ST_LEVEL`.
+            pub fn name(self) -> &'static str {
+                match self {
+                    $(Param::$name => stringify!($name),)*
+                    Param::$count => stringify!($count),
+                }
+            }
+
+            /// Looks up a real param, the sentinel isn't an index of one and isn't found.
+            pub fn from_name(name: &str) -> Option<Param> {
+                match name {
+                    $(stringify!($name) => Some(Param::$name),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+params! {
     //STAT_BEGIN = 0,
     //STAT_END = 199,
     //STAT_COUNT = 200,
@@ -476,5 +506,29 @@ pub enum Param {
     MERC_CANCEL_TIME = 808,
     MERC_CANCEL_ON_GLOBAL = 809,
     MERC_WAIT_FOR_MASTER = 810,
+    ;
     PARAMS_COUNT = 1000,
 }
+
+impl std::fmt::Debug for Param {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn names_roundtrip() {
+        assert_eq!(Param::ST_LEVEL.name(), "ST_LEVEL");
+        assert!(Param::from_name("ST_LEVEL") == Some(Param::ST_LEVEL));
+        assert!(Param::from_name("NOT_A_PARAM").is_none());
+        for &param in Param::ALL {
+            assert!(Param::from_name(param.name()) == Some(param));
+            assert!((param as usize) < Param::PARAMS_COUNT as usize);
+        }
+        assert!(Param::from_name("PARAMS_COUNT").is_none());
+    }
+}
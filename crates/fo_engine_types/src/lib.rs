@@ -1,4 +1,12 @@
 pub use fo_defines::DamageType;
+pub use fo_defines_fo4rp::fos::{COND_DEAD, CRITTER_NPC};
+use hex_geometry::{
+    grid::{FH_CRITTER, FH_DEAD_CRITTER, FH_NOSHOOT, FH_NOWAY},
+    HexGrid,
+};
+pub use primitives::Hex;
+
+pub mod mock;
 
 pub trait Engine: Sized {
     //TODO: generate MAX_DETERIORATION from headers?
@@ -11,16 +19,34 @@ pub trait Engine: Sized {
 }
 
 pub trait CritterLike<E: Engine> {
+    fn id(&self) -> u32;
+    fn hex(&self) -> Hex;
+    fn dir(&self) -> u8;
+    fn cond(&self) -> u8;
+    fn flags(&self) -> u32;
+    fn proto_id(&self) -> u16;
+    fn is_npc(&self) -> bool;
+    fn is_player(&self) -> bool {
+        !self.is_npc()
+    }
+    fn is_dead(&self) -> bool {
+        self.cond() == COND_DEAD
+    }
+    fn is_running(&self) -> bool;
     fn param(&self, param: E::Param) -> i32;
     fn armor(&self) -> Option<&E::Item>;
 }
 
 pub trait ItemLike<E: Engine> {
+    fn id(&self) -> u32;
     fn proto(&self) -> &E::ItemProto;
+    fn accessory(&self) -> u8;
+    fn flags(&self) -> u32;
+    fn count(&self) -> u32;
     fn deterioration(&self) -> i32;
     fn deterioration_proc(&self) -> i32 {
         let proc = self.deterioration() * 100 / E::MAX_DETERIORATION;
-        proc.min(100).max(0)
+        proc.clamp(0, 100)
     }
     fn durability_proc(&self) -> i32 {
         100 - self.deterioration_proc()
@@ -34,11 +60,48 @@ pub trait ItemLike<E: Engine> {
 }
 
 pub trait ItemProtoLike<E: Engine> {
+    fn proto_id(&self) -> u16;
+    fn item_type(&self) -> u32;
+    fn flags(&self) -> u32;
+    fn volume(&self) -> u32;
     fn resist(&self, damage: DamageType) -> i32;
     fn absorb(&self, damage: DamageType) -> i32;
     fn armor_class(&self) -> i32;
 }
 
+/// Server map, hex flags are laid out like `hex_geometry::grid`: map flags in high byte,
+/// proto flags in low byte.
+pub trait MapLike {
+    fn proto_id(&self) -> u16;
+    fn max_hex(&self) -> Hex;
+    /// Flags of the hex, `0` outside of the map.
+    fn hex_flags(&self, hex: Hex) -> u16;
+}
+
+/// `MapLike` as seen by geometry routines of `hex_geometry`.
+pub struct MapGrid<'a, M: ?Sized>(pub &'a M);
+
+impl<'a, M: MapLike + ?Sized> HexGrid for MapGrid<'a, M> {
+    fn max_hex(&self) -> Hex {
+        self.0.max_hex()
+    }
+    fn is_hex_passed(&self, hex: Hex) -> bool {
+        self.0.hex_flags(hex) & FH_NOWAY == 0
+    }
+    fn is_hex_raked(&self, hex: Hex) -> bool {
+        self.0.hex_flags(hex) & FH_NOSHOOT == 0
+    }
+    fn is_hex_critter(&self, hex: Hex) -> bool {
+        self.0.hex_flags(hex) & (FH_CRITTER | FH_DEAD_CRITTER) != 0
+    }
+}
+
+impl<'a, M: MapLike + ?Sized> check_look::LookMap for MapGrid<'a, M> {
+    fn proto_id(&self) -> u16 {
+        self.0.proto_id()
+    }
+}
+
 #[macro_export]
 macro_rules! impl_engine ((
     impl Engine for $engine:path {
@@ -46,7 +109,10 @@ macro_rules! impl_engine ((
         type ItemProto = $item_proto:path;
         type Critter = $critter:path;
         type Param = $param:path;
+        $(type Map = $map:path;)?
     }
+    critter_proto_id = |$cr_pid:ident| $proto_id:expr;
+    critter_is_npc = |$cr_npc:ident| $is_npc:expr;
 ) => {
     impl $crate::Engine for $engine {
         const MAX_DETERIORATION: i32 = 10000;
@@ -56,6 +122,32 @@ macro_rules! impl_engine ((
         type Param = $param;
     }
     impl $crate::CritterLike<$engine> for $critter {
+        fn id(&self) -> u32 {
+            self.Id
+        }
+        fn hex(&self) -> $crate::Hex {
+            $crate::Hex::new(self.HexX, self.HexY)
+        }
+        fn dir(&self) -> u8 {
+            self.Dir
+        }
+        fn cond(&self) -> u8 {
+            self.Cond
+        }
+        fn flags(&self) -> u32 {
+            self.Flags
+        }
+        fn proto_id(&self) -> u16 {
+            let $cr_pid = self;
+            $proto_id
+        }
+        fn is_npc(&self) -> bool {
+            let $cr_npc = self;
+            $is_npc
+        }
+        fn is_running(&self) -> bool {
+            self.IsRuning
+        }
         fn param(&self, param: $param) -> i32 {
             self.Params[param as usize]
         }
@@ -65,14 +157,38 @@ macro_rules! impl_engine ((
         }
     }
     impl $crate::ItemLike<$engine> for $item {
+        fn id(&self) -> u32 {
+            self.Id
+        }
         fn proto(&self) -> &$item_proto {
             unsafe { self.Proto.as_ref().expect("ItemProto") }
         }
+        fn accessory(&self) -> u8 {
+            self.Accessory
+        }
+        fn flags(&self) -> u32 {
+            self.Data.Flags
+        }
+        fn count(&self) -> u32 {
+            self.Data.Count
+        }
         fn deterioration(&self) -> i32 {
             self.Data.Deterioration as i32
         }
     }
     impl $crate::ItemProtoLike<$engine> for $item_proto {
+        fn proto_id(&self) -> u16 {
+            self.ProtoId
+        }
+        fn item_type(&self) -> u32 {
+            self.Type as u32
+        }
+        fn flags(&self) -> u32 {
+            self.Flags
+        }
+        fn volume(&self) -> u32 {
+            self.Volume
+        }
         fn resist(&self, damage: $crate::DamageType) -> i32 {
             use $crate::DamageType::*;
             match damage {
@@ -105,6 +221,28 @@ macro_rules! impl_engine ((
             self.Armor_AC
         }
     }
+    $(
+    impl $crate::MapLike for $map {
+        fn proto_id(&self) -> u16 {
+            self.Data.MapPid
+        }
+        fn max_hex(&self) -> $crate::Hex {
+            let proto = unsafe { self.Proto.as_ref().expect("Map prototype") };
+            $crate::Hex::new(proto.Header.MaxHexX, proto.Header.MaxHexY)
+        }
+        fn hex_flags(&self, hex: $crate::Hex) -> u16 {
+            let max = $crate::MapLike::max_hex(self);
+            if hex.x >= max.x || hex.y >= max.y {
+                return 0;
+            }
+            let index = hex.y as isize * max.x as isize + hex.x as isize;
+            let proto = unsafe { self.Proto.as_ref().expect("Map prototype") };
+            let map_flags = unsafe { *self.HexFlags.offset(index) } as u16;
+            let proto_flags = unsafe { *proto.HexFlags.offset(index) } as u16;
+            (map_flags << 8) | proto_flags
+        }
+    }
+    )?
 });
 
 #[cfg(test)]
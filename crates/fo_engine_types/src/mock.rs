@@ -0,0 +1,388 @@
+//! Plain Rust engine, so logic written against `Engine` traits can be tested without the game.
+use super::*;
+use check_look::Moving;
+use fo_defines_fo4rp::fos::{
+    ACCESS_MODER, QST_INVIS, QST_VISION, ST_ACCESS_LEVEL, ST_PERCEPTION, ST_PERCEPTION_EXT,
+};
+use hex_geometry::BlockerGrid;
+use std::sync::Arc;
+
+/// Size of `Critter::Params` array of the engine.
+pub const PARAMS_COUNT: usize = 1000;
+
+pub struct Mock;
+
+impl Engine for Mock {
+    const MAX_DETERIORATION: i32 = 10000;
+    type Item = MockItem;
+    type ItemProto = MockProto;
+    type Critter = MockCritter;
+    type Param = u32;
+}
+
+#[derive(Debug, Clone)]
+pub struct MockCritter {
+    pub id: u32,
+    pub hex: Hex,
+    pub dir: u8,
+    pub cond: u8,
+    pub flags: u32,
+    pub proto_id: u16,
+    pub is_npc: bool,
+    pub is_running: bool,
+    pub params: Vec<i32>,
+    pub armor: Option<MockItem>,
+}
+
+impl Default for MockCritter {
+    fn default() -> Self {
+        MockCritter {
+            id: 0,
+            hex: Hex::new(0, 0),
+            dir: 0,
+            cond: 1,
+            flags: 0,
+            proto_id: 0,
+            is_npc: false,
+            is_running: false,
+            params: vec![0; PARAMS_COUNT],
+            armor: None,
+        }
+    }
+}
+
+impl MockCritter {
+    pub fn with_param(mut self, param: u32, value: i32) -> Self {
+        self.params[param as usize] = value;
+        self
+    }
+}
+
+impl CritterLike<Mock> for MockCritter {
+    fn id(&self) -> u32 {
+        self.id
+    }
+    fn hex(&self) -> Hex {
+        self.hex
+    }
+    fn dir(&self) -> u8 {
+        self.dir
+    }
+    fn cond(&self) -> u8 {
+        self.cond
+    }
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+    fn proto_id(&self) -> u16 {
+        self.proto_id
+    }
+    fn is_npc(&self) -> bool {
+        self.is_npc
+    }
+    fn is_running(&self) -> bool {
+        self.is_running
+    }
+    fn param(&self, param: u32) -> i32 {
+        self.params[param as usize]
+    }
+    fn armor(&self) -> Option<&MockItem> {
+        self.armor.as_ref()
+    }
+}
+
+/// Params are read raw, without the getters of `fo_param_fo4rp`.
+impl check_look::LookCritter for MockCritter {
+    fn hex(&self) -> Hex {
+        self.hex
+    }
+    fn dir(&self) -> u8 {
+        self.dir
+    }
+    fn proto_id(&self) -> u16 {
+        self.proto_id
+    }
+    fn is_player(&self) -> bool {
+        !self.is_npc
+    }
+    fn is_dead(&self) -> bool {
+        self.cond == COND_DEAD
+    }
+    fn have_gm_vision(&self) -> bool {
+        self.param(ST_ACCESS_LEVEL) as u32 >= ACCESS_MODER && self.param(QST_VISION) > 0
+    }
+    fn vision(&self) -> u32 {
+        self.param(QST_VISION) as u32
+    }
+    fn invis(&self) -> u32 {
+        self.param(QST_INVIS) as u32
+    }
+    fn perception(&self) -> u32 {
+        (self.param(ST_PERCEPTION) + self.param(ST_PERCEPTION_EXT)).max(0) as u32
+    }
+    fn moving(&self) -> Moving {
+        if self.is_running {
+            Moving::Running
+        } else {
+            Moving::Still
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MockItem {
+    pub id: u32,
+    pub proto: Arc<MockProto>,
+    pub accessory: u8,
+    pub flags: u32,
+    pub count: u32,
+    pub deterioration: u16,
+}
+
+impl MockItem {
+    pub fn new(id: u32, proto: Arc<MockProto>) -> Self {
+        MockItem {
+            id,
+            flags: proto.flags,
+            proto,
+            count: 1,
+            ..Default::default()
+        }
+    }
+}
+
+impl ItemLike<Mock> for MockItem {
+    fn id(&self) -> u32 {
+        self.id
+    }
+    fn proto(&self) -> &MockProto {
+        &self.proto
+    }
+    fn accessory(&self) -> u8 {
+        self.accessory
+    }
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+    fn count(&self) -> u32 {
+        self.count
+    }
+    fn deterioration(&self) -> i32 {
+        self.deterioration as i32
+    }
+}
+
+/// Armor values are indexed by `DamageType` from `Normal` to `Explosion`.
+#[derive(Debug, Clone, Default)]
+pub struct MockProto {
+    pub proto_id: u16,
+    pub item_type: u32,
+    pub flags: u32,
+    pub volume: u32,
+    pub resist: [i32; 7],
+    pub absorb: [i32; 7],
+    pub armor_class: i32,
+}
+
+fn armor_index(damage: DamageType) -> Option<usize> {
+    use DamageType::*;
+    match damage {
+        Uncalled | Unknown => None,
+        _ => Some(damage as usize - Normal as usize),
+    }
+}
+
+impl ItemProtoLike<Mock> for MockProto {
+    fn proto_id(&self) -> u16 {
+        self.proto_id
+    }
+    fn item_type(&self) -> u32 {
+        self.item_type
+    }
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+    fn volume(&self) -> u32 {
+        self.volume
+    }
+    fn resist(&self, damage: DamageType) -> i32 {
+        armor_index(damage).map_or(0, |index| self.resist[index])
+    }
+    fn absorb(&self, damage: DamageType) -> i32 {
+        armor_index(damage).map_or(0, |index| self.absorb[index])
+    }
+    fn armor_class(&self) -> i32 {
+        self.armor_class
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MockMap {
+    pub proto_id: u16,
+    pub grid: BlockerGrid,
+}
+
+impl MockMap {
+    pub fn new(proto_id: u16, max: Hex) -> Self {
+        MockMap {
+            proto_id,
+            grid: BlockerGrid::new(max),
+        }
+    }
+    /// Marks hex of the critter, like `Map::SetFlagCritter` of the engine.
+    pub fn add_critter(&mut self, cr: &impl CritterLike<Mock>) {
+        let flag = if cr.is_dead() {
+            FH_DEAD_CRITTER
+        } else {
+            FH_CRITTER
+        };
+        self.grid.add_flags(cr.hex(), flag);
+    }
+}
+
+impl MapLike for MockMap {
+    fn proto_id(&self) -> u16 {
+        self.proto_id
+    }
+    fn max_hex(&self) -> Hex {
+        self.grid.max_hex()
+    }
+    fn hex_flags(&self, hex: Hex) -> u16 {
+        self.grid.flags(hex)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use hex_geometry::{
+        find_path,
+        grid::{FH_BLOCK, FH_NOTRAKE, FH_WALL},
+        PathRequest,
+    };
+
+    fn armor() -> MockItem {
+        let proto = Arc::new(MockProto {
+            proto_id: 3,
+            item_type: 1,
+            resist: [20, 40, 0, 0, 0, 500, 10],
+            absorb: [4, 6, 0, 0, 0, 0, 2],
+            armor_class: 15,
+            ..Default::default()
+        });
+        MockItem::new(100, proto)
+    }
+
+    #[test]
+    fn armor_deterioration() {
+        let mut item = armor();
+        assert_eq!(item.resist_proc(DamageType::Laser), 40);
+        assert_eq!(item.resist_proc(DamageType::Emp), 500);
+        assert_eq!(item.absorb_proc(DamageType::Explosion), 2);
+        assert_eq!(item.resist_proc(DamageType::Uncalled), 0);
+        assert_eq!(item.resist_proc(DamageType::Unknown), 0);
+
+        item.deterioration = 2500;
+        assert_eq!(item.deterioration_proc(), 25);
+        assert_eq!(item.resist_proc(DamageType::Laser), 30);
+        assert_eq!(item.absorb_proc(DamageType::Normal), 3);
+
+        item.deterioration = u16::MAX;
+        assert_eq!(item.deterioration_proc(), 100);
+        assert_eq!(item.resist_proc(DamageType::Laser), 0);
+    }
+
+    #[test]
+    fn critter_accessors() {
+        let cr = MockCritter {
+            id: 5000,
+            hex: Hex::new(10, 12),
+            proto_id: 62,
+            is_npc: true,
+            armor: Some(armor()),
+            ..Default::default()
+        }
+        .with_param(72, 9);
+        assert_eq!(cr.param(72), 9);
+        assert_eq!(cr.param(73), 0);
+        assert!(cr.is_npc() && !cr.is_player() && !cr.is_dead());
+        let ac = cr
+            .armor()
+            .map(ItemLike::proto)
+            .map_or(0, ItemProtoLike::armor_class);
+        assert_eq!(ac, 15);
+        assert!(MockCritter::default().armor().is_none());
+    }
+
+    #[test]
+    fn map_flags() {
+        let mut map = MockMap::new(7, Hex::new(10, 10));
+        for y in 0..9 {
+            map.grid
+                .add_flags(Hex::new(5, y), FH_WALL | FH_BLOCK | FH_NOTRAKE);
+        }
+        let alive = MockCritter {
+            hex: Hex::new(5, 9),
+            ..Default::default()
+        };
+        let dead = MockCritter {
+            hex: Hex::new(2, 2),
+            cond: COND_DEAD,
+            ..Default::default()
+        };
+        map.add_critter(&alive);
+        map.add_critter(&dead);
+
+        let grid = MapGrid(&map);
+        assert!(!grid.is_hex_passed(Hex::new(5, 9)) && grid.is_hex_raked(Hex::new(5, 9)));
+        assert!(grid.is_hex_passed(Hex::new(2, 2)) && grid.is_hex_critter(Hex::new(2, 2)));
+        assert_eq!(map.hex_flags(Hex::new(10, 0)), 0);
+
+        // Wall and critter close the map in half
+        let request = PathRequest::new(Hex::new(1, 1), Hex::new(8, 1));
+        assert!(find_path(&grid, &request).is_err());
+        map.grid.remove_flags(alive.hex, FH_CRITTER);
+        assert!(find_path(&MapGrid(&map), &request).is_ok());
+    }
+
+    #[test]
+    fn check_look_on_mocks() {
+        use check_look::{check_look, config::CheckLook};
+
+        let config = CheckLook::default();
+        let mut map = MockMap::new(7, Hex::new(40, 40));
+        for y in 0..40 {
+            map.grid
+                .add_flags(Hex::new(20, y), FH_WALL | FH_BLOCK | FH_NOTRAKE);
+        }
+        let cr = MockCritter {
+            hex: Hex::new(10, 10),
+            ..Default::default()
+        }
+        .with_param(ST_PERCEPTION, 5);
+        let near = MockCritter {
+            hex: Hex::new(12, 10),
+            ..Default::default()
+        };
+        let behind_wall = MockCritter {
+            hex: Hex::new(25, 10),
+            ..Default::default()
+        };
+        map.add_critter(&cr);
+        map.add_critter(&near);
+        map.add_critter(&behind_wall);
+        let grid = MapGrid(&map);
+
+        assert!(check_look(&config, &grid, &cr, &near));
+        assert!(!check_look(&config, &grid, &cr, &behind_wall));
+        let cr = cr.with_param(QST_VISION, 20);
+        assert!(check_look(&config, &grid, &cr, &behind_wall));
+        let dead_npc = MockCritter {
+            hex: cr.hex,
+            cond: COND_DEAD,
+            is_npc: true,
+            ..Default::default()
+        };
+        assert!(!check_look(&config, &grid, &dead_npc, &near));
+    }
+}
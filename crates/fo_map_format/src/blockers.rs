@@ -0,0 +1,339 @@
+//! Passability grid of a map, as the engine builds it from map objects and item protos.
+use crate::{objects::Kind, Map, MapObjectType};
+use fo_defines_fo4rp::fos::{
+    COND_DEAD, ITEM_GAG, ITEM_NO_BLOCK, ITEM_SHOOT_THRU, ITEM_TYPE_GRID, ITEM_TYPE_WALL,
+};
+use hex_geometry::{grid::*, move_hex_by_dir_unsafe, BlockerGrid, HexGrid, HEX_DIRS};
+use primitives::Hex;
+use std::collections::BTreeSet;
+
+/// Item proto fields that matter for blocking, e.g. taken from `fo_proto_format::ProtoItem`.
+#[derive(Debug, Clone, Default)]
+pub struct BlockerProto {
+    pub item_type: u8,
+    pub flags: u32,
+    /// `BlockLines` of multihex items: `(dir, steps)` pairs walked from the item's hex.
+    pub block_lines: Vec<(u8, u8)>,
+}
+
+impl BlockerProto {
+    fn is_passed(&self) -> bool {
+        self.flags & ITEM_NO_BLOCK != 0
+    }
+    fn is_raked(&self) -> bool {
+        self.flags & ITEM_SHOOT_THRU != 0
+    }
+}
+
+#[cfg(feature = "fo_proto_format")]
+impl From<&fo_proto_format::ProtoItem> for BlockerProto {
+    fn from(proto: &fo_proto_format::ProtoItem) -> Self {
+        BlockerProto {
+            item_type: proto.Type,
+            flags: proto.Flags.unwrap_or(0),
+            block_lines: proto
+                .BlockLines
+                .as_deref()
+                .map(parse_block_lines)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Parses `BlockLines` value of a proto, every two digits are direction and number of steps.
+pub fn parse_block_lines(lines: &str) -> Vec<(u8, u8)> {
+    let digits: Vec<u8> = lines.bytes().map(|byte| byte.wrapping_sub(b'0')).collect();
+    digits
+        .chunks_exact(2)
+        .map(|pair| (pair[0], pair[1]))
+        .take_while(|&(dir, steps)| dir < HEX_DIRS && steps != 0 && steps <= 9)
+        .collect()
+}
+
+/// Builds `BlockerGrid` of maps, protos are looked up by `protos` closure.
+pub struct GridBuilder<F> {
+    protos: F,
+    critters: bool,
+    missing_protos: BTreeSet<u16>,
+}
+
+impl<'p, F: Fn(u16) -> Option<&'p BlockerProto>> GridBuilder<F> {
+    pub fn new(protos: F) -> Self {
+        GridBuilder {
+            protos,
+            critters: false,
+            missing_protos: BTreeSet::new(),
+        }
+    }
+    /// Mark hexes of critters placed in the map, off by default.
+    pub fn with_critters(mut self, critters: bool) -> Self {
+        self.critters = critters;
+        self
+    }
+    /// Protos of map objects that weren't found, such objects are ignored.
+    pub fn missing_protos(&self) -> &BTreeSet<u16> {
+        &self.missing_protos
+    }
+    pub fn build(&mut self, map: &Map) -> BlockerGrid {
+        let max = Hex::new(map.header.max_hex_x, map.header.max_hex_y);
+        let mut grid = BlockerGrid::new(max);
+        for obj in &map.objects.0 {
+            let hex = match (obj.map_x, obj.map_y) {
+                (Some(x), Some(y)) if x < max.x && y < max.y => Hex::new(x, y),
+                _ => continue,
+            };
+            let ty = obj.kind.map_object_type();
+            if ty == MapObjectType::MAP_OBJECT_CRITTER {
+                if let (true, Kind::Critter { cond, .. }) = (self.critters, &obj.kind) {
+                    let flag = if *cond == Some(COND_DEAD) {
+                        FH_DEAD_CRITTER
+                    } else {
+                        FH_CRITTER
+                    };
+                    grid.add_flags(hex, flag);
+                }
+                continue;
+            }
+            let in_container = match &obj.kind {
+                Kind::Item {
+                    v9_in_container, ..
+                } => *v9_in_container == Some(true),
+                _ => false,
+            };
+            if in_container || obj.relations.container_uid.is_some() {
+                continue;
+            }
+            let proto = match (self.protos)(obj.proto_id) {
+                Some(proto) => proto,
+                None => {
+                    self.missing_protos.insert(obj.proto_id);
+                    continue;
+                }
+            };
+            if ty == MapObjectType::MAP_OBJECT_SCENERY {
+                place_scenery(&mut grid, hex, proto);
+            } else {
+                place_item(&mut grid, hex, proto);
+            }
+        }
+        grid
+    }
+}
+
+/// Proto flags, like `ProtoMap` of the engine does for scenery and walls.
+fn place_scenery(grid: &mut BlockerGrid, hex: Hex, proto: &BlockerProto) {
+    let mut flags = if proto.item_type as u32 == ITEM_TYPE_WALL {
+        FH_WALL
+    } else if proto.item_type as u32 == ITEM_TYPE_GRID {
+        FH_SCEN | FH_SCEN_GRID
+    } else {
+        FH_SCEN
+    };
+    if !proto.is_passed() {
+        flags |= FH_BLOCK;
+    }
+    if !proto.is_raked() {
+        flags |= FH_NOTRAKE;
+    }
+    grid.add_flags(hex, flags);
+    let line_flags = if proto.is_raked() {
+        FH_BLOCK
+    } else {
+        FH_BLOCK | FH_NOTRAKE
+    };
+    place_block_lines(grid, hex, proto, line_flags);
+}
+
+/// Map flags, like `Map::AddItem` of the engine does for items lying on the ground.
+fn place_item(grid: &mut BlockerGrid, hex: Hex, proto: &BlockerProto) {
+    let mut flags = FH_ITEM;
+    if !proto.is_passed() {
+        flags |= FH_BLOCK_ITEM;
+    }
+    if !proto.is_raked() {
+        flags |= FH_NRAKE_ITEM;
+    }
+    if proto.flags & ITEM_GAG != 0 {
+        flags |= FH_GAG_ITEM;
+    }
+    grid.add_flags(hex, flags);
+    let line_flags = if proto.is_raked() {
+        FH_BLOCK_ITEM
+    } else {
+        FH_BLOCK_ITEM | FH_NRAKE_ITEM
+    };
+    place_block_lines(grid, hex, proto, line_flags);
+}
+
+/// Walks `BlockLines` from the hex, lines may leave the map and come back.
+fn place_block_lines(grid: &mut BlockerGrid, hex: Hex, proto: &BlockerProto, flags: u16) {
+    let max = grid.max_hex();
+    let mut pos = (hex.x as i32, hex.y as i32);
+    for &(dir, steps) in &proto.block_lines {
+        for _ in 0..steps {
+            pos = move_hex_by_dir_unsafe(pos, dir, true);
+            let (x, y) = pos;
+            if x >= 0 && y >= 0 && x < max.x as i32 && y < max.y as i32 {
+                grid.add_flags(Hex::new(x as u16, y as u16), flags);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::prelude::nom::error::VerboseError;
+    use crate::{root, MapParserSettings};
+    use std::collections::BTreeMap;
+
+    const MAP: &str = "[Header]
+Version              4
+MaxHexX              20
+MaxHexY              20
+WorkHexX             10
+WorkHexY             10
+ScriptModule         -
+ScriptFunc           -
+NoLogOut             0
+Time                 -1
+DayTime              300  600  1140 1380
+DayColor0            18  18  53
+DayColor1            128 128 128
+DayColor2            103 95  86
+DayColor3            51  40  29
+
+[Tiles]
+
+[Objects]
+MapObjType           2
+ProtoId              1
+MapX                 2
+MapY                 2
+
+MapObjType           2
+ProtoId              2
+MapX                 5
+MapY                 5
+
+MapObjType           2
+ProtoId              3
+MapX                 8
+MapY                 8
+
+MapObjType           1
+ProtoId              4
+MapX                 12
+MapY                 12
+
+MapObjType           1
+ProtoId              4
+MapX                 13
+MapY                 13
+Item_InContainer     1
+
+MapObjType           0
+ProtoId              100
+MapX                 15
+MapY                 15
+Critter_Cond         1
+
+MapObjType           2
+ProtoId              99
+MapX                 1
+MapY                 1
+
+";
+
+    fn protos() -> BTreeMap<u16, BlockerProto> {
+        let mut protos = BTreeMap::new();
+        // Wall that can be shot through
+        protos.insert(
+            1,
+            BlockerProto {
+                item_type: ITEM_TYPE_WALL as u8,
+                flags: ITEM_SHOOT_THRU,
+                block_lines: vec![],
+            },
+        );
+        // Flat scenery
+        protos.insert(
+            2,
+            BlockerProto {
+                item_type: 11,
+                flags: ITEM_NO_BLOCK | ITEM_SHOOT_THRU,
+                block_lines: vec![],
+            },
+        );
+        // Multihex scenery: two steps down, one step to dir 3
+        protos.insert(
+            3,
+            BlockerProto {
+                item_type: 11,
+                flags: 0,
+                block_lines: parse_block_lines("2231"),
+            },
+        );
+        // Blocking item
+        protos.insert(
+            4,
+            BlockerProto {
+                item_type: 8,
+                flags: 0,
+                block_lines: vec![],
+            },
+        );
+        protos
+    }
+
+    #[test]
+    fn block_lines() {
+        assert_eq!(parse_block_lines("2231"), vec![(2, 2), (3, 1)]);
+        assert_eq!(parse_block_lines("22006"), vec![(2, 2)]);
+        assert_eq!(parse_block_lines(""), vec![]);
+    }
+
+    #[test]
+    fn grid_from_map() {
+        let (rest, map) = root::<VerboseError<&str>>(MapParserSettings::default())(MAP).unwrap();
+        assert!(rest.is_empty(), "{:?}", rest);
+        let protos = protos();
+        let mut builder = GridBuilder::new(|pid| protos.get(&pid));
+        let grid = builder.build(&map);
+        assert_eq!(
+            builder.missing_protos().iter().copied().collect::<Vec<_>>(),
+            vec![99]
+        );
+
+        let wall = Hex::new(2, 2);
+        assert!(!grid.is_hex_passed(wall) && grid.is_hex_raked(wall));
+        assert_eq!(grid.flags(wall) & FH_WALL, FH_WALL);
+
+        let flat = Hex::new(5, 5);
+        assert!(grid.is_hex_passed(flat) && grid.is_hex_raked(flat));
+
+        for &hex in &[
+            Hex::new(8, 8),
+            Hex::new(8, 9),
+            Hex::new(8, 10),
+            Hex::new(9, 11),
+        ] {
+            assert!(
+                !grid.is_hex_passed(hex) && !grid.is_hex_raked(hex),
+                "{:?}",
+                hex
+            );
+        }
+        assert!(grid.is_hex_passed(Hex::new(8, 11)));
+
+        let item = Hex::new(12, 12);
+        assert!(!grid.is_hex_passed(item) && !grid.is_hex_raked(item));
+        assert!(grid.is_hex_passed(Hex::new(13, 13)));
+
+        assert!(grid.is_hex_passed(Hex::new(15, 15)));
+        let grid = GridBuilder::new(|pid| protos.get(&pid))
+            .with_critters(true)
+            .build(&map);
+        assert!(!grid.is_hex_passed(Hex::new(15, 15)) && grid.is_hex_critter(Hex::new(15, 15)));
+    }
+}
@@ -1,3 +1,5 @@
+#[cfg(feature = "blockers")]
+pub mod blockers;
 mod header;
 mod objects;
 mod prelude;
@@ -135,6 +135,10 @@ macro_rules! impl_calc(
         args: ($($min:expr, $max:expr)?)
     } => {
         impl $decl {
+            /// Declared `(min, max)` of calculated value.
+            #[allow(dead_code)]
+            pub const RANGE: Option<(i32, i32)> = $crate::impl_calc!(@range $($min, $max)?);
+
             #[allow(dead_code)]
             pub fn calc$(<$lt>)?(&$($lt)? self) -> formula::prelude::tools::Op<$data, i32, impl formula::prelude::Formula<$data, i32>> {
                 use $crate::black_magic::{CalcSum, CalcBase};
@@ -144,6 +148,27 @@ macro_rules! impl_calc(
                 res
             }
         }
+    };
+    (@range) => { None };
+    (@range $min:expr, $max:expr) => { Some(($min, $max)) };
+);
+
+/// Lists declared ranges of all params of `impl_param!`, goes to `with_decls`.
+#[macro_export]
+macro_rules! impl_ranges(
+    {
+        lt: ($($lt:tt)?), data: $data:ty,
+        shared: ($fn_name:ident),
+        args: ($($decl:ident),+)
+    } => {
+        /// `(index, min, max)` of params with declared range.
+        pub fn $fn_name$(<$lt>)?() -> Vec<(u16, i32, i32)> {
+            use $crate::param_types::HasParamBase;
+            [$((Into::<u16>::into(<$decl as HasParamBase<$data>>::INDEX), $decl::RANGE)),+]
+                .iter()
+                .filter_map(|&(index, range)| range.map(|(min, max)| (index, min, max)))
+                .collect()
+        }
     }
 );
 
@@ -1,7 +1,7 @@
 use crate::raw_param::RawParam;
-use fo_engine_types_bindings::generated::r357::server::item::Item;
+use fo_engine_types::{DamageType, Engine, ItemLike, ItemProtoLike};
 use fo_param::param_types::ParamGet;
-use formula::prelude::{Formula, FormulaCompat, FormulaData};
+use formula::prelude::{FormulaCompat, FormulaData};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Time {
@@ -16,24 +16,54 @@ impl Time {
     }
 }
 
+/// Worn armor as seen by formulas.
+pub trait Armor {
+    fn resist_proc(&self, damage: DamageType) -> i32;
+    fn absorb_proc(&self, damage: DamageType) -> i32;
+    fn armor_class(&self) -> i32;
+}
+
+/// `Armor` of an item of any engine, real or `fo_engine_types::mock`.
+pub struct EngineArmor<'a, E: Engine>(pub &'a E::Item);
+
+impl<'a, E: Engine> Armor for EngineArmor<'a, E> {
+    fn resist_proc(&self, damage: DamageType) -> i32 {
+        self.0.resist_proc(damage)
+    }
+    fn absorb_proc(&self, damage: DamageType) -> i32 {
+        self.0.absorb_proc(damage)
+    }
+    fn armor_class(&self) -> i32 {
+        self.0.proto().armor_class()
+    }
+}
+
 pub struct Critter<'a> {
     pub param: &'a [i32; RawParam::PARAMS_COUNT as usize],
-    pub time: Time,
+    /// Game time, `None` if it's unknown, e.g. before the engine is started.
+    pub time: Option<Time>,
     pub full_second: u32,
-    armor: Option<Item>,
+    armor: Option<Box<dyn Armor + 'a>>,
 }
 
 impl<'a> Critter<'a> {
-    pub fn new(param: &'a [i32; RawParam::PARAMS_COUNT as usize], time: Time) -> Self {
+    pub fn new(
+        param: &'a [i32; RawParam::PARAMS_COUNT as usize],
+        time: impl Into<Option<Time>>,
+    ) -> Self {
         Critter {
             param,
-            time,
+            time: time.into(),
             full_second: 0,
             armor: None,
         }
     }
-    pub fn armor(&self) -> Option<&Item> {
-        self.armor.as_ref()
+    pub fn with_armor(mut self, armor: impl Armor + 'a) -> Self {
+        self.armor = Some(Box::new(armor));
+        self
+    }
+    pub fn armor(&self) -> Option<&dyn Armor> {
+        self.armor.as_deref()
     }
 }
 
@@ -48,3 +78,38 @@ impl<'a> FormulaData for &'a Critter<'a> {}
 
 pub trait CrOp<'a>: Send + Sync + FormulaCompat<&'a Critter<'a>, i32> {}
 impl<'a, T: Send + Sync + FormulaCompat<&'a Critter<'a>, i32>> CrOp<'a> for T {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::param::{absorb, resist};
+    use fo_engine_types::mock::{Mock, MockItem, MockProto};
+    use formula::prelude::Formula;
+    use std::sync::Arc;
+
+    #[test]
+    fn mock_armor() {
+        let proto = Arc::new(MockProto {
+            resist: [10, 40, 0, 0, 0, 0, 0],
+            absorb: [4, 0, 0, 0, 0, 0, 0],
+            ..Default::default()
+        });
+        let mut armor = MockItem::new(1, proto);
+        armor.deterioration = 2500;
+
+        let mut param = Box::new([0; RawParam::PARAMS_COUNT as usize]);
+        param[RawParam::ST_LASER_RESIST as usize] = 5;
+        let time = Time {
+            hour: 12,
+            minute: 0,
+            second: 0,
+        };
+        let naked = Critter::new(&param, time);
+        assert_eq!(resist::Laser.calc().compute(&naked), 5);
+
+        let cr = Critter::new(&param, time).with_armor(EngineArmor::<Mock>(&armor));
+        assert_eq!(resist::Laser.calc().compute(&cr), 35);
+        assert_eq!(resist::Normal.calc().compute(&cr), 7);
+        assert_eq!(absorb::Normal.calc().compute(&cr), 3);
+    }
+}
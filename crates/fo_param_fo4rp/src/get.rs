@@ -1,62 +1,244 @@
-use crate::{critter::Critter, param, raw_param::RawParam};
-use fo_param::param_types::{HasBoxedFormulas, ParamGet};
-use formula::prelude::{
-    tools::{self, BoxedFormula, Op},
-    Formula,
+use crate::{
+    critter::Critter,
+    param::{stat, timeout},
+    raw_param::RawParam,
 };
-use once_cell::sync::Lazy;
-/*
-static FORMULAS: Lazy<Vec<Option<BoxedFormula<&Critter, i32>>>> = Lazy::new(|| {
-    let mut formulas = vec![None; 1000];
+use fo_param::param_types::ParamGet;
+use formula::prelude::Formula;
 
-    let stats = param::stat::StatFormulas.make_boxed_formulas();
-    for (k, v) in stats {
-        let index = k as usize;
-        if let Some(old) = formulas[index].replace(v) {
-            panic!("Expected 0..1000 range of Params: {}", index);
+/// Params which values are computed by formulas, the rest are taken as is.
+macro_rules! formula_params {
+    ($($index:ident => $param:expr, $name:expr;)*) => {
+        /// Value of the param computed by its formula, `None` if the param has none.
+        pub fn formula_param<'a>(data: &'a Critter<'a>, index: u32) -> Option<i32> {
+            $(
+                if index == RawParam::$index as u32 {
+                    return Some($param.calc().compute(data));
+                }
+            )*
+            None
         }
-    }
-    formulas
-});
-*/
-
-pub fn param<'a>(data: &'a Critter<'a>, index: RawParam) -> i32 {
-    /*let mut formulas = vec![None; 1000];
 
-    let stats = param::stat::StatFormulas.make_boxed_formulas();
-    for (k, v) in stats {
-        let index = k as usize;
-        if let Some(old) = formulas[index].replace(v) {
-            panic!("Expected 0..1000 range of Params: {}", index);
+        /// Human readable breakdown of the param formula for the critter.
+        pub fn explain<'a>(data: &'a Critter<'a>, index: u32) -> Option<String> {
+            $(
+                if index == RawParam::$index as u32 {
+                    return $param.calc().full_info($name, Some(data)).ok();
+                }
+            )*
+            None
         }
-    }
+    };
+}
 
-    let formula = formulas[index as usize].as_ref().unwrap();
-    formula.compute(data)*/
+formula_params!(
+    ST_STRENGTH => stat::Strength, "Сила";
+    ST_PERCEPTION => stat::Perception, "Восприятие";
+    ST_ENDURANCE => stat::Endurance, "Выносливость";
+    TO_SK_FIRST_AID => timeout::of_skill::FirstAid, "ТаймаутПерваяПомощь";
+    TO_SK_DOCTOR => timeout::of_skill::Doctor, "ТаймаутДоктор";
+    TO_SK_REPAIR => timeout::of_skill::Repair, "ТаймаутРемонт";
+    TO_SK_SCIENCE => timeout::of_skill::Science, "ТаймаутНаука";
+    TO_SK_LOCKPICK => timeout::of_skill::LockPick, "ТаймаутВзлом";
+    TO_SK_STEAL => timeout::of_skill::Steal, "ТаймаутВоровство";
+    TO_SK_OUTDOORSMAN => timeout::of_skill::Outdoorsman, "ТаймаутВыживание";
+    TO_DEATH => timeout::Death, "ТаймаутСмерть";
+    TO_BATTLE => timeout::Battle, "ТаймаутБой";
+    TO_TRANSFER => timeout::Transfer, "ТаймаутПеремещение";
+    TO_REMOVE_FROM_GAME => timeout::RemoveFromGame, "ТаймаутВыходИзИгры";
+    TO_REPLICATION => timeout::Replication, "ТаймаутПерерождение";
+    TO_TIREDNESS => timeout::Tiredness, "ТаймаутУсталость";
+    TO_SNEAK => timeout::Sneak, "ТаймаутСкрытность";
+    TO_HEALING => timeout::Healing, "ТаймаутЛечение";
+    TO_STEALING => timeout::Stealing, "ТаймаутКража";
+    TO_AGGRESSOR => timeout::Agressor, "ТаймаутАгрессия";
+    TO_HAIR_GROW => timeout::HairGrow, "ТаймаутРостВолос";
+    TO_SAY => timeout::Say, "ТаймаутРечь";
+    TO_DEAD => timeout::Dead, "ТаймаутМертв";
+);
 
-    use crate::param::stat;
-    match index {
-        RawParam::ST_STRENGTH => stat::Strength.calc().compute(data),
-        RawParam::ST_PERCEPTION => stat::Perception.calc().compute(data),
-        RawParam::ST_ENDURANCE => stat::Endurance.calc().compute(data),
-        _ => data.get_param(index),
-    }
+pub fn param<'a>(data: &'a Critter<'a>, index: RawParam) -> i32 {
+    formula_param(data, index as u32).unwrap_or_else(|| data.get_param(index))
+}
+
+/// Seconds left of the timeout, for timeouts without their own formula too.
+pub fn timeout<'a>(data: &'a Critter<'a>, index: u32) -> i32 {
+    formula_param(data, index)
+        .unwrap_or_else(|| timeout::timeout_left(data.param[index as usize], data.full_second))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::critter::Time;
+    use proptest::prelude::*;
+
+    const PARAMS_COUNT: usize = RawParam::PARAMS_COUNT as usize;
+
+    /// Hand-written `getParam_*` callbacks of the server DLL, kept to check formulas against.
+    mod legacy {
+        use super::*;
+
+        fn param(params: &[i32], index: RawParam) -> i32 {
+            params[index as usize]
+        }
+
+        pub fn strength(params: &[i32], full_second: u32) -> i32 {
+            use RawParam::*;
+            let mut val = param(params, ST_STRENGTH) + param(params, ST_STRENGTH_EXT);
+            if param(params, PE_ADRENALINE_RUSH) > 0
+                && timeout(params, TO_BATTLE as u32, full_second) > 0
+                && param(params, ST_CURRENT_HP)
+                    <= (param(params, ST_MAX_LIFE)
+                        + param(params, ST_STRENGTH)
+                        + param(params, ST_ENDURANCE) * 2)
+                        / 2
+            {
+                val += 1;
+            }
+            val.clamp(1, 10)
+        }
+
+        pub fn perception(params: &[i32], time: Option<Time>) -> i32 {
+            use RawParam::*;
+            let mut val = if param(params, DAMAGE_EYE) != 0 {
+                1
+            } else {
+                param(params, ST_PERCEPTION) + param(params, ST_PERCEPTION_EXT)
+            };
+            if param(params, TRAIT_NIGHT_PERSON) != 0 {
+                val += night_person_bonus(time);
+            }
+            val.clamp(1, 10)
+        }
+
+        fn night_person_bonus(time: Option<Time>) -> i32 {
+            let (hour, minute) = match time {
+                Some(time) => (time.hour, time.minute),
+                None => return 0,
+            };
+            if !(6..=18).contains(&hour) || (hour == 6 && minute == 0) || (hour == 18 && minute > 0)
+            {
+                1
+            } else {
+                -1
+            }
+        }
+
+        pub fn timeout(params: &[i32], index: u32, full_second: u32) -> i32 {
+            let param = params[index as usize] as u32;
+            if param > full_second {
+                (param - full_second) as i32
+            } else {
+                0
+            }
+        }
+    }
+
+    /// Game time, `None` before the engine is started.
+    fn time() -> impl Strategy<Value = Option<Time>> {
+        prop::option::of(
+            (0..24u16, 0..60u16, 0..60u16).prop_map(|(hour, minute, second)| Time {
+                hour,
+                minute,
+                second,
+            }),
+        )
+    }
+
+    /// Params array with plausible values: stats around their range, flags of perks and
+    /// damages `0` or `1`. Timeouts end near current time or anywhere in `i32`, negative
+    /// ends and ends past `i32::MAX` of current time included: engine treats them as `u32`.
+    fn params(full_second: u32) -> impl Strategy<Value = Vec<i32>> {
+        use RawParam::{
+            DAMAGE_EYE, PE_ADRENALINE_RUSH, ST_CURRENT_HP, ST_MAX_LIFE, TO_SK_FIRST_AID,
+            TRAIT_NIGHT_PERSON,
+        };
+        (
+            prop::collection::vec(-5..15i32, 64),
+            prop::collection::vec(-50..400i32, 2),
+            prop::collection::vec(0..=1i32, 3),
+            prop::collection::vec(
+                prop_oneof![
+                    (-3600..3600i32).prop_map(move |left| (full_second as i32).wrapping_add(left)),
+                    any::<i32>(),
+                ],
+                50,
+            ),
+        )
+            .prop_map(move |(stats, life, flags, timeouts)| {
+                let mut params = vec![0; PARAMS_COUNT];
+                params[..64].copy_from_slice(&stats);
+                params[ST_CURRENT_HP as usize] = life[0];
+                params[ST_MAX_LIFE as usize] = life[1];
+                params[PE_ADRENALINE_RUSH as usize] = flags[0];
+                params[DAMAGE_EYE as usize] = flags[1];
+                params[TRAIT_NIGHT_PERSON as usize] = flags[2];
+                params[TO_SK_FIRST_AID as usize..][..50].copy_from_slice(&timeouts);
+                params
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn formulas_match_legacy_getters(
+            (full_second, params) in any::<u32>().prop_flat_map(|fs| (Just(fs), params(fs))),
+            time in time(),
+        ) {
+            let mut array = Box::new([0; PARAMS_COUNT]);
+            array.copy_from_slice(&params);
+            let mut cr = Critter::new(&array, time);
+            cr.full_second = full_second;
+
+            prop_assert_eq!(
+                param(&cr, RawParam::ST_STRENGTH),
+                legacy::strength(&params, full_second)
+            );
+            prop_assert_eq!(
+                param(&cr, RawParam::ST_PERCEPTION),
+                legacy::perception(&params, time)
+            );
+            for index in RawParam::TO_SK_FIRST_AID as u32..RawParam::TO_SK_FIRST_AID as u32 + 50 {
+                prop_assert_eq!(
+                    timeout(&cr, index),
+                    legacy::timeout(&params, index, full_second),
+                    "timeout {}", index
+                );
+            }
+        }
+    }
+
     #[test]
-    fn test_get_param() {
-        /*use crate::critter::*;
-        let mut params = [0i32; 1000];
+    fn timeout_wraps_like_engine() {
+        use crate::param::timeout::timeout_left;
+        assert_eq!(timeout_left(100, 40), 60);
+        assert_eq!(timeout_left(40, 100), 0);
+        // Negative end is far in the future for the engine
+        assert_eq!(timeout_left(-1, 0), -1);
+        assert_eq!(timeout_left(-1, 3_000_000_000), 1_294_967_295);
+        assert_eq!(timeout_left(i32::MAX, 3_000_000_000), 0);
+    }
+
+    #[test]
+    fn explanation() {
+        let mut params = Box::new([0; PARAMS_COUNT]);
+        params[RawParam::ST_PERCEPTION as usize] = 6;
+        params[RawParam::ST_PERCEPTION_EXT as usize] = 1;
+        params[RawParam::TRAIT_NIGHT_PERSON as usize] = 1;
         let time = Time {
             hour: 23,
             minute: 30,
             second: 0,
         };
-
         let cr = Critter::new(&params, time);
-        let res = param(&cr, RawParam::ST_STRENGTH);*/
+
+        let info = explain(&cr, RawParam::ST_PERCEPTION as u32).unwrap();
+        assert!(info.starts_with("Восприятие"), "{}", info);
+        assert!(info.contains("НочнаяПерсона"), "{}", info);
+        assert!(info.contains("= 8"), "{}", info);
+        assert_eq!(param(&cr, RawParam::ST_PERCEPTION), 8);
+
+        assert!(explain(&cr, RawParam::ST_CHARISMA as u32).is_none());
+        assert_eq!(param(&cr, RawParam::ST_CHARISMA), 0);
     }
 }
@@ -5,8 +5,6 @@ pub mod raw_param;
 
 #[cfg(test)]
 mod test {
-    use formula::prelude::{tools::PartFormula, *};
-
     /*#[test]
     fn guide() {
         invar!(BASE_HP, 25, "БазовыеЖизни");
@@ -30,7 +30,6 @@ pub fn sum_and_armor<'a>(
 ) -> impl CrOp<'a> {
     "ОтСтатов".part(from_stat.sum())
         + opaque("ОтБрони", move |cr: &Critter| {
-            use fo_engine_types::{CritterLike, ItemLike};
             cr.armor().map_or(0, |armor| armor.absorb_proc(damage_type))
         })
 }
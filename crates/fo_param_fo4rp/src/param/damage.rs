@@ -1,5 +1,4 @@
 #![deny(dead_code)]
-use super::prelude::*;
 
 mod impl_param {
     use crate::param::impl_prelude::*;
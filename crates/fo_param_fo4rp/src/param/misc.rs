@@ -7,6 +7,7 @@ mod impl_param {
         {
             lt: ('a), data: &'a Critter<'a>,
             with_args: ( impl_base!(""), impl_calc!()),
+            with_decls: ( impl_ranges!(ranges) ),
         },
         (LifeCurrent,               "ТекущееЗдоровье",      ST_CURRENT_HP,      ()),
         (ActionPointsCurrent,       "ТекущиеОД",            ST_CURRENT_AP,      (-9999, 9999)),
@@ -20,7 +21,7 @@ pub use impl_param::*;
 
 invar!(AP_DIVIDER, 100, "ДелительОД");
 impl ActionPointsCurrent {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.base() / AP_DIVIDER
     }
 }
@@ -29,7 +30,7 @@ invar!(APREGEN_PER_AGI, 50, "РегенОДзаЛовкость");
 invar!(APREGEN_PER_END, 20, "РегенОДзаВыносливость");
 invar!(APREGEN_BASE, 20, "БазовыйРегенОД");
 impl ActionPointsRegen {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.base()
             + stat::Agility.base() * APREGEN_PER_AGI
             + stat::Endurance.base() * APREGEN_PER_END
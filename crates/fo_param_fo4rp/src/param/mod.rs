@@ -20,9 +20,40 @@ mod prelude {
 mod impl_prelude {
     pub use crate::{critter::Critter, raw_param::RawParam::*};
     pub use fo_param::{
-        impl_base, impl_boxed_formulas, impl_calc, impl_ext, impl_fn, impl_param, impl_present,
+        impl_base, impl_calc, impl_ext, impl_fn, impl_param, impl_present, impl_ranges,
     };
     pub use formula::prelude::invar;
 
     pub type InvarI32 = formula::prelude::tools::Invar<i32>;
 }
+
+/// `(index, min, max)` of params with declared range of calculated value.
+pub fn all_ranges() -> Vec<(u16, i32, i32)> {
+    let mut ranges = stat::ranges();
+    ranges.extend(misc::ranges());
+    ranges.extend(resist::armor_ranges());
+    ranges.extend(resist::ranges());
+    ranges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::raw_param::RawParam;
+
+    #[test]
+    fn declared_ranges() {
+        let ranges = all_ranges();
+        let find = |param: RawParam| {
+            ranges
+                .iter()
+                .find(|&&(index, _, _)| index == param.into())
+                .map(|&(_, min, max)| (min, max))
+        };
+        assert_eq!(find(RawParam::ST_STRENGTH), Some((1, 10)));
+        assert_eq!(find(RawParam::ST_MOVE_AP), Some((0, 9999)));
+        assert_eq!(find(RawParam::ST_EMP_RESIST), Some((0, 999)));
+        assert_eq!(find(RawParam::ST_CURRENT_HP), None);
+        assert_eq!(find(RawParam::TO_BATTLE), None);
+    }
+}
@@ -11,6 +11,7 @@ mod impl_param {
                 impl_base!("База"), impl_ext!("Эффект"), impl_calc!(),
                 impl_fn!(super::sum_and_armor)
             ),
+            with_decls: ( impl_ranges!(armor_ranges) ),
         },
         (Normal,    "СопротивлениеНормальномуУрону",    ST_NORMAL_RESIST,   ST_NORMAL_RESIST_EXT,   (0, 90),  (DamageType::Normal)),
         (Laser,     "СопротивлениеЛазерномуУрону",      ST_LASER_RESIST,    ST_LASER_RESIST_EXT,    (0, 90),  (DamageType::Laser)),
@@ -25,6 +26,7 @@ mod impl_param {
         {
             lt: ('a), data: &'a Critter<'a>,
             with_args: ( impl_base!("База"), impl_ext!("Эффект"), impl_calc!() ),
+            with_decls: ( impl_ranges!(ranges) ),
         },
         (Radiation, "СопротивлениеРадиации",      ST_RADIATION_RESISTANCE,  ST_RADIATION_RESISTANCE_EXT,  (0, 95)),
         (Poison,    "СопротивлениеЯду",           ST_POISON_RESISTANCE,     ST_POISON_RESISTANCE_EXT,     (0, 95)),
@@ -38,7 +40,7 @@ invar!(
     "СопротивлениеРадиацииЗаВыносливость"
 );
 impl Radiation {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.sum() + stat::Endurance.calc() * RADIATION_RESISTANCE_PER_END
     }
 }
@@ -49,7 +51,7 @@ invar!(
     "СопротивлениеЯдуЗаВыносливость"
 );
 impl Poison {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.sum() + stat::Endurance.calc() * POSION_RESISTANCE_PER_END
     }
 }
@@ -60,7 +62,6 @@ pub fn sum_and_armor<'a>(
 ) -> impl CrOp<'a> {
     "ОтСтатов".part(from_stat.sum())
         + opaque("ОтБрони", move |cr: &Critter| {
-            use fo_engine_types::{CritterLike, ItemLike};
             cr.armor().map_or(0, |armor| armor.resist_proc(damage_type))
         })
 }
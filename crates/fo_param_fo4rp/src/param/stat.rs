@@ -8,6 +8,7 @@ mod impl_param {
         {
             lt: ('a), data: &'a Critter<'a>,
             with_args: ( impl_base!("База"), impl_ext!("Эффект"), impl_calc!()),
+            with_decls: ( impl_ranges!(ranges) ),
             //with_decls: ( impl_boxed_formulas!(StatFormulas::boxed_formulas) ),
         },
         (Strength,        "Сила",               ST_STRENGTH,        ST_STRENGTH_EXT,        (1, 10)),
@@ -35,13 +36,13 @@ invar!(BONUS_ZERO, 0, "НетБонуса");
 invar!(BONUS_RUSH, 1, "БонусЗаВыбросАдреналина");
 
 impl Strength {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         let low_life = less_or_equal(
             misc::LifeCurrent.base(),
             (LifeMax.base() + Strength.base() + Endurance.base() * int(2)) / int(2),
         );
         let rush_condition = perk::AdrenalineRush.present()
-            & not_equal(timeout::Battle.calc(), TIMEOUT_READY)
+            & greater_than(timeout::Battle.calc(), TIMEOUT_READY)
             & "МалоЗдоровья".part(low_life);
         let rush_bonus = "ОтВыбросаАдреналина".part(if_else(
             "ВыбросАдреналинаДействует".part(rush_condition),
@@ -56,7 +57,7 @@ impl Strength {
 invar!(DAMAGED_PERCEPTION, 1, "ПовреждённоеВосприятие");
 
 impl Perception {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         let maybe_damaged = if_else(damage::Eye.present(), DAMAGED_PERCEPTION, self.sum());
         "ОтВосприятия".part(maybe_damaged)
             + "ОтНочнойПерсоны".part(traits::NightPerson.make_bonus().compat())
@@ -72,7 +73,7 @@ impl Charisma {
 }
 
 impl Intellect {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         "ОтИнтеллекта".part(self.sum())
             + "ОтНочнойПерсоны".part(traits::NightPerson.make_bonus().compat())
     }
@@ -95,7 +96,7 @@ invar!(HP_PER_STR, 4, "ЗдоровьеЗаСилу");
 invar!(HP_PER_END, 8, "ЗдоровьеЗаВыносливость");
 
 impl LifeMax {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.sum()
             + "ОтСилы".part(Strength.base() * HP_PER_STR)
             + "ОтВыносливости".part(Endurance.base() * HP_PER_END)
@@ -105,7 +106,7 @@ impl LifeMax {
 invar!(APPOINTS_BASE, 100, "БазовыеОД");
 
 impl ActionPointsMax {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.sum() + APPOINTS_BASE // + Agility.base() / 2
     }
 }
@@ -115,7 +116,7 @@ invar!(CW_PER_STR, 10, "МаксВесЗаСилу");
 invar!(CW_BASE, 15, "БазовыйМаксВес");
 
 impl WeightMax {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         let small_frame = traits::SmallFrame.base() * SMALL_FRAME_CW_MALUS_DIV;
         let from_strength = Strength.base() * (CW_PER_STR - small_frame);
         "ОтМаксВеса".part(max(self.sum(), int(0)))
@@ -125,7 +126,7 @@ impl WeightMax {
 
 invar!(SEQUENCE_PER_PERCEPTION, 2, "ПорядокДействийЗаВосприятие");
 impl Sequence {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.sum() + Perception.calc() * SEQUENCE_PER_PERCEPTION
     }
 }
@@ -133,20 +134,20 @@ impl Sequence {
 invar!(MELEE_DAMAGE_BASE, 3, "БазовыйРукопашныйУрон");
 invar!(MELEE_DAMAGE_PER_STR, 2, "РукопашныйУронЗаСилу");
 impl MeleeDamage {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.sum() + MELEE_DAMAGE_BASE + "ОтСилы".part(Strength.base() * MELEE_DAMAGE_PER_STR)
     }
 }
 
 impl HealingRate {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         let from_endurance = "ОтВыносливости".part(max(int(1), Endurance.calc() / int(3)));
         self.sum() + from_endurance
     }
 }
 
 impl CriticalChance {
-    pub fn make_formula(&self) -> impl CrOp {
+    pub fn make_formula(&self) -> impl CrOp<'_> {
         self.sum() + Luck.calc()
     }
 }
@@ -156,12 +157,10 @@ impl CriticalMax {
 
 invar!(AC_PER_AGILITY, 5, "ОчковБрониЗаЛовкость");
 impl ArmorClass {
-    pub fn make_formula(&self) -> impl CrOp {
-        use fo_engine_types::{ItemLike, ItemProtoLike};
+    pub fn make_formula(&self) -> impl CrOp<'_> {
+        use crate::critter::Armor;
         let armor_ac = opaque("ОчкиНадетойБрони", |data: &Critter| {
-            data.armor()
-                .map(ItemLike::proto)
-                .map_or(0, ItemProtoLike::armor_class)
+            data.armor().map_or(0, Armor::armor_class)
         });
         self.sum() + Agility.calc() * AC_PER_AGILITY + misc::ArmorClassTurnBased.base() - armor_ac
     }
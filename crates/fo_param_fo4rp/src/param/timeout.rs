@@ -47,13 +47,21 @@ pub mod of_skill {
     pub use impl_param::*;
 }
 
-pub fn impl_timeout<'a>(timeout: &impl HasParamBase<&'a Critter<'a>>) -> impl CrOp<'a> {
-    max(
-        timeout.base()
-            - opaque(
-                "СекундОтСотворенияМира",
-                |data: &Critter| data.full_second as i32,
-            ),
-        int(0),
+pub fn impl_timeout<'a, T: HasParamBase<&'a Critter<'a>>>(_timeout: &T) -> impl CrOp<'a> {
+    let index = T::INDEX;
+    opaque(
+        "СекундДоОкончания",
+        move |data: &Critter| timeout_left(data.param[index as usize], data.full_second),
     )
 }
+
+/// Seconds left till the end of timeout, the way engine's `getParam_Timeout` does it:
+/// end of timeout is taken as unsigned and the difference wraps.
+pub fn timeout_left(end: i32, full_second: u32) -> i32 {
+    let end = end as u32;
+    if end > full_second {
+        (end - full_second) as i32
+    } else {
+        0
+    }
+}
@@ -22,12 +22,16 @@ invar!(BONUS_NIGHT_PERSON_DAY, -1, "ДневнойШтраф");
 invar!(BONUS_NIGHT_PERSON_NIGHT, 1, "НочнойБонус");
 
 impl NightPerson {
-    pub fn make_bonus(&self) -> impl CrOp {
+    pub fn make_bonus(&self) -> impl CrOp<'_> {
         let trait_present = self.present();
+        let time_known = opaque("ВремяИзвестно", |data: &Critter| {
+            data.time.is_some()
+        });
         let is_night = opaque("СейчасНочь", |data: &Critter| {
-            data.time.is_night()
+            data.time.is_some_and(|time| time.is_night())
         });
         let night_bonus = if_else(is_night, BONUS_NIGHT_PERSON_NIGHT, BONUS_NIGHT_PERSON_DAY);
+        let night_bonus = if_else(time_known, night_bonus, BONUS_ZERO);
         if_else(trait_present, night_bonus, BONUS_ZERO)
     }
 }
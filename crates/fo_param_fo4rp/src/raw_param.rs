@@ -1,7 +1,7 @@
-impl Into<u16> for RawParam {
-    fn into(self) -> u16 {
-        debug_assert!((RawParam::PARAMS_COUNT as isize) < (u16::max_value() as isize));
-        self as u16
+impl From<RawParam> for u16 {
+    fn from(param: RawParam) -> u16 {
+        debug_assert!((RawParam::PARAMS_COUNT as isize) < (u16::MAX as isize));
+        param as u16
     }
 }
 
@@ -0,0 +1,166 @@
+// Engine geometry (Common.cpp, LineTracer) used to produce `src/reference.rs`:
+//     g++ -ffp-contract=off -o engine engine.cpp && ./engine > ../src/reference.rs && rustfmt ../src/reference.rs
+#include <cmath>
+#include <cstdio>
+#include <cstdlib>
+typedef unsigned short ushort; typedef unsigned char uchar;
+#define SQRT3T2_FLOAT 3.4641016151f
+#define SQRT3_FLOAT 1.732050807568877f
+#define RAD2DEG 57.29577951f
+static bool Hexagonal = true;
+
+int GetDistance(int x1,int y1,int x2,int y2){
+    if(Hexagonal){
+        int dx = (x1 > x2 ? x1 - x2 : x2 - x1);
+        if(x1 % 2 == 0){
+            if(y2 <= y1){ int rx = y1 - y2 - dx / 2; return rx > 0 ? dx + rx : dx; }
+            else { int rx = y2 - y1 - (dx + 1) / 2; return rx > 0 ? dx + rx : dx; }
+        } else {
+            if(y2 >= y1){ int rx = y2 - y1 - dx / 2; return rx > 0 ? dx + rx : dx; }
+            else { int rx = y1 - y2 - (dx + 1) / 2; return rx > 0 ? dx + rx : dx; }
+        }
+    } else {
+        int dx = abs(x2 - x1); int dy = abs(y2 - y1); return dx > dy ? dx : dy;
+    }
+}
+int GetFarDir(int x1,int y1,int x2,int y2){
+    if(Hexagonal){
+        float hx=x1,hy=y1,tx=x2,ty=y2;
+        float nx = 3 * (tx - hx);
+        float ny = (ty - hy) * SQRT3T2_FLOAT - (float(x2 & 1) - float(x1 & 1)) * SQRT3_FLOAT;
+        float dir = 180.0f + RAD2DEG * atan2f(ny, nx);
+        if(dir >= 60.0f && dir < 120.0f) return 5;
+        if(dir >= 120.0f && dir < 180.0f) return 4;
+        if(dir >= 180.0f && dir < 240.0f) return 3;
+        if(dir >= 240.0f && dir < 300.0f) return 2;
+        if(dir >= 300.0f) return 1;
+        return 0;
+    } else {
+        float dir = 180.0f + RAD2DEG * atan2f((float)(x2 - x1), (float)(y2 - y1));
+        if(dir >= 22.5f && dir < 67.5f) return 7;
+        if(dir >= 67.5f && dir < 112.5f) return 0;
+        if(dir >= 112.5f && dir < 157.5f) return 1;
+        if(dir >= 157.5f && dir < 202.5f) return 2;
+        if(dir >= 202.5f && dir < 247.5f) return 3;
+        if(dir >= 247.5f && dir < 292.5f) return 4;
+        if(dir >= 292.5f && dir < 337.5f) return 5;
+        return 6;
+    }
+}
+void MoveHexByDirUnsafe(int& hx,int& hy,uchar dir){
+    if(Hexagonal){
+        switch(dir){
+        case 0: hx--; if(!(hx & 1)) hy--; break;
+        case 1: hx--; if(hx & 1) hy++; break;
+        case 2: hy++; break;
+        case 3: hx++; if(hx & 1) hy++; break;
+        case 4: hx++; if(!(hx & 1)) hy--; break;
+        case 5: hy--; break;
+        }
+    } else {
+        switch(dir){
+        case 0: hx--; break; case 1: hx--; hy++; break; case 2: hy++; break; case 3: hx++; hy++; break;
+        case 4: hx++; break; case 5: hx++; hy--; break; case 6: hy--; break; case 7: hx--; hy--; break;
+        }
+    }
+}
+bool MoveHexByDir(ushort& hx,ushort& hy,uchar dir,ushort maxhx,ushort maxhy){
+    int hx_=hx,hy_=hy; MoveHexByDirUnsafe(hx_,hy_,dir);
+    if(hx_>=0 && hx_<maxhx && hy_>=0 && hy_<maxhy){ hx=hx_; hy=hy_; return true; }
+    return false;
+}
+struct LineTracer {
+    ushort maxHx,maxHy; float x1,y1,x2,y2,dir; uchar dir1,dir2; float dx,dy;
+    void NormalizeDir(){ if(dir<=0.0f) dir=360.0f-fmod(-dir,360.0f); else if(dir>=0.0f) dir=fmod(dir,360.0f); }
+    LineTracer(ushort hx,ushort hy,ushort tx,ushort ty,ushort maxhx,ushort maxhy,float angle,bool is_square){
+        maxHx=maxhx; maxHy=maxhy;
+        if(is_square){
+            dir = atan2f((float)(ty - hy), (float)(tx - hx)) + angle;
+            dx = cosf(dir); dy = sinf(dir);
+            if(fabsf(dx) > fabsf(dy)){ dy /= fabsf(dx); dx = (dx > 0 ? 1.0f : -1.0f); }
+            else { dx /= fabsf(dy); dy = (dy > 0 ? 1.0f : -1.0f); }
+            x1 = (float)hx + 0.5f; y1 = (float)hy + 0.5f;
+        } else {
+            float nx = 3.0f * (float(tx) - float(hx));
+            float ny = (float(ty) - float(hy)) * SQRT3T2_FLOAT - (float(tx & 1) - float(hx & 1)) * SQRT3_FLOAT;
+            dir = 180.0f + RAD2DEG * atan2f(ny, nx);
+            if(angle != 0.0f){ dir += angle; NormalizeDir(); }
+            if(dir >= 30.0f && dir < 90.0f){ dir1=5; dir2=0; }
+            else if(dir >= 90.0f && dir < 150.0f){ dir1=4; dir2=5; }
+            else if(dir >= 150.0f && dir < 210.0f){ dir1=3; dir2=4; }
+            else if(dir >= 210.0f && dir < 270.0f){ dir1=2; dir2=3; }
+            else if(dir >= 270.0f && dir < 330.0f){ dir1=1; dir2=2; }
+            else { dir1=0; dir2=1; }
+            const float BIAS_FLOAT = 0.02f;
+            x1 = 3.0f * float(hx) + BIAS_FLOAT;
+            y1 = SQRT3T2_FLOAT * float(hy) - SQRT3_FLOAT * float(hx & 1) + BIAS_FLOAT;
+            x2 = 3.0f * float(tx) + BIAS_FLOAT + BIAS_FLOAT;
+            y2 = SQRT3T2_FLOAT * float(ty) - SQRT3_FLOAT * float(tx & 1) + BIAS_FLOAT;
+            if(angle != 0.0f){
+                x2 -= x1; y2 -= y1;
+                float xp = cosf(angle / RAD2DEG) * x2 - sinf(angle / RAD2DEG) * y2;
+                float yp = sinf(angle / RAD2DEG) * x2 + cosf(angle / RAD2DEG) * y2;
+                x2 = x1 + xp; y2 = y1 + yp;
+            }
+            dx = x2 - x1; dy = y2 - y1;
+        }
+    }
+    uchar GetNextHex(ushort& cx,ushort& cy){
+        ushort t1x=cx,t2x=cx,t1y=cy,t2y=cy;
+        MoveHexByDir(t1x,t1y,dir1,maxHx,maxHy); MoveHexByDir(t2x,t2y,dir2,maxHx,maxHy);
+        float dist1 = dx * (y1 - (SQRT3T2_FLOAT * t1y - (t1x & 1) * SQRT3_FLOAT)) - dy * (x1 - 3 * t1x);
+        float dist2 = dx * (y1 - (SQRT3T2_FLOAT * t2y - (t2x & 1) * SQRT3_FLOAT)) - dy * (x1 - 3 * t2x);
+        dist1 = dist1 > 0 ? dist1 : -dist1; dist2 = dist2 > 0 ? dist2 : -dist2;
+        if(dist1 <= dist2){ cx=t1x; cy=t1y; return dir1; }
+        cx=t2x; cy=t2y; return dir2;
+    }
+    void GetNextSquare(ushort& cx,ushort& cy){
+        x1 += dx; y1 += dy;
+        cx = (ushort)floorf(x1); cy = (ushort)floorf(y1);
+        if(cx >= maxHx) cx = maxHx - 1; if(cy >= maxHy) cy = maxHy - 1;
+    }
+};
+
+static unsigned seed = 12345;
+static int rnd(int n){ seed = seed * 1103515245u + 12345u; return (seed >> 16) % n; }
+
+int main(){
+    printf("//! Values produced by engine's geometry code, see `reference/engine.cpp`.\n\n");
+    printf("/// `(x1, y1, x2, y2, hex distance, hex direction, square distance, square direction)`\n");
+    printf("pub(crate) type Direction = (u16, u16, u16, u16, u32, u8, u32, u8);\n");
+    printf("/// `(from, to, angle in degrees, next hexes)`\n");
+    printf("pub(crate) type Trace = ((u16, u16), (u16, u16), f32, &'static [(u16, u16)]);\n\n");
+    // Distance and far direction
+    printf("pub(crate) const DIRECTIONS: &[Direction] = &[\n");
+    for(int i = 0; i < 300; i++){
+        int x1 = rnd(200), y1 = rnd(200), x2 = rnd(200), y2 = rnd(200);
+        if(i % 3 == 0){ x2 = x1 + rnd(9) - 4; y2 = y1 + rnd(9) - 4; }
+        if(x2 < 0) x2 = -x2; if(y2 < 0) y2 = -y2;
+        if(x1 == x2 && y1 == y2) x2++;
+        Hexagonal = true; int dh = GetDistance(x1,y1,x2,y2), fh = GetFarDir(x1,y1,x2,y2);
+        Hexagonal = false; int ds = GetDistance(x1,y1,x2,y2), fs = GetFarDir(x1,y1,x2,y2);
+        printf("    (%d, %d, %d, %d, %d, %d, %d, %d),\n", x1,y1,x2,y2,dh,fh,ds,fs);
+    }
+    printf("];\n\n");
+    // Traces on open 200x200 map, starting away from borders
+    const char* names[2] = {"HEX_TRACES", "SQUARE_TRACES"};
+    float angles[5] = {0.0f, 0.0f, 0.0f, 15.0f, -30.0f};
+    for(int sq = 0; sq < 2; sq++){
+        Hexagonal = !sq;
+        printf("pub(crate) const %s: &[Trace] = &[\n", names[sq]);
+        for(int i = 0; i < 60; i++){
+            int x1 = 60 + rnd(80), y1 = 60 + rnd(80), x2 = x1 + rnd(41) - 20, y2 = y1 + rnd(41) - 20;
+            if(x1 == x2 && y1 == y2) y2++;
+            float angle = angles[i % 5];
+            LineTracer lt(x1,y1,x2,y2,200,200,sq ? angle / RAD2DEG : angle,sq);
+            ushort cx = x1, cy = y1;
+            printf("    ((%d, %d), (%d, %d), %.1f, &[", x1,y1,x2,y2,angle);
+            for(int s = 0; s < 12; s++){
+                if(sq) lt.GetNextSquare(cx,cy); else lt.GetNextHex(cx,cy);
+                printf("%s(%d, %d)", s ? ", " : "", cx, cy);
+            }
+            printf("]),\n");
+        }
+        printf("];\n\n");
+    }
+}
@@ -0,0 +1,113 @@
+use super::HexGrid;
+use primitives::Hex;
+
+// Proto flags of the hex, low byte
+pub const FH_BLOCK: u16 = 0b0000_0001;
+pub const FH_NOTRAKE: u16 = 0b0000_0010;
+pub const FH_WALL: u16 = 0b0000_0100;
+pub const FH_SCEN: u16 = 0b0000_1000;
+pub const FH_SCEN_GRID: u16 = 0b0001_0000;
+pub const FH_TRIGGER: u16 = 0b0010_0000;
+// Map flags of the hex, high byte
+pub const FH_CRITTER: u16 = 0b0000_0001 << 8;
+pub const FH_DEAD_CRITTER: u16 = 0b0000_0010 << 8;
+pub const FH_ITEM: u16 = 0b0000_0100 << 8;
+pub const FH_BLOCK_ITEM: u16 = 0b0001_0000 << 8;
+pub const FH_NRAKE_ITEM: u16 = 0b0010_0000 << 8;
+pub const FH_WALK_ITEM: u16 = 0b0100_0000 << 8;
+pub const FH_GAG_ITEM: u16 = 0b1000_0000 << 8;
+
+pub const FH_NOWAY: u16 = FH_CRITTER | FH_BLOCK_ITEM | FH_BLOCK;
+pub const FH_NOSHOOT: u16 = FH_NRAKE_ITEM | FH_NOTRAKE;
+
+/// Hex flags of a map kept in memory, laid out like `Map::get_hex_flags_with_proto`
+/// of the engine: map flags in high byte, proto flags in low byte.
+#[derive(Debug, Clone)]
+pub struct BlockerGrid {
+    max: Hex,
+    hexagonal: bool,
+    flags: Vec<u16>,
+}
+
+impl BlockerGrid {
+    pub fn new(max: Hex) -> Self {
+        BlockerGrid {
+            max,
+            hexagonal: true,
+            flags: vec![0; max.x as usize * max.y as usize],
+        }
+    }
+    pub fn square(max: Hex) -> Self {
+        BlockerGrid {
+            hexagonal: false,
+            ..Self::new(max)
+        }
+    }
+    fn index(&self, hex: Hex) -> Option<usize> {
+        if hex.x < self.max.x && hex.y < self.max.y {
+            Some(hex.y as usize * self.max.x as usize + hex.x as usize)
+        } else {
+            None
+        }
+    }
+    /// Flags of the hex, `0` outside of the map.
+    pub fn flags(&self, hex: Hex) -> u16 {
+        self.index(hex).map(|index| self.flags[index]).unwrap_or(0)
+    }
+    /// Sets `flags` on the hex, hexes outside of the map are ignored.
+    pub fn add_flags(&mut self, hex: Hex, flags: u16) {
+        if let Some(index) = self.index(hex) {
+            self.flags[index] |= flags;
+        }
+    }
+    pub fn remove_flags(&mut self, hex: Hex, flags: u16) {
+        if let Some(index) = self.index(hex) {
+            self.flags[index] &= !flags;
+        }
+    }
+}
+
+impl HexGrid for BlockerGrid {
+    fn max_hex(&self) -> Hex {
+        self.max
+    }
+    fn is_hex_passed(&self, hex: Hex) -> bool {
+        self.flags(hex) & FH_NOWAY == 0
+    }
+    fn is_hex_raked(&self, hex: Hex) -> bool {
+        self.flags(hex) & FH_NOSHOOT == 0
+    }
+    fn is_hex_critter(&self, hex: Hex) -> bool {
+        self.flags(hex) & (FH_CRITTER | FH_DEAD_CRITTER) != 0
+    }
+    fn is_hexagonal(&self) -> bool {
+        self.hexagonal
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn flags_semantics() {
+        let mut grid = BlockerGrid::new(Hex::new(4, 4));
+        let hex = Hex::new(1, 2);
+        assert!(grid.is_hex_passed(hex) && grid.is_hex_raked(hex));
+
+        grid.add_flags(hex, FH_BLOCK);
+        assert!(!grid.is_hex_passed(hex) && grid.is_hex_raked(hex));
+        grid.add_flags(hex, FH_NOTRAKE);
+        assert!(!grid.is_hex_raked(hex));
+        grid.remove_flags(hex, FH_BLOCK | FH_NOTRAKE);
+
+        // Dead critters don't block
+        grid.add_flags(hex, FH_DEAD_CRITTER);
+        assert!(grid.is_hex_passed(hex) && grid.is_hex_critter(hex));
+        grid.add_flags(hex, FH_CRITTER);
+        assert!(!grid.is_hex_passed(hex) && grid.is_hex_raked(hex));
+
+        grid.add_flags(Hex::new(4, 0), FH_BLOCK);
+        assert_eq!(grid.flags(Hex::new(4, 0)), 0);
+    }
+}
@@ -0,0 +1,406 @@
+//! Map geometry of FOnline engine: distances, directions, line tracing and pathfinding
+//! on hexagonal and square maps, independent of engine types.
+use primitives::Hex;
+
+pub mod grid;
+mod path;
+mod trace;
+mod tracer;
+
+#[cfg(test)]
+mod reference;
+
+pub use grid::BlockerGrid;
+pub use path::{
+    find_path, find_path_with_cost, reachable, Path, PathError, PathRequest, PathResult, PathStep,
+    Reachability, MAX_PATH_LENGTH,
+};
+pub use trace::{get_hex_in_path, get_hex_in_path_wall, TraceInput, TraceOutput};
+pub use tracer::LineTracer;
+
+#[allow(clippy::excessive_precision)]
+const SQRT3T2_FLOAT: f32 = 3.4641016151;
+#[allow(clippy::excessive_precision)]
+const SQRT3_FLOAT: f32 = 1.732050807568877;
+#[allow(clippy::excessive_precision)]
+const RAD2DEG: f32 = 57.29577951;
+
+/// Number of directions on hexagonal map.
+pub const HEX_DIRS: u8 = 6;
+/// Number of directions on square map.
+pub const SQUARE_DIRS: u8 = 8;
+
+/// Map as seen by geometry routines.
+pub trait HexGrid {
+    /// Size of the map, valid hexes are strictly less.
+    fn max_hex(&self) -> Hex;
+    /// Hex can be walked through.
+    fn is_hex_passed(&self, hex: Hex) -> bool;
+    /// Hex can be shot through.
+    fn is_hex_raked(&self, hex: Hex) -> bool;
+    /// Hex is occupied by alive or dead critter.
+    fn is_hex_critter(&self, hex: Hex) -> bool;
+    /// `GameOpt.MapHexagonal` of the engine.
+    fn is_hexagonal(&self) -> bool {
+        true
+    }
+}
+
+impl<G: HexGrid + ?Sized> HexGrid for &G {
+    fn max_hex(&self) -> Hex {
+        (**self).max_hex()
+    }
+    fn is_hex_passed(&self, hex: Hex) -> bool {
+        (**self).is_hex_passed(hex)
+    }
+    fn is_hex_raked(&self, hex: Hex) -> bool {
+        (**self).is_hex_raked(hex)
+    }
+    fn is_hex_critter(&self, hex: Hex) -> bool {
+        (**self).is_hex_critter(hex)
+    }
+    fn is_hexagonal(&self) -> bool {
+        (**self).is_hexagonal()
+    }
+}
+
+/// Geometry of hexagonal maps.
+pub trait HexExt {
+    fn get_distance(self, other: Self) -> u32;
+    fn get_direction(self, other: Self) -> u8;
+}
+
+impl HexExt for Hex {
+    fn get_distance(self, other: Hex) -> u32 {
+        get_distance_hex(self, other, true)
+    }
+    fn get_direction(self, other: Hex) -> u8 {
+        get_direction(self, other, true)
+    }
+}
+
+pub fn dirs_count(hexagonal: bool) -> u8 {
+    if hexagonal {
+        HEX_DIRS
+    } else {
+        SQUARE_DIRS
+    }
+}
+
+fn get_distance(x1: i32, y1: i32, x2: i32, y2: i32, hexagonal: bool) -> u32 {
+    if hexagonal {
+        let dx = if x1 > x2 { x1 - x2 } else { x2 - x1 };
+        let rx = if x1 & 1 == 0 {
+            if y2 <= y1 {
+                y1 - y2 - dx / 2
+            } else {
+                y2 - y1 - (dx + 1) / 2
+            }
+        } else if y2 >= y1 {
+            y2 - y1 - dx / 2
+        } else {
+            y1 - y2 - (dx + 1) / 2
+        };
+        (if rx > 0 { dx + rx } else { dx }) as u32
+    } else {
+        let dx = i32::abs(x2 - x1) as u32;
+        let dy = i32::abs(y2 - y1) as u32;
+        u32::max(dx, dy)
+    }
+}
+
+/// Number of steps between hexes.
+pub fn get_distance_hex(begin_hex: Hex, end_hex: Hex, hexagonal: bool) -> u32 {
+    get_distance(
+        begin_hex.x as i32,
+        begin_hex.y as i32,
+        end_hex.x as i32,
+        end_hex.y as i32,
+        hexagonal,
+    )
+}
+
+fn get_far_dir(x1: i32, y1: i32, x2: i32, y2: i32, hexagonal: bool) -> u8 {
+    if hexagonal {
+        let hx = x1 as f32;
+        let hy = y1 as f32;
+        let tx = x2 as f32;
+        let ty = y2 as f32;
+        let nx = 3.0 * (tx - hx);
+        let ny = (ty - hy) * SQRT3T2_FLOAT - ((x2 & 1) as f32 - (x1 & 1) as f32) * SQRT3_FLOAT;
+        let dir = 180.0 + RAD2DEG * f32::atan2(ny, nx);
+
+        if (60.0..120.0).contains(&dir) {
+            5
+        } else if (120.0..180.0).contains(&dir) {
+            4
+        } else if (180.0..240.0).contains(&dir) {
+            3
+        } else if (240.0..300.0).contains(&dir) {
+            2
+        } else if dir >= 300.0 {
+            1
+        } else {
+            0
+        }
+    } else {
+        let dir = 180.0 + RAD2DEG * f32::atan2((x2 - x1) as f32, (y2 - y1) as f32);
+
+        if (22.5..67.5).contains(&dir) {
+            7
+        } else if (67.5..112.5).contains(&dir) {
+            0
+        } else if (112.5..157.5).contains(&dir) {
+            1
+        } else if (157.5..202.5).contains(&dir) {
+            2
+        } else if (202.5..247.5).contains(&dir) {
+            3
+        } else if (247.5..292.5).contains(&dir) {
+            4
+        } else if (292.5..337.5).contains(&dir) {
+            5
+        } else {
+            6
+        }
+    }
+}
+
+/// Direction to any hex, rounded to the nearest of map directions.
+pub fn get_direction(from_hex: Hex, to_hex: Hex, hexagonal: bool) -> u8 {
+    get_far_dir(
+        from_hex.x as i32,
+        from_hex.y as i32,
+        to_hex.x as i32,
+        to_hex.y as i32,
+        hexagonal,
+    )
+}
+
+/// Direction to adjacent hex, `0` if hexes aren't adjacent.
+pub fn get_near_dir(from_hex: Hex, to_hex: Hex, hexagonal: bool) -> u8 {
+    let (x1, y1, x2, y2) = (from_hex.x, from_hex.y, to_hex.x, to_hex.y);
+    if hexagonal {
+        if x1 & 1 != 0 {
+            if x1 > x2 && y1 > y2 {
+                0
+            } else if x1 > x2 && y1 == y2 {
+                1
+            } else if x1 == x2 && y1 < y2 {
+                2
+            } else if x1 < x2 && y1 == y2 {
+                3
+            } else if x1 < x2 && y1 > y2 {
+                4
+            } else if x1 == x2 && y1 > y2 {
+                5
+            } else {
+                0
+            }
+        } else if x1 > x2 && y1 == y2 {
+            0
+        } else if x1 > x2 && y1 < y2 {
+            1
+        } else if x1 == x2 && y1 < y2 {
+            2
+        } else if x1 < x2 && y1 < y2 {
+            3
+        } else if x1 < x2 && y1 == y2 {
+            4
+        } else if x1 == x2 && y1 > y2 {
+            5
+        } else {
+            0
+        }
+    } else if x1 > x2 && y1 == y2 {
+        0
+    } else if x1 > x2 && y1 < y2 {
+        1
+    } else if x1 == x2 && y1 < y2 {
+        2
+    } else if x1 < x2 && y1 < y2 {
+        3
+    } else if x1 < x2 && y1 == y2 {
+        4
+    } else if x1 < x2 && y1 > y2 {
+        5
+    } else if x1 == x2 && y1 > y2 {
+        6
+    } else if x1 > x2 && y1 > y2 {
+        7
+    } else {
+        0
+    }
+}
+
+/// Adjacent hex in direction, `None` if it's outside of `max`.
+pub fn move_hex_by_dir(hex: Hex, dir: u8, max: Hex, hexagonal: bool) -> Option<Hex> {
+    let (hxi, hyi) = move_hex_by_dir_unsafe((hex.x as i32, hex.y as i32), dir, hexagonal);
+
+    if hxi >= 0 && hxi < max.x as i32 && hyi >= 0 && hyi < max.y as i32 {
+        Some(Hex {
+            x: hxi as u16,
+            y: hyi as u16,
+        })
+    } else {
+        None
+    }
+}
+
+/// Adjacent position in direction, may be outside of the map.
+pub fn move_hex_by_dir_unsafe(
+    (mut hx, mut hy): (i32, i32),
+    dir: u8,
+    hexagonal: bool,
+) -> (i32, i32) {
+    if hexagonal {
+        match dir {
+            0 => {
+                hx -= 1;
+                if hx & 1 == 0 {
+                    hy -= 1;
+                }
+            }
+            1 => {
+                hx -= 1;
+                if hx & 1 != 0 {
+                    hy += 1;
+                }
+            }
+            2 => {
+                hy += 1;
+            }
+            3 => {
+                hx += 1;
+                if hx & 1 != 0 {
+                    hy += 1;
+                }
+            }
+            4 => {
+                hx += 1;
+                if hx & 1 == 0 {
+                    hy -= 1;
+                }
+            }
+            5 => {
+                hy -= 1;
+            }
+            _ => panic!("Invalid direction"),
+        }
+    } else {
+        match dir {
+            0 => {
+                hx -= 1;
+            }
+            1 => {
+                hx -= 1;
+                hy += 1;
+            }
+            2 => {
+                hy += 1;
+            }
+            3 => {
+                hx += 1;
+                hy += 1;
+            }
+            4 => {
+                hx += 1;
+            }
+            5 => {
+                hx += 1;
+                hy -= 1;
+            }
+            6 => {
+                hy -= 1;
+            }
+            7 => {
+                hx -= 1;
+                hy -= 1;
+            }
+            _ => panic!("Invalid direction"),
+        }
+    }
+    (hx, hy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    const MAX: Hex = Hex { x: 200, y: 200 };
+
+    fn hex() -> impl Strategy<Value = Hex> {
+        (0..MAX.x, 0..MAX.y).prop_map(|(x, y)| Hex::new(x, y))
+    }
+
+    #[test]
+    fn engine_distances_and_directions() {
+        for &(x1, y1, x2, y2, dist_hex, dir_hex, dist_square, dir_square) in reference::DIRECTIONS {
+            let (from, to) = (Hex::new(x1, y1), Hex::new(x2, y2));
+            assert_eq!(
+                get_distance_hex(from, to, true),
+                dist_hex,
+                "{:?}",
+                (from, to)
+            );
+            assert_eq!(get_direction(from, to, true), dir_hex, "{:?}", (from, to));
+            assert_eq!(get_distance_hex(from, to, false), dist_square);
+            assert_eq!(get_direction(from, to, false), dir_square);
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn distance_is_symmetric(a in hex(), b in hex(), hexagonal: bool) {
+            prop_assert_eq!(
+                get_distance_hex(a, b, hexagonal),
+                get_distance_hex(b, a, hexagonal)
+            );
+        }
+
+        #[test]
+        fn triangle_inequality(a in hex(), b in hex(), c in hex(), hexagonal: bool) {
+            prop_assert!(
+                get_distance_hex(a, c, hexagonal)
+                    <= get_distance_hex(a, b, hexagonal) + get_distance_hex(b, c, hexagonal)
+            );
+        }
+
+        #[test]
+        fn neighbours(from in hex(), hexagonal: bool) {
+            for dir in 0..dirs_count(hexagonal) {
+                if let Some(to) = move_hex_by_dir(from, dir, MAX, hexagonal) {
+                    prop_assert_eq!(get_distance_hex(from, to, hexagonal), 1);
+                    prop_assert_eq!(get_near_dir(from, to, hexagonal), dir);
+                    prop_assert_eq!(get_direction(from, to, hexagonal), dir);
+                    let back = (dir + dirs_count(hexagonal) / 2) % dirs_count(hexagonal);
+                    prop_assert_eq!(move_hex_by_dir(to, back, MAX, hexagonal), Some(from));
+                }
+            }
+        }
+
+        #[test]
+        fn steps_toward_target(from in hex(), to in hex(), hexagonal: bool) {
+            // Stepping in the nearest direction always gets closer
+            prop_assume!(from != to);
+            let dir = get_direction(from, to, hexagonal);
+            let next = move_hex_by_dir(from, dir, MAX, hexagonal).expect("Target is inside");
+            prop_assert!(
+                get_distance_hex(next, to, hexagonal) < get_distance_hex(from, to, hexagonal)
+            );
+        }
+    }
+
+    #[test]
+    fn map_borders() {
+        let max = Hex::new(10, 10);
+        assert_eq!(move_hex_by_dir(Hex::new(0, 0), 0, max, false), None);
+        assert_eq!(move_hex_by_dir(Hex::new(9, 9), 3, max, false), None);
+        assert_eq!(
+            move_hex_by_dir(Hex::new(9, 5), 2, max, true),
+            Some(Hex::new(9, 6))
+        );
+        assert_eq!(move_hex_by_dir(Hex::new(9, 5), 3, max, true), None);
+    }
+}
@@ -0,0 +1,418 @@
+use super::{dirs_count, get_distance_hex, move_hex_by_dir, HexGrid};
+use primitives::Hex;
+use std::{cmp::Reverse, collections::BinaryHeap, collections::VecDeque};
+
+/// `FPATH_MAX_PATH` of the engine.
+pub const MAX_PATH_LENGTH: u32 = 400;
+
+const UNREACHED: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+pub struct PathRequest {
+    pub from: Hex,
+    pub to: Hex,
+    /// Search stops as soon as target is within `cut` steps, `1` stops adjacent to it.
+    pub cut: u32,
+    /// Paths with more steps are not considered.
+    pub max_length: u32,
+}
+
+impl PathRequest {
+    pub fn new(from: Hex, to: Hex) -> Self {
+        PathRequest {
+            from,
+            to,
+            cut: 0,
+            max_length: MAX_PATH_LENGTH,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathStep {
+    pub hex: Hex,
+    /// Direction of the step, from previous hex to this one.
+    pub dir: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    /// Hexes to walk, without the starting one; empty if already there.
+    pub steps: Vec<PathStep>,
+    pub cost: u32,
+}
+
+impl Path {
+    pub fn last_hex(&self) -> Option<Hex> {
+        self.steps.last().map(|step| step.hex)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathError {
+    /// Start or target is outside of the map.
+    InvalidHexes,
+    /// Target is farther than `max_length`.
+    TooFar,
+    /// Target hex is blocked and `cut` is `0`.
+    TargetBusy,
+    /// No path within `max_length`.
+    NoWay,
+}
+
+pub type PathResult = Result<Path, PathError>;
+
+struct Nodes {
+    max: Hex,
+    cost: Vec<u32>,
+    steps: Vec<u32>,
+    dir: Vec<u8>,
+}
+
+impl Nodes {
+    fn new(max: Hex) -> Self {
+        let len = max.x as usize * max.y as usize;
+        Nodes {
+            max,
+            cost: vec![UNREACHED; len],
+            steps: vec![0; len],
+            dir: vec![0; len],
+        }
+    }
+    fn index(&self, hex: Hex) -> usize {
+        hex.y as usize * self.max.x as usize + hex.x as usize
+    }
+    fn hex(&self, index: usize) -> Hex {
+        let max_x = self.max.x as usize;
+        Hex::new((index % max_x) as u16, (index / max_x) as u16)
+    }
+}
+
+fn is_inside(hex: Hex, max: Hex) -> bool {
+    hex.x < max.x && hex.y < max.y
+}
+
+/// Shortest path where every step costs `1`.
+pub fn find_path<G: HexGrid + ?Sized>(grid: &G, request: &PathRequest) -> PathResult {
+    find_path_with_cost(grid, request, |_| 1)
+}
+
+/// Cheapest path, `cost` of entering a hex is at least `1`.
+///
+/// With non-uniform costs a cheaper route to a hex is preferred to a shorter one,
+/// so a path that only fits `max_length` by being more expensive may be missed.
+pub fn find_path_with_cost<G, C>(grid: &G, request: &PathRequest, mut cost: C) -> PathResult
+where
+    G: HexGrid + ?Sized,
+    C: FnMut(Hex) -> u32,
+{
+    let max = grid.max_hex();
+    let hexagonal = grid.is_hexagonal();
+    let PathRequest {
+        from,
+        to,
+        cut,
+        max_length,
+    } = *request;
+    if !is_inside(from, max) || !is_inside(to, max) {
+        return Err(PathError::InvalidHexes);
+    }
+    let heuristic = |hex: Hex| get_distance_hex(hex, to, hexagonal).saturating_sub(cut);
+    if heuristic(from) == 0 {
+        return Ok(Path {
+            steps: vec![],
+            cost: 0,
+        });
+    }
+    if heuristic(from) > max_length {
+        return Err(PathError::TooFar);
+    }
+    if cut == 0 && !grid.is_hex_passed(to) {
+        return Err(PathError::TargetBusy);
+    }
+
+    let mut nodes = Nodes::new(max);
+    let mut open = BinaryHeap::new();
+    let start = nodes.index(from);
+    nodes.cost[start] = 0;
+    open.push(Reverse((heuristic(from), 0, start)));
+
+    while let Some(Reverse((_, node_cost, index))) = open.pop() {
+        if node_cost > nodes.cost[index] {
+            // Stale entry, hex was reached cheaper
+            continue;
+        }
+        let hex = nodes.hex(index);
+        if heuristic(hex) == 0 {
+            return Ok(Path {
+                steps: restore_path(&nodes, from, hex, hexagonal),
+                cost: node_cost,
+            });
+        }
+        let steps = nodes.steps[index] + 1;
+        if steps > max_length {
+            continue;
+        }
+        for dir in 0..dirs_count(hexagonal) {
+            let next = match move_hex_by_dir(hex, dir, max, hexagonal) {
+                Some(next) if grid.is_hex_passed(next) => next,
+                _ => continue,
+            };
+            let next_index = nodes.index(next);
+            let next_cost = node_cost.saturating_add(cost(next).max(1));
+            if next_cost < nodes.cost[next_index] {
+                nodes.cost[next_index] = next_cost;
+                nodes.steps[next_index] = steps;
+                nodes.dir[next_index] = dir;
+                open.push(Reverse((
+                    next_cost.saturating_add(heuristic(next)),
+                    next_cost,
+                    next_index,
+                )));
+            }
+        }
+    }
+    Err(PathError::NoWay)
+}
+
+fn restore_path(nodes: &Nodes, from: Hex, to: Hex, hexagonal: bool) -> Vec<PathStep> {
+    let dirs = dirs_count(hexagonal);
+    let mut steps = vec![];
+    let mut hex = to;
+    while hex != from {
+        let dir = nodes.dir[nodes.index(hex)];
+        steps.push(PathStep { hex, dir });
+        hex = move_hex_by_dir(hex, (dir + dirs / 2) % dirs, nodes.max, hexagonal)
+            .expect("Previous hex is inside of the map");
+    }
+    steps.reverse();
+    steps
+}
+
+/// Hexes reachable by walking from some hex.
+#[derive(Debug, Clone)]
+pub struct Reachability {
+    max: Hex,
+    steps: Vec<u32>,
+}
+
+impl Reachability {
+    /// Number of steps to the hex, `None` if it can't be reached.
+    pub fn steps(&self, hex: Hex) -> Option<u32> {
+        if !is_inside(hex, self.max) {
+            return None;
+        }
+        let steps = self.steps[hex.y as usize * self.max.x as usize + hex.x as usize];
+        if steps == UNREACHED {
+            None
+        } else {
+            Some(steps)
+        }
+    }
+    pub fn is_reachable(&self, hex: Hex) -> bool {
+        self.steps(hex).is_some()
+    }
+    /// Reachable hexes with their number of steps, row by row.
+    pub fn hexes(&self) -> impl Iterator<Item = (Hex, u32)> + '_ {
+        let max_x = self.max.x as usize;
+        self.steps
+            .iter()
+            .enumerate()
+            .filter(|(_, &steps)| steps != UNREACHED)
+            .map(move |(index, &steps)| {
+                let hex = Hex::new((index % max_x) as u16, (index / max_x) as u16);
+                (hex, steps)
+            })
+    }
+    pub fn count(&self) -> usize {
+        self.hexes().count()
+    }
+}
+
+/// Flood fill from `from` through passable hexes, up to `max_length` steps.
+/// Starting hex is always reachable, even if it's blocked.
+pub fn reachable<G: HexGrid + ?Sized>(grid: &G, from: Hex, max_length: u32) -> Reachability {
+    let max = grid.max_hex();
+    let hexagonal = grid.is_hexagonal();
+    let mut nodes = Nodes::new(max);
+    let mut queue = VecDeque::new();
+    if is_inside(from, max) {
+        let start = nodes.index(from);
+        nodes.cost[start] = 0;
+        queue.push_back(from);
+    }
+    while let Some(hex) = queue.pop_front() {
+        let steps = nodes.cost[nodes.index(hex)] + 1;
+        if steps > max_length {
+            continue;
+        }
+        for dir in 0..dirs_count(hexagonal) {
+            let next = match move_hex_by_dir(hex, dir, max, hexagonal) {
+                Some(next) if grid.is_hex_passed(next) => next,
+                _ => continue,
+            };
+            let next_index = nodes.index(next);
+            if nodes.cost[next_index] == UNREACHED {
+                nodes.cost[next_index] = steps;
+                queue.push_back(next);
+            }
+        }
+    }
+    Reachability {
+        max,
+        steps: nodes.cost,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::grid::{BlockerGrid, FH_BLOCK, FH_CRITTER};
+    use proptest::prelude::*;
+
+    fn check_steps(grid: &BlockerGrid, from: Hex, path: &Path) {
+        let mut hex = from;
+        for step in &path.steps {
+            let next = move_hex_by_dir(hex, step.dir, grid.max_hex(), grid.is_hexagonal());
+            assert_eq!(next, Some(step.hex));
+            assert!(grid.is_hex_passed(step.hex));
+            hex = step.hex;
+        }
+    }
+
+    /// Wall across the map at `x`, with a gap at `gap_y`.
+    fn wall(max: Hex, x: u16, gap_y: Option<u16>) -> BlockerGrid {
+        let mut grid = BlockerGrid::new(max);
+        for y in (0..max.y).filter(|&y| Some(y) != gap_y) {
+            grid.add_flags(Hex::new(x, y), FH_BLOCK);
+        }
+        grid
+    }
+
+    #[test]
+    fn straight_path() {
+        for grid in &[
+            BlockerGrid::new(Hex::new(30, 30)),
+            BlockerGrid::square(Hex::new(30, 30)),
+        ] {
+            let (from, to) = (Hex::new(3, 4), Hex::new(20, 25));
+            let path = find_path(grid, &PathRequest::new(from, to)).unwrap();
+            let dist = get_distance_hex(from, to, grid.is_hexagonal());
+            assert_eq!(path.steps.len() as u32, dist);
+            assert_eq!(path.cost, dist);
+            assert_eq!(path.last_hex(), Some(to));
+            check_steps(grid, from, &path);
+        }
+    }
+
+    #[test]
+    fn through_the_gap() {
+        let grid = wall(Hex::new(20, 20), 10, Some(2));
+        let (from, to) = (Hex::new(5, 15), Hex::new(15, 15));
+        let path = find_path(&grid, &PathRequest::new(from, to)).unwrap();
+        assert!(path.steps.iter().any(|step| step.hex == Hex::new(10, 2)));
+        check_steps(&grid, from, &path);
+
+        let closed = wall(Hex::new(20, 20), 10, None);
+        assert_eq!(
+            find_path(&closed, &PathRequest::new(from, to)),
+            Err(PathError::NoWay)
+        );
+        let mut short = PathRequest::new(from, to);
+        short.max_length = 12;
+        assert_eq!(find_path(&grid, &short), Err(PathError::NoWay));
+        short.max_length = 5;
+        assert_eq!(find_path(&grid, &short), Err(PathError::TooFar));
+    }
+
+    #[test]
+    fn stop_adjacent() {
+        let mut grid = BlockerGrid::new(Hex::new(20, 20));
+        let (from, to) = (Hex::new(2, 2), Hex::new(12, 9));
+        grid.add_flags(to, FH_CRITTER);
+        let mut request = PathRequest::new(from, to);
+        assert_eq!(find_path(&grid, &request), Err(PathError::TargetBusy));
+
+        request.cut = 1;
+        let path = find_path(&grid, &request).unwrap();
+        let last = path.last_hex().unwrap();
+        assert_eq!(get_distance_hex(last, to, true), 1);
+        assert_eq!(
+            path.steps.len() as u32,
+            get_distance_hex(from, to, true) - 1
+        );
+
+        request.from = last;
+        assert_eq!(find_path(&grid, &request).unwrap().steps, vec![]);
+    }
+
+    #[test]
+    fn avoids_expensive_hexes() {
+        let grid = BlockerGrid::new(Hex::new(20, 20));
+        let (from, to) = (Hex::new(2, 10), Hex::new(16, 10));
+        let swamp = |hex: Hex| if hex.x == 9 && hex.y > 3 { 50 } else { 1 };
+        let path = find_path_with_cost(&grid, &PathRequest::new(from, to), swamp).unwrap();
+        assert!(path
+            .steps
+            .iter()
+            .all(|step| step.hex.x != 9 || step.hex.y <= 3));
+        assert_eq!(path.cost, path.steps.len() as u32);
+    }
+
+    #[test]
+    fn enclosed_room() {
+        let max = Hex::new(20, 20);
+        let mut grid = BlockerGrid::square(max);
+        for i in 4..=8 {
+            for &hex in &[
+                Hex::new(i, 4),
+                Hex::new(i, 8),
+                Hex::new(4, i),
+                Hex::new(8, i),
+            ] {
+                grid.add_flags(hex, FH_BLOCK);
+            }
+        }
+        let inside = reachable(&grid, Hex::new(6, 6), MAX_PATH_LENGTH);
+        assert_eq!(inside.count(), 9);
+        assert_eq!(inside.steps(Hex::new(7, 7)), Some(1));
+        assert!(!inside.is_reachable(Hex::new(0, 0)));
+
+        let outside = reachable(&grid, Hex::new(0, 0), MAX_PATH_LENGTH);
+        assert_eq!(outside.count(), 20 * 20 - 25);
+        let near = reachable(&grid, Hex::new(0, 0), 2);
+        assert_eq!(near.count(), 9);
+    }
+
+    fn grid_with_blocks() -> impl Strategy<Value = BlockerGrid> {
+        prop::collection::vec(any::<bool>(), 15 * 15).prop_map(|blocks| {
+            let mut grid = BlockerGrid::new(Hex::new(15, 15));
+            for (index, _) in blocks.iter().enumerate().filter(|(_, &block)| block) {
+                // About a quarter of hexes is blocked
+                if index % 2 == 0 {
+                    grid.add_flags(Hex::new(index as u16 % 15, index as u16 / 15), FH_BLOCK);
+                }
+            }
+            grid
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn path_agrees_with_reachability(
+            grid in grid_with_blocks(),
+            from in (0..15u16, 0..15u16),
+            to in (0..15u16, 0..15u16),
+        ) {
+            let (from, to) = (Hex::new(from.0, from.1), Hex::new(to.0, to.1));
+            let reach = reachable(&grid, from, MAX_PATH_LENGTH);
+            match find_path(&grid, &PathRequest::new(from, to)) {
+                Ok(path) => {
+                    prop_assert_eq!(Some(path.steps.len() as u32), reach.steps(to));
+                    check_steps(&grid, from, &path);
+                }
+                Err(_) => prop_assert!(!reach.is_reachable(to) && from != to),
+            }
+        }
+    }
+}
@@ -0,0 +1,2595 @@
+//! Values produced by engine's geometry code, see `reference/engine.cpp`.
+
+/// `(x1, y1, x2, y2, hex distance, hex direction, square distance, square direction)`
+pub(crate) type Direction = (u16, u16, u16, u16, u32, u8, u32, u8);
+/// `(from, to, angle in degrees, next hexes)`
+pub(crate) type Trace = ((u16, u16), (u16, u16), f32, &'static [(u16, u16)]);
+
+pub(crate) const DIRECTIONS: &[Direction] = &[
+    (36, 156, 38, 159, 4, 2, 3, 3),
+    (109, 90, 10, 29, 110, 0, 99, 7),
+    (52, 27, 158, 87, 113, 3, 106, 3),
+    (75, 168, 74, 166, 2, 0, 2, 7),
+    (159, 65, 71, 96, 88, 1, 88, 0),
+    (149, 164, 180, 44, 135, 5, 120, 6),
+    (29, 114, 25, 115, 4, 1, 4, 0),
+    (34, 50, 191, 151, 179, 3, 157, 3),
+    (166, 79, 192, 1, 91, 5, 78, 6),
+    (113, 150, 115, 147, 4, 4, 3, 5),
+    (101, 21, 172, 73, 88, 3, 71, 3),
+    (65, 97, 55, 158, 66, 2, 61, 2),
+    (158, 98, 160, 94, 5, 5, 4, 5),
+    (53, 37, 195, 20, 142, 4, 142, 4),
+    (96, 74, 57, 108, 53, 1, 39, 1),
+    (156, 19, 152, 19, 4, 1, 4, 0),
+    (91, 66, 197, 4, 115, 4, 106, 5),
+    (100, 165, 33, 198, 67, 1, 67, 1),
+    (89, 111, 92, 115, 6, 2, 4, 3),
+    (172, 84, 111, 114, 61, 1, 61, 1),
+    (142, 189, 148, 56, 136, 5, 133, 6),
+    (97, 122, 95, 121, 2, 0, 2, 7),
+    (24, 147, 107, 142, 83, 4, 83, 4),
+    (60, 162, 2, 69, 122, 5, 93, 7),
+    (197, 27, 200, 26, 3, 4, 3, 4),
+    (130, 138, 192, 178, 71, 3, 62, 3),
+    (158, 171, 52, 96, 128, 0, 106, 7),
+    (62, 46, 62, 43, 3, 5, 3, 6),
+    (80, 32, 109, 144, 126, 2, 112, 2),
+    (11, 112, 199, 130, 188, 3, 188, 4),
+    (84, 18, 84, 21, 3, 2, 3, 2),
+    (171, 156, 7, 82, 164, 0, 164, 7),
+    (60, 168, 10, 133, 60, 0, 50, 7),
+    (163, 163, 163, 161, 2, 5, 2, 6),
+    (184, 54, 7, 154, 188, 1, 177, 1),
+    (37, 112, 25, 145, 39, 2, 33, 2),
+    (105, 150, 102, 147, 4, 0, 3, 7),
+    (109, 191, 30, 74, 156, 0, 117, 7),
+    (57, 148, 6, 16, 157, 5, 132, 6),
+    (151, 153, 154, 154, 3, 3, 3, 4),
+    (113, 5, 49, 50, 77, 1, 64, 1),
+    (124, 161, 156, 58, 119, 5, 103, 6),
+    (172, 138, 174, 141, 4, 2, 3, 3),
+    (199, 29, 122, 104, 114, 1, 77, 1),
+    (74, 127, 109, 187, 77, 2, 60, 3),
+    (180, 177, 181, 178, 1, 3, 1, 3),
+    (118, 103, 80, 138, 54, 1, 38, 1),
+    (128, 140, 60, 22, 152, 5, 118, 7),
+    (6, 151, 3, 150, 3, 0, 3, 0),
+    (198, 175, 195, 179, 5, 1, 4, 1),
+    (114, 12, 167, 175, 189, 2, 163, 2),
+    (194, 20, 197, 19, 3, 4, 3, 4),
+    (67, 37, 39, 53, 30, 1, 28, 1),
+    (0, 31, 128, 193, 226, 3, 162, 3),
+    (194, 7, 197, 3, 6, 4, 4, 5),
+    (188, 25, 20, 162, 221, 1, 168, 1),
+    (88, 13, 38, 99, 111, 2, 86, 1),
+    (93, 160, 96, 164, 6, 2, 4, 3),
+    (35, 105, 116, 119, 81, 3, 81, 4),
+    (66, 171, 188, 151, 122, 4, 122, 4),
+    (42, 12, 44, 16, 5, 2, 4, 3),
+    (104, 160, 180, 129, 76, 4, 76, 4),
+    (23, 33, 46, 16, 28, 4, 23, 5),
+    (30, 129, 28, 129, 2, 1, 2, 0),
+    (152, 50, 194, 174, 145, 2, 124, 2),
+    (153, 78, 112, 71, 41, 0, 41, 0),
+    (77, 87, 74, 89, 4, 1, 3, 1),
+    (147, 99, 97, 152, 78, 1, 53, 1),
+    (159, 142, 104, 70, 99, 0, 72, 7),
+    (35, 136, 34, 133, 3, 5, 3, 6),
+    (129, 66, 186, 51, 57, 4, 57, 4),
+    (156, 38, 50, 194, 209, 1, 156, 1),
+    (122, 1, 118, 3, 4, 1, 4, 1),
+    (0, 172, 23, 130, 54, 5, 42, 5),
+    (167, 112, 68, 181, 119, 1, 99, 1),
+    (110, 102, 107, 103, 3, 1, 3, 0),
+    (22, 168, 25, 55, 115, 5, 113, 6),
+    (153, 147, 84, 145, 69, 0, 69, 0),
+    (142, 187, 144, 185, 3, 4, 2, 5),
+    (34, 196, 140, 135, 114, 4, 106, 5),
+    (31, 71, 58, 52, 32, 4, 27, 5),
+    (166, 182, 163, 184, 3, 1, 3, 1),
+    (147, 191, 128, 39, 161, 5, 152, 6),
+    (44, 131, 86, 145, 42, 3, 42, 4),
+    (5, 92, 3, 95, 4, 1, 3, 1),
+    (186, 104, 153, 18, 103, 5, 86, 6),
+    (191, 97, 103, 131, 88, 1, 88, 0),
+    (55, 68, 57, 68, 2, 3, 2, 4),
+    (54, 42, 199, 192, 222, 3, 150, 3),
+    (26, 99, 73, 127, 51, 3, 47, 3),
+    (146, 23, 143, 24, 3, 1, 3, 0),
+    (127, 143, 17, 65, 133, 0, 110, 7),
+    (122, 75, 64, 88, 58, 1, 58, 0),
+    (162, 49, 165, 51, 3, 3, 3, 3),
+    (136, 109, 142, 160, 54, 2, 51, 2),
+    (35, 162, 137, 146, 102, 4, 102, 4),
+    (31, 169, 35, 169, 4, 3, 4, 4),
+    (5, 64, 121, 143, 137, 3, 116, 3),
+    (168, 87, 145, 51, 48, 5, 36, 7),
+    (37, 139, 40, 138, 3, 4, 3, 4),
+    (189, 52, 89, 145, 143, 1, 100, 1),
+    (140, 140, 144, 36, 106, 5, 104, 6),
+    (52, 117, 53, 120, 3, 2, 3, 2),
+    (193, 157, 75, 107, 118, 0, 118, 7),
+    (87, 94, 138, 162, 94, 3, 68, 3),
+    (161, 197, 157, 197, 4, 1, 4, 0),
+    (60, 24, 178, 149, 184, 3, 125, 3),
+    (120, 176, 107, 7, 176, 5, 169, 6),
+    (175, 184, 177, 185, 2, 3, 2, 3),
+    (90, 153, 19, 110, 79, 0, 71, 7),
+    (79, 76, 150, 99, 71, 3, 71, 4),
+    (70, 195, 67, 194, 3, 0, 3, 0),
+    (102, 46, 35, 169, 156, 2, 123, 1),
+    (122, 170, 94, 97, 87, 5, 73, 6),
+    (152, 90, 155, 86, 6, 4, 4, 5),
+    (103, 10, 151, 45, 59, 3, 48, 3),
+    (120, 86, 139, 116, 39, 2, 30, 3),
+    (159, 148, 162, 147, 3, 4, 3, 4),
+    (55, 72, 122, 27, 78, 4, 67, 5),
+    (4, 47, 14, 96, 54, 2, 49, 2),
+    (18, 162, 19, 163, 1, 3, 1, 3),
+    (65, 102, 175, 196, 149, 3, 110, 3),
+    (68, 164, 41, 187, 36, 1, 27, 1),
+    (6, 164, 8, 164, 2, 3, 2, 4),
+    (14, 24, 3, 178, 159, 2, 154, 2),
+    (45, 51, 26, 195, 154, 2, 144, 2),
+    (33, 55, 33, 52, 3, 5, 3, 6),
+    (39, 198, 119, 142, 96, 4, 80, 5),
+    (157, 173, 11, 69, 177, 0, 146, 7),
+    (125, 199, 126, 201, 3, 2, 2, 3),
+    (149, 151, 92, 74, 105, 0, 77, 7),
+    (20, 28, 88, 12, 68, 4, 68, 4),
+    (118, 56, 117, 53, 4, 5, 3, 6),
+    (147, 179, 80, 173, 67, 0, 67, 0),
+    (124, 174, 111, 88, 93, 5, 86, 6),
+    (137, 79, 141, 82, 5, 3, 4, 3),
+    (191, 8, 92, 115, 157, 1, 107, 1),
+    (175, 20, 162, 112, 99, 2, 92, 2),
+    (3, 34, 3, 31, 3, 5, 3, 6),
+    (25, 188, 104, 86, 141, 4, 102, 5),
+    (78, 81, 70, 17, 68, 5, 64, 6),
+    (183, 193, 179, 194, 4, 1, 4, 0),
+    (164, 148, 186, 53, 106, 5, 95, 6),
+    (90, 192, 85, 76, 119, 5, 116, 6),
+    (15, 67, 15, 71, 4, 2, 4, 2),
+    (169, 165, 69, 129, 100, 0, 100, 0),
+    (40, 86, 194, 180, 171, 3, 154, 3),
+    (52, 35, 49, 35, 3, 0, 3, 0),
+    (46, 5, 29, 173, 176, 2, 168, 2),
+    (81, 98, 173, 134, 92, 3, 92, 4),
+    (97, 99, 100, 99, 3, 3, 3, 4),
+    (41, 78, 125, 58, 84, 4, 84, 4),
+    (44, 20, 3, 94, 94, 2, 74, 1),
+    (102, 101, 106, 98, 5, 4, 4, 5),
+    (65, 131, 158, 61, 116, 4, 93, 5),
+    (141, 127, 67, 8, 156, 5, 119, 7),
+    (110, 125, 110, 124, 1, 5, 1, 6),
+    (92, 16, 90, 170, 155, 2, 154, 2),
+    (179, 77, 120, 92, 59, 1, 59, 0),
+    (132, 193, 128, 193, 4, 1, 4, 0),
+    (84, 160, 55, 70, 105, 5, 90, 6),
+    (94, 66, 106, 77, 17, 3, 12, 3),
+    (9, 135, 9, 139, 4, 2, 4, 2),
+    (184, 167, 73, 164, 111, 0, 111, 0),
+    (177, 140, 165, 137, 12, 0, 12, 0),
+    (111, 124, 111, 125, 1, 2, 1, 2),
+    (195, 27, 147, 38, 48, 1, 48, 0),
+    (164, 52, 101, 167, 146, 2, 115, 1),
+    (117, 143, 114, 147, 6, 1, 4, 1),
+    (59, 175, 80, 111, 74, 5, 64, 6),
+    (167, 7, 193, 33, 39, 3, 26, 3),
+    (45, 64, 42, 60, 5, 0, 4, 7),
+    (120, 37, 194, 193, 193, 2, 156, 3),
+    (109, 193, 24, 112, 123, 0, 85, 7),
+    (3, 113, 6, 113, 3, 3, 3, 4),
+    (171, 88, 17, 160, 154, 1, 154, 1),
+    (19, 75, 197, 87, 178, 3, 178, 4),
+    (33, 96, 33, 98, 2, 2, 2, 2),
+    (46, 22, 123, 17, 77, 4, 77, 4),
+    (15, 171, 25, 36, 140, 5, 135, 6),
+    (108, 176, 105, 176, 3, 0, 3, 0),
+    (88, 168, 49, 52, 136, 5, 116, 6),
+    (104, 139, 85, 136, 19, 0, 19, 0),
+    (165, 2, 168, 1, 3, 4, 3, 4),
+    (188, 6, 49, 148, 211, 1, 142, 1),
+    (91, 92, 180, 79, 89, 4, 89, 4),
+    (38, 67, 42, 66, 4, 4, 4, 4),
+    (147, 196, 143, 103, 95, 5, 93, 6),
+    (190, 16, 159, 21, 31, 1, 31, 0),
+    (11, 67, 11, 68, 1, 2, 1, 2),
+    (1, 140, 194, 160, 193, 3, 193, 4),
+    (110, 30, 33, 196, 204, 2, 166, 1),
+    (15, 21, 18, 21, 3, 3, 3, 4),
+    (35, 70, 45, 160, 95, 2, 90, 2),
+    (135, 111, 164, 122, 29, 3, 29, 4),
+    (158, 101, 156, 100, 2, 0, 2, 7),
+    (4, 129, 178, 121, 174, 4, 174, 4),
+    (199, 138, 31, 32, 190, 0, 168, 7),
+    (171, 113, 174, 113, 3, 3, 3, 4),
+    (139, 180, 41, 160, 98, 0, 98, 0),
+    (15, 61, 81, 32, 66, 4, 66, 5),
+    (156, 32, 153, 29, 5, 0, 3, 7),
+    (96, 25, 153, 118, 121, 2, 93, 3),
+    (143, 177, 101, 89, 109, 5, 88, 7),
+    (125, 35, 123, 39, 5, 2, 4, 1),
+    (61, 29, 185, 30, 124, 3, 124, 4),
+    (65, 58, 84, 124, 76, 2, 66, 2),
+    (120, 11, 119, 7, 5, 5, 4, 6),
+    (123, 197, 127, 94, 105, 5, 103, 6),
+    (52, 173, 37, 169, 15, 0, 15, 0),
+    (184, 31, 185, 31, 1, 4, 1, 4),
+    (53, 52, 79, 178, 139, 2, 126, 2),
+    (33, 95, 47, 69, 33, 5, 26, 5),
+    (97, 121, 101, 118, 5, 4, 4, 5),
+    (59, 75, 164, 116, 105, 3, 105, 4),
+    (13, 14, 8, 81, 70, 2, 67, 2),
+    (67, 31, 67, 34, 3, 2, 3, 2),
+    (38, 153, 82, 20, 155, 5, 133, 6),
+    (94, 121, 86, 152, 35, 2, 31, 2),
+    (76, 115, 79, 114, 3, 4, 3, 4),
+    (37, 193, 56, 149, 53, 5, 44, 5),
+    (163, 156, 71, 127, 92, 0, 92, 0),
+    (182, 14, 178, 15, 4, 1, 4, 0),
+    (46, 91, 73, 115, 37, 3, 27, 3),
+    (52, 18, 65, 99, 87, 2, 81, 2),
+    (18, 151, 21, 153, 3, 3, 3, 3),
+    (174, 21, 157, 125, 112, 2, 104, 2),
+    (180, 137, 135, 16, 144, 5, 121, 6),
+    (23, 87, 25, 87, 2, 3, 2, 4),
+    (121, 111, 54, 23, 121, 0, 88, 7),
+    (3, 50, 121, 122, 131, 3, 118, 3),
+    (10, 165, 13, 169, 5, 3, 4, 3),
+    (181, 117, 6, 36, 175, 0, 175, 7),
+    (55, 68, 186, 179, 177, 3, 131, 3),
+    (59, 11, 56, 12, 3, 1, 3, 0),
+    (77, 127, 166, 157, 89, 3, 89, 4),
+    (113, 162, 131, 12, 159, 5, 150, 6),
+    (189, 93, 186, 92, 3, 0, 3, 0),
+    (111, 86, 86, 163, 90, 2, 77, 2),
+    (190, 173, 197, 90, 87, 5, 83, 6),
+    (46, 126, 48, 124, 3, 4, 2, 5),
+    (130, 74, 171, 140, 86, 2, 66, 3),
+    (8, 52, 101, 4, 95, 4, 93, 5),
+    (156, 1, 155, 0, 2, 0, 1, 7),
+    (193, 183, 105, 77, 150, 0, 106, 7),
+    (55, 163, 126, 136, 71, 4, 71, 4),
+    (49, 144, 46, 143, 3, 0, 3, 0),
+    (72, 151, 134, 84, 98, 4, 67, 5),
+    (111, 130, 196, 56, 116, 4, 85, 5),
+    (162, 14, 165, 15, 3, 3, 3, 4),
+    (155, 98, 92, 135, 69, 1, 63, 1),
+    (112, 149, 17, 17, 180, 0, 132, 7),
+    (14, 139, 10, 142, 5, 1, 4, 1),
+    (103, 80, 126, 1, 90, 5, 79, 6),
+    (178, 5, 2, 194, 277, 1, 189, 1),
+    (185, 177, 188, 173, 5, 4, 4, 5),
+    (169, 7, 147, 135, 139, 2, 128, 2),
+    (71, 155, 79, 158, 8, 3, 8, 4),
+    (117, 185, 116, 181, 4, 5, 4, 6),
+    (47, 135, 186, 57, 147, 4, 139, 5),
+    (2, 25, 8, 26, 6, 3, 6, 4),
+    (150, 154, 152, 152, 3, 4, 2, 5),
+    (17, 176, 134, 13, 221, 4, 163, 5),
+    (53, 4, 30, 45, 53, 2, 41, 1),
+    (76, 154, 80, 156, 4, 3, 4, 3),
+    (172, 107, 190, 90, 26, 4, 18, 5),
+    (74, 177, 95, 71, 117, 5, 106, 6),
+    (155, 35, 153, 34, 2, 0, 2, 7),
+    (172, 139, 162, 140, 10, 1, 10, 0),
+    (150, 13, 159, 58, 49, 2, 45, 2),
+    (33, 184, 29, 181, 5, 0, 4, 7),
+    (103, 192, 103, 66, 126, 5, 126, 6),
+    (6, 24, 126, 176, 212, 3, 152, 3),
+    (0, 102, 3, 106, 5, 3, 4, 3),
+    (29, 11, 82, 14, 53, 3, 53, 4),
+    (107, 158, 34, 199, 78, 1, 73, 1),
+    (111, 26, 110, 29, 4, 2, 3, 2),
+    (109, 48, 38, 100, 88, 1, 71, 1),
+    (60, 186, 19, 94, 113, 5, 92, 7),
+    (73, 0, 71, 1, 2, 1, 2, 1),
+    (115, 180, 42, 129, 87, 0, 73, 7),
+    (25, 19, 130, 195, 229, 2, 176, 3),
+    (171, 34, 169, 38, 5, 2, 4, 1),
+    (129, 197, 6, 119, 139, 0, 123, 7),
+    (120, 13, 2, 143, 189, 1, 130, 1),
+    (14, 185, 15, 187, 2, 2, 2, 3),
+    (135, 10, 103, 125, 131, 2, 115, 2),
+    (17, 20, 136, 29, 119, 3, 119, 4),
+    (4, 190, 8, 191, 4, 3, 4, 4),
+    (168, 123, 30, 198, 144, 1, 138, 1),
+    (129, 123, 199, 122, 70, 4, 70, 4),
+    (62, 7, 61, 5, 3, 5, 2, 7),
+    (187, 63, 26, 41, 161, 0, 161, 0),
+    (127, 173, 65, 183, 62, 1, 62, 0),
+    (179, 88, 179, 87, 1, 5, 1, 6),
+    (184, 103, 22, 110, 162, 1, 162, 0),
+    (149, 188, 100, 61, 151, 5, 127, 6),
+    (161, 199, 165, 198, 4, 4, 4, 4),
+    (65, 119, 60, 90, 31, 5, 29, 6),
+    (143, 196, 143, 10, 186, 5, 186, 6),
+];
+
+pub(crate) const HEX_TRACES: &[Trace] = &[
+    (
+        (132, 73),
+        (142, 60),
+        0.0,
+        &[
+            (133, 73),
+            (133, 72),
+            (134, 71),
+            (134, 70),
+            (135, 70),
+            (135, 69),
+            (136, 68),
+            (136, 67),
+            (137, 67),
+            (138, 66),
+            (138, 65),
+            (139, 65),
+        ],
+    ),
+    (
+        (116, 106),
+        (128, 113),
+        0.0,
+        &[
+            (117, 107),
+            (118, 107),
+            (119, 108),
+            (120, 108),
+            (121, 109),
+            (122, 109),
+            (122, 110),
+            (123, 111),
+            (124, 111),
+            (125, 112),
+            (126, 112),
+            (127, 113),
+        ],
+    ),
+    (
+        (95, 95),
+        (98, 105),
+        0.0,
+        &[
+            (95, 96),
+            (96, 96),
+            (96, 97),
+            (96, 98),
+            (96, 99),
+            (97, 100),
+            (97, 101),
+            (97, 102),
+            (97, 103),
+            (98, 103),
+            (98, 104),
+            (98, 105),
+        ],
+    ),
+    (
+        (102, 139),
+        (105, 151),
+        15.0,
+        &[
+            (102, 140),
+            (102, 141),
+            (102, 142),
+            (102, 143),
+            (102, 144),
+            (102, 145),
+            (102, 146),
+            (102, 147),
+            (102, 148),
+            (102, 149),
+            (102, 150),
+            (101, 151),
+        ],
+    ),
+    (
+        (130, 88),
+        (140, 82),
+        -30.0,
+        &[
+            (130, 87),
+            (131, 87),
+            (131, 86),
+            (132, 85),
+            (132, 84),
+            (133, 84),
+            (133, 83),
+            (133, 82),
+            (134, 81),
+            (134, 80),
+            (135, 80),
+            (135, 79),
+        ],
+    ),
+    (
+        (124, 82),
+        (138, 93),
+        0.0,
+        &[
+            (125, 83),
+            (126, 83),
+            (126, 84),
+            (127, 85),
+            (128, 85),
+            (129, 86),
+            (129, 87),
+            (130, 87),
+            (131, 88),
+            (132, 88),
+            (133, 89),
+            (133, 90),
+        ],
+    ),
+    (
+        (136, 65),
+        (126, 77),
+        0.0,
+        &[
+            (135, 66),
+            (135, 67),
+            (134, 67),
+            (134, 68),
+            (133, 69),
+            (132, 69),
+            (132, 70),
+            (131, 71),
+            (131, 72),
+            (130, 72),
+            (130, 73),
+            (129, 74),
+        ],
+    ),
+    (
+        (85, 74),
+        (102, 93),
+        0.0,
+        &[
+            (86, 74),
+            (86, 75),
+            (87, 76),
+            (87, 77),
+            (88, 77),
+            (89, 78),
+            (89, 79),
+            (90, 79),
+            (90, 80),
+            (91, 81),
+            (92, 81),
+            (92, 82),
+        ],
+    ),
+    (
+        (69, 117),
+        (68, 122),
+        15.0,
+        &[
+            (69, 118),
+            (68, 118),
+            (68, 119),
+            (67, 120),
+            (67, 121),
+            (67, 122),
+            (66, 122),
+            (66, 123),
+            (65, 124),
+            (65, 125),
+            (65, 126),
+            (64, 126),
+        ],
+    ),
+    (
+        (61, 125),
+        (79, 113),
+        -30.0,
+        &[
+            (61, 124),
+            (62, 123),
+            (62, 122),
+            (63, 122),
+            (63, 121),
+            (63, 120),
+            (64, 119),
+            (64, 118),
+            (64, 117),
+            (65, 117),
+            (65, 116),
+            (66, 115),
+        ],
+    ),
+    (
+        (123, 137),
+        (126, 123),
+        0.0,
+        &[
+            (123, 136),
+            (123, 135),
+            (124, 134),
+            (124, 133),
+            (124, 132),
+            (124, 131),
+            (124, 130),
+            (125, 130),
+            (125, 129),
+            (125, 128),
+            (125, 127),
+            (125, 126),
+        ],
+    ),
+    (
+        (107, 61),
+        (95, 60),
+        0.0,
+        &[
+            (106, 60),
+            (105, 61),
+            (104, 60),
+            (103, 61),
+            (102, 60),
+            (101, 61),
+            (100, 60),
+            (99, 60),
+            (98, 60),
+            (97, 60),
+            (96, 60),
+            (95, 60),
+        ],
+    ),
+    (
+        (72, 95),
+        (70, 83),
+        0.0,
+        &[
+            (72, 94),
+            (72, 93),
+            (72, 92),
+            (71, 92),
+            (71, 91),
+            (71, 90),
+            (71, 89),
+            (71, 88),
+            (71, 87),
+            (70, 86),
+            (70, 85),
+            (70, 84),
+        ],
+    ),
+    (
+        (66, 90),
+        (80, 95),
+        15.0,
+        &[
+            (67, 91),
+            (68, 91),
+            (69, 92),
+            (69, 93),
+            (70, 93),
+            (71, 94),
+            (72, 94),
+            (73, 95),
+            (74, 95),
+            (75, 96),
+            (75, 97),
+            (76, 97),
+        ],
+    ),
+    (
+        (101, 102),
+        (88, 88),
+        -30.0,
+        &[
+            (100, 101),
+            (99, 101),
+            (98, 101),
+            (97, 101),
+            (96, 100),
+            (95, 100),
+            (94, 99),
+            (93, 99),
+            (92, 99),
+            (91, 99),
+            (90, 98),
+            (89, 98),
+        ],
+    ),
+    (
+        (99, 114),
+        (88, 110),
+        0.0,
+        &[
+            (98, 113),
+            (97, 113),
+            (96, 113),
+            (95, 113),
+            (94, 112),
+            (93, 112),
+            (92, 111),
+            (91, 111),
+            (90, 111),
+            (89, 111),
+            (88, 110),
+            (87, 110),
+        ],
+    ),
+    (
+        (102, 86),
+        (82, 94),
+        0.0,
+        &[
+            (101, 87),
+            (100, 87),
+            (99, 88),
+            (98, 88),
+            (97, 89),
+            (96, 88),
+            (95, 89),
+            (94, 89),
+            (93, 90),
+            (92, 90),
+            (91, 91),
+            (90, 91),
+        ],
+    ),
+    (
+        (104, 106),
+        (122, 110),
+        0.0,
+        &[
+            (105, 107),
+            (106, 106),
+            (107, 107),
+            (108, 107),
+            (109, 108),
+            (110, 107),
+            (111, 108),
+            (112, 108),
+            (113, 109),
+            (114, 108),
+            (115, 109),
+            (116, 109),
+        ],
+    ),
+    (
+        (110, 134),
+        (114, 127),
+        15.0,
+        &[
+            (111, 134),
+            (111, 133),
+            (112, 132),
+            (113, 132),
+            (113, 131),
+            (114, 130),
+            (115, 130),
+            (115, 129),
+            (116, 128),
+            (117, 128),
+            (117, 127),
+            (118, 126),
+        ],
+    ),
+    (
+        (73, 72),
+        (77, 65),
+        -30.0,
+        &[
+            (73, 71),
+            (73, 70),
+            (73, 69),
+            (73, 68),
+            (73, 67),
+            (73, 66),
+            (73, 65),
+            (72, 64),
+            (72, 63),
+            (72, 62),
+            (72, 61),
+            (72, 60),
+        ],
+    ),
+    (
+        (91, 135),
+        (73, 138),
+        0.0,
+        &[
+            (90, 135),
+            (89, 135),
+            (88, 135),
+            (87, 136),
+            (86, 135),
+            (85, 136),
+            (84, 136),
+            (83, 136),
+            (82, 136),
+            (81, 137),
+            (80, 136),
+            (79, 137),
+        ],
+    ),
+    (
+        (120, 91),
+        (110, 81),
+        0.0,
+        &[
+            (119, 91),
+            (119, 90),
+            (118, 89),
+            (117, 89),
+            (117, 88),
+            (116, 87),
+            (115, 87),
+            (115, 86),
+            (114, 85),
+            (113, 85),
+            (113, 84),
+            (112, 83),
+        ],
+    ),
+    (
+        (100, 136),
+        (102, 132),
+        0.0,
+        &[
+            (100, 135),
+            (101, 135),
+            (101, 134),
+            (102, 133),
+            (102, 132),
+            (102, 131),
+            (103, 131),
+            (103, 130),
+            (104, 129),
+            (104, 128),
+            (104, 127),
+            (105, 127),
+        ],
+    ),
+    (
+        (135, 63),
+        (155, 63),
+        15.0,
+        &[
+            (136, 63),
+            (137, 63),
+            (138, 63),
+            (139, 64),
+            (140, 64),
+            (141, 64),
+            (142, 64),
+            (143, 65),
+            (144, 65),
+            (145, 65),
+            (146, 65),
+            (147, 66),
+        ],
+    ),
+    (
+        (64, 99),
+        (52, 105),
+        -30.0,
+        &[
+            (64, 100),
+            (63, 101),
+            (63, 102),
+            (62, 102),
+            (62, 103),
+            (61, 104),
+            (61, 105),
+            (60, 105),
+            (60, 106),
+            (59, 107),
+            (59, 108),
+            (58, 108),
+        ],
+    ),
+    (
+        (111, 123),
+        (100, 109),
+        0.0,
+        &[
+            (110, 122),
+            (110, 121),
+            (109, 121),
+            (109, 120),
+            (108, 119),
+            (108, 118),
+            (107, 118),
+            (106, 117),
+            (106, 116),
+            (105, 116),
+            (105, 115),
+            (104, 114),
+        ],
+    ),
+    (
+        (96, 125),
+        (77, 133),
+        0.0,
+        &[
+            (95, 126),
+            (94, 126),
+            (93, 127),
+            (92, 127),
+            (91, 127),
+            (90, 127),
+            (89, 128),
+            (88, 128),
+            (87, 129),
+            (86, 129),
+            (85, 130),
+            (84, 130),
+        ],
+    ),
+    (
+        (120, 133),
+        (118, 144),
+        0.0,
+        &[
+            (120, 134),
+            (120, 135),
+            (120, 136),
+            (119, 137),
+            (119, 138),
+            (119, 139),
+            (119, 140),
+            (119, 141),
+            (119, 142),
+            (118, 142),
+            (118, 143),
+            (118, 144),
+        ],
+    ),
+    (
+        (97, 104),
+        (88, 104),
+        15.0,
+        &[
+            (96, 103),
+            (95, 104),
+            (94, 103),
+            (93, 103),
+            (92, 103),
+            (91, 103),
+            (90, 102),
+            (89, 103),
+            (88, 102),
+            (87, 102),
+            (86, 102),
+            (85, 102),
+        ],
+    ),
+    (
+        (102, 75),
+        (113, 60),
+        -30.0,
+        &[
+            (102, 74),
+            (102, 73),
+            (102, 72),
+            (102, 71),
+            (102, 70),
+            (102, 69),
+            (102, 68),
+            (102, 67),
+            (102, 66),
+            (102, 65),
+            (102, 64),
+            (102, 63),
+        ],
+    ),
+    (
+        (109, 65),
+        (121, 61),
+        0.0,
+        &[
+            (110, 64),
+            (111, 64),
+            (112, 64),
+            (113, 64),
+            (114, 63),
+            (115, 63),
+            (116, 62),
+            (117, 62),
+            (118, 62),
+            (119, 62),
+            (120, 61),
+            (121, 61),
+        ],
+    ),
+    (
+        (103, 89),
+        (91, 97),
+        0.0,
+        &[
+            (102, 89),
+            (101, 90),
+            (100, 90),
+            (100, 91),
+            (99, 92),
+            (98, 92),
+            (97, 93),
+            (96, 93),
+            (95, 94),
+            (94, 94),
+            (94, 95),
+            (93, 96),
+        ],
+    ),
+    (
+        (73, 68),
+        (75, 57),
+        0.0,
+        &[
+            (73, 67),
+            (73, 66),
+            (74, 65),
+            (74, 64),
+            (74, 63),
+            (74, 62),
+            (74, 61),
+            (74, 60),
+            (75, 60),
+            (75, 59),
+            (75, 58),
+            (75, 57),
+        ],
+    ),
+    (
+        (72, 120),
+        (87, 135),
+        15.0,
+        &[
+            (72, 121),
+            (73, 122),
+            (73, 123),
+            (74, 123),
+            (74, 124),
+            (75, 125),
+            (75, 126),
+            (76, 126),
+            (76, 127),
+            (77, 128),
+            (77, 129),
+            (77, 130),
+        ],
+    ),
+    (
+        (103, 86),
+        (100, 78),
+        -30.0,
+        &[
+            (102, 85),
+            (101, 85),
+            (101, 84),
+            (100, 83),
+            (99, 83),
+            (98, 82),
+            (97, 82),
+            (97, 81),
+            (96, 80),
+            (95, 80),
+            (94, 79),
+            (93, 79),
+        ],
+    ),
+    (
+        (80, 139),
+        (83, 152),
+        0.0,
+        &[
+            (80, 140),
+            (80, 141),
+            (81, 142),
+            (81, 143),
+            (81, 144),
+            (81, 145),
+            (82, 145),
+            (82, 146),
+            (82, 147),
+            (82, 148),
+            (82, 149),
+            (83, 150),
+        ],
+    ),
+    (
+        (68, 104),
+        (62, 98),
+        0.0,
+        &[
+            (67, 104),
+            (67, 103),
+            (66, 102),
+            (65, 102),
+            (65, 101),
+            (64, 100),
+            (63, 100),
+            (63, 99),
+            (62, 98),
+            (61, 98),
+            (61, 97),
+            (60, 96),
+        ],
+    ),
+    (
+        (98, 98),
+        (92, 111),
+        0.0,
+        &[
+            (98, 99),
+            (97, 100),
+            (97, 101),
+            (97, 102),
+            (96, 102),
+            (96, 103),
+            (95, 104),
+            (95, 105),
+            (95, 106),
+            (94, 106),
+            (94, 107),
+            (94, 108),
+        ],
+    ),
+    (
+        (121, 137),
+        (139, 156),
+        15.0,
+        &[
+            (121, 138),
+            (122, 138),
+            (122, 139),
+            (123, 140),
+            (123, 141),
+            (123, 142),
+            (124, 142),
+            (124, 143),
+            (125, 144),
+            (125, 145),
+            (126, 145),
+            (126, 146),
+        ],
+    ),
+    (
+        (125, 137),
+        (123, 139),
+        -30.0,
+        &[
+            (125, 138),
+            (125, 139),
+            (124, 139),
+            (124, 140),
+            (124, 141),
+            (124, 142),
+            (124, 143),
+            (123, 144),
+            (123, 145),
+            (123, 146),
+            (123, 147),
+            (123, 148),
+        ],
+    ),
+    (
+        (75, 114),
+        (63, 133),
+        0.0,
+        &[
+            (75, 115),
+            (74, 115),
+            (74, 116),
+            (73, 117),
+            (73, 118),
+            (72, 118),
+            (72, 119),
+            (71, 120),
+            (71, 121),
+            (70, 121),
+            (70, 122),
+            (69, 123),
+        ],
+    ),
+    (
+        (93, 72),
+        (90, 80),
+        0.0,
+        &[
+            (93, 73),
+            (92, 73),
+            (92, 74),
+            (92, 75),
+            (92, 76),
+            (91, 77),
+            (91, 78),
+            (91, 79),
+            (90, 79),
+            (90, 80),
+            (90, 81),
+            (89, 82),
+        ],
+    ),
+    (
+        (61, 122),
+        (67, 103),
+        0.0,
+        &[
+            (61, 121),
+            (62, 120),
+            (62, 119),
+            (62, 118),
+            (62, 117),
+            (63, 117),
+            (63, 116),
+            (63, 115),
+            (63, 114),
+            (64, 113),
+            (64, 112),
+            (64, 111),
+        ],
+    ),
+    (
+        (90, 66),
+        (90, 67),
+        15.0,
+        &[
+            (90, 67),
+            (89, 68),
+            (89, 69),
+            (89, 70),
+            (89, 71),
+            (88, 71),
+            (88, 72),
+            (88, 73),
+            (88, 74),
+            (87, 75),
+            (87, 76),
+            (87, 77),
+        ],
+    ),
+    (
+        (107, 82),
+        (88, 80),
+        -30.0,
+        &[
+            (106, 82),
+            (105, 83),
+            (104, 83),
+            (103, 84),
+            (102, 84),
+            (101, 84),
+            (100, 84),
+            (99, 85),
+            (98, 85),
+            (97, 86),
+            (96, 86),
+            (95, 87),
+        ],
+    ),
+    (
+        (70, 105),
+        (63, 117),
+        0.0,
+        &[
+            (70, 106),
+            (69, 107),
+            (69, 108),
+            (68, 108),
+            (68, 109),
+            (67, 110),
+            (67, 111),
+            (66, 111),
+            (66, 112),
+            (65, 113),
+            (65, 114),
+            (64, 114),
+        ],
+    ),
+    (
+        (62, 71),
+        (79, 80),
+        0.0,
+        &[
+            (63, 72),
+            (64, 72),
+            (65, 73),
+            (66, 73),
+            (67, 74),
+            (68, 74),
+            (69, 75),
+            (70, 75),
+            (71, 76),
+            (72, 76),
+            (73, 77),
+            (74, 77),
+        ],
+    ),
+    (
+        (129, 134),
+        (119, 139),
+        0.0,
+        &[
+            (128, 134),
+            (127, 135),
+            (126, 135),
+            (125, 136),
+            (124, 136),
+            (123, 137),
+            (122, 137),
+            (121, 138),
+            (120, 138),
+            (119, 139),
+            (118, 139),
+            (117, 140),
+        ],
+    ),
+    (
+        (125, 69),
+        (112, 66),
+        15.0,
+        &[
+            (124, 68),
+            (123, 68),
+            (122, 67),
+            (121, 67),
+            (120, 66),
+            (119, 66),
+            (118, 65),
+            (117, 65),
+            (116, 64),
+            (115, 64),
+            (114, 64),
+            (113, 64),
+        ],
+    ),
+    (
+        (118, 115),
+        (134, 98),
+        -30.0,
+        &[
+            (118, 114),
+            (118, 113),
+            (119, 113),
+            (119, 112),
+            (119, 111),
+            (119, 110),
+            (119, 109),
+            (119, 108),
+            (120, 107),
+            (120, 106),
+            (120, 105),
+            (120, 104),
+        ],
+    ),
+    (
+        (88, 80),
+        (79, 80),
+        0.0,
+        &[
+            (87, 80),
+            (86, 80),
+            (85, 80),
+            (84, 80),
+            (83, 80),
+            (82, 80),
+            (81, 80),
+            (80, 80),
+            (79, 80),
+            (78, 79),
+            (77, 80),
+            (76, 79),
+        ],
+    ),
+    (
+        (125, 131),
+        (139, 126),
+        0.0,
+        &[
+            (126, 130),
+            (127, 130),
+            (128, 129),
+            (129, 130),
+            (130, 129),
+            (131, 129),
+            (132, 128),
+            (133, 128),
+            (134, 127),
+            (135, 127),
+            (136, 127),
+            (137, 127),
+        ],
+    ),
+    (
+        (126, 107),
+        (146, 122),
+        0.0,
+        &[
+            (127, 108),
+            (128, 108),
+            (128, 109),
+            (129, 110),
+            (130, 110),
+            (131, 111),
+            (132, 111),
+            (132, 112),
+            (133, 113),
+            (134, 113),
+            (135, 114),
+            (136, 114),
+        ],
+    ),
+    (
+        (120, 135),
+        (138, 129),
+        15.0,
+        &[
+            (121, 135),
+            (122, 135),
+            (123, 135),
+            (124, 135),
+            (125, 135),
+            (126, 134),
+            (127, 135),
+            (128, 134),
+            (129, 135),
+            (130, 134),
+            (131, 134),
+            (132, 134),
+        ],
+    ),
+    (
+        (86, 132),
+        (91, 118),
+        -30.0,
+        &[
+            (86, 131),
+            (86, 130),
+            (85, 130),
+            (85, 129),
+            (85, 128),
+            (85, 127),
+            (84, 126),
+            (84, 125),
+            (84, 124),
+            (84, 123),
+            (83, 123),
+            (83, 122),
+        ],
+    ),
+    (
+        (121, 137),
+        (101, 155),
+        0.0,
+        &[
+            (120, 137),
+            (120, 138),
+            (119, 139),
+            (118, 139),
+            (117, 140),
+            (117, 141),
+            (116, 141),
+            (115, 142),
+            (115, 143),
+            (114, 143),
+            (113, 144),
+            (112, 144),
+        ],
+    ),
+    (
+        (119, 68),
+        (129, 82),
+        0.0,
+        &[
+            (120, 68),
+            (120, 69),
+            (121, 70),
+            (121, 71),
+            (122, 71),
+            (122, 72),
+            (123, 73),
+            (123, 74),
+            (124, 74),
+            (124, 75),
+            (125, 76),
+            (125, 77),
+        ],
+    ),
+    (
+        (73, 82),
+        (70, 82),
+        0.0,
+        &[
+            (72, 82),
+            (71, 82),
+            (70, 82),
+            (69, 83),
+            (68, 82),
+            (67, 83),
+            (66, 83),
+            (65, 83),
+            (64, 83),
+            (63, 84),
+            (62, 83),
+            (61, 84),
+        ],
+    ),
+    (
+        (98, 65),
+        (80, 84),
+        15.0,
+        &[
+            (97, 66),
+            (96, 66),
+            (95, 67),
+            (94, 67),
+            (94, 68),
+            (93, 69),
+            (92, 69),
+            (91, 70),
+            (90, 70),
+            (89, 71),
+            (88, 71),
+            (87, 72),
+        ],
+    ),
+    (
+        (112, 102),
+        (121, 84),
+        -30.0,
+        &[
+            (112, 101),
+            (112, 100),
+            (112, 99),
+            (111, 99),
+            (111, 98),
+            (111, 97),
+            (111, 96),
+            (111, 95),
+            (111, 94),
+            (111, 93),
+            (111, 92),
+            (110, 91),
+        ],
+    ),
+];
+
+pub(crate) const SQUARE_TRACES: &[Trace] = &[
+    (
+        (101, 125),
+        (81, 140),
+        0.0,
+        &[
+            (100, 126),
+            (99, 127),
+            (98, 127),
+            (97, 128),
+            (96, 129),
+            (95, 130),
+            (94, 130),
+            (93, 131),
+            (92, 132),
+            (91, 133),
+            (90, 133),
+            (89, 134),
+        ],
+    ),
+    (
+        (66, 68),
+        (54, 67),
+        0.0,
+        &[
+            (65, 68),
+            (64, 68),
+            (63, 68),
+            (62, 68),
+            (61, 68),
+            (60, 67),
+            (59, 67),
+            (58, 67),
+            (57, 67),
+            (56, 67),
+            (55, 67),
+            (54, 67),
+        ],
+    ),
+    (
+        (72, 103),
+        (74, 100),
+        0.0,
+        &[
+            (73, 102),
+            (73, 101),
+            (74, 100),
+            (75, 99),
+            (75, 98),
+            (76, 97),
+            (77, 96),
+            (77, 95),
+            (78, 94),
+            (79, 93),
+            (79, 92),
+            (80, 91),
+        ],
+    ),
+    (
+        (78, 137),
+        (60, 144),
+        15.0,
+        &[
+            (77, 137),
+            (76, 137),
+            (75, 137),
+            (74, 137),
+            (73, 138),
+            (72, 138),
+            (71, 138),
+            (70, 138),
+            (69, 138),
+            (68, 138),
+            (67, 138),
+            (66, 138),
+        ],
+    ),
+    (
+        (66, 119),
+        (79, 105),
+        -30.0,
+        &[
+            (66, 118),
+            (66, 117),
+            (67, 116),
+            (67, 115),
+            (67, 114),
+            (67, 113),
+            (68, 112),
+            (68, 111),
+            (68, 110),
+            (68, 109),
+            (69, 108),
+            (69, 107),
+        ],
+    ),
+    (
+        (64, 108),
+        (53, 105),
+        0.0,
+        &[
+            (63, 108),
+            (62, 107),
+            (61, 107),
+            (60, 107),
+            (59, 107),
+            (58, 106),
+            (57, 106),
+            (56, 106),
+            (55, 106),
+            (54, 105),
+            (53, 105),
+            (52, 105),
+        ],
+    ),
+    (
+        (115, 116),
+        (120, 131),
+        0.0,
+        &[
+            (115, 117),
+            (116, 118),
+            (116, 119),
+            (116, 120),
+            (117, 121),
+            (117, 122),
+            (117, 123),
+            (118, 124),
+            (118, 125),
+            (118, 126),
+            (119, 127),
+            (119, 128),
+        ],
+    ),
+    (
+        (62, 130),
+        (45, 139),
+        0.0,
+        &[
+            (61, 131),
+            (60, 131),
+            (59, 132),
+            (58, 132),
+            (57, 133),
+            (56, 133),
+            (55, 134),
+            (54, 134),
+            (53, 135),
+            (52, 135),
+            (51, 136),
+            (50, 136),
+        ],
+    ),
+    (
+        (104, 96),
+        (117, 107),
+        15.0,
+        &[
+            (105, 97),
+            (105, 98),
+            (106, 99),
+            (107, 100),
+            (107, 101),
+            (108, 102),
+            (109, 103),
+            (110, 104),
+            (110, 105),
+            (111, 106),
+            (112, 107),
+            (112, 108),
+        ],
+    ),
+    (
+        (76, 73),
+        (84, 68),
+        -30.0,
+        &[
+            (77, 72),
+            (77, 71),
+            (78, 70),
+            (78, 69),
+            (79, 68),
+            (79, 67),
+            (80, 66),
+            (80, 65),
+            (81, 64),
+            (81, 63),
+            (82, 62),
+            (82, 61),
+        ],
+    ),
+    (
+        (124, 104),
+        (125, 113),
+        0.0,
+        &[
+            (124, 105),
+            (124, 106),
+            (124, 107),
+            (124, 108),
+            (125, 109),
+            (125, 110),
+            (125, 111),
+            (125, 112),
+            (125, 113),
+            (125, 114),
+            (125, 115),
+            (125, 116),
+        ],
+    ),
+    (
+        (105, 127),
+        (123, 125),
+        0.0,
+        &[
+            (106, 127),
+            (107, 127),
+            (108, 127),
+            (109, 127),
+            (110, 126),
+            (111, 126),
+            (112, 126),
+            (113, 126),
+            (114, 126),
+            (115, 126),
+            (116, 126),
+            (117, 126),
+        ],
+    ),
+    (
+        (84, 87),
+        (70, 86),
+        0.0,
+        &[
+            (83, 87),
+            (82, 87),
+            (81, 87),
+            (80, 87),
+            (79, 87),
+            (78, 87),
+            (77, 87),
+            (76, 86),
+            (75, 86),
+            (74, 86),
+            (73, 86),
+            (72, 86),
+        ],
+    ),
+    (
+        (81, 122),
+        (94, 122),
+        15.0,
+        &[
+            (82, 122),
+            (83, 123),
+            (84, 123),
+            (85, 123),
+            (86, 123),
+            (87, 124),
+            (88, 124),
+            (89, 124),
+            (90, 124),
+            (91, 125),
+            (92, 125),
+            (93, 125),
+        ],
+    ),
+    (
+        (65, 66),
+        (52, 76),
+        -30.0,
+        &[
+            (65, 67),
+            (64, 68),
+            (64, 69),
+            (63, 70),
+            (63, 71),
+            (63, 72),
+            (62, 73),
+            (62, 74),
+            (61, 75),
+            (61, 76),
+            (60, 77),
+            (60, 78),
+        ],
+    ),
+    (
+        (108, 111),
+        (98, 103),
+        0.0,
+        &[
+            (107, 110),
+            (106, 109),
+            (105, 109),
+            (104, 108),
+            (103, 107),
+            (102, 106),
+            (101, 105),
+            (100, 105),
+            (99, 104),
+            (98, 103),
+            (97, 102),
+            (96, 101),
+        ],
+    ),
+    (
+        (64, 139),
+        (65, 143),
+        0.0,
+        &[
+            (64, 140),
+            (65, 141),
+            (65, 142),
+            (65, 143),
+            (65, 144),
+            (66, 145),
+            (66, 146),
+            (66, 147),
+            (66, 148),
+            (67, 149),
+            (67, 150),
+            (67, 151),
+        ],
+    ),
+    (
+        (92, 78),
+        (78, 66),
+        0.0,
+        &[
+            (91, 77),
+            (90, 76),
+            (89, 75),
+            (88, 75),
+            (87, 74),
+            (86, 73),
+            (85, 72),
+            (84, 71),
+            (83, 70),
+            (82, 69),
+            (81, 69),
+            (80, 68),
+        ],
+    ),
+    (
+        (123, 128),
+        (128, 144),
+        15.0,
+        &[
+            (123, 129),
+            (123, 130),
+            (123, 131),
+            (123, 132),
+            (123, 133),
+            (123, 134),
+            (123, 135),
+            (123, 136),
+            (123, 137),
+            (123, 138),
+            (123, 139),
+            (123, 140),
+        ],
+    ),
+    (
+        (129, 130),
+        (140, 142),
+        -30.0,
+        &[
+            (130, 130),
+            (131, 131),
+            (132, 131),
+            (133, 131),
+            (134, 132),
+            (135, 132),
+            (136, 132),
+            (137, 133),
+            (138, 133),
+            (139, 133),
+            (140, 133),
+            (141, 134),
+        ],
+    ),
+    (
+        (71, 91),
+        (73, 88),
+        0.0,
+        &[
+            (72, 90),
+            (72, 89),
+            (73, 88),
+            (74, 87),
+            (74, 86),
+            (75, 85),
+            (76, 84),
+            (76, 83),
+            (77, 82),
+            (78, 81),
+            (78, 80),
+            (79, 79),
+        ],
+    ),
+    (
+        (102, 131),
+        (103, 138),
+        0.0,
+        &[
+            (102, 132),
+            (102, 133),
+            (102, 134),
+            (103, 135),
+            (103, 136),
+            (103, 137),
+            (103, 138),
+            (103, 139),
+            (103, 140),
+            (103, 141),
+            (104, 142),
+            (104, 143),
+        ],
+    ),
+    (
+        (69, 114),
+        (63, 129),
+        0.0,
+        &[
+            (69, 115),
+            (68, 116),
+            (68, 117),
+            (67, 118),
+            (67, 119),
+            (67, 120),
+            (66, 121),
+            (66, 122),
+            (65, 123),
+            (65, 124),
+            (65, 125),
+            (64, 126),
+        ],
+    ),
+    (
+        (115, 102),
+        (111, 90),
+        15.0,
+        &[
+            (115, 101),
+            (115, 100),
+            (115, 99),
+            (115, 98),
+            (115, 97),
+            (115, 96),
+            (115, 95),
+            (115, 94),
+            (114, 93),
+            (114, 92),
+            (114, 91),
+            (114, 90),
+        ],
+    ),
+    (
+        (68, 134),
+        (63, 151),
+        -30.0,
+        &[
+            (68, 135),
+            (68, 136),
+            (69, 137),
+            (69, 138),
+            (69, 139),
+            (69, 140),
+            (70, 141),
+            (70, 142),
+            (70, 143),
+            (70, 144),
+            (71, 145),
+            (71, 146),
+        ],
+    ),
+    (
+        (107, 106),
+        (96, 112),
+        0.0,
+        &[
+            (106, 107),
+            (105, 107),
+            (104, 108),
+            (103, 108),
+            (102, 109),
+            (101, 109),
+            (100, 110),
+            (99, 110),
+            (98, 111),
+            (97, 111),
+            (96, 112),
+            (95, 113),
+        ],
+    ),
+    (
+        (139, 102),
+        (143, 85),
+        0.0,
+        &[
+            (139, 101),
+            (139, 100),
+            (140, 99),
+            (140, 98),
+            (140, 97),
+            (140, 96),
+            (141, 95),
+            (141, 94),
+            (141, 93),
+            (141, 92),
+            (142, 91),
+            (142, 90),
+        ],
+    ),
+    (
+        (78, 74),
+        (69, 77),
+        0.0,
+        &[
+            (77, 74),
+            (76, 75),
+            (75, 75),
+            (74, 75),
+            (73, 76),
+            (72, 76),
+            (71, 76),
+            (70, 77),
+            (69, 77),
+            (68, 77),
+            (67, 78),
+            (66, 78),
+        ],
+    ),
+    (
+        (98, 91),
+        (115, 86),
+        15.0,
+        &[
+            (99, 91),
+            (100, 91),
+            (101, 91),
+            (102, 91),
+            (103, 91),
+            (104, 91),
+            (105, 91),
+            (106, 91),
+            (107, 91),
+            (108, 91),
+            (109, 91),
+            (110, 91),
+        ],
+    ),
+    (
+        (134, 64),
+        (142, 71),
+        -30.0,
+        &[
+            (135, 64),
+            (136, 64),
+            (137, 65),
+            (138, 65),
+            (139, 65),
+            (140, 65),
+            (141, 65),
+            (142, 66),
+            (143, 66),
+            (144, 66),
+            (145, 66),
+            (146, 66),
+        ],
+    ),
+    (
+        (88, 108),
+        (84, 124),
+        0.0,
+        &[
+            (88, 109),
+            (88, 110),
+            (87, 111),
+            (87, 112),
+            (87, 113),
+            (87, 114),
+            (86, 115),
+            (86, 116),
+            (86, 117),
+            (86, 118),
+            (85, 119),
+            (85, 120),
+        ],
+    ),
+    (
+        (92, 94),
+        (78, 91),
+        0.0,
+        &[
+            (91, 94),
+            (90, 94),
+            (89, 93),
+            (88, 93),
+            (87, 93),
+            (86, 93),
+            (85, 92),
+            (84, 92),
+            (83, 92),
+            (82, 92),
+            (81, 92),
+            (80, 91),
+        ],
+    ),
+    (
+        (92, 90),
+        (74, 98),
+        0.0,
+        &[
+            (91, 90),
+            (90, 91),
+            (89, 91),
+            (88, 92),
+            (87, 92),
+            (86, 93),
+            (85, 93),
+            (84, 94),
+            (83, 94),
+            (82, 94),
+            (81, 95),
+            (80, 95),
+        ],
+    ),
+    (
+        (130, 133),
+        (130, 151),
+        15.0,
+        &[
+            (130, 134),
+            (129, 135),
+            (129, 136),
+            (129, 137),
+            (129, 138),
+            (128, 139),
+            (128, 140),
+            (128, 141),
+            (128, 142),
+            (127, 143),
+            (127, 144),
+            (127, 145),
+        ],
+    ),
+    (
+        (70, 83),
+        (71, 74),
+        -30.0,
+        &[
+            (70, 82),
+            (69, 81),
+            (69, 80),
+            (68, 79),
+            (68, 78),
+            (67, 77),
+            (67, 76),
+            (66, 75),
+            (66, 74),
+            (66, 73),
+            (65, 72),
+            (65, 71),
+        ],
+    ),
+    (
+        (136, 64),
+        (131, 55),
+        0.0,
+        &[
+            (135, 63),
+            (135, 62),
+            (134, 61),
+            (134, 60),
+            (133, 59),
+            (133, 58),
+            (132, 57),
+            (132, 56),
+            (131, 55),
+            (130, 54),
+            (130, 53),
+            (129, 52),
+        ],
+    ),
+    (
+        (77, 129),
+        (77, 123),
+        0.0,
+        &[
+            (77, 128),
+            (77, 127),
+            (77, 126),
+            (77, 125),
+            (77, 124),
+            (77, 123),
+            (77, 122),
+            (77, 121),
+            (77, 120),
+            (77, 119),
+            (77, 118),
+            (77, 117),
+        ],
+    ),
+    (
+        (122, 72),
+        (132, 90),
+        0.0,
+        &[
+            (123, 73),
+            (123, 74),
+            (124, 75),
+            (124, 76),
+            (125, 77),
+            (125, 78),
+            (126, 79),
+            (126, 80),
+            (127, 81),
+            (128, 82),
+            (128, 83),
+            (129, 84),
+        ],
+    ),
+    (
+        (81, 73),
+        (96, 79),
+        15.0,
+        &[
+            (82, 74),
+            (83, 74),
+            (84, 75),
+            (85, 76),
+            (86, 77),
+            (87, 77),
+            (88, 78),
+            (89, 79),
+            (90, 80),
+            (91, 80),
+            (92, 81),
+            (93, 82),
+        ],
+    ),
+    (
+        (110, 112),
+        (103, 123),
+        -30.0,
+        &[
+            (110, 113),
+            (110, 114),
+            (110, 115),
+            (110, 116),
+            (110, 117),
+            (110, 118),
+            (110, 119),
+            (110, 120),
+            (110, 121),
+            (110, 122),
+            (110, 123),
+            (109, 124),
+        ],
+    ),
+    (
+        (64, 110),
+        (76, 121),
+        0.0,
+        &[
+            (65, 111),
+            (66, 112),
+            (67, 113),
+            (68, 114),
+            (69, 115),
+            (70, 115),
+            (71, 116),
+            (72, 117),
+            (73, 118),
+            (74, 119),
+            (75, 120),
+            (76, 121),
+        ],
+    ),
+    (
+        (136, 104),
+        (133, 116),
+        0.0,
+        &[
+            (136, 105),
+            (136, 106),
+            (135, 107),
+            (135, 108),
+            (135, 109),
+            (135, 110),
+            (134, 111),
+            (134, 112),
+            (134, 113),
+            (134, 114),
+            (133, 115),
+            (133, 116),
+        ],
+    ),
+    (
+        (67, 94),
+        (70, 76),
+        0.0,
+        &[
+            (67, 93),
+            (67, 92),
+            (67, 91),
+            (68, 90),
+            (68, 89),
+            (68, 88),
+            (68, 87),
+            (68, 86),
+            (68, 85),
+            (69, 84),
+            (69, 83),
+            (69, 82),
+        ],
+    ),
+    (
+        (104, 92),
+        (101, 83),
+        15.0,
+        &[
+            (104, 91),
+            (104, 90),
+            (104, 89),
+            (104, 88),
+            (104, 87),
+            (104, 86),
+            (104, 85),
+            (104, 84),
+            (103, 83),
+            (103, 82),
+            (103, 81),
+            (103, 80),
+        ],
+    ),
+    (
+        (80, 130),
+        (84, 150),
+        -30.0,
+        &[
+            (81, 131),
+            (82, 132),
+            (83, 133),
+            (84, 134),
+            (84, 135),
+            (85, 136),
+            (86, 137),
+            (87, 138),
+            (88, 139),
+            (89, 140),
+            (90, 141),
+            (91, 142),
+        ],
+    ),
+    (
+        (136, 131),
+        (150, 118),
+        0.0,
+        &[
+            (137, 130),
+            (138, 129),
+            (139, 128),
+            (140, 127),
+            (141, 126),
+            (142, 125),
+            (143, 124),
+            (144, 124),
+            (145, 123),
+            (146, 122),
+            (147, 121),
+            (148, 120),
+        ],
+    ),
+    (
+        (136, 96),
+        (134, 96),
+        0.0,
+        &[
+            (135, 96),
+            (134, 96),
+            (133, 96),
+            (132, 96),
+            (131, 96),
+            (130, 96),
+            (129, 96),
+            (128, 96),
+            (127, 96),
+            (126, 96),
+            (125, 96),
+            (124, 96),
+        ],
+    ),
+    (
+        (65, 83),
+        (76, 100),
+        0.0,
+        &[
+            (66, 84),
+            (66, 85),
+            (67, 86),
+            (68, 87),
+            (68, 88),
+            (69, 89),
+            (70, 90),
+            (70, 91),
+            (71, 92),
+            (71, 93),
+            (72, 94),
+            (73, 95),
+        ],
+    ),
+    (
+        (136, 91),
+        (143, 81),
+        15.0,
+        &[
+            (137, 90),
+            (138, 89),
+            (139, 88),
+            (140, 88),
+            (141, 87),
+            (142, 86),
+            (143, 85),
+            (144, 84),
+            (145, 83),
+            (146, 83),
+            (147, 82),
+            (148, 81),
+        ],
+    ),
+    (
+        (133, 138),
+        (145, 138),
+        -30.0,
+        &[
+            (134, 137),
+            (135, 137),
+            (136, 136),
+            (137, 136),
+            (138, 135),
+            (139, 135),
+            (140, 134),
+            (141, 133),
+            (142, 133),
+            (143, 132),
+            (144, 132),
+            (145, 131),
+        ],
+    ),
+    (
+        (74, 128),
+        (87, 117),
+        0.0,
+        &[
+            (75, 127),
+            (76, 126),
+            (77, 125),
+            (78, 125),
+            (79, 124),
+            (80, 123),
+            (81, 122),
+            (82, 121),
+            (83, 120),
+            (84, 120),
+            (85, 119),
+            (86, 118),
+        ],
+    ),
+    (
+        (132, 85),
+        (139, 94),
+        0.0,
+        &[
+            (133, 86),
+            (134, 87),
+            (134, 88),
+            (135, 89),
+            (136, 90),
+            (137, 91),
+            (137, 92),
+            (138, 93),
+            (139, 94),
+            (140, 95),
+            (141, 96),
+            (141, 97),
+        ],
+    ),
+    (
+        (132, 104),
+        (135, 107),
+        0.0,
+        &[
+            (133, 105),
+            (134, 106),
+            (135, 107),
+            (136, 108),
+            (137, 109),
+            (138, 110),
+            (139, 111),
+            (140, 112),
+            (141, 113),
+            (142, 114),
+            (143, 115),
+            (144, 116),
+        ],
+    ),
+    (
+        (106, 90),
+        (118, 91),
+        15.0,
+        &[
+            (107, 90),
+            (108, 91),
+            (109, 91),
+            (110, 91),
+            (111, 92),
+            (112, 92),
+            (113, 93),
+            (114, 93),
+            (115, 93),
+            (116, 94),
+            (117, 94),
+            (118, 94),
+        ],
+    ),
+    (
+        (133, 90),
+        (141, 89),
+        -30.0,
+        &[
+            (134, 89),
+            (135, 88),
+            (136, 88),
+            (137, 87),
+            (138, 86),
+            (139, 85),
+            (140, 85),
+            (141, 84),
+            (142, 83),
+            (143, 82),
+            (144, 82),
+            (145, 81),
+        ],
+    ),
+    (
+        (61, 79),
+        (65, 64),
+        0.0,
+        &[
+            (61, 78),
+            (62, 77),
+            (62, 76),
+            (62, 75),
+            (62, 74),
+            (63, 73),
+            (63, 72),
+            (63, 71),
+            (63, 70),
+            (64, 69),
+            (64, 68),
+            (64, 67),
+        ],
+    ),
+    (
+        (61, 88),
+        (55, 83),
+        0.0,
+        &[
+            (60, 87),
+            (59, 86),
+            (58, 85),
+            (57, 85),
+            (56, 84),
+            (55, 83),
+            (54, 82),
+            (53, 81),
+            (52, 80),
+            (51, 80),
+            (50, 79),
+            (49, 78),
+        ],
+    ),
+    (
+        (65, 132),
+        (53, 127),
+        0.0,
+        &[
+            (64, 132),
+            (63, 131),
+            (62, 131),
+            (61, 130),
+            (60, 130),
+            (59, 129),
+            (58, 129),
+            (57, 129),
+            (56, 128),
+            (55, 128),
+            (54, 127),
+            (53, 127),
+        ],
+    ),
+    (
+        (100, 80),
+        (101, 60),
+        15.0,
+        &[
+            (100, 79),
+            (101, 78),
+            (101, 77),
+            (101, 76),
+            (102, 75),
+            (102, 74),
+            (102, 73),
+            (103, 72),
+            (103, 71),
+            (103, 70),
+            (104, 69),
+            (104, 68),
+        ],
+    ),
+    (
+        (118, 99),
+        (128, 96),
+        -30.0,
+        &[
+            (119, 98),
+            (120, 97),
+            (121, 96),
+            (122, 95),
+            (123, 94),
+            (124, 93),
+            (125, 92),
+            (126, 91),
+            (126, 90),
+            (127, 89),
+            (128, 88),
+            (129, 87),
+        ],
+    ),
+];
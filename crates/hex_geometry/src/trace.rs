@@ -0,0 +1,188 @@
+use super::{get_distance_hex, HexGrid, LineTracer};
+use primitives::Hex;
+
+/// Line trace over the map, port of engine's `TraceBullet`.
+pub struct TraceInput<'a, G: ?Sized> {
+    pub map: &'a G,
+    pub begin_hex: Hex,
+    pub end_hex: Hex,
+    /// Rotation of the line, in degrees.
+    pub angle: f32,
+    /// Steps to trace, `0` means up to `end_hex`.
+    pub dist: u32,
+    pub want_last_passed: bool,
+    pub last_passed_skip_critters: bool,
+}
+
+impl<'a, G: HexGrid + ?Sized> TraceInput<'a, G> {
+    pub fn new(map: &'a G, begin_hex: Hex, end_hex: Hex, angle: f32, dist: u32) -> Self {
+        Self {
+            map,
+            begin_hex,
+            end_hex,
+            angle,
+            dist,
+            want_last_passed: false,
+            last_passed_skip_critters: false,
+        }
+    }
+    pub fn trace(&self) -> TraceOutput {
+        let max_hex = self.map.max_hex();
+        let hexagonal = self.map.is_hexagonal();
+
+        let dist = if self.dist == 0 {
+            get_distance_hex(self.begin_hex, self.end_hex, hexagonal)
+        } else {
+            self.dist
+        };
+
+        let mut output = TraceOutput {
+            pre_block: self.begin_hex,
+            block: self.begin_hex,
+            last_passed: None,
+            is_full_trace: false,
+        };
+
+        let mut line_tracer = LineTracer::new(
+            self.begin_hex,
+            self.end_hex,
+            max_hex,
+            self.angle,
+            !hexagonal,
+        );
+
+        let mut last_passed_ok = false;
+        for i in 0.. {
+            if i >= dist {
+                output.is_full_trace = true;
+                break;
+            }
+
+            if hexagonal {
+                line_tracer.get_next_hex(&mut output.block);
+            } else {
+                line_tracer.get_next_square(&mut output.block);
+            }
+
+            if self.want_last_passed && !last_passed_ok {
+                if self.map.is_hex_passed(output.block) {
+                    output.last_passed = Some(output.block);
+                } else if !self.map.is_hex_critter(output.block) || !self.last_passed_skip_critters
+                {
+                    last_passed_ok = true;
+                }
+            }
+
+            if !self.map.is_hex_raked(output.block) {
+                break;
+            }
+
+            output.pre_block = output.block;
+        }
+        output
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceOutput {
+    /// Last raked hex of the trace.
+    pub pre_block: Hex,
+    /// Hex that stopped the trace, or its last hex.
+    pub block: Hex,
+    /// Last passed hex before the first non-passed one, if requested.
+    pub last_passed: Option<Hex>,
+    pub is_full_trace: bool,
+}
+
+/// Last hex a critter could walk to along the line, `begin_hex` if none.
+pub fn get_hex_in_path_wall<G: HexGrid + ?Sized>(
+    map: &G,
+    begin_hex: Hex,
+    end_hex: Hex,
+    angle: f32,
+    dist: u32,
+) -> Hex {
+    let mut trace = TraceInput::new(map, begin_hex, end_hex, angle, dist);
+    trace.want_last_passed = true;
+    let output = trace.trace();
+    output.last_passed.unwrap_or(begin_hex)
+}
+
+/// Last hex a bullet could fly to along the line.
+pub fn get_hex_in_path<G: HexGrid + ?Sized>(
+    map: &G,
+    begin_hex: Hex,
+    end_hex: Hex,
+    angle: f32,
+    dist: u32,
+) -> Hex {
+    let trace = TraceInput::new(map, begin_hex, end_hex, angle, dist);
+    let output = trace.trace();
+    output.pre_block
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashSet;
+
+    struct Walls {
+        max: Hex,
+        walls: HashSet<Hex>,
+        hexagonal: bool,
+    }
+
+    impl HexGrid for Walls {
+        fn max_hex(&self) -> Hex {
+            self.max
+        }
+        fn is_hex_passed(&self, hex: Hex) -> bool {
+            !self.walls.contains(&hex)
+        }
+        fn is_hex_raked(&self, hex: Hex) -> bool {
+            !self.walls.contains(&hex)
+        }
+        fn is_hex_critter(&self, _hex: Hex) -> bool {
+            false
+        }
+        fn is_hexagonal(&self) -> bool {
+            self.hexagonal
+        }
+    }
+
+    fn walls(hexagonal: bool) -> Walls {
+        // Vertical wall at x = 5
+        Walls {
+            max: Hex::new(20, 20),
+            walls: (0..20).map(|y| Hex::new(5, y)).collect(),
+            hexagonal,
+        }
+    }
+
+    #[test]
+    fn stops_before_wall() {
+        for &hexagonal in &[true, false] {
+            let map = walls(hexagonal);
+            let begin = Hex::new(1, 10);
+            let end = Hex::new(9, 10);
+            assert_eq!(get_hex_in_path(&map, begin, end, 0.0, 0), Hex::new(4, 10));
+            assert_eq!(
+                get_hex_in_path_wall(&map, begin, end, 0.0, 0),
+                Hex::new(4, 10)
+            );
+            let output = TraceInput::new(&map, begin, Hex::new(3, 10), 0.0, 0).trace();
+            assert!(output.is_full_trace);
+            assert_eq!(output.block, Hex::new(3, 10));
+        }
+    }
+
+    #[test]
+    fn wall_next_to_start() {
+        let map = walls(true);
+        let begin = Hex::new(4, 3);
+        assert_eq!(
+            get_hex_in_path_wall(&map, begin, Hex::new(8, 3), 0.0, 0),
+            begin
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use super::{move_hex_by_dir, RAD2DEG, SQRT3T2_FLOAT, SQRT3_FLOAT};
+use primitives::Hex;
+
+/// Walks hexes along a line, port of engine's `LineTracer`.
+#[derive(Debug, Clone)]
+pub struct LineTracer {
+    max: Hex,
+    x1: f32,
+    y1: f32,
+    /// Candidate directions of the next hex on hexagonal map.
+    dir1: u8,
+    dir2: u8,
+    dx: f32,
+    dy: f32,
+}
+
+impl LineTracer {
+    /// Line from `from` to `to` rotated by `angle` degrees.
+    pub fn new(from: Hex, to: Hex, max: Hex, angle: f32, is_square: bool) -> Self {
+        if is_square {
+            // Engine adds `angle` here as radians, while hexagonal branch treats it as degrees;
+            // we take degrees in both cases.
+            let dir = f32::atan2(to.y as f32 - from.y as f32, to.x as f32 - from.x as f32)
+                + angle / RAD2DEG;
+            let mut dx = f32::cos(dir);
+            let mut dy = f32::sin(dir);
+            if dx.abs() > dy.abs() {
+                dy /= dx.abs();
+                dx = if dx > 0.0 { 1.0 } else { -1.0 };
+            } else {
+                dx /= dy.abs();
+                dy = if dy > 0.0 { 1.0 } else { -1.0 };
+            }
+            LineTracer {
+                max,
+                x1: from.x as f32 + 0.5,
+                y1: from.y as f32 + 0.5,
+                dir1: 0,
+                dir2: 0,
+                dx,
+                dy,
+            }
+        } else {
+            const BIAS_FLOAT: f32 = 0.02;
+
+            let nx = 3.0 * (to.x as f32 - from.x as f32);
+            let ny = (to.y as f32 - from.y as f32) * SQRT3T2_FLOAT
+                - ((to.x & 1) as f32 - (from.x & 1) as f32) * SQRT3_FLOAT;
+            let mut dir = 180.0 + RAD2DEG * f32::atan2(ny, nx);
+            if angle != 0.0 {
+                dir = Self::normalize_dir(dir + angle);
+            }
+
+            let (dir1, dir2) = if (30.0..90.0).contains(&dir) {
+                (5, 0)
+            } else if (90.0..150.0).contains(&dir) {
+                (4, 5)
+            } else if (150.0..210.0).contains(&dir) {
+                (3, 4)
+            } else if (210.0..270.0).contains(&dir) {
+                (2, 3)
+            } else if (270.0..330.0).contains(&dir) {
+                (1, 2)
+            } else {
+                (0, 1)
+            };
+
+            let x1 = 3.0 * from.x as f32 + BIAS_FLOAT;
+            let y1 = SQRT3T2_FLOAT * from.y as f32 - SQRT3_FLOAT * (from.x & 1) as f32 + BIAS_FLOAT;
+            let mut x2 = 3.0 * to.x as f32 + BIAS_FLOAT + BIAS_FLOAT;
+            let mut y2 = SQRT3T2_FLOAT * to.y as f32 - SQRT3_FLOAT * (to.x & 1) as f32 + BIAS_FLOAT;
+
+            if angle != 0.0 {
+                x2 -= x1;
+                y2 -= y1;
+                let xp = f32::cos(angle / RAD2DEG) * x2 - f32::sin(angle / RAD2DEG) * y2;
+                let yp = f32::sin(angle / RAD2DEG) * x2 + f32::cos(angle / RAD2DEG) * y2;
+                x2 = x1 + xp;
+                y2 = y1 + yp;
+            }
+
+            LineTracer {
+                max,
+                x1,
+                y1,
+                dir1,
+                dir2,
+                dx: x2 - x1,
+                dy: y2 - y1,
+            }
+        }
+    }
+    fn dist(&self, hex: Hex) -> f32 {
+        f32::abs(
+            self.dx * (self.y1 - (SQRT3T2_FLOAT * hex.y as f32 - (hex.x & 1) as f32 * SQRT3_FLOAT))
+                - self.dy * (self.x1 - 3.0 * hex.x as f32),
+        )
+    }
+    /// Moves `hex` to the next hex of the line on hexagonal map, returns direction of the step.
+    pub fn get_next_hex(&self, hex: &mut Hex) -> u8 {
+        let t1 = move_hex_by_dir(*hex, self.dir1, self.max, true);
+        let t2 = move_hex_by_dir(*hex, self.dir2, self.max, true);
+        match (t1, t2) {
+            (Some(t1), Some(t2)) => {
+                let dist1 = self.dist(t1);
+                let dist2 = self.dist(t2);
+                if dist1 <= dist2 {
+                    // Left hand biased
+                    *hex = t1;
+                    self.dir1
+                } else {
+                    *hex = t2;
+                    self.dir2
+                }
+            }
+            (Some(t1), None) => {
+                *hex = t1;
+                self.dir1
+            }
+            (None, Some(t2)) => {
+                *hex = t2;
+                self.dir2
+            }
+            (None, None) => 0,
+        }
+    }
+    /// Moves `hex` to the next square of the line on square map, staying inside of the map.
+    pub fn get_next_square(&mut self, hex: &mut Hex) {
+        self.x1 += self.dx;
+        self.y1 += self.dy;
+        let clamp = |pos: f32, max: u16| (pos.floor().max(0.0) as u16).min(max.saturating_sub(1));
+        hex.x = clamp(self.x1, self.max.x);
+        hex.y = clamp(self.y1, self.max.y);
+    }
+    fn normalize_dir(dir: f32) -> f32 {
+        use std::ops::Rem;
+        if dir <= 0.0 {
+            360.0 - f32::rem(-dir, 360.0)
+        } else {
+            f32::rem(dir, 360.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::reference;
+
+    const MAX: Hex = Hex { x: 200, y: 200 };
+
+    fn check_traces(traces: &[reference::Trace], is_square: bool) {
+        for &((x1, y1), (x2, y2), angle, expected) in traces {
+            let (from, to) = (Hex::new(x1, y1), Hex::new(x2, y2));
+            let mut tracer = LineTracer::new(from, to, MAX, angle, is_square);
+            let mut hex = from;
+            let path: Vec<_> = expected
+                .iter()
+                .map(|_| {
+                    if is_square {
+                        tracer.get_next_square(&mut hex);
+                    } else {
+                        tracer.get_next_hex(&mut hex);
+                    }
+                    (hex.x, hex.y)
+                })
+                .collect();
+            assert_eq!(path, expected, "{:?} -> {:?}, angle {}", from, to, angle);
+        }
+    }
+
+    #[test]
+    fn engine_hex_traces() {
+        check_traces(reference::HEX_TRACES, false);
+    }
+
+    #[test]
+    fn engine_square_traces() {
+        check_traces(reference::SQUARE_TRACES, true);
+    }
+
+    #[test]
+    fn square_trace_stays_on_map() {
+        let max = Hex::new(10, 10);
+        let mut tracer = LineTracer::new(Hex::new(2, 2), Hex::new(0, 0), max, 0.0, true);
+        let mut hex = Hex::new(2, 2);
+        for _ in 0..5 {
+            tracer.get_next_square(&mut hex);
+        }
+        assert_eq!(hex, Hex::new(0, 0));
+    }
+}
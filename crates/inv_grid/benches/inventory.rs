@@ -0,0 +1,77 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use inv_grid::{Inventory, Size};
+
+/// Loot of a typical wastelander: weapons, armor, ammo, drugs and junk.
+fn loot() -> Vec<Size> {
+    let mut items = Vec::new();
+    let mut push =
+        |count, width, height| items.resize(items.len() + count, Size::new(width, height));
+    push(1, 2, 3); // armor
+    push(1, 1, 4); // rifle
+    push(1, 2, 2); // smg
+    push(2, 1, 2); // pistols
+    push(4, 2, 1); // ammo
+    push(6, 1, 1); // drugs
+    push(3, 1, 3); // tools
+    push(2, 2, 1); // food
+    items
+}
+
+fn fill(size: Size, items: &[Size], budget: u32) -> usize {
+    let mut inv = Inventory::new(size).with_rotation(true).with_budget(budget);
+    items
+        .iter()
+        .enumerate()
+        .filter(|&(key, &item)| inv.try_insert(key, item).is_ok())
+        .count()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let loot = loot();
+
+    c.bench_function("backpack 6x8 first fit", |b| {
+        b.iter(|| fill(black_box(Size::new(6, 8)), &loot, 0))
+    });
+    c.bench_function("backpack 6x8 exact", |b| {
+        b.iter(|| fill(black_box(Size::new(6, 8)), &loot, inv_grid::DEFAULT_BUDGET))
+    });
+    // Exactly as many cells as the loot takes, not a single spare one
+    let tight = Size::new(5, 9);
+    assert_eq!(fill(tight, &loot, inv_grid::DEFAULT_BUDGET), loot.len());
+    c.bench_function("tight bag 5x9 exact", |b| {
+        b.iter(|| fill(black_box(tight), &loot, inv_grid::DEFAULT_BUDGET))
+    });
+    // Four 2x3 items around a single cell, fits only as a pinwheel: greedy path gives up
+    // on the last item
+    let pinwheel = [Size::new(2, 3); 4]
+        .iter()
+        .copied()
+        .chain(Some(Size::new(1, 1)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        fill(Size::new(5, 5), &pinwheel, inv_grid::DEFAULT_BUDGET),
+        pinwheel.len()
+    );
+    c.bench_function("pinwheel 5x5 exact", |b| {
+        b.iter(|| {
+            fill(
+                black_box(Size::new(5, 5)),
+                &pinwheel,
+                inv_grid::DEFAULT_BUDGET,
+            )
+        })
+    });
+    c.bench_function("backpack 6x8 auto arrange", |b| {
+        let mut inv = Inventory::new(Size::new(6, 8)).with_rotation(true);
+        for (key, &item) in loot.iter().enumerate() {
+            let _ = inv.try_insert(key, item);
+        }
+        for key in (0..loot.len()).step_by(3) {
+            inv.remove(&key);
+        }
+        b.iter(|| black_box(inv.clone()).auto_arrange().is_some())
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
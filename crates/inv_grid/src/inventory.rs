@@ -0,0 +1,202 @@
+use super::{
+    solver::{exact, first_fit_decreasing, Cells},
+    Placement, Size,
+};
+use std::fmt;
+
+/// Steps of exhaustive search before giving up, enough for bags of a few dozens of items.
+pub const DEFAULT_BUDGET: u32 = 200_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InsertError {
+    /// Item with the same key is already inside.
+    Duplicate,
+    /// Item is bigger than the container.
+    TooLarge,
+    /// No arrangement with the item was found.
+    NoRoom,
+}
+
+impl fmt::Display for InsertError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InsertError::Duplicate => write!(f, "item is already in the inventory"),
+            InsertError::TooLarge => write!(f, "item is bigger than the inventory"),
+            InsertError::NoRoom => write!(f, "no room for the item"),
+        }
+    }
+}
+
+impl std::error::Error for InsertError {}
+
+/// Container grid with items placed by key, e.g. item id.
+#[derive(Debug, Clone)]
+pub struct Inventory<K> {
+    size: Size,
+    rotation: bool,
+    budget: u32,
+    cells: Cells,
+    items: Vec<(K, Placement)>,
+}
+
+impl<K: Clone + PartialEq> Inventory<K> {
+    pub fn new(size: Size) -> Self {
+        Inventory {
+            size,
+            rotation: false,
+            budget: DEFAULT_BUDGET,
+            cells: Cells::new(size),
+            items: Vec::new(),
+        }
+    }
+    /// Allow items to be turned by 90 degrees, off by default.
+    pub fn with_rotation(mut self, rotation: bool) -> Self {
+        self.rotation = rotation;
+        self
+    }
+    /// Steps of exhaustive search, `0` leaves greedy placement only.
+    pub fn with_budget(mut self, budget: u32) -> Self {
+        self.budget = budget;
+        self
+    }
+    pub fn size(&self) -> Size {
+        self.size
+    }
+    pub fn free_volume(&self) -> u16 {
+        let used: u16 = self.items.iter().map(|(_, p)| p.size.volume()).sum();
+        self.size.volume() - used
+    }
+    pub fn items(&self) -> &[(K, Placement)] {
+        &self.items
+    }
+    pub fn get(&self, key: &K) -> Option<Placement> {
+        self.items
+            .iter()
+            .find(|(item, _)| item == key)
+            .map(|&(_, placement)| placement)
+    }
+    /// Puts the item at the first free place; if there is none, rearranges the whole
+    /// inventory, so other items may be moved.
+    pub fn try_insert(&mut self, key: K, size: Size) -> Result<Placement, InsertError> {
+        if self.get(&key).is_some() {
+            return Err(InsertError::Duplicate);
+        }
+        if !(size.fits(self.size) || self.rotation && size.rotated().fits(self.size)) {
+            return Err(InsertError::TooLarge);
+        }
+        if size.volume() > self.free_volume() {
+            return Err(InsertError::NoRoom);
+        }
+        if let Some(placement) = self.cells.first_fit(size, self.rotation) {
+            self.cells.set(placement, true);
+            self.items.push((key, placement));
+            return Ok(placement);
+        }
+        let mut sizes = self.sizes();
+        sizes.push(size);
+        let placements = self.arrange(&sizes).ok_or(InsertError::NoRoom)?;
+        self.items.push((key, placements[placements.len() - 1]));
+        self.apply(&placements);
+        Ok(placements[placements.len() - 1])
+    }
+    pub fn remove(&mut self, key: &K) -> Option<Placement> {
+        let index = self.items.iter().position(|(item, _)| item == key)?;
+        let (_, placement) = self.items.remove(index);
+        self.cells.set(placement, false);
+        Some(placement)
+    }
+    /// Packs items from the top left corner, biggest first. Returns new layout, or `None`
+    /// if no arrangement was found and items are left as they were.
+    pub fn auto_arrange(&mut self) -> Option<&[(K, Placement)]> {
+        let placements = self.arrange(&self.sizes())?;
+        self.apply(&placements);
+        Some(&self.items)
+    }
+    /// Sizes of items, as they were inserted.
+    fn sizes(&self) -> Vec<Size> {
+        self.items.iter().map(|(_, p)| p.size).collect()
+    }
+    fn arrange(&self, sizes: &[Size]) -> Option<Vec<Placement>> {
+        first_fit_decreasing(self.size, sizes, self.rotation)
+            .or_else(|| exact(self.size, sizes, self.rotation, self.budget))
+    }
+    fn apply(&mut self, placements: &[Placement]) {
+        self.cells = Cells::new(self.size);
+        for ((_, placement), &new) in self.items.iter_mut().zip(placements) {
+            *placement = new;
+            self.cells.set(new, true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn insert_and_remove() {
+        let mut inv = Inventory::new(Size::new(4, 3));
+        let rifle = inv.try_insert(1, Size::new(4, 1)).unwrap();
+        assert_eq!(
+            rifle,
+            Placement {
+                x: 0,
+                y: 0,
+                size: Size::new(4, 1)
+            }
+        );
+        let armor = inv.try_insert(2, Size::new(2, 2)).unwrap();
+        assert_eq!((armor.x, armor.y), (0, 1));
+        assert_eq!(
+            inv.try_insert(2, Size::new(1, 1)),
+            Err(InsertError::Duplicate)
+        );
+        assert_eq!(
+            inv.try_insert(3, Size::new(5, 1)),
+            Err(InsertError::TooLarge)
+        );
+        assert_eq!(inv.try_insert(3, Size::new(3, 2)), Err(InsertError::NoRoom));
+        assert_eq!(inv.free_volume(), 4);
+
+        assert_eq!(inv.remove(&1), Some(rifle));
+        assert_eq!(inv.remove(&1), None);
+        assert_eq!(inv.free_volume(), 8);
+        assert_eq!(inv.get(&2), Some(armor));
+    }
+
+    #[test]
+    fn insert_rearranges() {
+        // Two 1x1 in the middle of the row leave no room for 2x1 without moving them
+        let mut inv = Inventory::new(Size::new(4, 1));
+        inv.try_insert(1, Size::new(1, 1)).unwrap();
+        inv.try_insert(2, Size::new(1, 1)).unwrap();
+        inv.try_insert(3, Size::new(1, 1)).unwrap();
+        inv.remove(&1);
+        inv.remove(&3);
+        inv.try_insert(4, Size::new(1, 1)).unwrap();
+        assert_eq!(inv.get(&4).map(|p| p.x), Some(0));
+        let placement = inv.try_insert(5, Size::new(2, 1)).unwrap();
+        assert_eq!(placement.size, Size::new(2, 1));
+        assert_eq!(inv.free_volume(), 0);
+
+        let mut xs: Vec<u8> = inv.items().iter().map(|(_, p)| p.x).collect();
+        xs.sort_unstable();
+        assert_eq!(xs, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn rotation_and_arrange() {
+        let mut inv = Inventory::new(Size::new(2, 4)).with_rotation(true);
+        let placement = inv.try_insert("rifle", Size::new(4, 1)).unwrap();
+        assert_eq!(placement.size, Size::new(1, 4));
+        inv.try_insert("stimpak", Size::new(1, 1)).unwrap();
+        inv.try_insert("ammo", Size::new(1, 2)).unwrap();
+        inv.remove(&"stimpak");
+
+        let items = inv.auto_arrange().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(inv.free_volume(), 2);
+        // Greedy path keeps the rest of the column free for another long item
+        assert_eq!(inv.try_insert("knife", Size::new(1, 2)).map(|p| p.x), Ok(1));
+    }
+}
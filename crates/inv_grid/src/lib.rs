@@ -1,184 +1,143 @@
-use pack::puzzle::{
-    piece::{Position, Template},
-    pieces::Bag,
-    solver::{solve, Target},
-};
-
-type Pos = (i8, i8, i8);
-
-#[derive(Clone)]
-struct Size {
-    width: u8,
-    height: u8,
+//! Tetris-style inventory: items occupy rectangles of cells in a container grid.
+use fo_engine_types::{Engine, ItemProtoLike};
+use std::{collections::HashMap, fmt, str::FromStr};
+
+mod inventory;
+mod solver;
+
+pub use inventory::{InsertError, Inventory, DEFAULT_BUDGET};
+
+/// Width and height in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Size {
+    pub width: u8,
+    pub height: u8,
 }
+
 impl Size {
-    fn volume(&self) -> u16 {
+    pub fn new(width: u8, height: u8) -> Self {
+        Size { width, height }
+    }
+    pub fn volume(self) -> u16 {
         self.width as u16 * self.height as u16
     }
-    fn brick(&self) -> Vec<Position<Pos>> {
-        let mut positions = Vec::with_capacity(self.volume() as usize);
-        for x in 0..self.width {
-            for y in 0..self.height {
-                positions.push(Position::new(x as i8, y as i8, 0));
-            }
-        }
-        positions
+    pub fn rotated(self) -> Self {
+        Size::new(self.height, self.width)
     }
-    fn target(&self) -> Target<Pos> {
-        Target::new(self.brick())
+    /// Nearly square size of at least `volume` cells, for protos without explicit size.
+    pub fn from_volume(volume: u32) -> Self {
+        let volume = volume.clamp(1, u8::MAX as u32 * u8::MAX as u32);
+        let mut width = 1;
+        while width * width < volume {
+            width += 1;
+        }
+        let height = volume.div_ceil(width);
+        Size::new(width as u8, height as u8)
     }
-    fn template(&self) -> Template<Pos> {
-        Template::new(self.brick())
+    fn fits(self, other: Size) -> bool {
+        self.width <= other.width && self.height <= other.height
     }
-    fn template_with_data<D>(&self, data: D) -> Template<Pos, D> {
-        Template::with_data(self.brick(), data)
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
     }
 }
 
-struct Inventory {
-    size: Size,
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSizeError(String);
+
+impl fmt::Display for ParseSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid size {:?}, expected `WxH`", self.0)
+    }
 }
 
-#[derive(Clone)]
-struct Item {
-    size: Size,
+impl std::error::Error for ParseSizeError {}
+
+/// Parses `WxH`, e.g. `2x3`.
+impl FromStr for Size {
+    type Err = ParseSizeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseSizeError(s.to_owned());
+        let (width, height) = s.split_once('x').ok_or_else(err)?;
+        let width: u8 = width.trim().parse().map_err(|_| err())?;
+        let height: u8 = height.trim().parse().map_err(|_| err())?;
+        if width == 0 || height == 0 {
+            return Err(err());
+        }
+        Ok(Size::new(width, height))
+    }
 }
 
-struct LooseBag {
-    items: Vec<Item>,
-    volume: u16,
-    free_volume: u16,
+/// Top left cell of the item and its size as placed, i.e. rotated if it was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub x: u8,
+    pub y: u8,
+    pub size: Size,
 }
-impl LooseBag {
-    fn new(volume: u16) -> Self {
-        Self {
-            volume,
-            items: Vec::new(),
-            free_volume: volume,
-        }
+
+impl Placement {
+    fn cells(self) -> impl Iterator<Item = (u8, u8)> {
+        let Placement { x, y, size } = self;
+        (y..y + size.height).flat_map(move |y| (x..x + size.width).map(move |x| (x, y)))
     }
-    fn push(&mut self, item: Item) -> Result<(), Item> {
-        match self.free_volume.checked_sub(item.size.volume()) {
-            Some(free_volume) => {
-                self.free_volume = free_volume;
-                self.items.push(item);
-                Ok(())
-            }
-            None => Err(item),
-        }
+}
+
+/// Inventory sizes of item protos: set explicitly, or derived from `Volume` of the proto.
+#[derive(Debug, Clone, Default)]
+pub struct ProtoSizes {
+    sizes: HashMap<u16, Size>,
+}
+
+impl ProtoSizes {
+    pub fn new() -> Self {
+        Self::default()
     }
-    fn push_many(&mut self, count: u8, item: Item) -> Result<(), ()> {
-        match self
-            .free_volume
-            .checked_sub(item.size.volume() * count as u16)
-        {
-            Some(free_volume) => {
-                self.free_volume = free_volume;
-                self.items
-                    .extend(std::iter::repeat(item).take(count as usize));
-                Ok(())
-            }
-            None => Err(()),
-        }
+    pub fn insert(&mut self, proto_id: u16, size: Size) -> Option<Size> {
+        self.sizes.insert(proto_id, size)
     }
-    fn used_volume(&self) -> u16 {
-        self.volume
-            .checked_sub(self.free_volume)
-            .expect("Used more volume then available")
+    pub fn size(&self, proto_id: u16, volume: u32) -> Size {
+        self.sizes
+            .get(&proto_id)
+            .copied()
+            .unwrap_or_else(|| Size::from_volume(volume))
     }
-    fn bag(&self) -> Bag<Pos, u8> {
-        Bag::new(
-            self.items
-                .iter()
-                .enumerate()
-                .map(|(i, item)| (1, item.size.template_with_data(i as u8)))
-                .collect(),
-        )
+    pub fn of_proto<E: Engine>(&self, proto: &E::ItemProto) -> Size {
+        self.size(proto.proto_id(), proto.volume())
     }
 }
 
 #[cfg(test)]
-mod tests {
+mod test {
     use super::*;
-    fn item(width: u8, height: u8) -> Item {
-        let size = Size { width, height };
-        Item { size }
-    }
-    fn set1() -> (Inventory, LooseBag) {
-        let inventory = Inventory {
-            size: Size {
-                width: 8,
-                height: 8,
-            },
-        };
-        let mut volume = inventory.size.volume();
-        let mut loose = LooseBag::new(volume);
-
-        loose.push_many(4, item(1, 1));
-        loose.push_many(3, item(1, 2));
-        loose.push_many(3, item(2, 1));
-        loose.push_many(3, item(2, 2));
+    use fo_engine_types::mock::{Mock, MockProto};
 
-        loose.push_many(2, item(3, 1));
-        loose.push_many(2, item(1, 3));
-        loose.push_many(2, item(3, 2));
-        loose.push_many(2, item(2, 3));
-        assert_eq!(loose.free_volume, 0);
-
-        (inventory, loose)
-    }
     #[test]
-    fn it_works() {
-        let (inventory, loose_bag) = set1();
-        let target = inventory.size.target();
-        for (i, item) in loose_bag.items.iter().enumerate() {
-            let brick = item.size.brick();
-            print_positions(&item.size, Some((i, &brick[..])).into_iter());
-            println!();
-        }
-        let bag = loose_bag.bag();
-        let mut solutions = 0;
-        let mut final_solution = None;
-        solve(&target, bag, &mut |solution| {
-            //println!("{}", solution);
-            if solution.pieces().len() == loose_bag.items.len() {
-                final_solution = Some(solution);
-                true
-            } else {
-                solutions += 1;
-                solutions > 100
-            }
-        });
-        dbg!(solutions);
-
-        //println!("{:?}", final_solution);
-        if let Some(final_solution) = final_solution {
-            print_positions(
-                &inventory.size,
-                final_solution
-                    .pieces()
-                    .iter()
-                    .map(|piece| piece.positions())
-                    .enumerate(),
-            );
-        }
-    }
-}
+    fn sizes() {
+        assert_eq!(Size::from_volume(0), Size::new(1, 1));
+        assert_eq!(Size::from_volume(4), Size::new(2, 2));
+        assert_eq!(Size::from_volume(5), Size::new(3, 2));
+        assert_eq!(Size::from_volume(10), Size::new(4, 3));
+        assert_eq!("2x3".parse(), Ok(Size::new(2, 3)));
+        assert!("2x0".parse::<Size>().is_err());
+        assert!("23".parse::<Size>().is_err());
 
-fn print_positions<'a>(size: &Size, iter: impl Iterator<Item = (usize, &'a [Position<Pos>])>) {
-    let mut grid = vec![0; size.volume() as usize];
-    for (i, piece) in iter {
-        for position in piece {
-            let base = position.base();
-            grid[base.0 as usize + base.1 as usize * size.width as usize] = i + 1;
-        }
-    }
-    let mut iter = grid.iter();
-    for y in 0..size.height {
-        for x in 0..size.width {
-            let i = iter.next().unwrap();
-            print!("{:03} ", i);
-        }
-        println!();
+        let mut sizes = ProtoSizes::new();
+        sizes.insert(10, Size::new(1, 4));
+        let rifle = MockProto {
+            proto_id: 10,
+            volume: 8,
+            ..Default::default()
+        };
+        let stimpak = MockProto {
+            proto_id: 40,
+            volume: 1,
+            ..Default::default()
+        };
+        assert_eq!(sizes.of_proto::<Mock>(&rifle), Size::new(1, 4));
+        assert_eq!(sizes.of_proto::<Mock>(&stimpak), Size::new(1, 1));
     }
 }
@@ -0,0 +1,272 @@
+//! Arrangement of items in a container: greedy first fit and exhaustive search.
+use super::{Placement, Size};
+use std::cmp::Reverse;
+
+/// Occupied cells of a container.
+#[derive(Debug, Clone)]
+pub(crate) struct Cells {
+    size: Size,
+    busy: Vec<bool>,
+}
+
+impl Cells {
+    pub(crate) fn new(size: Size) -> Self {
+        Cells {
+            size,
+            busy: vec![false; size.volume() as usize],
+        }
+    }
+    fn index(&self, x: u8, y: u8) -> usize {
+        y as usize * self.size.width as usize + x as usize
+    }
+    pub(crate) fn is_free(&self, placement: Placement) -> bool {
+        let Placement { x, y, size } = placement;
+        x as u16 + size.width as u16 <= self.size.width as u16
+            && y as u16 + size.height as u16 <= self.size.height as u16
+            && placement.cells().all(|(x, y)| !self.busy[self.index(x, y)])
+    }
+    pub(crate) fn set(&mut self, placement: Placement, busy: bool) {
+        for (x, y) in placement.cells() {
+            let index = self.index(x, y);
+            self.busy[index] = busy;
+        }
+    }
+    /// First free place for the item, scanning rows top to bottom.
+    pub(crate) fn first_fit(&self, size: Size, rotation: bool) -> Option<Placement> {
+        for y in 0..self.size.height {
+            for x in 0..self.size.width {
+                for size in orientations(size, rotation) {
+                    let placement = Placement { x, y, size };
+                    if self.is_free(placement) {
+                        return Some(placement);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+fn orientations(size: Size, rotation: bool) -> impl Iterator<Item = Size> {
+    let rotated = Some(size.rotated()).filter(|&rotated| rotation && rotated != size);
+    std::iter::once(size).chain(rotated)
+}
+
+/// Places items at first fit one by one, biggest first.
+pub(crate) fn first_fit_decreasing(
+    grid: Size,
+    items: &[Size],
+    rotation: bool,
+) -> Option<Vec<Placement>> {
+    let mut order: Vec<usize> = (0..items.len()).collect();
+    order.sort_by_key(|&i| Reverse((items[i].volume(), items[i].width.max(items[i].height))));
+    let mut cells = Cells::new(grid);
+    let mut placements = vec![None; items.len()];
+    for i in order {
+        let placement = cells.first_fit(items[i], rotation)?;
+        cells.set(placement, true);
+        placements[i] = Some(placement);
+    }
+    placements.into_iter().collect()
+}
+
+/// Items of the same size, interchangeable for the search.
+struct Group {
+    size: Size,
+    items: Vec<usize>,
+    placed: Vec<Placement>,
+}
+
+struct Search {
+    cells: Cells,
+    groups: Vec<Group>,
+    rotation: bool,
+    /// Cells that are allowed to stay empty.
+    slack: u32,
+    left: usize,
+    budget: u32,
+}
+
+impl Search {
+    fn run(&mut self, from: usize) -> bool {
+        if self.left == 0 {
+            return true;
+        }
+        if self.budget == 0 {
+            return false;
+        }
+        self.budget -= 1;
+        // Every cell before the first free one is taken, so it's the top left corner of the next item
+        let cell = match (from..self.cells.busy.len()).find(|&i| !self.cells.busy[i]) {
+            Some(cell) => cell,
+            None => return false,
+        };
+        let width = self.cells.size.width as usize;
+        let (x, y) = ((cell % width) as u8, (cell / width) as u8);
+        for group in 0..self.groups.len() {
+            if self.groups[group].placed.len() == self.groups[group].items.len() {
+                continue;
+            }
+            for size in orientations(self.groups[group].size, self.rotation) {
+                let placement = Placement { x, y, size };
+                if !self.cells.is_free(placement) {
+                    continue;
+                }
+                self.cells.set(placement, true);
+                self.groups[group].placed.push(placement);
+                self.left -= 1;
+                if self.run(cell + 1) {
+                    return true;
+                }
+                self.left += 1;
+                self.groups[group].placed.pop();
+                self.cells.set(placement, false);
+            }
+        }
+        if self.slack > 0 {
+            self.slack -= 1;
+            self.cells.busy[cell] = true;
+            if self.run(cell + 1) {
+                return true;
+            }
+            self.cells.busy[cell] = false;
+            self.slack += 1;
+        }
+        false
+    }
+}
+
+/// Exhaustive search of an arrangement, gives up after `budget` steps.
+pub(crate) fn exact(
+    grid: Size,
+    items: &[Size],
+    rotation: bool,
+    budget: u32,
+) -> Option<Vec<Placement>> {
+    let volume: u32 = items.iter().map(|size| size.volume() as u32).sum();
+    let slack = (grid.volume() as u32).checked_sub(volume)?;
+    let fits = |size: Size| size.fits(grid) || (rotation && size.rotated().fits(grid));
+    if !items.iter().all(|&size| fits(size)) {
+        return None;
+    }
+
+    let mut groups: Vec<Group> = Vec::new();
+    for (i, &size) in items.iter().enumerate() {
+        let size = if rotation && size.width < size.height {
+            size.rotated()
+        } else {
+            size
+        };
+        match groups.iter_mut().find(|group| group.size == size) {
+            Some(group) => group.items.push(i),
+            None => groups.push(Group {
+                size,
+                items: vec![i],
+                placed: Vec::new(),
+            }),
+        }
+    }
+    // Big items first, small ones fill the gaps
+    groups.sort_by_key(|group| Reverse(group.size.volume()));
+
+    let mut search = Search {
+        cells: Cells::new(grid),
+        groups,
+        rotation,
+        slack,
+        left: items.len(),
+        budget,
+    };
+    if !search.run(0) {
+        return None;
+    }
+    let mut placements = vec![None; items.len()];
+    for group in &search.groups {
+        for (&i, &placement) in group.items.iter().zip(&group.placed) {
+            placements[i] = Some(placement);
+        }
+    }
+    placements.into_iter().collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(grid: Size, items: &[Size], placements: &[Placement]) {
+        let mut cells = Cells::new(grid);
+        for (item, &placement) in items.iter().zip(placements) {
+            assert!(
+                placement.size == *item || placement.size == item.rotated(),
+                "{:?} {:?}",
+                item,
+                placement
+            );
+            assert!(cells.is_free(placement), "{:?} overlaps", placement);
+            cells.set(placement, true);
+        }
+    }
+
+    /// 8x8 container filled completely.
+    fn full_set() -> Vec<Size> {
+        let mut items = Vec::new();
+        let mut push =
+            |count, width, height| items.resize(items.len() + count, Size::new(width, height));
+        push(4, 1, 1);
+        push(3, 1, 2);
+        push(3, 2, 1);
+        push(3, 2, 2);
+        push(2, 3, 1);
+        push(2, 1, 3);
+        push(2, 3, 2);
+        push(2, 2, 3);
+        items
+    }
+
+    #[test]
+    fn exact_fills_container() {
+        let grid = Size::new(8, 8);
+        let items = full_set();
+        assert_eq!(
+            items.iter().map(|size| size.volume()).sum::<u16>(),
+            grid.volume()
+        );
+        let placements = exact(grid, &items, false, 1_000_000).expect("Arrangement");
+        check(grid, &items, &placements);
+        let placements = exact(grid, &items, true, 1_000_000).expect("Arrangement");
+        check(grid, &items, &placements);
+    }
+
+    #[test]
+    fn pinwheel() {
+        // Only arrangement is the pinwheel around the center cell, first fit misses it
+        let grid = Size::new(3, 3);
+        let items = [
+            Size::new(2, 1),
+            Size::new(2, 1),
+            Size::new(1, 2),
+            Size::new(1, 2),
+            Size::new(1, 1),
+        ];
+        assert!(first_fit_decreasing(grid, &items, false).is_none());
+        let placements = exact(grid, &items, false, 1_000_000).expect("Arrangement");
+        check(grid, &items, &placements);
+    }
+
+    #[test]
+    fn impossible() {
+        let grid = Size::new(3, 3);
+        // Fits by volume, but two 2x2 can't share 3x3
+        let items = [Size::new(2, 2), Size::new(2, 2)];
+        assert!(exact(grid, &items, true, 1_000_000).is_none());
+        assert!(exact(grid, &[Size::new(4, 1)], true, 1_000_000).is_none());
+        assert!(exact(Size::new(4, 1), &[Size::new(1, 4)], false, 1_000_000).is_none());
+        assert!(exact(Size::new(4, 1), &[Size::new(1, 4)], true, 1_000_000).is_some());
+    }
+
+    #[test]
+    fn budget() {
+        let grid = Size::new(8, 8);
+        assert!(exact(grid, &full_set(), false, 10).is_none());
+    }
+}
@@ -0,0 +1,375 @@
+//! GM commands of the bot. Logic doesn't touch Discord, data comes through `GmBackend`,
+//! so it's tested with a fake backend and without network.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Ord, Eq)]
+pub enum Rank {
+    Unknown,
+    Player,
+    GameMaster,
+    Developer,
+    Admin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Online,
+    Char,
+    Owner,
+    Chars,
+    Announce,
+}
+
+impl Command {
+    pub const ALL: [Command; 5] = [
+        Command::Online,
+        Command::Char,
+        Command::Owner,
+        Command::Chars,
+        Command::Announce,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::Online => "online",
+            Command::Char => "char",
+            Command::Owner => "owner",
+            Command::Chars => "chars",
+            Command::Announce => "announce",
+        }
+    }
+    /// Lowest rank allowed to run the command.
+    pub fn min_rank(self) -> Rank {
+        match self {
+            Command::Online => Rank::Player,
+            Command::Char | Command::Owner | Command::Chars => Rank::GameMaster,
+            Command::Announce => Rank::Admin,
+        }
+    }
+    pub fn usage(self) -> &'static str {
+        match self {
+            Command::Online => "~online",
+            Command::Char => "~char <name>",
+            Command::Owner => "~owner <name>",
+            Command::Chars => "~chars @user",
+            Command::Announce => "~announce <text>",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharSummary {
+    pub name: String,
+    pub id: u32,
+    pub level: i32,
+    pub hp: i32,
+    pub cond: String,
+    pub map_pid: u16,
+    /// Discord id of the owner.
+    pub owner: Option<u64>,
+}
+
+/// State of the game server as the web server sees it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerState {
+    Online {
+        players: u32,
+    },
+    Offline,
+    /// Not known yet, or being updated right now.
+    Unknown,
+}
+
+/// Game data needed by commands, implemented by the web server.
+pub trait GmBackend: Send + Sync {
+    /// Rank given by guild role with this name.
+    fn role_rank(&self, role: &str) -> Rank;
+    fn server_state(&self) -> ServerState;
+    fn character(&self, name: &str) -> Option<CharSummary>;
+    /// Names of characters owned by Discord user.
+    fn characters_of(&self, user_id: u64) -> Vec<String>;
+    /// Channel for `~announce`, `None` if announcements are disabled.
+    fn announce_channel(&self) -> Option<String>;
+}
+
+/// Discord side of a command call.
+#[derive(Debug, Clone, Default)]
+pub struct Invocation {
+    pub author_id: u64,
+    /// Names of the author's roles in the main guild.
+    pub role_names: Vec<String>,
+    /// Everything after the command name.
+    pub args: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    Reply(String),
+    Announce { channel: String, text: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    Forbidden(Rank),
+    Usage(&'static str),
+    CharNotFound(String),
+    NoAnnounceChannel,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Forbidden(rank) => write!(f, "Only for {:?} and above", rank),
+            CommandError::Usage(usage) => write!(f, "Usage: {}", usage),
+            CommandError::CharNotFound(name) => write!(f, "No character {:?}", name),
+            CommandError::NoAnnounceChannel => write!(f, "Announcements are disabled"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+fn mention(user_id: u64) -> String {
+    format!("<@{}>", user_id)
+}
+
+/// User id from mention (`<@123>`, `<@!123>`) or plain id.
+fn parse_user(arg: &str) -> Option<u64> {
+    let arg = arg.trim();
+    let id = match arg.strip_prefix("<@") {
+        Some(rest) => rest.strip_suffix('>')?.trim_start_matches('!'),
+        None => arg,
+    };
+    id.parse().ok()
+}
+
+pub fn execute(
+    backend: &dyn GmBackend,
+    command: Command,
+    invocation: &Invocation,
+) -> Result<Action, CommandError> {
+    let rank = invocation
+        .role_names
+        .iter()
+        .map(|role| backend.role_rank(role))
+        .max()
+        .unwrap_or(Rank::Unknown);
+    if rank < command.min_rank() {
+        return Err(CommandError::Forbidden(command.min_rank()));
+    }
+    let args = invocation.args.trim();
+    let required = || {
+        if args.is_empty() {
+            Err(CommandError::Usage(command.usage()))
+        } else {
+            Ok(args)
+        }
+    };
+    let character = |name: &str| {
+        backend
+            .character(name)
+            .ok_or_else(|| CommandError::CharNotFound(name.into()))
+    };
+    let text = match command {
+        Command::Online => match backend.server_state() {
+            ServerState::Online { players } => format!("Server is online, players: {}", players),
+            ServerState::Offline => "Server is offline".into(),
+            ServerState::Unknown => "Server state is unknown, try again later".into(),
+        },
+        Command::Char => {
+            let cr = character(required()?)?;
+            format!(
+                "{} (id {}): level {}, HP {}, {}, map {}, owner: {}",
+                cr.name,
+                cr.id,
+                cr.level,
+                cr.hp,
+                cr.cond,
+                cr.map_pid,
+                cr.owner.map_or_else(|| "none".into(), mention)
+            )
+        }
+        Command::Owner => {
+            let cr = character(required()?)?;
+            match cr.owner {
+                Some(owner) => format!("{} is owned by {}", cr.name, mention(owner)),
+                None => format!("{} has no owner", cr.name),
+            }
+        }
+        Command::Chars => {
+            let user_id = parse_user(required()?).ok_or(CommandError::Usage(command.usage()))?;
+            let chars = backend.characters_of(user_id);
+            if chars.is_empty() {
+                format!("{} owns no characters", mention(user_id))
+            } else {
+                format!("{} owns: {}", mention(user_id), chars.join(", "))
+            }
+        }
+        Command::Announce => {
+            let text = required()?;
+            let channel = backend
+                .announce_channel()
+                .ok_or(CommandError::NoAnnounceChannel)?;
+            return Ok(Action::Announce {
+                channel,
+                text: text.into(),
+            });
+        }
+    };
+    Ok(Action::Reply(text))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct FakeBackend {
+        state: ServerState,
+        chars: Vec<CharSummary>,
+    }
+
+    impl GmBackend for FakeBackend {
+        fn role_rank(&self, role: &str) -> Rank {
+            match role {
+                "Игрок" => Rank::Player,
+                "GM" => Rank::GameMaster,
+                "Adm" => Rank::Admin,
+                _ => Rank::Unknown,
+            }
+        }
+        fn server_state(&self) -> ServerState {
+            self.state
+        }
+        fn character(&self, name: &str) -> Option<CharSummary> {
+            self.chars.iter().find(|cr| cr.name == name).cloned()
+        }
+        fn characters_of(&self, user_id: u64) -> Vec<String> {
+            self.chars
+                .iter()
+                .filter(|cr| cr.owner == Some(user_id))
+                .map(|cr| cr.name.clone())
+                .collect()
+        }
+        fn announce_channel(&self) -> Option<String> {
+            Some("news".into())
+        }
+    }
+
+    fn backend() -> FakeBackend {
+        let char = |name: &str, id, owner| CharSummary {
+            name: name.into(),
+            id,
+            level: 3,
+            hp: 50,
+            cond: "ALIVE".into(),
+            map_pid: 42,
+            owner,
+        };
+        FakeBackend {
+            state: ServerState::Online { players: 7 },
+            chars: vec![
+                char("Вася", 1, Some(100)),
+                char("Петя", 2, Some(100)),
+                char("Npc", 3, None),
+            ],
+        }
+    }
+
+    fn call(role: &str, command: Command, args: &str) -> Result<Action, CommandError> {
+        let invocation = Invocation {
+            author_id: 1,
+            role_names: vec!["@everyone".into(), role.into()],
+            args: args.into(),
+        };
+        execute(&backend(), command, &invocation)
+    }
+
+    fn reply(text: &str) -> Result<Action, CommandError> {
+        Ok(Action::Reply(text.into()))
+    }
+
+    #[test]
+    fn ranks() {
+        assert_eq!(
+            call("Игрок", Command::Online, ""),
+            reply("Server is online, players: 7")
+        );
+        assert_eq!(
+            call("", Command::Online, ""),
+            Err(CommandError::Forbidden(Rank::Player))
+        );
+        assert_eq!(
+            call("Игрок", Command::Char, "Вася"),
+            Err(CommandError::Forbidden(Rank::GameMaster))
+        );
+        assert_eq!(
+            call("GM", Command::Announce, "Event!"),
+            Err(CommandError::Forbidden(Rank::Admin))
+        );
+    }
+
+    #[test]
+    fn server_state() {
+        let invocation = Invocation {
+            role_names: vec!["Игрок".into()],
+            ..Default::default()
+        };
+        let state = |state| {
+            let backend = FakeBackend { state, ..backend() };
+            execute(&backend, Command::Online, &invocation)
+        };
+        assert_eq!(state(ServerState::Offline), reply("Server is offline"));
+        assert_eq!(
+            state(ServerState::Unknown),
+            reply("Server state is unknown, try again later")
+        );
+    }
+
+    #[test]
+    fn characters() {
+        assert_eq!(
+            call("GM", Command::Char, " Вася "),
+            reply("Вася (id 1): level 3, HP 50, ALIVE, map 42, owner: <@100>")
+        );
+        assert_eq!(
+            call("GM", Command::Char, "Коля"),
+            Err(CommandError::CharNotFound("Коля".into()))
+        );
+        assert_eq!(
+            call("GM", Command::Char, ""),
+            Err(CommandError::Usage("~char <name>"))
+        );
+        assert_eq!(call("GM", Command::Owner, "Npc"), reply("Npc has no owner"));
+        assert_eq!(
+            call("GM", Command::Owner, "Петя"),
+            reply("Петя is owned by <@100>")
+        );
+    }
+
+    #[test]
+    fn owned_characters() {
+        assert_eq!(
+            call("GM", Command::Chars, "<@!100>"),
+            reply("<@100> owns: Вася, Петя")
+        );
+        assert_eq!(
+            call("GM", Command::Chars, "200"),
+            reply("<@200> owns no characters")
+        );
+        assert_eq!(
+            call("GM", Command::Chars, "<@bob>"),
+            Err(CommandError::Usage("~chars @user"))
+        );
+    }
+
+    #[test]
+    fn announce() {
+        assert_eq!(
+            call("Adm", Command::Announce, "Server restart in 5 minutes"),
+            Ok(Action::Announce {
+                channel: "news".into(),
+                text: "Server restart in 5 minutes".into()
+            })
+        );
+    }
+}
@@ -1,25 +1,34 @@
+pub mod commands;
+
+use commands::{Action, Command, GmBackend, Invocation};
 use parking_lot::{RwLock, RwLockReadGuard};
 pub use serenity::{
     self,
     model::guild::{Guild, Role},
 };
-use serenity::{client::bridge::gateway::GatewayIntents, model::guild::Member};
 use serenity::{
+    cache::Cache,
     client::{bridge::gateway::ShardManager, Client},
     framework::standard::{
         macros::{command, group, hook},
-        CommandError, CommandResult, DispatchError, StandardFramework,
+        Args, CommandError, CommandResult, DispatchError, StandardFramework,
     },
-    model::prelude::{Message, UserId},
-    prelude::{Context, EventHandler, Mutex, TypeMapKey},
+    http::Http,
+    model::prelude::{ChannelId, Message, UserId},
+    prelude::{Context, EventHandler, Mutex, TypeMap, TypeMapKey},
     CacheAndHttp,
 };
+use serenity::{client::bridge::gateway::GatewayIntents, model::guild::Member};
 use std::{collections::HashMap, env, future::Future, sync::Arc, thread::JoinHandle};
 
 #[group]
 #[commands(private)]
 struct General;
 
+#[group]
+#[commands(online, char_info, owner, chars, announce)]
+struct Gm;
+
 struct Handler;
 
 impl EventHandler for Handler {}
@@ -29,11 +38,19 @@ impl TypeMapKey for MainGuild {
     type Value = u64;
 }
 
+struct Backend;
+impl TypeMapKey for Backend {
+    type Value = Arc<dyn GmBackend>;
+}
+
 #[derive(Clone)]
 pub struct MrHandy {
     pub cache_and_http: Arc<CacheAndHttp>,
     pub shard_manager: Arc<Mutex<ShardManager>>,
     pub main_guild_id: u64,
+    data: Arc<serenity::prelude::RwLock<TypeMap>>,
+    /// Ids of main guild channels by name, resolved on the first message.
+    channel_ids: Arc<RwLock<HashMap<String, ChannelId>>>,
 }
 
 impl MrHandy {
@@ -77,24 +94,34 @@ impl MrHandy {
     }
 
     pub async fn send_message(&self, channel: String, text: String) -> Result<(), Error> {
-        let channel_id = self
-            .with_guild(move |guild| {
-                let guild = guild.ok_or(Error::NoMainGuild)?;
-                let channel = guild
-                    .channels
-                    .values()
-                    .find(|ch| &ch.name == &channel)
-                    .ok_or_else(|| Error::ChannelNotFound(channel))?;
-                Ok(channel.id)
-            })
-            .await?;
-        let _ = channel_id
-            .say(&self.cache_and_http.http, text)
-            .await
-            .map_err(Error::Serenity)?;
+        let channel_id = self.channel_id(channel).await?;
+        if let Err(err) = channel_id.say(&self.cache_and_http.http, text).await {
+            // Channel may have been recreated, look it up by name next time
+            self.channel_ids.write().retain(|_, id| *id != channel_id);
+            return Err(Error::Serenity(err));
+        }
         Ok(())
     }
 
+    async fn channel_id(&self, channel: String) -> Result<ChannelId, Error> {
+        if let Some(&id) = self.channel_ids.read().get(&channel) {
+            return Ok(id);
+        }
+        let id = find_channel(
+            &self.cache_and_http.cache,
+            self.main_guild_id,
+            channel.clone(),
+        )
+        .await?;
+        self.channel_ids.write().insert(channel, id);
+        Ok(id)
+    }
+
+    /// Enables GM commands, they answer with an error until backend is set.
+    pub async fn set_commands_backend(&self, backend: Arc<dyn GmBackend>) {
+        self.data.write().await.insert::<Backend>(backend);
+    }
+
     pub fn get_roles<O, F: Fn(&Role) -> O>(guild: &Guild, member: &Member, fun: F) -> Vec<O> {
         member
             .roles
@@ -164,6 +191,7 @@ pub enum ConditionColor {
     Red,
 }
 
+#[derive(Debug)]
 pub enum Error {
     NoMainGuild,
     ChannelNotFound(String),
@@ -204,6 +232,7 @@ pub async fn init(token: &str, main_guild_id: u64) -> (MrHandy, Client) {
         .configure(|c| c.prefix("~")) // set the bot's prefix to "~"
         .on_dispatch_error(dispatch_error_hook)
         .group(&GENERAL_GROUP)
+        .group(&GM_GROUP)
         .after(after_hook);
     let client = Client::builder(token)
         .intents(GatewayIntents::all())
@@ -211,28 +240,137 @@ pub async fn init(token: &str, main_guild_id: u64) -> (MrHandy, Client) {
         .framework(framework)
         .await
         .expect("Error creating client");
-    /*{
+    {
         let mut data = client.data.write().await;
         data.insert::<MainGuild>(main_guild_id);
-    }*/
+    }
     let cache_and_http = Arc::clone(&client.cache_and_http);
     let shard_manager = Arc::clone(&client.shard_manager);
+    let data = Arc::clone(&client.data);
 
     (
         MrHandy {
             cache_and_http,
             shard_manager,
             main_guild_id,
+            data,
+            channel_ids: Default::default(),
         },
         client,
     )
 }
 
+async fn find_channel(cache: &Cache, guild_id: u64, channel: String) -> Result<ChannelId, Error> {
+    cache
+        .guild_field(guild_id, move |guild| {
+            let channel = guild
+                .channels
+                .values()
+                .find(|ch| &ch.name == &channel)
+                .ok_or_else(|| Error::ChannelNotFound(channel))?;
+            Ok(channel.id)
+        })
+        .await
+        .unwrap_or(Err(Error::NoMainGuild))
+}
+
+async fn send_to_channel(
+    cache: &Cache,
+    http: &Http,
+    guild_id: u64,
+    channel: String,
+    text: String,
+) -> Result<(), Error> {
+    let channel_id = find_channel(cache, guild_id, channel).await?;
+    let _ = channel_id.say(http, text).await.map_err(Error::Serenity)?;
+    Ok(())
+}
+
 #[command]
 async fn private(ctx: &Context, msg: &Message) -> CommandResult {
     msg.author.dm(ctx, |msg| msg.content(":eyes:")).await?;
     Ok(())
 }
+
+/// Runs GM command with roles of the author in the main guild and answers in the same channel.
+async fn run_gm_command(
+    ctx: &Context,
+    msg: &Message,
+    command: Command,
+    args: Args,
+) -> CommandResult {
+    let (backend, main_guild_id) = {
+        let data = ctx.data.read().await;
+        let main_guild_id = *data.get::<MainGuild>().ok_or("MainGuild isn't set")?;
+        let backend = data.get::<Backend>().cloned();
+        (backend, main_guild_id)
+    };
+    let backend = match backend {
+        Some(backend) => backend,
+        None => {
+            msg.reply(ctx, "Not ready yet").await?;
+            return Ok(());
+        }
+    };
+    let author = msg.author.id;
+    let role_names = ctx
+        .cache
+        .guild_field(main_guild_id, |guild| {
+            guild
+                .members
+                .get(&author)
+                .map(|member| MrHandy::get_roles(guild, member, |role| role.name.clone()))
+                .unwrap_or_default()
+        })
+        .await
+        .unwrap_or_default();
+    let invocation = Invocation {
+        author_id: author.0,
+        role_names,
+        args: args.rest().to_owned(),
+    };
+    // Backend reads saves from disk, so it's kept off the runtime
+    let result =
+        tokio::task::spawn_blocking(move || commands::execute(&*backend, command, &invocation))
+            .await?;
+    let reply = match result {
+        Ok(Action::Reply(text)) => text,
+        Ok(Action::Announce { channel, text }) => {
+            match send_to_channel(&ctx.cache, &ctx.http, main_guild_id, channel, text).await {
+                Ok(()) => "Announced".into(),
+                Err(err) => format!("Can't announce: {:?}", err),
+            }
+        }
+        Err(err) => err.to_string(),
+    };
+    msg.reply(ctx, reply).await?;
+    Ok(())
+}
+
+#[command]
+async fn online(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run_gm_command(ctx, msg, Command::Online, args).await
+}
+
+#[command("char")]
+async fn char_info(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run_gm_command(ctx, msg, Command::Char, args).await
+}
+
+#[command]
+async fn owner(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run_gm_command(ctx, msg, Command::Owner, args).await
+}
+
+#[command]
+async fn chars(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run_gm_command(ctx, msg, Command::Chars, args).await
+}
+
+#[command]
+async fn announce(ctx: &Context, msg: &Message, args: Args) -> CommandResult {
+    run_gm_command(ctx, msg, Command::Announce, args).await
+}
 /*
 #[command]
 fn ping(ctx: &mut Context, msg: &Message) -> CommandResult {
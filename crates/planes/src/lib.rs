@@ -1,33 +1,48 @@
+//! Layout of the world map: planes of existence with patches of locations on them.
 use euclid::{Point2D, Rect, Size2D};
+use serde::{Deserialize, Serialize};
+use std::{fmt, path::Path};
 
-struct PlaneSpace;
-type PlaneScalar = f64;
-type PlaneRect = Rect<PlaneScalar, PlaneSpace>;
-type PlaneSize = Size2D<PlaneScalar, PlaneSpace>;
-type PlanePoint = Point2D<PlaneScalar, PlaneSpace>;
+mod svg;
 
-#[derive(Debug)]
-struct MultiPlane {
-    planes: Vec<Plane>,
+pub struct PlaneSpace;
+pub type PlaneScalar = f64;
+pub type PlaneRect = Rect<PlaneScalar, PlaneSpace>;
+pub type PlaneSize = Size2D<PlaneScalar, PlaneSpace>;
+pub type PlanePoint = Point2D<PlaneScalar, PlaneSpace>;
+
+/// Cells of the patch grid.
+pub type GridSize = Size2D<u16, ()>;
+pub type GridPoint = Point2D<u16, ()>;
+pub type GridRect = Rect<u16, ()>;
+
+/// Patches closer than that are considered touching.
+const EPSILON: PlaneScalar = 1e-6;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct MultiPlane {
+    pub planes: Vec<Plane>,
 }
 
 // as in "plane of existence"
-#[derive(Debug)]
-struct Plane {
-    name: String,
-    size: PlaneSize,
-    patches: Vec<Patch>,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Plane {
+    pub name: String,
+    pub size: PlaneSize,
+    pub patches: Vec<Patch>,
 }
 
 // as in "patches of land"
-#[derive(Debug, Default)]
-struct Location {
-    proto: u16,
-    id: Option<u32>,
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Location {
+    pub proto: u16,
+    /// Id of the location once it's created in game.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u32>,
 }
 
 impl Location {
-    fn with_proto(proto: u16) -> Self {
+    pub fn with_proto(proto: u16) -> Self {
         Self {
             proto,
             ..Default::default()
@@ -35,58 +50,283 @@ impl Location {
     }
 }
 
-#[derive(Debug)]
-struct Patch {
-    geometry: Geometry,
-    locations: Locations,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Patch {
+    pub geometry: Geometry,
+    pub locations: Locations,
 }
 
-#[derive(Debug)]
-enum Locations {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Locations {
     Single(Location),
+    /// Locations of arbitrary rectangles of cells.
     Grid(Grid<Cell>),
+    /// Location per cell, row by row.
     UniformGrid(Grid<Option<Location>>),
 }
 
 impl Locations {
-    fn uniform_grid(width: u16, cells: Vec<Option<Location>>) -> Self {
-        let height = (cells.len() as u16 + width - 1) / width;
+    /// Zero width is taken as one, cells that don't fit in `u16::MAX` rows are dropped.
+    pub fn uniform_grid(width: u16, mut cells: Vec<Option<Location>>) -> Self {
+        let width = width.max(1);
+        let height = cells.len().div_ceil(width as usize).min(u16::MAX as usize);
+        cells.truncate(height * width as usize);
         Locations::UniformGrid(Grid {
-            size: (width, height).into(),
+            size: (width, height as u16).into(),
             cells,
         })
     }
+    /// Uniform grid of location protos, `0` is an empty cell.
+    pub fn uniform_grid_of_protos(width: u16, protos: &[u16]) -> Self {
+        let cells = protos
+            .iter()
+            .map(|&proto| {
+                Some(proto)
+                    .filter(|&proto| proto != 0)
+                    .map(Location::with_proto)
+            })
+            .collect();
+        Self::uniform_grid(width, cells)
+    }
+    pub fn grid_size(&self) -> GridSize {
+        match self {
+            Locations::Single(_) => (1, 1).into(),
+            Locations::Grid(grid) => grid.size,
+            Locations::UniformGrid(grid) => grid.size,
+        }
+    }
+    pub fn at(&self, point: GridPoint) -> Option<&Location> {
+        match self {
+            Locations::Single(location) => Some(location).filter(|_| point == GridPoint::zero()),
+            Locations::Grid(grid) => grid
+                .cells
+                .iter()
+                .find(|cell| cell.rect.contains(point))
+                .map(|cell| &cell.location),
+            Locations::UniformGrid(grid) => {
+                if point.x >= grid.size.width {
+                    return None;
+                }
+                let index = point.y as usize * grid.size.width as usize + point.x as usize;
+                grid.cells.get(index)?.as_ref()
+            }
+        }
+    }
+    /// Locations with cells they take.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = (GridRect, &Location)> + '_> {
+        match self {
+            Locations::Single(location) => Box::new(std::iter::once((
+                GridRect::new(GridPoint::zero(), (1, 1).into()),
+                location,
+            ))),
+            Locations::Grid(grid) => {
+                Box::new(grid.cells.iter().map(|cell| (cell.rect, &cell.location)))
+            }
+            Locations::UniformGrid(grid) => {
+                let width = grid.size.width.max(1) as usize;
+                Box::new(grid.cells.iter().enumerate().filter_map(move |(i, cell)| {
+                    let origin = ((i % width) as u16, (i / width) as u16).into();
+                    Some((GridRect::new(origin, (1, 1).into()), cell.as_ref()?))
+                }))
+            }
+        }
+    }
 }
 
-#[derive(Debug)]
-struct Geometry {
-    size: OuterSize,
-    position: PlanePoint,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Geometry {
+    pub size: OuterSize,
+    /// Top left corner of the patch.
+    pub position: PlanePoint,
 }
 
-#[derive(Debug)]
-struct Grid<C> {
-    size: Size2D<u16, ()>,
-    cells: Vec<C>,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Grid<C> {
+    pub size: GridSize,
+    pub cells: Vec<C>,
 }
 
-#[derive(Debug)]
-struct Cell {
-    location: Location,
-    rect: Rect<u16, ()>,
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub location: Location,
+    pub rect: GridRect,
 }
 
-#[derive(Debug)]
-enum OuterSize {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OuterSize {
+    /// Side of a grid cell, patch size follows the grid.
     Scale(PlaneScalar),
+    /// Size of the whole patch, cells are stretched to fill it.
     Border(PlaneSize),
 }
 
+impl Patch {
+    pub fn rect(&self) -> PlaneRect {
+        let size = match self.geometry.size {
+            OuterSize::Scale(scale) => {
+                let grid = self.locations.grid_size();
+                PlaneSize::new(grid.width as PlaneScalar, grid.height as PlaneScalar) * scale
+            }
+            OuterSize::Border(size) => size,
+        };
+        PlaneRect::new(self.geometry.position, size)
+    }
+    pub fn cell_size(&self) -> PlaneSize {
+        let grid = self.locations.grid_size();
+        let size = self.rect().size;
+        PlaneSize::new(
+            size.width / grid.width.max(1) as PlaneScalar,
+            size.height / grid.height.max(1) as PlaneScalar,
+        )
+    }
+    /// Plane rectangle of the cells.
+    pub fn plane_rect(&self, rect: GridRect) -> PlaneRect {
+        let cell = self.cell_size();
+        let origin = self.geometry.position
+            + PlaneSize::new(
+                rect.origin.x as PlaneScalar * cell.width,
+                rect.origin.y as PlaneScalar * cell.height,
+            );
+        let size = PlaneSize::new(
+            rect.size.width as PlaneScalar * cell.width,
+            rect.size.height as PlaneScalar * cell.height,
+        );
+        PlaneRect::new(origin, size)
+    }
+    /// Cell under the plane point, if it's inside of the patch.
+    pub fn cell_at(&self, point: PlanePoint) -> Option<GridPoint> {
+        if !self.rect().contains(point) {
+            return None;
+        }
+        let grid = self.locations.grid_size();
+        let cell = self.cell_size();
+        let offset = point - self.geometry.position;
+        // Rounding may push the point on the far edge into the next cell
+        let x = (offset.x / cell.width) as u16;
+        let y = (offset.y / cell.height) as u16;
+        Some(
+            (
+                x.min(grid.width.saturating_sub(1)),
+                y.min(grid.height.saturating_sub(1)),
+            )
+                .into(),
+        )
+    }
+    pub fn location_at(&self, point: PlanePoint) -> Option<&Location> {
+        self.locations.at(self.cell_at(point)?)
+    }
+    /// Locations with their plane rectangles.
+    pub fn locations(&self) -> impl Iterator<Item = (PlaneRect, &Location)> {
+        self.locations
+            .iter()
+            .map(move |(rect, location)| (self.plane_rect(rect), location))
+    }
+    /// Patches share a piece of border and don't overlap.
+    pub fn is_adjacent(&self, other: &Patch) -> bool {
+        let (a, b) = (self.rect(), other.rect());
+        let overlap = |a0: PlaneScalar, a1: PlaneScalar, b0: PlaneScalar, b1: PlaneScalar| {
+            a1.min(b1) - a0.max(b0)
+        };
+        let x = overlap(a.min_x(), a.max_x(), b.min_x(), b.max_x());
+        let y = overlap(a.min_y(), a.max_y(), b.min_y(), b.max_y());
+        (x.abs() <= EPSILON && y > EPSILON) || (y.abs() <= EPSILON && x > EPSILON)
+    }
+}
+
+impl Plane {
+    /// Topmost patch under the point, later patches are drawn over earlier ones.
+    pub fn patch_at(&self, point: PlanePoint) -> Option<(usize, &Patch)> {
+        self.patches
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, patch)| patch.rect().contains(point))
+    }
+    /// Location under the point, empty cell of the topmost patch hides patches below it.
+    pub fn location_at(&self, point: PlanePoint) -> Option<&Location> {
+        self.patch_at(point)?.1.location_at(point)
+    }
+    /// Indices of patches adjacent to the patch.
+    pub fn neighbors(&self, index: usize) -> impl Iterator<Item = usize> + '_ {
+        let patch = self.patches.get(index);
+        self.patches
+            .iter()
+            .enumerate()
+            .filter(move |&(i, other)| i != index && patch.is_some_and(|p| p.is_adjacent(other)))
+            .map(|(i, _)| i)
+    }
+    /// Pairs of adjacent patches, lower index first.
+    pub fn adjacency(&self) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        for (i, a) in self.patches.iter().enumerate() {
+            for (j, b) in self.patches.iter().enumerate().skip(i + 1) {
+                if a.is_adjacent(b) {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs
+    }
+    /// Overview of patches and locations, elements carry `data-*` attributes for scripts.
+    pub fn to_svg(&self) -> String {
+        svg::plane(self)
+    }
+}
+
+impl MultiPlane {
+    pub fn plane(&self, name: &str) -> Option<&Plane> {
+        self.planes.iter().find(|plane| plane.name == name)
+    }
+    pub fn from_ron(text: &str) -> Result<Self, PlanesError> {
+        ron::from_str(text).map_err(PlanesError::Parse)
+    }
+    pub fn to_ron(&self) -> Result<String, PlanesError> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(PlanesError::Serialize)
+    }
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PlanesError> {
+        let text = std::fs::read_to_string(path).map_err(PlanesError::Io)?;
+        Self::from_ron(&text)
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PlanesError> {
+        std::fs::write(path, self.to_ron()?).map_err(PlanesError::Io)
+    }
+}
+
+#[derive(Debug)]
+pub enum PlanesError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for PlanesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanesError::Io(err) => write!(f, "{}", err),
+            PlanesError::Parse(err) => write!(f, "{}", err),
+            PlanesError::Serialize(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for PlanesError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    #[test]
-    fn print_debug() {
+
+    #[rustfmt::skip]
+    const FORP_OVERWORLD: &[u16] = &[
+         0, 28,  0,  0,  0,
+         1,  2,  3,  4,  5,
+         6,  7,  8,  9, 10,
+        11, 12, 13, 14, 15,
+        16, 17, 18, 19, 20,
+        21, 22, 23, 24, 25,
+    ];
+
+    fn world() -> MultiPlane {
         let single = Patch {
             geometry: Geometry {
                 size: OuterSize::Border((100.0, 100.0).into()),
@@ -140,14 +380,64 @@ mod tests {
                 ],
             ),
         };
-        let world = MultiPlane {
+        MultiPlane {
             planes: vec![Plane {
                 name: "Overworld".into(),
                 size: (1_000_000.0, 1_000_000.0).into(),
-                patches: vec![single, grid],
+                patches: vec![single, grid, uniform_grid],
             }],
+        }
+    }
+
+    /// fo4rp overworld with a 2x1 location on the east and a single location on the south.
+    fn forp() -> Plane {
+        let overworld = Patch {
+            geometry: Geometry {
+                size: OuterSize::Scale(50.0),
+                position: (0.0, 0.0).into(),
+            },
+            locations: Locations::uniform_grid_of_protos(5, FORP_OVERWORLD),
+        };
+        let east = Patch {
+            geometry: Geometry {
+                size: OuterSize::Border((100.0, 100.0).into()),
+                position: (250.0, 50.0).into(),
+            },
+            locations: Locations::Grid(Grid {
+                size: (2, 2).into(),
+                cells: vec![Cell {
+                    location: Location::with_proto(30),
+                    rect: Rect::new((0, 0).into(), (2, 1).into()),
+                }],
+            }),
+        };
+        let south = Patch {
+            geometry: Geometry {
+                size: OuterSize::Scale(40.0),
+                position: (100.0, 300.0).into(),
+            },
+            locations: Locations::Single(Location {
+                proto: 40,
+                id: Some(7),
+            }),
         };
-        println!("{:?}", world);
+        let far = Patch {
+            geometry: Geometry {
+                size: OuterSize::Scale(10.0),
+                position: (1000.0, 1000.0).into(),
+            },
+            locations: Locations::Single(Location::with_proto(50)),
+        };
+        Plane {
+            name: "fo4rp".into(),
+            size: (2000.0, 2000.0).into(),
+            patches: vec![overworld, east, south, far],
+        }
+    }
+
+    #[test]
+    fn print_debug() {
+        println!("{:?}", world());
     }
 
     #[test]
@@ -166,15 +456,83 @@ mod tests {
                 }
             }
         }
+        let size = |width, cells: usize| match Locations::uniform_grid(width, vec![None; cells]) {
+            Locations::UniformGrid(grid) => (grid.size.width, grid.size.height, grid.cells.len()),
+            _ => unreachable!(),
+        };
+        assert_eq!(size(0, 3), (1, 3, 3));
+        assert_eq!(size(0, 0), (1, 0, 0));
+        assert_eq!(size(1, 70_000), (1, u16::MAX, u16::MAX as usize));
+        assert_eq!(size(300, 70_000), (300, 234, 70_000));
     }
-}
 
-#[rustfmt::skip]
-const FORP_OVERWORLD: &[u16] = &[
-     0, 28,  0,  0,  0,
-     1,  2,  3,  4,  5,
-     6,  7,  8,  9, 10, 
-    11, 12, 13, 14, 15,
-    16, 17, 18, 19, 20,
-    21, 22, 23, 24, 25,
-];
+    #[test]
+    fn hit_test() {
+        let plane = forp();
+        let proto_at = |x, y| plane.location_at((x, y).into()).map(|loc| loc.proto);
+        assert_eq!(plane.patches[0].rect().size, (250.0, 300.0).into());
+        assert_eq!(proto_at(0.0, 0.0), None);
+        assert_eq!(proto_at(75.0, 25.0), Some(28));
+        assert_eq!(proto_at(60.0, 60.0), Some(2));
+        assert_eq!(proto_at(249.9, 299.9), Some(25));
+        assert_eq!(proto_at(250.0, 60.0), Some(30));
+        assert_eq!(proto_at(349.0, 99.0), Some(30));
+        assert_eq!(proto_at(300.0, 120.0), None);
+        assert_eq!(proto_at(139.0, 339.0), Some(40));
+        assert_eq!(proto_at(140.0, 300.0), None);
+        assert_eq!(proto_at(-1.0, 0.0), None);
+        assert_eq!(
+            plane.patch_at((300.0, 120.0).into()).map(|(i, _)| i),
+            Some(1)
+        );
+
+        let world = world();
+        let overworld = world.plane("Overworld").unwrap();
+        let proto_at = |x, y| overworld.location_at((x, y).into()).map(|loc| loc.proto);
+        assert_eq!(proto_at(1050.0, 1050.0), Some(100));
+        assert_eq!(proto_at(2004.5, 1001.5), Some(10));
+        assert_eq!(proto_at(1000.5, 2001.5), Some(201));
+        assert_eq!(proto_at(1000.5, 2000.5), None);
+    }
+
+    #[test]
+    fn adjacency() {
+        let plane = forp();
+        assert_eq!(plane.adjacency(), vec![(0, 1), (0, 2)]);
+        assert_eq!(plane.neighbors(0).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(plane.neighbors(1).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(plane.neighbors(3).count(), 0);
+        assert_eq!(plane.neighbors(10).count(), 0);
+
+        // Touching by corner only isn't adjacency, overlapping isn't either
+        let mut corner = plane.patches[3].clone();
+        corner.geometry.position = (250.0, 300.0).into();
+        assert!(!plane.patches[0].is_adjacent(&corner));
+        corner.geometry.position = (240.0, 290.0).into();
+        assert!(!plane.patches[0].is_adjacent(&corner));
+    }
+
+    #[test]
+    fn ron_roundtrip() {
+        let world = MultiPlane {
+            planes: vec![world().planes.remove(0), forp()],
+        };
+        let text = world.to_ron().unwrap();
+        assert_eq!(MultiPlane::from_ron(&text).unwrap(), world);
+
+        let text = r#"(planes: [(
+            name: "Test",
+            size: (100.0, 100.0),
+            patches: [(
+                geometry: (size: Scale(10.0), position: (0.0, 0.0)),
+                locations: UniformGrid((size: (2, 1), cells: [None, Some((proto: 3))])),
+            )],
+        )])"#;
+        let world = MultiPlane::from_ron(text).unwrap();
+        assert_eq!(
+            world.planes[0].location_at((15.0, 5.0).into()),
+            Some(&Location::with_proto(3))
+        );
+        assert!(MultiPlane::from_ron("(planes: [(name: 1)])").is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! SVG overview of a plane.
+use super::{Plane, PlaneRect};
+use std::fmt::Write;
+
+const STYLE: &str = "\
+.patch > rect { fill: none; stroke: #555; stroke-dasharray: 4; vector-effect: non-scaling-stroke; }
+.location > rect { fill: #dca; stroke: #333; vector-effect: non-scaling-stroke; }
+.location > text { font-family: sans-serif; text-anchor: middle; dominant-baseline: central; }";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn rect_attrs(rect: PlaneRect) -> String {
+    format!(
+        r#"x="{}" y="{}" width="{}" height="{}""#,
+        rect.origin.x, rect.origin.y, rect.size.width, rect.size.height
+    )
+}
+
+/// Patches are `g.patch` with `data-patch` index, locations inside of them are `g.location`
+/// with `data-proto` and `data-id` if the location is created.
+pub(crate) fn plane(plane: &Plane) -> String {
+    // Plane is usually much bigger than the patches, fit the view to them
+    let view = plane
+        .patches
+        .iter()
+        .map(|patch| patch.rect())
+        .reduce(|a, b| a.union(&b))
+        .unwrap_or_else(|| PlaneRect::from_size(plane.size));
+
+    let mut svg = String::new();
+    // Writing to `String` never fails
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        view.origin.x, view.origin.y, view.size.width, view.size.height
+    );
+    let _ = writeln!(svg, "<title>{}</title>", escape(&plane.name));
+    let _ = writeln!(svg, "<style>\n{}\n</style>", STYLE);
+    for (index, patch) in plane.patches.iter().enumerate() {
+        let _ = writeln!(svg, r#"<g class="patch" data-patch="{}">"#, index);
+        let _ = writeln!(svg, "<rect {}/>", rect_attrs(patch.rect()));
+        for (rect, location) in patch.locations() {
+            let id = location
+                .id
+                .map(|id| format!(r#" data-id="{}""#, id))
+                .unwrap_or_default();
+            let center = rect.center();
+            let font = rect.size.width.min(rect.size.height) / 3.0;
+            let _ = writeln!(
+                svg,
+                r#"<g class="location" data-proto="{proto}"{id}><title>{proto}</title><rect {rect}/><text x="{x}" y="{y}" font-size="{font}">{proto}</text></g>"#,
+                proto = location.proto,
+                id = id,
+                rect = rect_attrs(rect),
+                x = center.x,
+                y = center.y,
+                font = font,
+            );
+        }
+        svg.push_str("</g>\n");
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Geometry, Location, Locations, OuterSize, Patch, Plane};
+
+    #[test]
+    fn overview() {
+        let plane = Plane {
+            name: "Den & Klamath".into(),
+            size: (1000.0, 1000.0).into(),
+            patches: vec![
+                Patch {
+                    geometry: Geometry {
+                        size: OuterSize::Scale(10.0),
+                        position: (100.0, 100.0).into(),
+                    },
+                    locations: Locations::uniform_grid_of_protos(2, &[0, 5]),
+                },
+                Patch {
+                    geometry: Geometry {
+                        size: OuterSize::Scale(10.0),
+                        position: (100.0, 110.0).into(),
+                    },
+                    locations: Locations::Single(Location {
+                        proto: 6,
+                        id: Some(42),
+                    }),
+                },
+            ],
+        };
+        let svg = plane.to_svg();
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="100 100 20 20">"#)
+        );
+        assert!(svg.contains("<title>Den &amp; Klamath</title>"));
+        assert!(svg.contains(r#"<g class="patch" data-patch="1">"#));
+        assert!(svg.contains(r#"<g class="location" data-proto="5"><title>5</title><rect x="110" y="100" width="10" height="10"/>"#));
+        assert!(svg.contains(r#"data-proto="6" data-id="42""#));
+        assert_eq!(svg.matches(r#"class="location""#).count(), 2);
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+}
@@ -42,7 +42,7 @@ impl<T: Validate> MaybeInvalid<T> {
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Hex {
     pub x: u16,
     pub y: u16,
@@ -5,14 +5,61 @@ pub mod server_dll_web {
     use super::*;
 
     pub const HANDSHAKE: u16 = 0xBABA;
-    pub const VERSION: u16 = 5;
+    pub const VERSION: u16 = 8;
+    /// Oldest peer version still accepted, bump only on breaking changes of existing messages.
+    pub const MIN_VERSION: u16 = 6;
     #[derive(Debug, Clone, Deserialize, Serialize)]
     pub enum ServerDllToWeb {
         PlayerConnected(u32),
         PlayerAuth(u32),
         Status(ServerStatus),
         DiscordSendMessage{channel: String, text: String},
+        Statistics(ServerStatistics),
+        GameEvent(GameEvent),
     }
+    impl ServerDllToWeb {
+        /// Oldest protocol version that knows this message, peers with older one can't decode it.
+        pub fn min_version(&self) -> u16 {
+            match self {
+                ServerDllToWeb::Statistics(_) => 7,
+                ServerDllToWeb::GameEvent(_) => 8,
+                _ => MIN_VERSION,
+            }
+        }
+    }
+    /// Something worth telling about in Discord, `id` and `name` are of the critter involved.
+    #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+    pub enum GameEvent {
+        PlayerLogin { id: u32, name: String },
+        PlayerLogout { id: u32, name: String },
+        Death { id: u32, name: String, killer: Option<String>, map_pid: u16 },
+        GmAction { id: u32, name: String, action: String },
+        ServerStart,
+        ServerStop,
+    }
+    impl GameEvent {
+        pub fn kind(&self) -> GameEventKind {
+            match self {
+                GameEvent::PlayerLogin { .. } => GameEventKind::Login,
+                GameEvent::PlayerLogout { .. } => GameEventKind::Logout,
+                GameEvent::Death { .. } => GameEventKind::Death,
+                GameEvent::GmAction { .. } => GameEventKind::GmAction,
+                GameEvent::ServerStart => GameEventKind::ServerStart,
+                GameEvent::ServerStop => GameEventKind::ServerStop,
+            }
+        }
+    }
+    #[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    #[serde(rename_all = "snake_case")]
+    pub enum GameEventKind {
+        Login,
+        Logout,
+        Death,
+        GmAction,
+        ServerStart,
+        ServerStop,
+    }
+
     #[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
     pub enum DayTime {
         Morning,
@@ -32,7 +79,7 @@ pub mod server_dll_web {
         }
     }
 
-    #[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+    #[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
     #[repr(C)]
     pub struct ServerStatistics
     {
@@ -74,7 +121,9 @@ pub mod client_dll_overlay {
     use super::*;
 
     pub const HANDSHAKE: u16 = 0xB00B;
-    pub const VERSION: u16 = 5;
+    pub const VERSION: u16 = 6;
+    /// Oldest peer version still accepted, bump only on breaking changes of existing messages.
+    pub const MIN_VERSION: u16 = 6;
 
     #[derive(Debug, Clone, Deserialize, Serialize)]
     pub enum OverlayToClientDll {
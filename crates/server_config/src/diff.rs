@@ -0,0 +1,106 @@
+use super::{value_text, ServerConfig};
+use std::collections::BTreeSet;
+use toml::Value;
+
+/// Paths of values that are never printed, only reported as changed.
+const SECRETS: &[&str] = &["bridge.secret"];
+
+/// Changed values as `path: old -> new` lines, secrets as `path: changed`.
+pub fn diff(old: &ServerConfig, new: &ServerConfig) -> Result<Vec<String>, toml::ser::Error> {
+    let old = Value::try_from(old)?;
+    let new = Value::try_from(new)?;
+    let mut diff = vec![];
+    value_diff("", Some(&old), Some(&new), &mut diff);
+    Ok(diff)
+}
+
+fn value_diff(path: &str, old: Option<&Value>, new: Option<&Value>, diff: &mut Vec<String>) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_owned()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match (old, new) {
+        (Some(Value::Table(old)), Some(Value::Table(new))) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                value_diff(&join(key), old.get(key), new.get(key), diff);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for i in 0..old.len().max(new.len()) {
+                let path = format!("{}[{}]", path, i);
+                value_diff(&path, old.get(i), new.get(i), diff);
+            }
+        }
+        (Some(Value::Table(_)), None) | (Some(Value::Array(_)), None) => {
+            diff.push(format!("{}: removed", path));
+        }
+        (None, Some(Value::Table(new))) => {
+            for (key, value) in new {
+                value_diff(&join(key), None, Some(value), diff);
+            }
+        }
+        (None, Some(Value::Array(new))) => {
+            for (i, value) in new.iter().enumerate() {
+                value_diff(&format!("{}[{}]", path, i), None, Some(value), diff);
+            }
+        }
+        (old, new) if old != new && SECRETS.contains(&path) => {
+            diff.push(format!("{}: changed", path));
+        }
+        (old, new) if old != new => {
+            diff.push(format!("{}: {} -> {}", path, show(old), show(new)));
+        }
+        _ => {}
+    }
+}
+
+fn show(value: Option<&Value>) -> String {
+    value.map(value_text).unwrap_or_else(|| "none".into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn changed_values() {
+        let old = ServerConfig::default();
+        let mut new = ServerConfig::default();
+        assert!(diff(&old, &new).unwrap().is_empty());
+        new.check_look.senses[1].wall_rate[4] = 0.5;
+        new.bridge.secret = Some("secret".into());
+        assert_eq!(
+            diff(&old, &new).unwrap(),
+            vec![
+                "bridge.secret: changed",
+                "check_look.senses[1].wall_rate[4]: 0.3 -> 0.5",
+            ]
+        );
+        assert!(diff(&new, &old)
+            .unwrap()
+            .iter()
+            .all(|line| !line.contains("secret\"")));
+        let removed = new.check_look.senses.pop().unwrap();
+        assert_eq!(
+            diff(&old, &new).unwrap().last().unwrap(),
+            "check_look.senses[1]: removed"
+        );
+        new.check_look.senses.push(removed);
+        new.check_look.senses.remove(0);
+        assert_eq!(
+            diff(&new, &old).unwrap()[1..4],
+            [
+                "check_look.senses[0].dir_rate[0]: 0.8 -> 1.0",
+                "check_look.senses[0].dir_rate[1]: 1.0 -> 0.8",
+                "check_look.senses[0].dir_rate[2]: 0.8 -> 0.5",
+            ]
+        );
+        assert!(diff(&new, &old)
+            .unwrap()
+            .contains(&"check_look.senses[1].npc.basic_bonus: none -> 25".to_string()));
+    }
+}
@@ -0,0 +1,259 @@
+use super::{value_text, ServerConfig};
+use toml::{value::Table, Value};
+
+struct Field {
+    /// Path of the key, without array indices.
+    path: &'static str,
+    doc: &'static str,
+    /// Commented out value, for optional keys that are absent by default.
+    example: Option<&'static str>,
+}
+
+const fn field(path: &'static str, doc: &'static str) -> Field {
+    Field {
+        path,
+        doc,
+        example: None,
+    }
+}
+
+const fn optional(path: &'static str, doc: &'static str, example: &'static str) -> Field {
+    Field {
+        path,
+        doc,
+        example: Some(example),
+    }
+}
+
+/// Every key of the config, in order of the dump.
+const FIELDS: &[Field] = &[
+    field(
+        "check_look",
+        "Who sees and hears whom, see `check_look_sim` to try it out",
+    ),
+    field(
+        "check_look.map_utility_start",
+        "Same as MAP_UTILITY_START from _maps.fos, grants invisibility between non-gms",
+    ),
+    field(
+        "check_look.npc_fast",
+        "Fast check for mobs: skips direction, moving and walls logic, uses just basic distance",
+    ),
+    field("check_look.npc_fast.enable", "Use fast check"),
+    field(
+        "check_look.npc_fast.sense_index",
+        "Index, starting from 0, of the sense for fast check",
+    ),
+    field(
+        "check_look.npc_fast.fast_from",
+        "Range start of mobs' ProtoIds, from which fast check kicks in",
+    ),
+    field("check_look.npc_fast.fast_to", "Range end of mobs' ProtoIds"),
+    field(
+        "check_look.senses",
+        "Senses, i.e. vision and hearing; opponent is noticed if any of them does.\n\
+         Basic distance is (basic_bonus + perception * basic_perception_rate),\n\
+         multiplied by direction and moving rates, and by wall rate if line of sight is blocked",
+    ),
+    field(
+        "check_look.senses.dir_rate",
+        "Rates by difference between own direction and direction towards the target, 0..=3",
+    ),
+    field(
+        "check_look.senses.wall_rate",
+        "Rates when line of sight is blocked by wall, by perception 1..=10; in 0.0..=1.0",
+    ),
+    field("check_look.senses.player", "Basic distance of players"),
+    field("check_look.senses.player.basic_bonus", "Distance bonus"),
+    field(
+        "check_look.senses.player.basic_perception_rate",
+        "Distance per point of perception",
+    ),
+    field("check_look.senses.npc", "Basic distance of npcs"),
+    field("check_look.senses.npc.basic_bonus", "Distance bonus"),
+    field(
+        "check_look.senses.npc.basic_perception_rate",
+        "Distance per point of perception",
+    ),
+    field(
+        "check_look.senses.self_moving",
+        "Rates by how oneself is moving",
+    ),
+    field("check_look.senses.self_moving.still", "Standing still"),
+    field(
+        "check_look.senses.self_moving.walking",
+        "Walking, engine doesn't tell it from standing yet",
+    ),
+    field("check_look.senses.self_moving.running", "Running"),
+    field(
+        "check_look.senses.target_moving",
+        "Rates by how the target is moving",
+    ),
+    field("check_look.senses.target_moving.still", "Standing still"),
+    field(
+        "check_look.senses.target_moving.walking",
+        "Walking, engine doesn't tell it from standing yet",
+    ),
+    field("check_look.senses.target_moving.running", "Running"),
+    field("bridge", "Connection to the web server"),
+    field("bridge.addr", "Address of the web server bridge"),
+    optional(
+        "bridge.secret",
+        "Shared secret, must match `bridge.secret` of the web server",
+        "\"\"",
+    ),
+    optional(
+        "bridge.tls",
+        "Encrypt connection, trust certificate(s) from `ca` PEM file",
+        "{ domain = \"localhost\", ca = \"bridge.pem\" }",
+    ),
+];
+
+fn find(path: &str) -> Option<&'static Field> {
+    FIELDS.iter().find(|field| field.path == path)
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_owned()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn comment(out: &mut String, doc: &str) {
+    for line in doc.lines() {
+        out.push_str("# ");
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+fn is_section(value: &Value) -> bool {
+    match value {
+        Value::Table(_) => true,
+        Value::Array(array) => array.first().map(Value::is_table).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Keys of the table in order of `FIELDS`, including absent optional ones.
+fn keys(path: &str, table: &Table) -> Vec<&'static str> {
+    let prefix = join(path, "");
+    FIELDS
+        .iter()
+        .filter_map(|field| {
+            let key = field.path.strip_prefix(prefix.as_str())?;
+            if key.contains('.') || !(table.contains_key(key) || field.example.is_some()) {
+                return None;
+            }
+            Some(key)
+        })
+        .collect()
+}
+
+fn table(out: &mut String, path: &str, table: &Table) {
+    let keys = keys(path, table);
+    for &key in &keys {
+        let field_path = join(path, key);
+        let field = find(&field_path).expect("Key from FIELDS");
+        match table.get(key) {
+            Some(value) if is_section(value) => {}
+            Some(value) => {
+                comment(out, field.doc);
+                out.push_str(&format!("{} = {}\n", key, value_text(value)));
+            }
+            None => {
+                comment(out, field.doc);
+                out.push_str(&format!("# {} = {}\n", key, field.example.unwrap_or("")));
+            }
+        }
+    }
+    for &key in &keys {
+        let field_path = join(path, key);
+        let doc = find(&field_path).expect("Key from FIELDS").doc;
+        match table.get(key) {
+            Some(Value::Table(sub)) => {
+                out.push('\n');
+                comment(out, doc);
+                out.push_str(&format!("[{}]\n", field_path));
+                self::table(out, &field_path, sub);
+            }
+            Some(value @ Value::Array(_)) if is_section(value) => {
+                for item in value.as_array().into_iter().flatten() {
+                    out.push('\n');
+                    comment(out, doc);
+                    out.push_str(&format!("[[{}]]\n", field_path));
+                    if let Value::Table(sub) = item {
+                        self::table(out, &field_path, sub);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Default config as TOML, with comment for every key.
+pub fn default_config_toml() -> String {
+    let value = Value::try_from(ServerConfig::default()).expect("Default config is serializable");
+    let mut out = String::from("# Default ServerConfig.toml, every key is optional\n");
+    if let Value::Table(root) = &value {
+        table(&mut out, "", root);
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Paths of all keys of the value, without array indices.
+    fn paths(path: &str, value: &Value, out: &mut Vec<String>) {
+        match value {
+            Value::Table(table) => {
+                for (key, value) in table {
+                    let path = join(path, key);
+                    paths(&path, value, out);
+                    out.push(path);
+                }
+            }
+            Value::Array(array) => {
+                for item in array {
+                    paths(path, item, out);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn every_field_documented() {
+        let value = Value::try_from(ServerConfig::default()).unwrap();
+        let mut all = vec![];
+        paths("", &value, &mut all);
+        for path in &all {
+            assert!(find(path).is_some(), "{} isn't documented", path);
+        }
+        for field in FIELDS {
+            assert!(
+                all.iter().any(|path| path == field.path) || field.example.is_some(),
+                "{} isn't in config",
+                field.path
+            );
+        }
+    }
+
+    #[test]
+    fn dump_is_default() {
+        let dump = default_config_toml();
+        let config = crate::parse(&dump).unwrap_or_else(|err| panic!("{}\n{}", err, dump));
+        assert!(crate::diff(&ServerConfig::default(), &config)
+            .unwrap()
+            .is_empty());
+        assert!(dump.contains("\n# secret = \"\"\n"));
+        let with_tls = dump.replace("\n# tls = ", "\ntls = ");
+        let config = crate::parse(&with_tls).unwrap();
+        assert_eq!(config.bridge.tls.unwrap().domain, "localhost");
+    }
+}
@@ -0,0 +1,195 @@
+//! `ServerConfig.toml` of the server dll: schema, strict parsing and validation.
+mod diff;
+mod dump;
+
+pub use check_look::config::{CheckLook, InvalidConfig};
+pub use diff::diff;
+pub use dump::default_config_toml;
+
+use serde::{Deserialize, Serialize};
+use std::{fmt, net::SocketAddr, path::Path, path::PathBuf};
+
+#[derive(Deserialize, Serialize, Default)]
+#[serde(default)]
+pub struct ServerConfig {
+    pub check_look: CheckLook,
+    #[serde(default)]
+    pub bridge: Bridge,
+}
+
+impl ServerConfig {
+    pub fn validate(&self) -> Result<(), InvalidConfig> {
+        self.check_look.validate()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Bridge {
+    pub addr: SocketAddr,
+    /// Shared secret, same as `bridge.secret` in the web server config
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub tls: Option<BridgeTls>,
+}
+impl Bridge {
+    fn defaul_addr() -> SocketAddr {
+        "127.0.0.1:33852".parse().unwrap()
+    }
+}
+impl Default for Bridge {
+    fn default() -> Self {
+        Self {
+            addr: Self::defaul_addr(),
+            secret: None,
+            tls: None,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct BridgeTls {
+    /// Domain name in the web server certificate
+    pub domain: String,
+    /// PEM file with trusted certificates, i.e. self-signed certificate of the web server
+    pub ca: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(std::io::Error),
+    Toml(toml::de::Error),
+    /// Paths of keys that aren't part of the config, most likely typos.
+    UnknownKeys(Vec<String>),
+    Invalid(InvalidConfig),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "{}", err),
+            ConfigError::Toml(err) => write!(f, "{}", err),
+            ConfigError::UnknownKeys(keys) => write!(f, "unknown keys: {}", keys.join(", ")),
+            ConfigError::Invalid(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Parses and validates the config, unknown keys are errors instead of being ignored.
+pub fn parse(text: &str) -> Result<ServerConfig, ConfigError> {
+    let (config, unknown) = parse_lenient(text)?;
+    if !unknown.is_empty() {
+        return Err(ConfigError::UnknownKeys(unknown));
+    }
+    Ok(config)
+}
+
+/// Parses and validates the config, paths of unknown keys are returned along with it.
+pub fn parse_lenient(text: &str) -> Result<(ServerConfig, Vec<String>), ConfigError> {
+    let mut unknown = vec![];
+    let mut de = toml::Deserializer::new(text);
+    let config: ServerConfig =
+        serde_ignored::deserialize(&mut de, |path| unknown.push(path.to_string()))
+            .map_err(ConfigError::Toml)?;
+    config.validate().map_err(ConfigError::Invalid)?;
+    Ok((config, unknown))
+}
+
+pub fn load(path: impl AsRef<Path>) -> Result<ServerConfig, ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+    parse(&text)
+}
+
+/// Same as `load`, but unknown keys are returned instead of failing.
+pub fn load_lenient(path: impl AsRef<Path>) -> Result<(ServerConfig, Vec<String>), ConfigError> {
+    let text = std::fs::read_to_string(path).map_err(ConfigError::Io)?;
+    parse_lenient(&text)
+}
+
+/// Value as it's written in config, floats are `f32`.
+fn value_text(value: &toml::Value) -> String {
+    match value {
+        toml::Value::Float(float) => format!("{:?}", *float as f32),
+        toml::Value::Array(array) => {
+            let items: Vec<_> = array.iter().map(value_text).collect();
+            format!("[{}]", items.join(", "))
+        }
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn config_examples() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../dll/server/config_examples");
+        let mut count = 0;
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension() != Some("toml".as_ref()) {
+                continue;
+            }
+            if let Err(err) = load(&path) {
+                panic!("{:?}: {}", path, err);
+            }
+            count += 1;
+        }
+        assert!(count >= 2);
+    }
+
+    #[test]
+    fn unknown_keys() {
+        let text = "
+[check_look.npc_fast]
+enabel = false
+
+[bridge]
+adr = \"127.0.0.1:1\"
+addr = \"127.0.0.1:1\"
+";
+        match parse(text) {
+            Err(ConfigError::UnknownKeys(keys)) => {
+                assert_eq!(keys, vec!["check_look.npc_fast.enabel", "bridge.adr"])
+            }
+            res => panic!("{:?}", res.map(|_| ())),
+        }
+        let (config, unknown) = parse_lenient(text).unwrap();
+        assert_eq!(unknown.len(), 2);
+        assert_eq!(config.bridge.addr, "127.0.0.1:1".parse().unwrap());
+    }
+
+    #[test]
+    fn invalid() {
+        let text = "
+[[check_look.senses]]
+player = { basic_bonus = 10, basic_perception_rate = 5 }
+npc = { basic_bonus = 10, basic_perception_rate = 5 }
+dir_rate = [1.0, 0.8, 0.5]
+";
+        match parse(text) {
+            Err(ConfigError::Toml(err)) => {
+                assert!(
+                    err.to_string().contains("check_look.senses.dir_rate"),
+                    "{}",
+                    err
+                )
+            }
+            res => panic!("{:?}", res.map(|_| ())),
+        }
+        let text = "
+[check_look.npc_fast]
+sense_index = 2
+";
+        match parse(text) {
+            Err(ConfigError::Invalid(InvalidConfig::FastSenseIndex {
+                index: 2,
+                senses: 2,
+            })) => {}
+            res => panic!("{:?}", res.map(|_| ())),
+        }
+    }
+}
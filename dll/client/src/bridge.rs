@@ -1,7 +1,7 @@
-use bridge::BridgeClientCell;
+use bridge::{BridgeClientCell, Policy, Protocol};
 use protocol::message::client_dll_overlay::{
     Avatar, Char, ClientDllToOverlay as MsgOut, Message, OverlayToClientDll as MsgIn, Position,
-    HANDSHAKE, VERSION,
+    HANDSHAKE, MIN_VERSION, VERSION,
 };
 use tnf_common::{
     defines::{CritterParam, FoDefines},
@@ -97,7 +97,14 @@ pub extern "C" fn connect_to_overlay(url: &ScriptString, web: &ScriptString) {
 
     let url = url.string();
     let addr: SocketAddr = url.parse().expect("malformed socket address");
-    BRIDGE.connect(addr, HANDSHAKE, VERSION);
+    let protocol = Protocol::new(HANDSHAKE, VERSION).min_version(MIN_VERSION);
+    // only the latest avatars and visibility matter, chat messages are kept in order
+    let policy = Policy::default().coalesce(|msg| match msg {
+        MsgOut::UpdateAvatars(_) => Some(0),
+        MsgOut::OverlayHide(_) => Some(1),
+        MsgOut::Message(_) => None,
+    });
+    BRIDGE.connect_with(addr, protocol, policy);
 }
 
 #[no_mangle]
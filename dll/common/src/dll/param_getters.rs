@@ -1,80 +1,52 @@
+//! Engine's `getParam_*` callbacks, values are computed by `fo_param_fo4rp` formulas.
 use crate::{
-    defines::CritterParam,
-    defines_fo4rp::param::Param as PAR,
     engine_types::{game_options::game_state, mutual::CritterMutual},
     primitives::*,
 };
+use fo_param_fo4rp::{
+    critter::{Critter, Time},
+    get,
+    raw_param::RawParam,
+};
+
+fn with_critter<R>(cr: &CritterMutual, f: impl FnOnce(&Critter) -> R) -> R {
+    // Game state is missing only before the engine is started: time is unknown then,
+    // so there is no night person bonus, and the world has just been created
+    let (time, full_second) = match game_state() {
+        Some(state) => {
+            let time = Time {
+                hour: state.Hour,
+                minute: state.Minute,
+                second: state.Second,
+            };
+            (Some(time), state.FullSecond)
+        }
+        None => (None, 0),
+    };
+    let mut critter = Critter::new(&cr.Params, time);
+    critter.full_second = full_second;
+    f(&critter)
+}
 
 #[no_mangle]
 #[allow(non_snake_case)]
 pub extern "C" fn getParam_Strength(cr: &CritterMutual, _: uint) -> int {
-    let mut val: int = cr.param(PAR::ST_STRENGTH) + cr.param(PAR::ST_STRENGTH_EXT);
-    if cr.param( PAR::PE_ADRENALINE_RUSH ) > 0 && getParam_Timeout( cr, PAR::TO_BATTLE as uint ) > 0 // Adrenaline rush perk
-        && cr.param( PAR::ST_CURRENT_HP ) <= (
-                cr.param( PAR::ST_MAX_LIFE ) +
-                cr.param( PAR::ST_STRENGTH ) +
-                cr.param( PAR::ST_ENDURANCE ) * 2
-            ) / 2
-    {
-        val += 1;
-    }
-    clamp(val, 1, 10)
+    with_critter(cr, |cr| get::param(cr, RawParam::ST_STRENGTH))
 }
 
 #[no_mangle]
 #[allow(non_snake_case)]
 pub extern "C" fn getParam_Perception(cr: &CritterMutual, _: uint) -> int {
-    let mut val: int = if cr.bparam(PAR::DAMAGE_EYE) {
-        1
-    } else {
-        cr.param(PAR::ST_PERCEPTION) + cr.param(PAR::ST_PERCEPTION_EXT)
-    };
-    if cr.bparam(PAR::TRAIT_NIGHT_PERSON) {
-        val += get_night_person_bonus();
-    }
-    clamp(val, 1, 10)
-}
-
-fn get_night_person_bonus() -> int {
-    if let Some(game_options) = game_state() {
-        let hour = game_options.Hour;
-        let minute = game_options.Minute;
-        if hour < 6 || hour > 18 {
-            1
-        } else if hour == 6 && minute == 0 {
-            1
-        } else if hour == 18 && minute > 0 {
-            1
-        } else {
-            -1
-        }
-    } else {
-        0
-    }
+    with_critter(cr, |cr| get::param(cr, RawParam::ST_PERCEPTION))
 }
 
 #[no_mangle]
 #[allow(non_snake_case)]
 pub extern "C" fn getParam_Timeout(cr: &CritterMutual, index: uint) -> int {
-    let full_second = game_state().map(|g| g.FullSecond).unwrap_or(0);
-    let param = cr.Params[index as usize] as uint;
-    if param > full_second {
-        (param - full_second) as int
-    } else {
-        0
-    }
+    with_critter(cr, |cr| get::timeout(cr, index))
 }
 
-fn clamp<T: std::cmp::Ord>(val: T, min: T, max: T) -> T
-where
-    T: Sized,
-{
-    assert!(min <= max);
-    if val < min {
-        min
-    } else if val > max {
-        max
-    } else {
-        val
-    }
+/// Breakdown of the param formula, `None` if the param is taken as is.
+pub fn explain(cr: &CritterMutual, index: uint) -> Option<String> {
+    with_critter(cr, |cr| get::explain(cr, index))
 }
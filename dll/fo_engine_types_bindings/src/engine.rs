@@ -13,12 +13,14 @@ pub mod r357 {
                 type Critter = critter::CritterCl;
                 type Param = u32;
             }
+            critter_proto_id = |cr| cr.Pid;
+            critter_is_npc = |cr| cr.Flags & fo_engine_types::CRITTER_NPC != 0;
         );
     }
 
     #[cfg(feature = "server")]
     pub mod server {
-        use crate::generated::r357::server::{critter, item};
+        use crate::generated::r357::server::{critter, item, map};
         use fo_engine_types::impl_engine;
 
         pub struct Server;
@@ -28,7 +30,10 @@ pub mod r357 {
                 type ItemProto = item::ProtoItem;
                 type Critter = critter::Critter;
                 type Param = u32;
+                type Map = map::Map;
             }
+            critter_proto_id = |cr| cr.ProtoId;
+            critter_is_npc = |cr| cr.CritterIsNpc;
         );
     }
 }
@@ -48,12 +53,14 @@ pub mod r476 {
                 type Critter = critter::CritterCl;
                 type Param = u32;
             }
+            critter_proto_id = |cr| cr.Pid;
+            critter_is_npc = |cr| cr.Flags & fo_engine_types::CRITTER_NPC != 0;
         );
     }
 
     #[cfg(feature = "server")]
     pub mod server {
-        use crate::generated::r476::server::{critter, item};
+        use crate::generated::r476::server::{critter, item, map};
         use fo_engine_types::impl_engine;
 
         pub struct Server;
@@ -63,7 +70,10 @@ pub mod r476 {
                 type ItemProto = item::ProtoItem;
                 type Critter = critter::Critter;
                 type Param = u32;
+                type Map = map::Map;
             }
+            critter_proto_id = |cr| cr.ProtoId;
+            critter_is_npc = |cr| cr.CritterIsNpc;
         );
     }
 }
@@ -1,17 +1,25 @@
 use lazy_static::lazy_static;
 
-pub use protocol::message::server_dll_web::{ServerDllToWeb as MsgOut, ServerWebToDll as MsgIn, ServerStatus, DayTime};
+use ::bridge::{
+    auth::{Auth, Role},
+    frame::{self, Frame},
+    BridgeError, BridgeMessage, Protocol, Session,
+};
+pub use protocol::message::server_dll_web::{ServerDllToWeb as MsgOut, ServerWebToDll as MsgIn, ServerStatus, ServerStatistics, DayTime, GameEvent};
+use protocol::message::server_dll_web::{HANDSHAKE, MIN_VERSION, VERSION};
 use std::{
-    io::{Read, Write},
+    convert::TryFrom,
+    io::{ErrorKind, Read, Write},
+    net::TcpStream,
     sync::{
         mpsc::{channel, Receiver, Sender},
         Arc, Mutex,
     },
     thread::{self, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
-use crate::config::config;
+use crate::config::{config, BridgeTls};
 
 //pub type MsgIn = message::ServerWebToDll;
 //pub type MsgOut = message::ServerDllToWeb;
@@ -71,46 +79,152 @@ impl Bridge {
             thread,
         }
     }
-    fn run(sender: Sender<MsgIn>, receiver: &mut Receiver<MsgOut>) -> bincode::Result<()> {
-        let stream = std::net::TcpStream::connect_timeout(
-            &config().bridge.addr,
-            Duration::from_millis(500),
-        )?;
-        //stream.set_read_timeout(Some(Duration::from_millis(500)));
-        //stream.set_write_timeout(Some(Duration::from_millis(500)));
-        let mut reader = stream;
-        let mut writer = reader.try_clone()?;
-
-        let read_thread = thread::spawn(move || -> bincode::Result<_> {
-            loop {
-                let msg: MsgIn = bincode::deserialize_from(&mut reader)?;
-                /*let mut buf = [0u8; std::mem::size_of::<MsgIn>()];
-                //assert_eq!(std::mem::align_of_val(&buf), std::mem::align_of::<MsgIn>());
-                reader.read_exact(&mut buf)?;
-                let msg: MsgIn = unsafe { std::mem::transmute(buf) };*/
-                if let Err(err) = sender.send(msg) {
-                    return Ok(err);
+    fn run(sender: Sender<MsgIn>, receiver: &mut Receiver<MsgOut>) -> Result<(), BridgeError> {
+        let (addr, secret, tls) = {
+            let config = config();
+            let bridge = &config.bridge;
+            let tls = bridge.tls.as_ref().map(tls_connection).transpose()?;
+            (bridge.addr, bridge.secret.clone(), tls)
+        };
+        let stream = TcpStream::connect_timeout(&addr, Duration::from_millis(500))
+            .map_err(BridgeError::Io)?;
+        stream.set_read_timeout(Some(POLL_TIMEOUT)).map_err(BridgeError::Io)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT)).map_err(BridgeError::Io)?;
+        stream.set_nodelay(true).map_err(BridgeError::Io)?;
+        match tls {
+            Some(tls) => Bridge::serve(
+                Connection::new(rustls::StreamOwned::new(tls, stream)),
+                secret.as_deref(),
+                sender,
+                receiver,
+            ),
+            None => Bridge::serve(Connection::new(stream), secret.as_deref(), sender, receiver),
+        }
+    }
+    fn serve<S: Read + Write>(
+        mut conn: Connection<S>,
+        secret: Option<&str>,
+        sender: Sender<MsgIn>,
+        receiver: &mut Receiver<MsgOut>,
+    ) -> Result<(), BridgeError> {
+        let session = Bridge::handshake(&mut conn, secret)?;
+        loop {
+            for msg in receiver.try_iter() {
+                if msg.min_version() > session.version {
+                    continue;
                 }
+                conn.write(&BridgeMessage::Data(msg))?;
             }
-        });
-        let write_res = (|| -> bincode::Result<_> {
-            loop {
-                match receiver.recv() {
-                    Ok(msg) => {
-                        bincode::serialize_into(&writer, &msg)?;
+            while let Some(msg) = conn.poll()? {
+                match msg {
+                    BridgeMessage::Data(msg) => {
+                        if let Err(err) = sender.send(msg) {
+                            eprintln!("Bridge receiver dropped: {:?}", err);
+                            return Ok(());
+                        }
                     }
-                    Err(err) => {
-                        return Ok(err);
+                    BridgeMessage::Hang => return Err(BridgeError::Hang),
+                    BridgeMessage::Challenge(_) => {
+                        eprintln!("Bridge: authentication failed: web server requires it, but `bridge.secret` isn't set");
+                        return Err(BridgeError::Unauthorized);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    fn handshake<S: Read + Write>(conn: &mut Connection<S>, secret: Option<&str>) -> Result<Session, BridgeError> {
+        let protocol = Protocol::new(HANDSHAKE, VERSION).min_version(MIN_VERSION);
+        conn.write(&BridgeMessage::Handshake(protocol.clone()))?;
+        let session = match conn.wait(HANDSHAKE_TIMEOUT)? {
+            BridgeMessage::Handshake(peer) => protocol.negotiate(&peer)?,
+            _ => return Err(BridgeError::NoHandshake),
+        };
+        eprintln!("Bridge: negotiated version {}", session.version);
+        if let Some(secret) = secret {
+            let mut auth = Auth::new(secret.as_bytes(), Role::Connector)?;
+            conn.write(&auth.challenge())?;
+            let res = conn.wait(HANDSHAKE_TIMEOUT).and_then(|challenge| {
+                let proof = auth.proof(challenge)?;
+                conn.write(&proof)?;
+                auth.verify(conn.wait(HANDSHAKE_TIMEOUT)?)
+            });
+            if let Err(err) = res {
+                eprintln!("Bridge: authentication failed, check `bridge.secret` on both sides: {:?}", err);
+                return Err(err);
+            }
+            eprintln!("Bridge: authenticated");
+        }
+        Ok(session)
+    }
+}
+
+const POLL_TIMEOUT: Duration = Duration::from_millis(50);
+const WRITE_TIMEOUT: Duration = Duration::from_millis(2000);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(2000);
+
+/// Reads frames from a stream with short read timeout, so one thread can both read and write.
+struct Connection<S> {
+    stream: S,
+    inbox: Vec<u8>,
+}
+
+impl<S: Read + Write> Connection<S> {
+    fn new(stream: S) -> Self {
+        Connection { stream, inbox: Vec::with_capacity(4096) }
+    }
+    fn write(&mut self, msg: &BridgeMessage<MsgOut>) -> Result<(), BridgeError> {
+        frame::write(&mut self.stream, msg)
+    }
+    /// Next message, if it arrives before read timeout.
+    fn poll(&mut self) -> Result<Option<BridgeMessage<MsgIn>>, BridgeError> {
+        loop {
+            if let Some(len) = frame::frame_len(&self.inbox)? {
+                let frame = frame::decode(frame::payload(&self.inbox[..len]));
+                self.inbox.drain(..len);
+                match frame? {
+                    Frame::Message(msg) => return Ok(Some(msg)),
+                    Frame::Unknown(tag) => {
+                        eprintln!("Bridge: skipping unknown frame {:?}", tag);
+                        continue;
                     }
-                };
+                }
+            }
+            let mut buf = [0u8; 4096];
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Err(BridgeError::Io(ErrorKind::UnexpectedEof.into())),
+                Ok(len) => self.inbox.extend_from_slice(&buf[..len]),
+                Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                    return Ok(None)
+                }
+                Err(err) => return Err(BridgeError::Io(err)),
             }
-        })();
-        eprintln!("Writer exited: {:?}, joining reader", write_res);
-        let read_res = read_thread.join().expect("error joining reader thread");
-        eprintln!("Reader exited: {:?}", read_res);
-        write_res?;
-        read_res?;
-
-        Ok(())
+        }
+    }
+    fn wait(&mut self, timeout: Duration) -> Result<BridgeMessage<MsgIn>, BridgeError> {
+        let deadline = Instant::now() + timeout;
+        while Instant::now() < deadline {
+            if let Some(msg) = self.poll()? {
+                return Ok(msg);
+            }
+        }
+        Err(BridgeError::Io(ErrorKind::TimedOut.into()))
+    }
+}
+
+fn tls_connection(tls: &BridgeTls) -> Result<rustls::ClientConnection, BridgeError> {
+    fn invalid<E: std::fmt::Debug>(err: E) -> BridgeError {
+        BridgeError::Io(std::io::Error::new(ErrorKind::InvalidData, format!("{:?}", err)))
+    }
+    let pem = std::fs::read(&tls.ca).map_err(BridgeError::Io)?;
+    let mut roots = rustls::RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut pem.as_slice()).map_err(BridgeError::Io)? {
+        roots.add(&rustls::Certificate(cert)).map_err(invalid)?;
     }
+    let config = rustls::ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let name = rustls::ServerName::try_from(tls.domain.as_str()).map_err(invalid)?;
+    rustls::ClientConnection::new(Arc::new(config), name).map_err(invalid)
 }
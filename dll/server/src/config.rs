@@ -1,213 +1,102 @@
 use arc_swap::ArcSwap;
-use derivative::Derivative;
 use once_cell::sync::Lazy;
-use serde::Deserialize;
-use std::{ops::Deref, sync::Arc, net::SocketAddr};
+use server_config::ConfigError;
+use std::{
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
 
-mod defaults {
-    pub const MAP_UTILITY_START: u16 = 92;
-    pub const NPC_FAST_FROM: u16 = 2200;
-    pub const NPC_FAST_TO: u16 = u16::max_value();
+pub use check_look::config::{CheckLook, CritterRates, MovingRates, NpcFast, SenseRates};
+pub use server_config::{Bridge, BridgeTls, ServerConfig};
 
-    pub const DEFAULT_BONUS: u32 = 10;
-    pub const DEFAULT_PERCEPTION: u32 = 5;
+const CONFIG_PATH: &str = "ServerConfig.toml";
+/// How often `main_loop` looks at modification time of the config file.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
 
-    pub const VIEW_BONUS: u32 = 10;
-    pub const VIEW_PERCEPTION: u32 = 5;
-
-    pub const HEAR_BONUS: u32 = 5;
-    pub const HEAR_BONUS_NPC: u32 = 25;
-    pub const HEAR_PERCEPTION: u32 = 2;
-
-    pub const DIR_RATE_DEFAULT: [f32; 4] = [1.0; 4];
-    //                                    0    1    2    3
-    pub const DIR_RATE_VIEW: [f32; 4] = [1.0, 0.8, 0.5, 0.4];
-    pub const DIR_RATE_HEAR: [f32; 4] = [0.8, 1.0, 0.8, 0.8];
-
-    pub const MOVING_DEFAULT: f32 = 1.0;
-    pub const MOVING_SELF_RUN: f32 = 0.8;
-    pub const MOVING_TARGET_RUN: f32 = 3.0;
-
-    pub const WALL_RATE_DEFAULT: [f32; 10] = [0.0; 10];
-    pub const WALL_RATE_HEAR: [f32; 10] = [
-        0.1, 0.1, 0.1, 0.1, // 1..=4
-        0.3, 0.3, 0.3, 0.3, // 5..=8
-        0.4, 0.4, // 9..=10
-    ];
-}
-use defaults::*;
+static CONFIG: Lazy<ArcSwap<ServerConfig>> =
+    Lazy::new(|| ArcSwap::new(Arc::new(load_config_or_default())));
 
-#[derive(Deserialize, Default)]
-#[serde(default)]
-pub struct ServerConfig {
-    pub check_look: CheckLook,
-    #[serde(default)]
-    pub bridge: Bridge,
-}
+/// Modification time of the config file, when it was read last time.
+static MODIFIED: Mutex<Option<SystemTime>> = Mutex::new(None);
 
-#[derive(Deserialize, Derivative)]
-#[derivative(Default)]
-#[serde(default)]
-pub struct CheckLook {
-    pub npc_fast: NpcFast,
-    #[derivative(Default(value = "vec![SenseRates::default_view(), SenseRates::default_hear()]"))]
-    pub senses: Vec<SenseRates>,
-    #[derivative(Default(value = "MAP_UTILITY_START"))]
-    pub map_utility_start: u16,
+pub fn config() -> impl Deref<Target = impl Deref<Target = ServerConfig>> {
+    CONFIG.load()
 }
 
-#[derive(Deserialize, Derivative)]
-#[derivative(Default)]
-#[serde(default)]
-pub struct SenseRates {
-    pub player: CritterRates,
-    pub npc: CritterRates,
-    #[derivative(Default(value = "DIR_RATE_DEFAULT"))]
-    pub dir_rate: [f32; 4],
-    pub self_moving: MovingRates,
-    pub target_moving: MovingRates,
-    #[derivative(Default(value = "WALL_RATE_DEFAULT"))]
-    pub wall_rate: [f32; 10],
-}
-impl SenseRates {
-    fn default_view() -> Self {
-        Self {
-            dir_rate: DIR_RATE_VIEW,
-            ..Default::default()
-        }
-    }
-    fn default_hear() -> Self {
-        Self {
-            player: CritterRates::default_hear_player(),
-            npc: CritterRates::default_hear_npc(),
-            dir_rate: DIR_RATE_HEAR,
-            self_moving: MovingRates::default_hear_self(),
-            target_moving: MovingRates::default_hear_target(),
-            wall_rate: WALL_RATE_HEAR,
-        }
+/// Loads the config again, old one is kept if new one is invalid.
+#[no_mangle]
+pub extern "C" fn reload_config() {
+    match load_config() {
+        Ok(new_config) => swap_config(new_config),
+        Err(err) => eprintln!("Error reloading server config, keeping old one: {}", err),
     }
 }
 
-/*
-#[derivative(Default(value = "VIEW_BONUS"))]
-    pub view_bonus: u32,
-    #[derivative(Default(value = "VIEW_PERCEPTION"))]
-    pub view_perception: u32,
-    #[derivative(Default(value = "HEAR_BONUS"))]
-    pub hear_bonus: u32,
-    #[derivative(Default(value = "HEAR_PERCEPTION"))]
-    pub hear_perception: u32,
-    */
-
-#[derive(Deserialize, Derivative)]
-#[derivative(Default)]
-#[serde(default)]
-pub struct NpcFast {
-    #[derivative(Default(value = "true"))]
-    pub enable: bool,
-    #[derivative(Default(value = "0"))]
-    pub sense_index: usize,
-    #[derivative(Default(value = "NPC_FAST_FROM"))]
-    pub fast_from: u16,
-    #[derivative(Default(value = "NPC_FAST_TO"))]
-    pub fast_to: u16,
-}
-
-#[derive(Deserialize, Derivative)]
-#[derivative(Default)]
-pub struct CritterRates {
-    #[derivative(Default(value = "DEFAULT_BONUS"))]
-    pub basic_bonus: u32,
-    #[derivative(Default(value = "DEFAULT_PERCEPTION"))]
-    pub basic_perception_rate: u32,
-}
-impl CritterRates {
-    const fn default_hear_npc() -> Self {
-        Self {
-            basic_bonus: HEAR_BONUS_NPC,
-            basic_perception_rate: HEAR_PERCEPTION,
+/// Reloads the config if its file was modified, called from `main_loop`.
+pub fn reload_if_modified() {
+    static LAST_POLL: Mutex<Option<Instant>> = Mutex::new(None);
+    {
+        let mut last_poll = LAST_POLL.lock().expect("poisoned config poll timer");
+        match *last_poll {
+            Some(time) if time.elapsed() < POLL_INTERVAL => return,
+            _ => *last_poll = Some(Instant::now()),
         }
     }
-    const fn default_hear_player() -> Self {
-        Self {
-            basic_bonus: HEAR_BONUS,
-            basic_perception_rate: HEAR_PERCEPTION,
-        }
+    // Make sure initial load is done, so it isn't mistaken for a modification
+    Lazy::force(&CONFIG);
+    let modified = file_modified();
+    if modified.is_some() && modified != *MODIFIED.lock().expect("poisoned config mtime") {
+        eprintln!("Server config modified, reloading");
+        reload_config();
     }
 }
 
-#[derive(Deserialize, Derivative)]
-#[derivative(Default)]
-pub struct MovingRates {
-    #[derivative(Default(value = "MOVING_DEFAULT"))]
-    pub still: f32,
-    #[derivative(Default(value = "MOVING_DEFAULT"))]
-    pub walking: f32,
-    #[derivative(Default(value = "MOVING_DEFAULT"))]
-    pub running: f32,
-}
-impl MovingRates {
-    fn default_hear_self() -> Self {
-        MovingRates {
-            running: MOVING_SELF_RUN,
-            ..Default::default()
+fn swap_config(new_config: ServerConfig) {
+    let old_config = CONFIG.swap(Arc::new(new_config));
+    let new_config = CONFIG.load();
+    match server_config::diff(&old_config, &new_config) {
+        Ok(diff) if diff.is_empty() => eprintln!("Server config reloaded, nothing changed"),
+        Ok(diff) => {
+            eprintln!("Server config reloaded:");
+            for line in &diff {
+                eprintln!("    {}", line);
+            }
+            if diff.iter().any(|line| line.starts_with("bridge")) {
+                eprintln!("Bridge settings will be used on next reconnect");
+            }
         }
-    }
-    fn default_hear_target() -> Self {
-        MovingRates {
-            running: MOVING_TARGET_RUN,
-            ..Default::default()
-        }
-    }
-}
-
-#[derive(Deserialize)]
-pub struct Bridge {
-    pub addr: SocketAddr,
-}
-impl Bridge {
-    fn defaul_addr() -> SocketAddr {
-        "127.0.0.1:33852".parse().unwrap()
-    }
-}
-impl Default for Bridge {
-    fn default() -> Self {
-        Self { addr: Self::defaul_addr() }
+        Err(err) => eprintln!("Server config reloaded, can't compare: {:?}", err),
     }
 }
 
-static CONFIG: Lazy<ArcSwap<ServerConfig>> =
-    Lazy::new(|| ArcSwap::new(Arc::new(load_config_or_default())));
-
-pub fn config() -> impl Deref<Target = impl Deref<Target = ServerConfig>> {
-    CONFIG.load()
+fn file_modified() -> Option<SystemTime> {
+    std::fs::metadata(CONFIG_PATH).and_then(|meta| meta.modified()).ok()
 }
 
-#[no_mangle]
-pub extern "C" fn reload_config() {
-    let new_config = load_config_or_default();
-    CONFIG.store(Arc::new(new_config));
-}
-
-#[derive(Debug)]
-enum ConfigError {
-    Io(std::io::Error),
-    Toml(toml::de::Error),
+/// Loads the config on dll start, panics if the file exists but can't be used.
+pub fn init() {
+    Lazy::force(&CONFIG);
 }
 
 fn load_config_or_default() -> ServerConfig {
-    let res = load_config();
-    match res {
+    match load_config() {
         Ok(config) => config,
-        Err(err) => {
-            eprintln!("Error loading server config: {:?}", err);
+        Err(ConfigError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("No {}, using default server config", CONFIG_PATH);
             Default::default()
         }
+        Err(err) => panic!("Invalid {}: {}", CONFIG_PATH, err),
     }
 }
 
+/// Unknown keys are only reported, so a typo doesn't throw away the rest of the config.
 fn load_config() -> Result<ServerConfig, ConfigError> {
-    let str = std::fs::read_to_string("ServerConfig.toml").map_err(ConfigError::Io)?;
-    let config: ServerConfig = toml::de::from_str(&str).map_err(ConfigError::Toml)?;
+    // Remembered even if the file is invalid, so it isn't reloaded until next modification
+    *MODIFIED.lock().expect("poisoned config mtime") = file_modified();
+    let (config, unknown) = server_config::load_lenient(CONFIG_PATH)?;
+    for key in &unknown {
+        eprintln!("Warning: unknown key `{}` in {}, ignored", key, CONFIG_PATH);
+    }
     Ok(config)
 }
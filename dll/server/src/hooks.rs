@@ -1,6 +1,10 @@
 //use crate::webserver;
 use crate::bridge;
 use cstr::cstr;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 use tnf_common::engine_types::{ScriptArray, ScriptString, critter::Critter};
 use tnf_common::{
     state::State,
@@ -22,6 +26,7 @@ pub extern "C" fn main_loop() {
         param::change_uparams,
     };
     //bridge::init();
+    crate::config::reload_if_modified();
     let messages = bridge::receive();
     for message in messages {
         use bridge::MsgIn;
@@ -99,6 +104,25 @@ pub extern "C" fn main_loop() {
         bridge::ServerStatus{connections, day_time}
     });
     bridge::send_one(bridge::MsgOut::Status(status));
+
+    if statistics_due() {
+        let statistics = crate::Server::with(|server| server.statistics());
+        bridge::send_one(bridge::MsgOut::Statistics(statistics));
+    }
+}
+
+const STATISTICS_INTERVAL: Duration = Duration::from_secs(60);
+
+fn statistics_due() -> bool {
+    static LAST_SENT: Mutex<Option<Instant>> = Mutex::new(None);
+    let mut last_sent = LAST_SENT.lock().expect("poisoned statistics timer");
+    match *last_sent {
+        Some(time) if time.elapsed() < STATISTICS_INTERVAL => false,
+        _ => {
+            *last_sent = Some(Instant::now());
+            true
+        }
+    }
 }
 
 #[no_mangle]
@@ -140,6 +164,60 @@ pub extern "C" fn discord_send_message(channel: Option<&ScriptString>, text: Opt
     }
 }
 
+fn send_event(event: bridge::GameEvent) {
+    bridge::send_one(bridge::MsgOut::GameEvent(event));
+}
+
+#[no_mangle]
+pub extern "C" fn server_start() {
+    send_event(bridge::GameEvent::ServerStart);
+}
+
+#[no_mangle]
+pub extern "C" fn server_stop() {
+    send_event(bridge::GameEvent::ServerStop);
+}
+
+#[no_mangle]
+pub extern "C" fn player_logged_in(player: &Critter) {
+    send_event(bridge::GameEvent::PlayerLogin {
+        id: player.Id,
+        name: player.NameStr.string(),
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn player_logged_out(player: &Critter) {
+    send_event(bridge::GameEvent::PlayerLogout {
+        id: player.Id,
+        name: player.NameStr.string(),
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn critter_dead(cr: &Critter, killer: Option<&Critter>) {
+    if cr.CritterIsNpc {
+        return;
+    }
+    send_event(bridge::GameEvent::Death {
+        id: cr.Id,
+        name: cr.NameStr.string(),
+        killer: killer.map(|killer| killer.NameStr.string()),
+        map_pid: cr.MapPid,
+    });
+}
+
+#[no_mangle]
+pub extern "C" fn gm_action(gm: &Critter, action: Option<&ScriptString>) {
+    if let Some(action) = action {
+        send_event(bridge::GameEvent::GmAction {
+            id: gm.Id,
+            name: gm.NameStr.string(),
+            action: action.string(),
+        });
+    }
+}
+
 #[no_mangle]
 pub extern "C" fn update_character(cr: &Critter) {
     //if let Err(err) = WEBSERVER.update_critter(cr) {
@@ -22,6 +22,9 @@ impl Server {
     fn statistics_connections(&self) -> u32 {
         unsafe { (*self.api.Server_Statistics()).cur_online }
     }
+    fn statistics(&self) -> bridge::ServerStatistics {
+        unsafe { *self.api.Server_Statistics() }
+    }
 }
 
 impl State for Server {
@@ -53,6 +56,7 @@ pub extern "C" fn DllMainEx(is_compiler: bool) {
 
         tnf_common::check_dll_reload();
         Server::create();
+        config::init();
     }
 }
 
@@ -1,10 +1,14 @@
+use crate::Server;
 use tnf_common::{
     defines::CritterParamMut,
     defines_fo4rp::param::Param,
+    dll::param_getters,
     engine_types::{
         critter::Critter,
         game_options::{critter_change_param, game_state},
+        ScriptString,
     },
+    state::State,
 };
 //use crate::engine_functions::inv_vec_push_box;
 
@@ -32,3 +36,11 @@ pub fn change_uparams(cr: &mut Critter, params: &[(Param, u32)]) -> bool {
     }*/
     check
 }
+
+/// Breakdown of the param formula for GM inspector, empty if the param has no formula.
+#[no_mangle]
+pub extern "C" fn param_explain(cr: &Critter, index: u32) -> *mut ScriptString {
+    let info = param_getters::explain(cr, index).unwrap_or_default();
+    Server::with(|server| ScriptString::from_string(&server.angelscript, &info))
+}
+//# pragma bindfunc "string@ ParamExplain(Critter&, uint) -> rust_dll/server.dll param_explain"
@@ -1,4 +1,5 @@
-use crate::config::{config, CheckLook, CritterRates, MovingRates, SenseRates};
+use crate::config::{config, CheckLook};
+use check_look::{LookCritter, LookMap, Moving};
 use tnf_common::{
     dll::param_getters,
     engine_types::{critter::Critter, map::Map},
@@ -6,7 +7,7 @@ use tnf_common::{
     utils::map::{
         get_distance_hex,
         server::{get_hex_in_path, get_hex_in_path_wall},
-        HexExt,
+        HexExt, HexGrid,
     },
 };
 
@@ -143,122 +144,79 @@ pub extern "C" fn check_look(
     smart
 }
 
-fn check_look_smart(config: &CheckLook, map: &Map, cr: &Critter, opponent: &Critter) -> bool {
-    if map.proto_id() == config.map_utility_start
-        && opponent.is_player()
-        && cr.is_player()
-        && !cr.have_gm_vision()
-    {
-        return false;
-    }
+/// Engine critter for `check_look` crate.
+struct LookCr<'a>(&'a Critter);
 
-    let cr_hex = cr.hex();
-    let opp_hex = opponent.hex();
-    let dist = cr_hex.get_distance(opp_hex);
-
-    use tnf_common::{defines::CritterParam, defines_fo4rp::param::Param};
-
-    let cr_vision = cr.uparam(Param::QST_VISION);
-
-    let opp_invis = opponent.uparam(Param::QST_INVIS);
-
-    if cr_vision >= dist && opp_invis <= dist {
-        return true;
+impl LookCritter for LookCr<'_> {
+    fn hex(&self) -> Hex {
+        self.0.hex()
     }
-    if opp_invis != 0 && (opp_invis - 1) < dist {
-        // && ( !( cr.IsPlayer() ) || cr.IsPlayer() && !isGM( cr ) ) )
-        return false;
+    fn dir(&self) -> u8 {
+        self.0.Dir
     }
-    if opp_invis > dist || cr_vision >= dist {
-        return true;
+    fn proto_id(&self) -> u16 {
+        self.0.ProtoId
     }
-
-    let cr_perception = param_getters::getParam_Perception(cr, 0) as u32;
-    assert!(cr_perception >= 1 && cr_perception <= 10);
-
-    fn basic_dist(rates: &CritterRates, perception: u32) -> u32 {
-        rates.basic_bonus + perception * rates.basic_perception_rate
+    fn is_player(&self) -> bool {
+        self.0.is_player()
     }
-
-    let self_is_npc = cr.is_npc();
-
-    if self_is_npc {
-        if cr.is_dead() {
-            return false;
-        }
-        let npc_fast = &config.npc_fast;
-        if npc_fast.enable && cr.ProtoId >= npc_fast.fast_from && cr.ProtoId <= npc_fast.fast_to {
-            return basic_dist(&config.senses[npc_fast.sense_index].npc, cr_perception) >= dist;
-        }
+    fn is_dead(&self) -> bool {
+        self.0.is_dead()
     }
-
-    let start_dir = cr_hex.get_direction(opp_hex);
-    let mut look_dir = i8::abs(start_dir as i8 - cr.Dir as i8); //Направление
-
-    if look_dir > 3 {
-        look_dir = 6 - look_dir
+    fn have_gm_vision(&self) -> bool {
+        self.0.have_gm_vision()
     }
-    assert!(look_dir >= 0 && look_dir <= 3);
-
-    fn moving_rate(cr: &Critter, rates: &MovingRates) -> f32 {
-        if cr.IsRuning {
-            rates.running
-        //} else if cr.is_walking() {
-        //    rates.walking
+    fn vision(&self) -> u32 {
+        use tnf_common::{defines::CritterParam, defines_fo4rp::param::Param};
+        self.0.uparam(Param::QST_VISION)
+    }
+    fn invis(&self) -> u32 {
+        use tnf_common::{defines::CritterParam, defines_fo4rp::param::Param};
+        self.0.uparam(Param::QST_INVIS)
+    }
+    fn perception(&self) -> u32 {
+        param_getters::getParam_Perception(self.0, 0) as u32
+    }
+    fn moving(&self) -> Moving {
+        if self.0.IsRuning {
+            Moving::Running
         } else {
-            rates.still
+            Moving::Still
         }
     }
+}
 
-    fn sense_mul(rates: &SenseRates, cr: &Critter, opponent: &Critter, look_dir: i8) -> f32 {
-        rates.dir_rate[look_dir as usize]
-            * moving_rate(cr, &rates.self_moving)
-            * moving_rate(opponent, &rates.target_moving)
-    }
-
-    let senses: Vec<(f32, f32)> = config
-        .senses
-        .iter()
-        .map(|sense| {
-            let critter_rates = if self_is_npc {
-                &sense.npc
-            } else {
-                &sense.player
-            };
-            let basic_dist = basic_dist(critter_rates, cr_perception);
-            let sense_mul = sense_mul(sense, cr, opponent, look_dir);
-            let wall_mul = sense.wall_rate[cr_perception as usize - 1];
-            let clear_dist = basic_dist as f32 * sense_mul;
-            //dbg!(clear_dist, wall_mul);
-            (clear_dist, wall_mul)
-        })
-        .collect();
-
-    let max_dist = senses
-        .iter()
-        .map(|(dist, _wall_mul)| *dist as u32)
-        .max()
-        .expect("At least one sense");
-
-    //dbg!(dist, max_dist);
-    if dist > max_dist {
-        return false;
+/// Engine map for `check_look` crate, grid itself is `HexGrid for Map` of `tnf_common`.
+struct LookMp<'a>(&'a Map);
+
+impl HexGrid for LookMp<'_> {
+    fn max_hex(&self) -> Hex {
+        HexGrid::max_hex(self.0)
+    }
+    fn is_hex_passed(&self, hex: Hex) -> bool {
+        HexGrid::is_hex_passed(self.0, hex)
+    }
+    fn is_hex_raked(&self, hex: Hex) -> bool {
+        HexGrid::is_hex_raked(self.0, hex)
+    }
+    fn is_hex_critter(&self, hex: Hex) -> bool {
+        HexGrid::is_hex_critter(self.0, hex)
+    }
+    fn is_hexagonal(&self) -> bool {
+        HexGrid::is_hexagonal(self.0)
     }
+}
 
-    let end_hex = get_hex_in_path(map, cr_hex, opp_hex, 0.0, dist);
-    if dist > cr_hex.get_distance(end_hex) {
-        for (basic_dist, wall_mull) in senses {
-            //dbg!(basic_dist * wall_mull, dist);
-            if (basic_dist * wall_mull) as u32 >= dist {
-                return true;
-            }
-        }
-        false
-    } else {
-        true
+impl LookMap for LookMp<'_> {
+    fn proto_id(&self) -> u16 {
+        self.0.proto_id()
     }
 }
 
+fn check_look_smart(config: &CheckLook, map: &Map, cr: &Critter, opponent: &Critter) -> bool {
+    check_look::check_look(config, &LookMp(map), &LookCr(cr), &LookCr(opponent))
+}
+
 fn _check_look_old(config: &CheckLook, map: &Map, cr: &Critter, opponent: &Critter) -> bool {
     if map.proto_id() == config.map_utility_start
         && opponent.is_player()
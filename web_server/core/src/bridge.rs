@@ -1,8 +1,15 @@
 use crate::{
-    database::{ownership, CharTrunk, Root, VersionedError},
+    chat::ChatBackend,
+    database::{ownership, statistics::unix_time, CharTrunk, Root, VersionedError},
+    events,
     utils::blocking,
     web::AppState,
 };
+use ::bridge::{
+    auth::{Auth, Role},
+    frame::{self, Frame},
+    BridgeMessage, Protocol, Session,
+};
 use actix_codec::{Decoder, Encoder, Framed};
 use actix_rt::net::TcpStream;
 pub use actix_server::Server;
@@ -13,20 +20,26 @@ use futures::{
     channel::mpsc::{channel, Sender, TrySendError},
     future, Future, StreamExt, TryFutureExt, TryStreamExt,
 };
-use mrhandy::{Condition, ConditionColor};
+use mrhandy::{commands::ServerState, Condition, ConditionColor};
 use parking_lot::RwLock;
 use serde::Serialize;
 use std::{convert::TryInto, ffi::CStr, sync::Arc};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio_rustls::TlsAcceptor;
 
 pub use protocol::message::server_dll_web::{
     DayTime, ServerDllToWeb as MsgIn, ServerStatus, ServerWebToDll as MsgOut,
 };
+use protocol::message::server_dll_web::{HANDSHAKE, MIN_VERSION, VERSION};
 pub type MsgOutSender = Sender<MsgOut>;
 //pub type MsgOutSendError = SendError<MsgOut>;
 pub type MsgOutSendError = TrySendError<MsgOut>;
 
 type BridgeResult<T> = Result<T, BridgeError>;
 
+/// Time for the peer to finish handshake and authentication.
+const HANDSHAKE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
 /// Simple logger service, it just prints fact of the new connections
 /*fn logger<T: AsyncRead + AsyncWrite + std::fmt::Debug>(
     stream: T,
@@ -137,7 +150,17 @@ impl Status {
         //self.new = Some((server, Instant::now()));
         self.new = Some(server);
     }
-    pub async fn new_status(&mut self, mrhandy: &mrhandy::MrHandy) {
+    /// Server state according to the last shown status.
+    pub fn state(&self) -> ServerState {
+        match (&self.current.kind, &self.current.status) {
+            (StatusKind::Online, Some(status)) => ServerState::Online {
+                players: status.connections,
+            },
+            (StatusKind::Offline, _) => ServerState::Offline,
+            _ => ServerState::Unknown,
+        }
+    }
+    pub async fn new_status(&mut self, chat: &dyn ChatBackend) {
         use StatusKind::*;
         let new = match (&self.current.kind, self.new.take()) {
             (Online, None) => Unwell.into(),
@@ -151,7 +174,7 @@ impl Status {
         };
         let condition = new.condition();
 
-        if mrhandy.set_activity(condition).await {
+        if chat.set_presence(condition).await {
             self.current = new;
             self.new = None;
         }
@@ -209,6 +232,10 @@ impl BridgeData {
 }
 
 async fn start_impl(data: BridgeData) -> Server {
+    let tls = data.state.config.bridge.tls.as_ref().map(|cert| {
+        let config = cert.server_config().expect("Bridge TLS config");
+        TlsAcceptor::from(Arc::new(config))
+    });
     Server::build()
         .workers(1)
         .bind(
@@ -217,30 +244,24 @@ async fn start_impl(data: BridgeData) -> Server {
             data.state.config.bridge.addr,
             move || {
                 let data = data.clone();
-                // service for converting incoming TcpStream to a SslStream<TcpStream>
+                let tls = tls.clone();
+                // service for converting incoming TcpStream to a TlsStream<TcpStream>
                 fn_service(move |tcp_stream: TcpStream| {
                     let data = data.clone();
-
-                    let (sender, receiver) = channel(128);
-                    data.bridge().set_sender(sender);
-
-                    let framed = Framed::new(tcp_stream, WebSide);
-                    let (sink, stream) = framed.split();
-
-                    futures::stream::select(
-                        stream
-                            .map_err(BridgeError::Bincode)
-                            //.filter_map(handle_message)
-                            .and_then(move |msg| handle_message_async(msg, data.clone()))
-                            .boxed(),
-                        receiver.map(Result::Ok), //.map_err(|_| BridgeError::SenderDropped),
-                    )
-                    .try_filter(drop_nop)
-                    .map_err(|err| {
-                        std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", err)).into()
-                    })
-                    .inspect_ok(|msg| println!("Sending: {:?}", msg))
-                    .forward(sink)
+                    let tls = tls.clone();
+
+                    async move {
+                        match tls {
+                            Some(tls) => {
+                                let accept = tls.accept(tcp_stream);
+                                let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, accept)
+                                    .await
+                                    .map_err(|_| std::io::ErrorKind::TimedOut)??;
+                                serve(stream, data).await
+                            }
+                            None => serve(tcp_stream, data).await,
+                        }
+                    }
                 })
             },
         )
@@ -248,6 +269,119 @@ async fn start_impl(data: BridgeData) -> Server {
         .run()
 }
 
+async fn serve<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    data: BridgeData,
+) -> std::io::Result<()> {
+    let secret = data.state.config.bridge.secret.as_deref();
+    let accepted = tokio::time::timeout(HANDSHAKE_TIMEOUT, async {
+        let session = handshake(&mut stream).await?;
+        println!("Bridge: negotiated version {}", session.version);
+        if let Some(secret) = secret {
+            authenticate(&mut stream, secret).await?;
+            println!("Bridge: authenticated");
+        }
+        Ok::<_, std::io::Error>(session)
+    })
+    .await;
+    match accepted {
+        Ok(Ok(_session)) => {}
+        Ok(Err(err)) => {
+            eprintln!("Bridge: connection rejected: {}", err);
+            return Err(err);
+        }
+        Err(_) => {
+            eprintln!("Bridge: handshake timed out");
+            return Err(std::io::ErrorKind::TimedOut.into());
+        }
+    }
+
+    let (sender, receiver) = channel(128);
+    data.bridge().set_sender(sender);
+
+    let framed = Framed::new(stream, WebSide);
+    let (sink, stream) = framed.split();
+
+    futures::stream::select(
+        stream
+            .map_err(BridgeError::Io)
+            //.filter_map(handle_message)
+            .and_then(move |msg| handle_message_async(msg, data.clone()))
+            .boxed(),
+        receiver.map(Result::Ok), //.map_err(|_| BridgeError::SenderDropped),
+    )
+    .try_filter(drop_nop)
+    .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, format!("{:?}", err)))
+    .inspect_ok(|msg| println!("Sending: {:?}", msg))
+    .forward(sink)
+    .await
+}
+
+async fn handshake<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S) -> std::io::Result<Session> {
+    let protocol = Protocol::new(HANDSHAKE, VERSION).min_version(MIN_VERSION);
+    write_frame(stream, &BridgeMessage::Handshake(protocol.clone())).await?;
+    match read_frame(stream).await? {
+        BridgeMessage::Handshake(peer) => protocol.negotiate(&peer).map_err(frame_error),
+        _ => Err(frame_error(::bridge::BridgeError::NoHandshake)),
+    }
+}
+
+/// Challenge-response with shared secret, see `bridge::auth`.
+async fn authenticate<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    secret: &str,
+) -> std::io::Result<()> {
+    let mut auth = Auth::new(secret.as_bytes(), Role::Listener).map_err(frame_error)?;
+    write_frame(stream, &auth.challenge()).await?;
+    let proof = auth
+        .proof(read_frame(stream).await?)
+        .map_err(|_| auth_failed("peer didn't send a challenge, is its `bridge.secret` set?"))?;
+    write_frame(stream, &proof).await?;
+    auth.verify(read_frame(stream).await?)
+        .map_err(|_| auth_failed("wrong proof, `bridge.secret` differs on the peer"))
+}
+
+fn auth_failed(reason: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::PermissionDenied,
+        format!("authentication failed: {}", reason),
+    )
+}
+
+async fn write_frame<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    msg: &BridgeMessage<MsgOut>,
+) -> std::io::Result<()> {
+    let buf = frame::encode(msg).map_err(frame_error)?;
+    stream.write_all(&buf).await?;
+    stream.flush().await
+}
+
+/// Reads next known handshake or authentication message, skipping unknown frames.
+async fn read_frame<S: AsyncRead + Unpin>(stream: &mut S) -> std::io::Result<BridgeMessage<MsgIn>> {
+    loop {
+        let len = stream.read_u32().await?;
+        if len > frame::MAX_HANDSHAKE_LEN {
+            return Err(frame_error(::bridge::BridgeError::FrameTooLarge(
+                len as u64,
+            )));
+        }
+        let mut payload = vec![0u8; len as usize];
+        stream.read_exact(&mut payload).await?;
+        match frame::decode(&payload).map_err(frame_error)? {
+            Frame::Message(msg) => return Ok(msg),
+            Frame::Unknown(tag) => eprintln!("Bridge: skipping unknown frame {:?}", tag),
+        }
+    }
+}
+
+fn frame_error(err: ::bridge::BridgeError) -> std::io::Error {
+    match err {
+        ::bridge::BridgeError::Io(err) => err,
+        err => std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{:?}", err)),
+    }
+}
+
 fn handle_message_async(
     msg_in: MsgIn,
     data: BridgeData,
@@ -296,8 +430,8 @@ fn handle_message_async(
                 fut.await
             }
             MsgIn::DiscordSendMessage { channel, text } => {
-                if let Some(mrhandy) = data.state.mrhandy.as_ref() {
-                    let _ = mrhandy.send_message(channel, text).await;
+                if let Err(err) = data.state.chat.send_message(channel, text).await {
+                    eprintln!("Can't send message from server: {}", err);
                 }
                 Ok(MsgOut::Nop)
             }
@@ -306,6 +440,20 @@ fn handle_message_async(
                 status.update(server);
                 Ok(MsgOut::Nop)
             }
+            MsgIn::GameEvent(event) => {
+                if let Some(feed) = data.state.event_feed.as_ref() {
+                    feed.lock().push(&event, events::render_event);
+                }
+                Ok(MsgOut::Nop)
+            }
+            MsgIn::Statistics(statistics) => {
+                let history = data.state.sled_db.statistics.clone();
+                let res = blocking(move || history.record(unix_time(), &statistics)).await;
+                if let Err(err) = res {
+                    eprintln!("Can't record server statistics: {:?}", err);
+                }
+                Ok(MsgOut::Nop)
+            }
         }
     }
 }
@@ -327,24 +475,26 @@ struct WebSide;
 
 impl Decoder for WebSide {
     type Item = MsgIn;
-    type Error = bincode::Error;
+    type Error = std::io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        use bincode::ErrorKind as BinKind;
-        use std::io::ErrorKind;
-        if src.is_empty() {
-            return Ok(None);
-        }
-        match partial_read(src, |buf| bincode::deserialize_from(buf)) {
-            Err(err) => {
-                if let BinKind::Io(err) = &*err {
-                    if let ErrorKind::UnexpectedEof = err.kind() {
-                        return Ok(None);
-                    }
+        loop {
+            let len = match frame::frame_len(src).map_err(frame_error)? {
+                Some(len) => len,
+                None => return Ok(None),
+            };
+            let buf = src.split_to(len);
+            match frame::decode(frame::payload(&buf)).map_err(frame_error)? {
+                Frame::Message(BridgeMessage::Data(msg)) => return Ok(Some(msg)),
+                Frame::Message(BridgeMessage::Challenge(_)) => {
+                    let err =
+                        auth_failed("peer requires authentication, but `bridge.secret` isn't set");
+                    eprintln!("Bridge: {}", err);
+                    return Err(err);
                 }
-                Err(err)
+                Frame::Message(_) => {}
+                Frame::Unknown(tag) => eprintln!("Bridge: skipping unknown frame {:?}", tag),
             }
-            Ok(ok) => Ok(Some(ok)),
         }
     }
 }
@@ -353,18 +503,17 @@ impl Decoder for WebSide {
 mod test {
     use super::*;
     #[test]
-    fn test_partial_read() {
-        use bytes::BufMut;
-        use std::io::Read;
+    fn test_decode_frames() {
         let mut bytes = BytesMut::new();
-        bytes.put_slice(b"Hello world!");
-        let res: std::io::Result<_> = partial_read(&mut bytes, |buf| {
-            let mut hello = [0u8; 5];
-            buf.read_exact(&mut hello)?;
-            Ok(hello)
-        });
-        assert_eq!(&res.unwrap(), &b"Hello"[..]);
-        assert_eq!(&bytes, &b" world!"[..]);
+        let frame = frame::encode(&BridgeMessage::Data(MsgIn::PlayerAuth(42))).unwrap();
+        bytes.extend_from_slice(&frame);
+        bytes.extend_from_slice(&frame[..frame.len() - 1]);
+        match WebSide.decode(&mut bytes).unwrap() {
+            Some(MsgIn::PlayerAuth(42)) => {}
+            msg => panic!("Unexpected message: {:?}", msg),
+        }
+        assert!(WebSide.decode(&mut bytes).unwrap().is_none());
+        assert_eq!(bytes.len(), frame.len() - 1);
     }
 
     fn render_status(status: StatusDisplay) -> String {
@@ -428,29 +577,11 @@ mod test {
     }*/
 }
 
-fn partial_read<O, E>(src: &mut BytesMut, fun: fn(&mut &[u8]) -> Result<O, E>) -> Result<O, E> {
-    use bytes::Buf;
-    let mut buf: &[u8] = &*src;
-    let mut len = buf.len();
-    let ret = fun(&mut buf)?;
-    if buf.len() > len {
-        panic!("buffer bigger than it was");
-    }
-    len -= buf.len();
-    if len > 0 {
-        src.advance(len);
-    }
-    Ok(ret)
-}
-
 impl Encoder<MsgOut> for WebSide {
-    type Error = bincode::Error;
+    type Error = std::io::Error;
 
     fn encode(&mut self, item: MsgOut, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        //const LEN: usize = std::mem::size_of::<MsgOut>();
-        //let buf: [u8; LEN] = unsafe { std::mem::transmute(item) };
-        //dst.extend_from_slice(&buf);
-        let buf = bincode::serialize(&item)?;
+        let buf = frame::encode(&BridgeMessage::Data(item)).map_err(frame_error)?;
         dst.extend_from_slice(&buf);
         Ok(())
     }
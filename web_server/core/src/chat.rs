@@ -0,0 +1,175 @@
+//! Chat platform the web server talks to: identity, roles, messaging and presence.
+//! Discord through `MrHandy` in production, `InMemoryChat` in tests and without Discord config.
+use futures::future::BoxFuture;
+use mrhandy::{Condition, MrHandy};
+use parking_lot::{Mutex, RwLock};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Messages kept by `InMemoryChat`, older ones are dropped.
+const KEEP_MESSAGES: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMember {
+    pub id: u64,
+    pub name: String,
+    pub nick: Option<String>,
+    /// Role names, mapped to ranks by `config::Roles`.
+    pub roles: Vec<String>,
+}
+
+pub type ChatMembers = BTreeMap<u64, ChatMember>;
+
+/// Where messages go.
+pub trait MessageSink: Send + Sync {
+    fn send_message(&self, channel: String, text: String) -> BoxFuture<'_, Result<(), String>>;
+}
+
+pub trait ChatBackend: MessageSink {
+    /// Member of the main guild, `None` if user isn't there or guild is unavailable.
+    fn member(&self, user_id: u64) -> BoxFuture<'_, Option<ChatMember>>;
+    /// All members of the main guild, `None` if guild is unavailable.
+    fn members(&self) -> BoxFuture<'_, Option<ChatMembers>>;
+    /// Shows server condition, returns `false` if it wasn't shown and should be retried.
+    fn set_presence(&self, condition: Condition) -> BoxFuture<'_, bool>;
+}
+
+fn chat_member(
+    guild: &mrhandy::Guild,
+    member: &mrhandy::serenity::model::guild::Member,
+) -> ChatMember {
+    let (name, nick) = MrHandy::get_name_nick(member);
+    ChatMember {
+        id: member.user.id.0,
+        name,
+        nick,
+        roles: MrHandy::get_roles(guild, member, |role| role.name.clone()),
+    }
+}
+
+impl MessageSink for MrHandy {
+    fn send_message(&self, channel: String, text: String) -> BoxFuture<'_, Result<(), String>> {
+        Box::pin(async move {
+            MrHandy::send_message(self, channel, text)
+                .await
+                .map_err(|err| format!("{:?}", err))
+        })
+    }
+}
+
+impl ChatBackend for MrHandy {
+    fn member(&self, user_id: u64) -> BoxFuture<'_, Option<ChatMember>> {
+        Box::pin(async move { self.with_guild_member(user_id, chat_member).await.ok() })
+    }
+    fn members(&self) -> BoxFuture<'_, Option<ChatMembers>> {
+        Box::pin(self.with_guild(|guild| {
+            let guild = guild?;
+            let members = guild
+                .members
+                .values()
+                .map(|member| {
+                    let member = chat_member(guild, member);
+                    (member.id, member)
+                })
+                .collect();
+            Some(members)
+        }))
+    }
+    fn set_presence(&self, condition: Condition) -> BoxFuture<'_, bool> {
+        Box::pin(self.set_activity(condition))
+    }
+}
+
+/// Chat without network: members are set by hand, messages and presence are only remembered.
+#[derive(Default)]
+pub struct InMemoryChat {
+    members: RwLock<ChatMembers>,
+    messages: Mutex<VecDeque<(String, String)>>,
+    presence: Mutex<Option<String>>,
+}
+
+impl InMemoryChat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add_member(&self, member: ChatMember) {
+        self.members.write().insert(member.id, member);
+    }
+    /// Last sent messages as `(channel, text)`, oldest first.
+    pub fn messages(&self) -> Vec<(String, String)> {
+        self.messages.lock().iter().cloned().collect()
+    }
+    pub fn presence(&self) -> Option<String> {
+        self.presence.lock().clone()
+    }
+}
+
+impl MessageSink for InMemoryChat {
+    fn send_message(&self, channel: String, text: String) -> BoxFuture<'_, Result<(), String>> {
+        let mut messages = self.messages.lock();
+        if messages.len() >= KEEP_MESSAGES {
+            messages.pop_front();
+        }
+        messages.push_back((channel, text));
+        Box::pin(futures::future::ready(Ok(())))
+    }
+}
+
+impl ChatBackend for InMemoryChat {
+    fn member(&self, user_id: u64) -> BoxFuture<'_, Option<ChatMember>> {
+        let member = self.members.read().get(&user_id).cloned();
+        Box::pin(futures::future::ready(member))
+    }
+    fn members(&self) -> BoxFuture<'_, Option<ChatMembers>> {
+        let members = self.members.read().clone();
+        Box::pin(futures::future::ready(Some(members)))
+    }
+    fn set_presence(&self, condition: Condition) -> BoxFuture<'_, bool> {
+        *self.presence.lock() = Some(condition.name);
+        Box::pin(futures::future::ready(true))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use futures::executor::block_on;
+    use mrhandy::ConditionColor;
+
+    fn member(id: u64, roles: &[&str]) -> ChatMember {
+        ChatMember {
+            id,
+            name: format!("user{}", id),
+            nick: None,
+            roles: roles.iter().map(|&role| role.to_owned()).collect(),
+        }
+    }
+
+    #[test]
+    fn members() {
+        let chat = InMemoryChat::new();
+        chat.add_member(member(1, &["GM"]));
+        chat.add_member(member(2, &[]));
+        assert_eq!(block_on(chat.member(1)), Some(member(1, &["GM"])));
+        assert_eq!(block_on(chat.member(3)), None);
+        let members = block_on(chat.members()).unwrap();
+        assert_eq!(members.keys().copied().collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn messages_and_presence() {
+        let chat = InMemoryChat::new();
+        for i in 0..KEEP_MESSAGES + 1 {
+            block_on(chat.send_message("log".into(), i.to_string())).unwrap();
+        }
+        let messages = chat.messages();
+        assert_eq!(messages.len(), KEEP_MESSAGES);
+        assert_eq!(messages[0], ("log".into(), "1".into()));
+
+        let condition = Condition {
+            name: "Players: 3".into(),
+            color: ConditionColor::Green,
+        };
+        assert!(block_on(chat.set_presence(condition)));
+        assert_eq!(chat.presence().as_deref(), Some("Players: 3"));
+    }
+}
@@ -1,4 +1,5 @@
 use actix_web::cookie::Key as CookieKey;
+use protocol::message::server_dll_web::GameEventKind;
 use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::net::SocketAddr;
@@ -108,6 +109,9 @@ pub struct OAuth {
 #[derive(Debug, Deserialize, Clone)]
 pub struct Bot {
     pub token: String,
+    /// Channel for `~announce` command, announcements are disabled if not set.
+    #[serde(default)]
+    pub announce_channel: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -160,6 +164,9 @@ impl PrivatePaths {
 #[derive(Debug, Deserialize, Clone)]
 pub struct Paths {
     pub save_clients: PathBuf, // "../../FO4RP/save/clients/"
+    /// Watch `save_clients` for changes instead of rescanning it on every request
+    #[serde(default)]
+    pub watch_clients: bool,
     pub proto_items: PathBuf,  // "../../FO4RP/proto/items/items.lst"
     #[cfg(feature = "fo_map_format")]
     pub maps: PathBuf, // "../../FO4RP/maps/"
@@ -240,6 +247,12 @@ impl Session {
 #[derive(Debug, Deserialize, Clone)]
 pub struct Bridge {
     pub addr: SocketAddr,
+    /// Shared secret, server DLL has to prove it knows it before bridge is used
+    #[serde(default)]
+    pub secret: Option<String>,
+    /// Encrypt bridge connection
+    #[serde(default)]
+    pub tls: Option<Cert>,
 }
 impl Bridge {
     fn defaul_addr() -> SocketAddr {
@@ -250,10 +263,61 @@ impl Default for Bridge {
     fn default() -> Self {
         Self {
             addr: Self::defaul_addr(),
+            secret: None,
+            tls: None,
         }
     }
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct Snapshots {
+    /// Minutes between snapshots of changed characters
+    #[serde(default = "Snapshots::default_interval")]
+    pub interval: u64,
+    /// Alert if character gains more experience per hour
+    #[serde(default)]
+    pub exp_per_hour: Option<i32>,
+}
+impl Snapshots {
+    fn default_interval() -> u64 {
+        10
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Events {
+    /// Seconds to gather events into one message
+    #[serde(default = "Events::default_batch_seconds")]
+    pub batch_seconds: u64,
+    /// Messages per minute allowed for one channel, the rest waits for the next batch
+    #[serde(default = "Events::default_max_per_minute")]
+    pub max_per_minute: usize,
+    #[serde(default)]
+    pub routes: Vec<EventRoute>,
+}
+impl Events {
+    fn default_batch_seconds() -> u64 {
+        5
+    }
+    fn default_max_per_minute() -> usize {
+        10
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct EventRoute {
+    pub channel: String,
+    pub kinds: Vec<GameEventKind>,
+    /// Template to format events with
+    #[serde(default = "EventRoute::default_template")]
+    pub template: String,
+}
+impl EventRoute {
+    fn default_template() -> String {
+        "event.txt".into()
+    }
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct Config {
     pub host: Host,
@@ -262,6 +326,12 @@ pub struct Config {
     pub session: Session,
     #[serde(default)]
     pub bridge: Bridge,
+    /// Keep history of characters' saves, disabled if missing
+    #[serde(default)]
+    pub snapshots: Option<Snapshots>,
+    /// Game events feed to Discord, disabled if missing
+    #[serde(default)]
+    pub events: Option<Events>,
 }
 
 #[derive(Debug)]
@@ -1,29 +1,95 @@
 use arc_swap::ArcSwapAny;
-use std::{io, path::PathBuf, sync::Arc};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    io,
+    path::PathBuf,
+    sync::{mpsc, Arc},
+    time::Duration,
+};
 
 use clients_db::{ClientsDb, CritterInfo};
 
 type InnerCritter = Arc<CritterInfo>;
 type InnerClients = Arc<ClientsDb>;
+type SharedClients = Arc<ArcSwapAny<InnerClients>>;
+
+/// Time to gather burst of file events, i.e. while server saves all the clients.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
 pub struct CrittersDb {
-    clients: ArcSwapAny<InnerClients>,
+    clients: SharedClients,
     path: PathBuf,
+    watcher: Option<RecommendedWatcher>,
 }
 
 impl CrittersDb {
-    pub fn new(path: PathBuf) -> Self {
-        let clients = Arc::new(ClientsDb::new(&path));
+    pub fn new(path: PathBuf, watch: bool) -> Self {
+        let clients = Arc::new(ArcSwapAny::from(Arc::new(ClientsDb::new(&path))));
+        let watcher = if watch {
+            watch_clients(Arc::clone(&clients), path.clone())
+                .map_err(|err| eprintln!("Can't watch clients folder: {:?}", err))
+                .ok()
+        } else {
+            None
+        };
         CrittersDb {
-            clients: ArcSwapAny::from(clients),
+            clients,
             path,
+            watcher,
         }
     }
     pub fn list_clients(&self) -> InnerClients {
-        let clients = Arc::new(ClientsDb::new(&self.path));
-        self.clients.store(Arc::clone(&clients));
-        clients
+        if self.watcher.is_some() {
+            self.clients.load_full()
+        } else {
+            refresh(&self.clients, &self.path)
+        }
     }
     pub fn client_info(&self, name: &str) -> io::Result<InnerCritter> {
         self.clients.load().client_info(name)
     }
 }
+
+fn refresh(clients: &ArcSwapAny<InnerClients>, path: &PathBuf) -> InnerClients {
+    let old = clients.load_full();
+    match old.refreshed(path) {
+        Ok(new) => {
+            let new = Arc::new(new);
+            clients.store(Arc::clone(&new));
+            new
+        }
+        Err(err) => {
+            eprintln!("Can't refresh clients: {:?}", err);
+            old
+        }
+    }
+}
+
+fn watch_clients(clients: SharedClients, path: PathBuf) -> notify::Result<RecommendedWatcher> {
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher =
+        notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+            Ok(event) if matches!(event.kind, EventKind::Access(_)) => {}
+            Ok(event) => {
+                let is_client = event
+                    .paths
+                    .iter()
+                    .any(|path| path.extension() == Some("client".as_ref()));
+                if is_client {
+                    let _ = sender.send(());
+                }
+            }
+            Err(err) => eprintln!("Clients watcher error: {:?}", err),
+        })?;
+    watcher.watch(&path, RecursiveMode::NonRecursive)?;
+
+    // Thread stops when watcher, and so sender, is dropped.
+    std::thread::spawn(move || {
+        while receiver.recv().is_ok() {
+            std::thread::sleep(DEBOUNCE);
+            receiver.try_iter().for_each(drop);
+            refresh(&clients, &path);
+        }
+    });
+    Ok(watcher)
+}
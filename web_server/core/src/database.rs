@@ -11,18 +11,45 @@ pub use character::CharTrunk;
 
 pub mod ownership;
 
+pub mod statistics;
+pub use statistics::StatisticsHistory;
+
+pub mod snapshots;
+pub use snapshots::CharacterHistory;
+
 mod tools;
 
 #[derive(Clone)]
 pub struct SledDb {
     _db: sled::Db,
     pub root: Root,
+    pub statistics: StatisticsHistory,
+    pub characters: CharacterHistory,
 }
 
 impl SledDb {
     pub fn new(db: sled::Db) -> Self {
         let tree = db.open_tree("fo4rp").expect("Can't open 'fo4rp' Tree");
         let root = Root::new(tree);
-        SledDb { _db: db, root }
+        if let Err(err) = ownership::ensure_owners_index(&root) {
+            eprintln!("Can't build owners index: {:?}", err);
+        }
+        let statistics = db
+            .open_tree("statistics")
+            .expect("Can't open 'statistics' Tree");
+        let statistics = StatisticsHistory::new(statistics);
+        let characters = db
+            .open_tree("snapshots")
+            .expect("Can't open 'snapshots' Tree");
+        let alerts = db
+            .open_tree("snapshot_alerts")
+            .expect("Can't open 'snapshot_alerts' Tree");
+        let characters = CharacterHistory::new(characters, alerts);
+        SledDb {
+            _db: db,
+            root,
+            statistics,
+            characters,
+        }
     }
 }
@@ -1,9 +1,13 @@
-use super::{tools::slice_to_u64, CharTrunk, Root, VersionedError};
+use super::{statistics::unix_time, tools::slice_to_u64, CharTrunk, Root, VersionedError};
+use serde::{Deserialize, Serialize};
+use sled::transaction::{
+    abort, ConflictableTransactionResult, TransactionError, TransactionalTree,
+};
 
 pub fn get_ownership(root: &Root, char_id: u32) -> Result<Option<u64>, VersionedError> {
     let result = root
         .trunk(char_id, None, CharTrunk::default())
-        .get_bare_branch("owner_id");
+        .get_bare_branch(OWNER_BRANCH);
     match result {
         Ok(owner) => {
             let user_id = slice_to_u64(&*owner);
@@ -17,21 +21,223 @@ pub fn get_ownership(root: &Root, char_id: u32) -> Result<Option<u64>, Versioned
     }
 }
 
+/// Claims character for the user, fails if it's already owned by someone else.
 pub fn set_ownership(root: &Root, char_id: u32, user_id: u64) -> Result<(), VersionedError> {
-    let new_owner = user_id.to_be_bytes();
-    let result = root
-        .trunk(char_id, None, CharTrunk::default())
-        .get_bare_branch_or_default("owner_id", &new_owner, |_| true);
-    match result {
+    let keys = OwnerKeys::new(root, char_id)?;
+    transaction(root, |tx| match read_owner(tx, &keys)? {
         // aleady same owner
-        Ok(Some(owner)) if &*owner == &new_owner => Ok(()),
-        // successfully setted
-        Ok(None) => Ok(()),
-        Err(err) => Err(err),
-        _ => Err(VersionedError::AccessDenied),
+        Some(owner) if owner == user_id => Ok(()),
+        Some(_) => abort(VersionedError::AccessDenied),
+        None => {
+            let change = OwnershipChange::new(char_id, None, Some(user_id), None, "");
+            write_owner(tx, &keys, &change)
+        }
+    })
+}
+
+/// Moves character to another user or revokes ownership if `to` is `None`, on behalf of GM.
+/// Returns previous owner.
+pub fn transfer_ownership(
+    root: &Root,
+    char_id: u32,
+    to: Option<u64>,
+    gm_id: u64,
+    reason: &str,
+) -> Result<Option<u64>, VersionedError> {
+    let keys = OwnerKeys::new(root, char_id)?;
+    transaction(root, |tx| {
+        let from = read_owner(tx, &keys)?;
+        if from == to {
+            return Ok(from);
+        }
+        let change = OwnershipChange::new(char_id, from, to, Some(gm_id), reason);
+        write_owner(tx, &keys, &change)?;
+        Ok(from)
+    })
+}
+
+/// Ids of characters owned by the user, from the reverse index.
+pub fn characters_of(root: &Root, user_id: u64) -> Result<Vec<u32>, VersionedError> {
+    let prefix = user_chars_prefix(user_id);
+    root.tree()
+        .scan_prefix(&prefix)
+        .keys()
+        .map(|key| {
+            let key = key.map_err(VersionedError::Sled)?;
+            std::str::from_utf8(&key[prefix.len()..])
+                .ok()
+                .and_then(|id| u32::from_str_radix(id, 16).ok())
+                .ok_or(VersionedError::ValueParse(key))
+        })
+        .collect()
+}
+
+/// Fills reverse index from `char/<id>/owner_id` records, for databases created before the index.
+/// Returns number of indexed characters.
+pub fn reindex_owners(root: &Root) -> Result<usize, VersionedError> {
+    let mut count = 0;
+    for res in root.tree().scan_prefix(CHAR_PREFIX) {
+        let (key, value) = res.map_err(VersionedError::Sled)?;
+        let char_id = match parse_owner_key(&key) {
+            Some(char_id) => char_id,
+            None => continue,
+        };
+        let user_id = slice_to_u64(&value).ok_or(VersionedError::ValueParse(value))?;
+        root.tree()
+            .insert(user_char_key(user_id, char_id), &[])
+            .map_err(VersionedError::Sled)?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Builds reverse index once, on the first start with it.
+pub fn ensure_owners_index(root: &Root) -> Result<(), VersionedError> {
+    let tree = root.tree();
+    if tree
+        .contains_key(INDEX_MARKER)
+        .map_err(VersionedError::Sled)?
+    {
+        return Ok(());
+    }
+    let count = reindex_owners(root)?;
+    tree.insert(INDEX_MARKER, &[])
+        .map_err(VersionedError::Sled)?;
+    println!("Indexed owners of {} characters", count);
+    Ok(())
+}
+
+/// Ownership changes of the character, oldest first, or of all characters if `char_id` is `None`.
+pub fn ownership_log(
+    root: &Root,
+    char_id: Option<u32>,
+) -> Result<Vec<OwnershipChange>, VersionedError> {
+    let mut log = vec![];
+    for value in root.tree().scan_prefix(AUDIT_PREFIX).values() {
+        let value = value.map_err(VersionedError::Sled)?;
+        let change: OwnershipChange =
+            bincode::deserialize(&value).map_err(VersionedError::Bincode)?;
+        match char_id {
+            Some(char_id) if char_id != change.char_id => {}
+            _ => log.push(change),
+        }
+    }
+    Ok(log)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnershipChange {
+    /// Unix time, in seconds.
+    pub time: u64,
+    pub char_id: u32,
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    /// GM who made the change, `None` if player claimed the character.
+    pub gm_id: Option<u64>,
+    pub reason: String,
+}
+
+impl OwnershipChange {
+    fn new(
+        char_id: u32,
+        from: Option<u64>,
+        to: Option<u64>,
+        gm_id: Option<u64>,
+        reason: &str,
+    ) -> Self {
+        OwnershipChange {
+            time: unix_time(),
+            char_id,
+            from,
+            to,
+            gm_id,
+            reason: reason.into(),
+        }
+    }
+}
+
+const CHAR_PREFIX: &str = "char/";
+const OWNER_BRANCH: &str = "owner_id";
+const AUDIT_PREFIX: &str = "audit/ownership/";
+const INDEX_MARKER: &str = "meta/owners_index";
+
+struct OwnerKeys {
+    char_id: u32,
+    owner: String,
+}
+
+impl OwnerKeys {
+    fn new(root: &Root, char_id: u32) -> Result<Self, VersionedError> {
+        let owner = root
+            .trunk(char_id, None, CharTrunk::default())
+            .branch_key(OWNER_BRANCH)?;
+        Ok(OwnerKeys { char_id, owner })
     }
 }
 
+fn user_chars_prefix(user_id: u64) -> String {
+    format!("user/{:016X}/chars/", user_id)
+}
+
+fn user_char_key(user_id: u64, char_id: u32) -> String {
+    format!("{}{:08X}", user_chars_prefix(user_id), char_id)
+}
+
+/// Character id from `char/<id>/owner_id` key.
+fn parse_owner_key(key: &[u8]) -> Option<u32> {
+    let key = std::str::from_utf8(key).ok()?;
+    let id = key
+        .strip_prefix(CHAR_PREFIX)?
+        .strip_suffix(OWNER_BRANCH)?
+        .strip_suffix('/')?;
+    u32::from_str_radix(id, 16).ok()
+}
+
+type TxResult<T> = ConflictableTransactionResult<T, VersionedError>;
+
+fn transaction<T, F>(root: &Root, fun: F) -> Result<T, VersionedError>
+where
+    F: Fn(&TransactionalTree) -> TxResult<T>,
+{
+    root.tree().transaction(fun).map_err(|err| match err {
+        TransactionError::Abort(err) => err,
+        TransactionError::Storage(err) => VersionedError::Sled(err),
+    })
+}
+
+fn read_owner(tx: &TransactionalTree, keys: &OwnerKeys) -> TxResult<Option<u64>> {
+    match tx.get(&keys.owner)? {
+        Some(owner) => match slice_to_u64(&owner) {
+            Some(owner) => Ok(Some(owner)),
+            None => abort(VersionedError::ValueParse(owner)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// Updates owner, reverse index and audit log in one go.
+fn write_owner(tx: &TransactionalTree, keys: &OwnerKeys, change: &OwnershipChange) -> TxResult<()> {
+    if let Some(from) = change.from {
+        tx.remove(user_char_key(from, keys.char_id).as_bytes())?;
+    }
+    match change.to {
+        Some(to) => {
+            tx.insert(keys.owner.as_bytes(), &to.to_be_bytes())?;
+            tx.insert(user_char_key(to, keys.char_id).as_bytes(), &[])?;
+        }
+        None => {
+            tx.remove(keys.owner.as_bytes())?;
+        }
+    }
+    let entry = match bincode::serialize(change) {
+        Ok(entry) => entry,
+        Err(err) => return abort(VersionedError::Bincode(err)),
+    };
+    let audit_key = format!("{}{:016X}", AUDIT_PREFIX, tx.generate_id()?);
+    tx.insert(audit_key.as_bytes(), entry)?;
+    Ok(())
+}
+
 pub fn get_auth(root: &Root, char_id: u32) -> Result<Option<sled::IVec>, VersionedError> {
     let result = root
         .trunk(char_id, None, CharTrunk::default())
@@ -42,3 +248,87 @@ pub fn get_auth(root: &Root, char_id: u32) -> Result<Option<sled::IVec>, Version
         Err(err) => Err(err),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn root() -> Root {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        Root::new(db.open_tree("fo4rp").unwrap())
+    }
+
+    #[test]
+    fn claim() {
+        let root = root();
+        set_ownership(&root, 1, 100).unwrap();
+        set_ownership(&root, 1, 100).unwrap();
+        set_ownership(&root, 2, 100).unwrap();
+        assert!(matches!(
+            set_ownership(&root, 1, 200),
+            Err(VersionedError::AccessDenied)
+        ));
+        assert_eq!(get_ownership(&root, 1).unwrap(), Some(100));
+        assert_eq!(characters_of(&root, 100).unwrap(), vec![1, 2]);
+        assert!(characters_of(&root, 200).unwrap().is_empty());
+
+        let log = ownership_log(&root, None).unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(
+            (log[0].char_id, log[0].to, log[0].gm_id),
+            (1, Some(100), None)
+        );
+    }
+
+    #[test]
+    fn transfer_and_revoke() {
+        let root = root();
+        set_ownership(&root, 1, 100).unwrap();
+        assert_eq!(
+            transfer_ownership(&root, 1, Some(200), 7, "account sold").unwrap(),
+            Some(100)
+        );
+        assert_eq!(get_ownership(&root, 1).unwrap(), Some(200));
+        assert!(characters_of(&root, 100).unwrap().is_empty());
+        assert_eq!(characters_of(&root, 200).unwrap(), vec![1]);
+
+        assert_eq!(
+            transfer_ownership(&root, 1, None, 7, "").unwrap(),
+            Some(200)
+        );
+        assert_eq!(get_ownership(&root, 1).unwrap(), None);
+        assert!(characters_of(&root, 200).unwrap().is_empty());
+        // free to claim again
+        set_ownership(&root, 1, 300).unwrap();
+
+        let log = ownership_log(&root, Some(1)).unwrap();
+        let owners: Vec<_> = log.iter().map(|change| (change.from, change.to)).collect();
+        assert_eq!(
+            owners,
+            vec![
+                (None, Some(100)),
+                (Some(100), Some(200)),
+                (Some(200), None),
+                (None, Some(300))
+            ]
+        );
+        assert_eq!(log[1].gm_id, Some(7));
+        assert_eq!(log[1].reason, "account sold");
+        assert!(ownership_log(&root, Some(2)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reindex() {
+        let root = root();
+        // Records written before the reverse index existed
+        for &(char_id, user_id) in &[(1u32, 100u64), (0xAB, 100), (3, 200)] {
+            let key = format!("char/{:08X}/owner_id", char_id);
+            root.tree().insert(key, &user_id.to_be_bytes()).unwrap();
+        }
+        root.tree().insert("char/00000001/ver", &[0; 4]).unwrap();
+        assert!(characters_of(&root, 100).unwrap().is_empty());
+        assert_eq!(reindex_owners(&root).unwrap(), 3);
+        assert_eq!(characters_of(&root, 100).unwrap(), vec![1, 0xAB]);
+        assert_eq!(characters_of(&root, 200).unwrap(), vec![3]);
+    }
+}
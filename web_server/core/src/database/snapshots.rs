@@ -0,0 +1,410 @@
+//! History of characters' saves, to see how a character changed over time.
+//!
+//! Snapshot is stored only if character changed since the previous one, as deflated bincode of
+//! non-zero params. Keys are character id and time, both big-endian, so snapshots of a character
+//! are sorted by time. Alerts are stored with the same keys, as bincode of their messages.
+use actix_web::error::BlockingError;
+use clients_db::CritterInfo;
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use fo_defines_fo4rp::param::Param;
+use serde::{Deserialize, Serialize};
+
+const HOUR: u64 = 60 * 60;
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    Sled(sled::Error),
+    Bincode(bincode::Error),
+    Blocking,
+}
+
+impl From<BlockingError> for SnapshotError {
+    fn from(_err: BlockingError) -> Self {
+        SnapshotError::Blocking
+    }
+}
+
+impl From<sled::Error> for SnapshotError {
+    fn from(err: sled::Error) -> Self {
+        SnapshotError::Sled(err)
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(err: bincode::Error) -> Self {
+        SnapshotError::Bincode(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Location {
+    pub map_id: u32,
+    pub map_pid: u16,
+    pub hex_x: u16,
+    pub hex_y: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unix time, in seconds.
+    pub time: u64,
+    pub location: Location,
+    pub cond: u8,
+    /// Non-zero params, sorted by index.
+    params: Vec<(u16, i32)>,
+}
+
+impl Snapshot {
+    pub fn new(time: u64, info: &CritterInfo) -> Self {
+        Snapshot {
+            time,
+            location: Location {
+                map_id: info.map_id,
+                map_pid: info.map_pid,
+                hex_x: info.hex_x,
+                hex_y: info.hex_y,
+            },
+            cond: info.cond,
+            params: info
+                .params
+                .iter()
+                .enumerate()
+                .filter(|(_, &value)| value != 0)
+                .map(|(index, &value)| (index as u16, value))
+                .collect(),
+        }
+    }
+    pub fn param(&self, param: Param) -> i32 {
+        self.param_by_index(param as u16)
+    }
+    fn param_by_index(&self, index: u16) -> i32 {
+        self.params
+            .binary_search_by_key(&index, |&(index, _)| index)
+            .map_or(0, |pos| self.params[pos].1)
+    }
+    fn same_state(&self, other: &Snapshot) -> bool {
+        self.location == other.location && self.cond == other.cond && self.params == other.params
+    }
+    fn encode(&self) -> Result<Vec<u8>, bincode::Error> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        bincode::serialize_into(&mut encoder, self)?;
+        Ok(encoder.finish()?)
+    }
+    fn decode(bytes: &[u8]) -> Result<Self, bincode::Error> {
+        bincode::deserialize_from(DeflateDecoder::new(bytes))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ParamChange {
+    pub name: String,
+    pub old: i32,
+    pub new: i32,
+}
+
+/// Changes between two snapshots of the same character, grouped for the GM view.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SnapshotDiff {
+    pub stats: Vec<ParamChange>,
+    pub skills: Vec<ParamChange>,
+    pub perks: Vec<ParamChange>,
+    pub other: Vec<ParamChange>,
+    pub location: Option<(Location, Location)>,
+    pub cond: Option<(u8, u8)>,
+}
+
+impl SnapshotDiff {
+    pub fn new(old: &Snapshot, new: &Snapshot) -> Self {
+        let mut diff = SnapshotDiff::default();
+        let mut indexes: Vec<u16> = old
+            .params
+            .iter()
+            .chain(&new.params)
+            .map(|&(index, _)| index)
+            .collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+        for index in indexes {
+            let (old_value, new_value) = (old.param_by_index(index), new.param_by_index(index));
+            if old_value == new_value {
+                continue;
+            }
+            let name = Param::ALL
+                .iter()
+                .find(|&&param| param as u16 == index)
+                .map(|param| param.name());
+            let group = match name {
+                Some(name) if name.starts_with("ST_") => &mut diff.stats,
+                Some(name) if name.starts_with("SK_") => &mut diff.skills,
+                Some(name) if name.starts_with("PE_") => &mut diff.perks,
+                _ => &mut diff.other,
+            };
+            group.push(ParamChange {
+                name: name.map_or_else(|| format!("#{}", index), String::from),
+                old: old_value,
+                new: new_value,
+            });
+        }
+        if old.location != new.location {
+            diff.location = Some((old.location, new.location));
+        }
+        if old.cond != new.cond {
+            diff.cond = Some((old.cond, new.cond));
+        }
+        diff
+    }
+    pub fn is_empty(&self) -> bool {
+        self.stats.is_empty()
+            && self.skills.is_empty()
+            && self.perks.is_empty()
+            && self.other.is_empty()
+            && self.location.is_none()
+            && self.cond.is_none()
+    }
+}
+
+/// Hook checking consecutive snapshots of a character for suspicious changes.
+pub trait AlertRule: Send + Sync {
+    /// Describes what's wrong, `None` if change looks fine.
+    fn check(&self, prev: &Snapshot, next: &Snapshot) -> Option<String>;
+}
+
+/// Param grown faster than `per_hour`. Gaps shorter than an hour count as a full hour, so
+/// a single quest reward doesn't look like a huge rate.
+#[derive(Debug, Clone, Copy)]
+pub struct ParamRate {
+    pub param: Param,
+    pub per_hour: i32,
+}
+
+impl AlertRule for ParamRate {
+    fn check(&self, prev: &Snapshot, next: &Snapshot) -> Option<String> {
+        let gained = i64::from(next.param(self.param)) - i64::from(prev.param(self.param));
+        let elapsed = next.time.saturating_sub(prev.time).max(HOUR);
+        let rate = gained * HOUR as i64 / elapsed as i64;
+        if rate > i64::from(self.per_hour) {
+            Some(format!(
+                "{} gained {} in {} min, {} per hour",
+                self.param.name(),
+                gained,
+                next.time.saturating_sub(prev.time) / 60,
+                rate
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Alert {
+    pub id: u32,
+    pub time: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Recorded {
+    /// Number of characters with new snapshot.
+    pub stored: usize,
+    pub alerts: Vec<Alert>,
+}
+
+#[derive(Clone)]
+pub struct CharacterHistory {
+    tree: sled::Tree,
+    alerts: sled::Tree,
+}
+
+fn key(id: u32, time: u64) -> [u8; 12] {
+    let mut key = [0; 12];
+    key[..4].copy_from_slice(&id.to_be_bytes());
+    key[4..].copy_from_slice(&time.to_be_bytes());
+    key
+}
+
+impl CharacterHistory {
+    pub fn new(tree: sled::Tree, alerts: sled::Tree) -> Self {
+        CharacterHistory { tree, alerts }
+    }
+    /// Stores snapshots of changed characters, checks them against alert rules and stores
+    /// raised alerts.
+    pub fn record<'a>(
+        &self,
+        time: u64,
+        clients: impl IntoIterator<Item = &'a CritterInfo>,
+        rules: &[Box<dyn AlertRule>],
+    ) -> Result<Recorded, SnapshotError> {
+        let mut recorded = Recorded::default();
+        for info in clients {
+            let snapshot = Snapshot::new(time, info);
+            let prev = self.last(info.id)?;
+            if let Some(prev) = &prev {
+                if prev.same_state(&snapshot) {
+                    continue;
+                }
+            }
+            self.tree.insert(key(info.id, time), snapshot.encode()?)?;
+            recorded.stored += 1;
+            if let Some(prev) = &prev {
+                let messages: Vec<String> = rules
+                    .iter()
+                    .filter_map(|rule| rule.check(prev, &snapshot))
+                    .collect();
+                if !messages.is_empty() {
+                    self.alerts
+                        .insert(key(info.id, time), bincode::serialize(&messages)?)?;
+                }
+                recorded
+                    .alerts
+                    .extend(messages.into_iter().map(|message| Alert {
+                        id: info.id,
+                        time,
+                        message,
+                    }));
+            }
+        }
+        Ok(recorded)
+    }
+    /// Times of all stored snapshots of character, oldest first.
+    pub fn times(&self, id: u32) -> Result<Vec<u64>, SnapshotError> {
+        self.tree
+            .scan_prefix(id.to_be_bytes())
+            .keys()
+            .map(|key| {
+                let key = key?;
+                let mut time = [0; 8];
+                time.copy_from_slice(&key[4..]);
+                Ok(u64::from_be_bytes(time))
+            })
+            .collect()
+    }
+    pub fn get(&self, id: u32, time: u64) -> Result<Option<Snapshot>, SnapshotError> {
+        match self.tree.get(key(id, time))? {
+            Some(bytes) => Ok(Some(Snapshot::decode(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+    /// Stored alerts of character, newest first.
+    pub fn alerts(&self, id: u32) -> Result<Vec<Alert>, SnapshotError> {
+        let mut alerts = vec![];
+        for entry in self.alerts.scan_prefix(id.to_be_bytes()).rev() {
+            let (key, bytes) = entry?;
+            let mut time = [0; 8];
+            time.copy_from_slice(&key[4..]);
+            let time = u64::from_be_bytes(time);
+            let messages: Vec<String> = bincode::deserialize(&bytes)?;
+            alerts.extend(
+                messages
+                    .into_iter()
+                    .map(|message| Alert { id, time, message }),
+            );
+        }
+        Ok(alerts)
+    }
+    pub fn last(&self, id: u32) -> Result<Option<Snapshot>, SnapshotError> {
+        match self.tree.scan_prefix(id.to_be_bytes()).values().next_back() {
+            Some(bytes) => Ok(Some(Snapshot::decode(&bytes?)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn history() -> CharacterHistory {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        CharacterHistory::new(
+            db.open_tree("snapshots").unwrap(),
+            db.open_tree("snapshot_alerts").unwrap(),
+        )
+    }
+
+    fn info(id: u32, exp: i32) -> CritterInfo {
+        let mut params = [0; 1000];
+        params[Param::ST_EXPERIENCE as usize] = exp;
+        params[Param::SK_SMALL_GUNS as usize] = 50;
+        CritterInfo {
+            id,
+            hex_x: 10,
+            hex_y: 20,
+            dir: 0,
+            cond: 1,
+            map_id: 1,
+            map_pid: 2,
+            params,
+            name: format!("client{}", id),
+            ip: Default::default(),
+        }
+    }
+
+    fn exp_rule() -> Vec<Box<dyn AlertRule>> {
+        vec![Box::new(ParamRate {
+            param: Param::ST_EXPERIENCE,
+            per_hour: 10_000,
+        })]
+    }
+
+    #[test]
+    fn stores_only_changes() {
+        let history = history();
+        let rules = exp_rule();
+        let recorded = history
+            .record(1000, &[info(1, 0), info(2, 0)], &rules)
+            .unwrap();
+        assert_eq!(recorded.stored, 2);
+        let recorded = history
+            .record(2000, &[info(1, 0), info(2, 500)], &rules)
+            .unwrap();
+        assert_eq!(recorded.stored, 1);
+        assert!(recorded.alerts.is_empty());
+        assert_eq!(history.times(1).unwrap(), vec![1000]);
+        assert_eq!(history.times(2).unwrap(), vec![1000, 2000]);
+        let last = history.last(2).unwrap().unwrap();
+        assert_eq!(last.param(Param::ST_EXPERIENCE), 500);
+        assert_eq!(history.get(2, 1000).unwrap().unwrap().time, 1000);
+    }
+
+    #[test]
+    fn diff_groups_params() {
+        let old = info(1, 100);
+        let mut new = info(1, 300);
+        new.params[Param::SK_SMALL_GUNS as usize] = 60;
+        new.params[Param::PE_BOOKWORM as usize] = 1;
+        new.params[999] = 7;
+        new.map_pid = 3;
+        let diff = SnapshotDiff::new(&Snapshot::new(0, &old), &Snapshot::new(60, &new));
+        let change = |name: &str, old, new| ParamChange {
+            name: name.into(),
+            old,
+            new,
+        };
+        assert_eq!(diff.stats, vec![change("ST_EXPERIENCE", 100, 300)]);
+        assert_eq!(diff.skills, vec![change("SK_SMALL_GUNS", 50, 60)]);
+        assert_eq!(diff.perks, vec![change("PE_BOOKWORM", 0, 1)]);
+        assert_eq!(diff.other, vec![change("#999", 0, 7)]);
+        let (from, to) = diff.location.unwrap();
+        assert_eq!((from.map_pid, to.map_pid), (2, 3));
+        assert!(diff.cond.is_none());
+    }
+
+    #[test]
+    fn alerts_on_experience_rate() {
+        let history = history();
+        let rules = exp_rule();
+        history.record(0, &[info(1, 0)], &rules).unwrap();
+        // quick gain below the hourly threshold
+        let recorded = history.record(600, &[info(1, 9_000)], &rules).unwrap();
+        assert!(recorded.alerts.is_empty());
+        let recorded = history.record(1200, &[info(1, 30_000)], &rules).unwrap();
+        assert_eq!(recorded.alerts.len(), 1);
+        assert_eq!(recorded.alerts[0].id, 1);
+        assert!(recorded.alerts[0]
+            .message
+            .starts_with("ST_EXPERIENCE gained 21000"));
+        assert_eq!(history.alerts(1).unwrap(), recorded.alerts);
+        assert!(history.alerts(2).unwrap().is_empty());
+    }
+}
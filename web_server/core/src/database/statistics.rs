@@ -0,0 +1,307 @@
+//! Rolling history of `ServerStatistics` sent by the server DLL.
+//!
+//! Every received snapshot is kept as a raw sample for a couple of days and merged into
+//! hourly and daily buckets, which live much longer.
+use actix_web::error::BlockingError;
+use protocol::message::server_dll_web::ServerStatistics;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const LAST_KEY: &[u8] = b"last";
+
+#[derive(Debug)]
+pub enum StatisticsError {
+    Sled(sled::Error),
+    Bincode(bincode::Error),
+    Blocking,
+}
+
+impl From<BlockingError> for StatisticsError {
+    fn from(_err: BlockingError) -> Self {
+        StatisticsError::Blocking
+    }
+}
+
+impl From<sled::Error> for StatisticsError {
+    fn from(err: sled::Error) -> Self {
+        StatisticsError::Sled(err)
+    }
+}
+
+impl From<bincode::Error> for StatisticsError {
+    fn from(err: bincode::Error) -> Self {
+        StatisticsError::Bincode(err)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    Raw,
+    #[default]
+    Hour,
+    Day,
+}
+
+impl Resolution {
+    const ALL: [Resolution; 3] = [Resolution::Raw, Resolution::Hour, Resolution::Day];
+
+    fn prefix(self) -> &'static [u8] {
+        match self {
+            Resolution::Raw => b"raw/",
+            Resolution::Hour => b"hour/",
+            Resolution::Day => b"day/",
+        }
+    }
+    fn key(self, time: u64) -> Vec<u8> {
+        let mut key = self.prefix().to_vec();
+        key.extend_from_slice(&time.to_be_bytes());
+        key
+    }
+    /// Start of the bucket `time` belongs to.
+    fn bucket(self, time: u64) -> u64 {
+        match self {
+            Resolution::Raw => time,
+            Resolution::Hour => time - time % HOUR,
+            Resolution::Day => time - time % DAY,
+        }
+    }
+    /// How long samples are kept, `None` for forever.
+    fn retention(self) -> Option<u64> {
+        match self {
+            Resolution::Raw => Some(2 * DAY),
+            Resolution::Hour => Some(90 * DAY),
+            Resolution::Day => None,
+        }
+    }
+    /// Period shown by default.
+    pub fn default_period(self) -> u64 {
+        match self {
+            Resolution::Raw => DAY,
+            Resolution::Hour => 7 * DAY,
+            Resolution::Day => 365 * DAY,
+        }
+    }
+}
+
+/// Statistics aggregated over a bucket of time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Sample {
+    /// Unix time of the bucket start, in seconds.
+    pub time: u64,
+    /// Number of raw samples merged into this one.
+    pub count: u32,
+    pub online_avg: f32,
+    pub online_max: u32,
+    pub fps_avg: f32,
+    pub fps_min: u32,
+    pub loop_avg: f32,
+    pub loop_max: u32,
+    /// Lags happened during the bucket.
+    pub lags: u32,
+    /// Bytes sent during the bucket.
+    pub bytes_send: i64,
+    /// Bytes received during the bucket.
+    pub bytes_recv: i64,
+    pub compress_ratio: f32,
+    /// Server uptime at the end of the bucket.
+    pub uptime: u32,
+}
+
+impl Sample {
+    /// Makes sample out of cumulative counters, `prev` is previous snapshot, if any.
+    pub fn from_raw(time: u64, stats: &ServerStatistics, prev: Option<&ServerStatistics>) -> Self {
+        // Counters are reset on server restart, so uptime going backwards means new run.
+        let prev = prev.filter(|prev| {
+            prev.uptime <= stats.uptime && prev.server_start_tick == stats.server_start_tick
+        });
+        let (lags, bytes_send, bytes_recv) = match prev {
+            Some(prev) => (
+                stats.lags_count.saturating_sub(prev.lags_count),
+                (stats.bytes_send - prev.bytes_send).max(0),
+                (stats.bytes_recv - prev.bytes_recv).max(0),
+            ),
+            None => (stats.lags_count, stats.bytes_send, stats.bytes_recv),
+        };
+        Sample {
+            time,
+            count: 1,
+            online_avg: stats.cur_online as f32,
+            online_max: stats.cur_online,
+            fps_avg: stats.fps as f32,
+            fps_min: stats.fps,
+            loop_avg: stats.loop_time as f32,
+            loop_max: stats.loop_max,
+            lags,
+            bytes_send,
+            bytes_recv,
+            compress_ratio: stats.compress_ratio,
+            uptime: stats.uptime,
+        }
+    }
+    /// Merges later sample into this one, keeping own bucket time.
+    pub fn merge(&mut self, other: &Sample) {
+        if self.count == 0 {
+            *self = Sample {
+                time: self.time,
+                ..other.clone()
+            };
+            return;
+        }
+        let (own_count, other_count) = (self.count as f32, other.count as f32);
+        let count = self.count + other.count;
+        let weighted = |own: f32, other_val: f32| {
+            (own * own_count + other_val * other_count) / (own_count + other_count)
+        };
+        self.online_avg = weighted(self.online_avg, other.online_avg);
+        self.fps_avg = weighted(self.fps_avg, other.fps_avg);
+        self.loop_avg = weighted(self.loop_avg, other.loop_avg);
+        self.compress_ratio = weighted(self.compress_ratio, other.compress_ratio);
+        self.online_max = self.online_max.max(other.online_max);
+        self.fps_min = self.fps_min.min(other.fps_min);
+        self.loop_max = self.loop_max.max(other.loop_max);
+        self.lags += other.lags;
+        self.bytes_send += other.bytes_send;
+        self.bytes_recv += other.bytes_recv;
+        self.uptime = other.uptime;
+        self.count = count;
+    }
+}
+
+#[derive(Clone)]
+pub struct StatisticsHistory {
+    tree: sled::Tree,
+}
+
+impl StatisticsHistory {
+    pub fn new(tree: sled::Tree) -> Self {
+        StatisticsHistory { tree }
+    }
+    /// Stores snapshot received at `time` and drops samples older than retention period.
+    pub fn record(&self, time: u64, stats: &ServerStatistics) -> Result<Sample, StatisticsError> {
+        let prev = match self.tree.get(LAST_KEY)? {
+            Some(bytes) => Some(bincode::deserialize::<ServerStatistics>(&bytes)?),
+            None => None,
+        };
+        let sample = Sample::from_raw(time, stats, prev.as_ref());
+        for &resolution in &Resolution::ALL {
+            let bucket = resolution.bucket(time);
+            let key = resolution.key(bucket);
+            let mut merged = match self.tree.get(&key)? {
+                Some(bytes) if resolution != Resolution::Raw => bincode::deserialize(&bytes)?,
+                _ => Sample {
+                    time: bucket,
+                    ..Default::default()
+                },
+            };
+            merged.merge(&sample);
+            self.tree.insert(key, bincode::serialize(&merged)?)?;
+            if let Some(retention) = resolution.retention() {
+                self.prune(resolution, time.saturating_sub(retention))?;
+            }
+        }
+        self.tree.insert(LAST_KEY, bincode::serialize(stats)?)?;
+        Ok(sample)
+    }
+    /// Samples with bucket time not earlier than `since`.
+    pub fn query(
+        &self,
+        resolution: Resolution,
+        since: u64,
+    ) -> Result<Vec<Sample>, StatisticsError> {
+        let from = resolution.key(resolution.bucket(since));
+        let to = resolution.key(u64::MAX);
+        self.tree
+            .range(from..=to)
+            .values()
+            .map(|value| Ok(bincode::deserialize(&value?)?))
+            .collect()
+    }
+    fn prune(&self, resolution: Resolution, before: u64) -> Result<(), StatisticsError> {
+        let from = resolution.key(0);
+        let to = resolution.key(before);
+        for key in self.tree.range(from..to).keys() {
+            self.tree.remove(key?)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn history() -> StatisticsHistory {
+        let db = sled::Config::new().temporary(true).open().unwrap();
+        StatisticsHistory::new(db.open_tree("statistics").unwrap())
+    }
+
+    fn stats(uptime: u32, online: u32, lags_count: u32) -> ServerStatistics {
+        ServerStatistics {
+            uptime,
+            cur_online: online,
+            lags_count,
+            fps: 100,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn lags_are_counted_between_snapshots() {
+        let first = Sample::from_raw(0, &stats(10, 1, 5), None);
+        assert_eq!(first.lags, 5);
+        let second = Sample::from_raw(60, &stats(70, 1, 7), Some(&stats(10, 1, 5)));
+        assert_eq!(second.lags, 2);
+        // server restarted
+        let restarted = Sample::from_raw(120, &stats(5, 1, 1), Some(&stats(70, 1, 7)));
+        assert_eq!(restarted.lags, 1);
+    }
+
+    #[test]
+    fn merge_weights_averages() {
+        let mut sample = Sample::from_raw(0, &stats(10, 2, 0), None);
+        sample.merge(&Sample::from_raw(60, &stats(70, 4, 0), None));
+        sample.merge(&Sample::from_raw(120, &stats(130, 6, 0), None));
+        assert_eq!(sample.time, 0);
+        assert_eq!(sample.count, 3);
+        assert_eq!(sample.online_avg, 4.0);
+        assert_eq!(sample.online_max, 6);
+        assert_eq!(sample.uptime, 130);
+    }
+
+    #[test]
+    fn record_and_downsample() {
+        let history = history();
+        let start = 10 * DAY;
+        for minute in 0..120 {
+            let time = start + minute * 60;
+            let uptime = (minute * 60) as u32;
+            history
+                .record(time, &stats(uptime, minute as u32 % 10, minute as u32))
+                .unwrap();
+        }
+        assert_eq!(history.query(Resolution::Raw, start).unwrap().len(), 120);
+        let hours = history.query(Resolution::Hour, start).unwrap();
+        assert_eq!(hours.len(), 2);
+        assert_eq!(hours[0].time, start);
+        assert_eq!(hours[0].count, 60);
+        assert_eq!(hours[1].lags, 60);
+        assert_eq!(history.query(Resolution::Day, 0).unwrap().len(), 1);
+
+        // raw samples fall out of retention
+        history
+            .record(start + 3 * DAY, &stats(3 * DAY as u32, 0, 120))
+            .unwrap();
+        assert_eq!(history.query(Resolution::Raw, 0).unwrap().len(), 1);
+        assert_eq!(history.query(Resolution::Hour, 0).unwrap().len(), 3);
+    }
+}
@@ -48,7 +48,7 @@ impl<'a, T: Bark> Trunk<'a, T> {
     pub fn bark(&self) -> &T {
         &self.bark
     }
-    fn branch_key(&self, branch: &str) -> Result<String, VersionedError> {
+    pub(super) fn branch_key(&self, branch: &str) -> Result<String, VersionedError> {
         let mut key = String::with_capacity(32);
         write!(key, "{}/{:08X}/{}", self.bark.trunk(), self.id, branch)
             .map_err(VersionedError::WriteFmt)?;
@@ -24,6 +24,7 @@ pub enum VersionedError {
     NotFound,
     Blocking,
     ConcurrentWrites,
+    Bincode(bincode::Error),
 }
 
 impl From<BlockingError> for VersionedError {
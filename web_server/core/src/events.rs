@@ -0,0 +1,323 @@
+//! Feed of game events to Discord channels: routing by kind, templated text,
+//! batching and per-channel rate limiting.
+use crate::{
+    chat::MessageSink,
+    config,
+    templates::{self, TemplatesError},
+};
+use protocol::message::server_dll_web::{GameEvent, GameEventKind};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// Discord limit for message length.
+const MAX_MESSAGE_LEN: usize = 2000;
+/// Lines kept per channel while it's rate limited, older ones are dropped.
+const MAX_PENDING: usize = 200;
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Event as seen by templates, fields missing in the event are absent.
+#[derive(Debug, Serialize)]
+pub struct EventView<'a> {
+    kind: GameEventKind,
+    id: Option<u32>,
+    name: Option<&'a str>,
+    killer: Option<&'a str>,
+    map_pid: Option<u16>,
+    action: Option<&'a str>,
+}
+
+impl<'a> EventView<'a> {
+    pub fn new(event: &'a GameEvent) -> Self {
+        let mut view = EventView {
+            kind: event.kind(),
+            id: None,
+            name: None,
+            killer: None,
+            map_pid: None,
+            action: None,
+        };
+        match event {
+            GameEvent::PlayerLogin { id, name } | GameEvent::PlayerLogout { id, name } => {
+                view.id = Some(*id);
+                view.name = Some(name);
+            }
+            GameEvent::Death {
+                id,
+                name,
+                killer,
+                map_pid,
+            } => {
+                view.id = Some(*id);
+                view.name = Some(name);
+                view.killer = killer.as_deref();
+                view.map_pid = Some(*map_pid);
+            }
+            GameEvent::GmAction { id, name, action } => {
+                view.id = Some(*id);
+                view.name = Some(name);
+                view.action = Some(action);
+            }
+            GameEvent::ServerStart | GameEvent::ServerStop => {}
+        }
+        view
+    }
+}
+
+pub fn render_event(template: &str, event: &GameEvent) -> Result<String, TemplatesError> {
+    templates::render(template, &EventView::new(event), Default::default())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Outgoing {
+    pub channel: String,
+    pub text: String,
+}
+
+#[derive(Debug, Default)]
+struct ChannelQueue {
+    pending: VecDeque<String>,
+    dropped: usize,
+    /// Send times within the rate window.
+    sent: VecDeque<Instant>,
+}
+
+impl ChannelQueue {
+    fn push(&mut self, line: String) {
+        if self.pending.len() >= MAX_PENDING {
+            self.pending.pop_front();
+            self.dropped += 1;
+        }
+        self.pending.push_back(line);
+    }
+    fn allowed(&mut self, now: Instant, max_per_minute: usize) -> bool {
+        while let Some(&time) = self.sent.front() {
+            if now.saturating_duration_since(time) >= RATE_WINDOW {
+                self.sent.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.sent.len() < max_per_minute
+    }
+    /// Joins pending lines into one message that fits Discord limit.
+    fn take_message(&mut self) -> String {
+        let mut text = String::new();
+        if self.dropped > 0 {
+            text = format!("... {} events skipped", self.dropped);
+            self.dropped = 0;
+        }
+        while let Some(line) = self.pending.front() {
+            let len = text.len() + line.len() + 1;
+            if !text.is_empty() && len > MAX_MESSAGE_LEN {
+                break;
+            }
+            let line = self.pending.pop_front().expect("Checked above");
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(truncate(&line, MAX_MESSAGE_LEN));
+        }
+        text
+    }
+}
+
+fn truncate(line: &str, max: usize) -> &str {
+    if line.len() <= max {
+        return line;
+    }
+    let mut end = max;
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    &line[..end]
+}
+
+pub struct EventFeed {
+    routes: Vec<config::EventRoute>,
+    max_per_minute: usize,
+    queues: BTreeMap<String, ChannelQueue>,
+}
+
+impl EventFeed {
+    pub fn new(config: &config::Events) -> Self {
+        EventFeed {
+            routes: config.routes.clone(),
+            max_per_minute: config.max_per_minute.max(1),
+            queues: BTreeMap::new(),
+        }
+    }
+    /// Queues event for every route that wants its kind, `render` formats it with route's template.
+    pub fn push<R>(&mut self, event: &GameEvent, mut render: R)
+    where
+        R: FnMut(&str, &GameEvent) -> Result<String, TemplatesError>,
+    {
+        let kind = event.kind();
+        for route in &self.routes {
+            if !route.kinds.contains(&kind) {
+                continue;
+            }
+            match render(&route.template, event) {
+                Ok(text) => {
+                    let text = text.trim();
+                    if !text.is_empty() {
+                        self.queues
+                            .entry(route.channel.clone())
+                            .or_default()
+                            .push(text.to_owned());
+                    }
+                }
+                Err(err) => eprintln!("Can't render {:?} with {}: {}", kind, route.template, err),
+            }
+        }
+    }
+    /// Messages to send now, channels over the rate limit keep their events for later.
+    pub fn flush(&mut self, now: Instant) -> Vec<Outgoing> {
+        let mut messages = vec![];
+        for (channel, queue) in &mut self.queues {
+            while (!queue.pending.is_empty() || queue.dropped > 0)
+                && queue.allowed(now, self.max_per_minute)
+            {
+                queue.sent.push_back(now);
+                messages.push(Outgoing {
+                    channel: channel.clone(),
+                    text: queue.take_message(),
+                });
+            }
+        }
+        messages
+    }
+}
+
+pub async fn deliver<S: MessageSink + ?Sized>(sink: &S, messages: Vec<Outgoing>) {
+    for Outgoing { channel, text } in messages {
+        if let Err(err) = sink.send_message(channel.clone(), text).await {
+            eprintln!("Can't send events to {:?}: {}", channel, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chat::InMemoryChat;
+
+    fn feed(max_per_minute: usize) -> EventFeed {
+        let route = |channel: &str, kinds: &[GameEventKind]| config::EventRoute {
+            channel: channel.into(),
+            kinds: kinds.to_vec(),
+            template: "event.txt".into(),
+        };
+        EventFeed::new(&config::Events {
+            batch_seconds: 5,
+            max_per_minute,
+            routes: vec![
+                route(
+                    "game",
+                    &[
+                        GameEventKind::Login,
+                        GameEventKind::Logout,
+                        GameEventKind::Death,
+                    ],
+                ),
+                route("gm", &[GameEventKind::GmAction, GameEventKind::Death]),
+            ],
+        })
+    }
+
+    fn render(_template: &str, event: &GameEvent) -> Result<String, TemplatesError> {
+        let view = EventView::new(event);
+        Ok(format!("{:?} {}", view.kind, view.name.unwrap_or("-")))
+    }
+
+    fn login(name: &str) -> GameEvent {
+        GameEvent::PlayerLogin {
+            id: 1,
+            name: name.into(),
+        }
+    }
+
+    #[test]
+    fn routing_and_batching() {
+        let mut feed = feed(10);
+        feed.push(&login("Вася"), render);
+        feed.push(&GameEvent::ServerStart, render);
+        feed.push(
+            &GameEvent::Death {
+                id: 2,
+                name: "Петя".into(),
+                killer: None,
+                map_pid: 5,
+            },
+            render,
+        );
+        let now = Instant::now();
+        assert_eq!(
+            feed.flush(now),
+            vec![
+                Outgoing {
+                    channel: "game".into(),
+                    text: "Login Вася\nDeath Петя".into()
+                },
+                Outgoing {
+                    channel: "gm".into(),
+                    text: "Death Петя".into()
+                },
+            ]
+        );
+        assert!(feed.flush(now).is_empty());
+    }
+
+    #[test]
+    fn rate_limit() {
+        let mut feed = feed(2);
+        let start = Instant::now();
+        for round in 0..3 {
+            feed.push(&login(&format!("player{}", round)), render);
+            let sent = feed.flush(start + Duration::from_secs(round));
+            assert_eq!(sent.len(), if round < 2 { 1 } else { 0 });
+        }
+        // Held back event goes out when the window moves
+        let sent = feed.flush(start + RATE_WINDOW);
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].text, "Login player2");
+    }
+
+    #[test]
+    fn long_batches_are_split() {
+        let mut feed = feed(10);
+        let name = "x".repeat(900);
+        for _ in 0..3 {
+            feed.push(&login(&name), render);
+        }
+        let sent = feed.flush(Instant::now());
+        assert_eq!(sent.len(), 2);
+        assert!(sent.iter().all(|msg| msg.text.len() <= MAX_MESSAGE_LEN));
+        assert_eq!(sent[0].text.lines().count(), 2);
+    }
+
+    #[test]
+    fn overflow_is_reported() {
+        let mut feed = feed(1);
+        let now = Instant::now();
+        for _ in 0..MAX_PENDING + 5 {
+            feed.push(&login("a"), render);
+        }
+        let sent = feed.flush(now);
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].text.starts_with("... 5 events skipped\nLogin a"));
+    }
+
+    #[test]
+    fn delivers_to_chat() {
+        let chat = InMemoryChat::new();
+        let messages = vec![Outgoing {
+            channel: "game".into(),
+            text: "hello".into(),
+        }];
+        futures::executor::block_on(deliver(&chat, messages));
+        assert_eq!(chat.messages(), vec![("game".into(), "hello".into())]);
+    }
+}
@@ -1,7 +1,9 @@
 pub mod bridge;
+pub mod chat;
 pub mod config;
 pub mod critters_db;
 pub mod database;
+pub mod events;
 mod templates;
 pub mod utils;
 pub mod web;
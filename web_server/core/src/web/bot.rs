@@ -0,0 +1,72 @@
+use super::{meta::role_name_to_rank, web, AppState};
+use crate::database::ownership::{characters_of, get_ownership};
+use fo_defines_fo4rp::param::Param;
+use mrhandy::commands::{CharSummary, GmBackend, Rank, ServerState};
+
+/// Game data for GM commands of the Discord bot.
+pub struct BotBackend {
+    state: web::Data<AppState>,
+}
+
+impl BotBackend {
+    pub fn new(state: web::Data<AppState>) -> Self {
+        BotBackend { state }
+    }
+}
+
+impl GmBackend for BotBackend {
+    fn role_rank(&self, role: &str) -> Rank {
+        match &self.state.config.discord {
+            Some(discord) => role_name_to_rank(&discord.roles, role),
+            None => Rank::Unknown,
+        }
+    }
+    fn server_state(&self) -> ServerState {
+        // Status updater holds the lock while it posts the new status, don't guess meanwhile
+        match self.state.server_status.try_lock() {
+            Ok(status) => status.state(),
+            Err(_) => ServerState::Unknown,
+        }
+    }
+    fn character(&self, name: &str) -> Option<CharSummary> {
+        let info = self.state.critters_db.client_info(name).ok()?;
+        let owner = get_ownership(&self.state.sled_db.root, info.id)
+            .map_err(|err| eprintln!("Can't get owner of {}: {:?}", info.id, err))
+            .ok()
+            .flatten();
+        Some(CharSummary {
+            name: info.name.clone(),
+            id: info.id,
+            level: info.param(Param::ST_LEVEL),
+            hp: info.param(Param::ST_CURRENT_HP),
+            cond: info.cond().into(),
+            map_pid: info.map_pid,
+            owner,
+        })
+    }
+    fn characters_of(&self, user_id: u64) -> Vec<String> {
+        let ids = match characters_of(&self.state.sled_db.root, user_id) {
+            Ok(ids) => ids,
+            Err(err) => {
+                eprintln!("Can't get characters of {}: {:?}", user_id, err);
+                return vec![];
+            }
+        };
+        let clients = self.state.critters_db.list_clients();
+        ids.into_iter()
+            .map(|id| match clients.index().by_id(id) {
+                Some(name) => name.to_owned(),
+                None => format!("#{}", id),
+            })
+            .collect()
+    }
+    fn announce_channel(&self) -> Option<String> {
+        self.state
+            .config
+            .discord
+            .as_ref()?
+            .bot
+            .announce_channel
+            .clone()
+    }
+}
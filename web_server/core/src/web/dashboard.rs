@@ -0,0 +1,70 @@
+use super::{web, AppState, HttpResponse};
+use crate::{
+    config::Host,
+    database::statistics::{unix_time, Resolution, Sample},
+    templates,
+    utils::blocking,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    #[serde(default)]
+    resolution: Resolution,
+    /// Period in hours, default depends on resolution.
+    hours: Option<u64>,
+}
+
+async fn history(
+    data: &web::Data<AppState>,
+    query: &HistoryQuery,
+) -> actix_web::Result<Vec<Sample>> {
+    let resolution = query.resolution;
+    let period = query
+        .hours
+        .map(|hours| hours * 60 * 60)
+        .unwrap_or_else(|| resolution.default_period());
+    let since = unix_time().saturating_sub(period);
+    let history = data.sled_db.statistics.clone();
+    blocking(move || history.query(resolution, since))
+        .await
+        .map_err(super::internal_error)
+}
+
+pub async fn statistics_json(
+    data: web::Data<AppState>,
+    query: web::Query<HistoryQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let samples = history(&data, &query).await?;
+    Ok(HttpResponse::Ok().json(samples))
+}
+
+pub async fn statistics(
+    data: web::Data<AppState>,
+    query: web::Query<HistoryQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let samples = history(&data, &query).await?;
+    let body = Dashboard {
+        resolution: query.resolution,
+        samples,
+    }
+    .render(&data.config.host)
+    .map_err(super::internal_error)?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
+#[derive(Debug, Serialize)]
+struct Dashboard {
+    resolution: Resolution,
+    samples: Vec<Sample>,
+}
+
+impl Dashboard {
+    fn render(&self, host: &Host) -> Result<String, templates::TemplatesError> {
+        templates::render(
+            "gm_statistics.html",
+            self,
+            templates::RenderConfig { host: Some(host) },
+        )
+    }
+}
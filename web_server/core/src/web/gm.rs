@@ -1,18 +1,18 @@
 use super::{web, AppState, HttpResponse};
 use crate::{
+    chat::ChatMembers,
     config::Host,
     database::{ownership::get_ownership, Root},
     templates,
 };
-use clients_db::{fix_encoding::os_str_debug, ClientRecord};
+use clients_db::{fix_encoding::os_str_debug, ClientRecord, ClientsDb};
 use fo_defines::CritterParam;
 use fo_defines_fo4rp::{fos, param::Param};
-use serde::Serialize;
-use std::{borrow::Cow, net::Ipv4Addr, time::Duration};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::BTreeSet, net::Ipv4Addr, time::Duration};
 
 pub async fn clients(data: web::Data<AppState>) -> actix_web::Result<HttpResponse> {
-    let mrhandy = data.mrhandy.as_ref().expect("Discord config");
-    let members = mrhandy.clone_members().await;
+    let members = data.chat.members().await;
     let res = web::block(move || {
         let clients = data.critters_db.list_clients();
         let list = ClientsList::new(
@@ -58,7 +58,7 @@ const GAMEMODS: [&'static str; fos::GAME_MAX as usize] =
     ["START", "ADVENTURE", "SURVIVAL", "ARCADE", "TEST"];
 
 fn get_name<'a>(
-    members: Option<&'a mrhandy::Members>,
+    members: Option<&'a ChatMembers>,
     root: &Root,
     id: u32,
 ) -> Result<OwnerInfo<'a>, &'static str> {
@@ -66,11 +66,11 @@ fn get_name<'a>(
     let owner = get_ownership(root, id)
         .map_err(|_| "Err")?
         .ok_or("No owner")?;
-    let member = match members.get(owner) {
+    let member = match members.get(&owner) {
         Some(member) => member,
         None => return Ok(OwnerInfo::Id(owner)),
     };
-    let name = member.user.name.as_str();
+    let name = member.name.as_str();
     Ok(match member.nick.as_ref() {
         None => OwnerInfo::Name(name),
         Some(nick) => OwnerInfo::NickName(name, nick.as_str()),
@@ -88,7 +88,7 @@ impl<'a> ClientsList<'a> {
     fn new<I: Iterator<Item = (&'a String, &'a ClientRecord)>>(
         clients: I,
         root: &Root,
-        members: Option<&'a mrhandy::Members>,
+        members: Option<&'a ChatMembers>,
     ) -> Self {
         Self {
             clients: clients
@@ -145,3 +145,134 @@ fn ago(duration: &Duration) -> (String, bool) {
         secs < 60 * 5,
     )
 }
+
+#[derive(Debug, Deserialize)]
+pub struct ClustersQuery {
+    /// Only clients seen during last days.
+    days: Option<u64>,
+}
+
+/// Builds the report on blocking pool and hands it to `output` there.
+async fn with_clusters_report<R, E, F>(
+    data: web::Data<AppState>,
+    query: ClustersQuery,
+    output: F,
+) -> actix_web::Result<R>
+where
+    F: FnOnce(&ClustersReport, &Host) -> Result<R, E> + Send + 'static,
+    R: Send + 'static,
+    E: std::fmt::Debug + Send + 'static,
+{
+    let members = data.chat.members().await;
+    let res = web::block(move || {
+        let clients = data.critters_db.list_clients();
+        let max_age = query
+            .days
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60));
+        let report = ClustersReport::new(&clients, max_age, &data.sled_db.root, members.as_ref());
+        output(&report, &data.config.host)
+    })
+    .await?
+    .map_err(super::internal_error)?;
+    Ok(res)
+}
+
+pub async fn clusters_json(
+    data: web::Data<AppState>,
+    query: web::Query<ClustersQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let report = with_clusters_report(data, query.into_inner(), |report, _host| {
+        serde_json::to_string(report)
+    })
+    .await?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(report))
+}
+
+pub async fn clusters(
+    data: web::Data<AppState>,
+    query: web::Query<ClustersQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let body = with_clusters_report(data, query.into_inner(), |report, host| {
+        templates::render(
+            "gm_clusters.html",
+            report,
+            templates::RenderConfig { host: Some(host) },
+        )
+    })
+    .await?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
+#[derive(Debug, Serialize)]
+struct ClustersReport<'a> {
+    days: Option<u64>,
+    clusters: Vec<ClusterRow<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClusterRow<'a> {
+    /// Number of distinct Discord owners, characters without one aren't counted.
+    owners: usize,
+    multi_owner: bool,
+    shared_ips: Vec<Ipv4Addr>,
+    members: Vec<ClusterMember<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct ClusterMember<'a> {
+    name: &'a str,
+    id: u32,
+    owner_id: Option<u64>,
+    discord: Result<OwnerInfo<'a>, &'static str>,
+    last_seen: Option<(String, bool)>,
+}
+
+impl<'a> ClustersReport<'a> {
+    fn new(
+        clients: &'a ClientsDb,
+        max_age: Option<Duration>,
+        root: &Root,
+        members: Option<&'a ChatMembers>,
+    ) -> Self {
+        let mut clusters: Vec<_> = clients
+            .ip_clusters(max_age)
+            .into_iter()
+            .map(|cluster| {
+                let members: Vec<_> = cluster
+                    .clients
+                    .iter()
+                    .map(|&(name, info)| ClusterMember {
+                        name,
+                        id: info.id,
+                        owner_id: get_ownership(root, info.id).ok().flatten(),
+                        discord: get_name(members, root, info.id),
+                        last_seen: clients
+                            .clients()
+                            .get(name)
+                            .and_then(|record| record.modified)
+                            .and_then(|time| time.elapsed().ok())
+                            .as_ref()
+                            .map(ago),
+                    })
+                    .collect();
+                let owners: BTreeSet<u64> = members.iter().filter_map(|m| m.owner_id).collect();
+                ClusterRow {
+                    owners: owners.len(),
+                    multi_owner: owners.len() > 1,
+                    shared_ips: cluster.shared_ips,
+                    members,
+                }
+            })
+            .collect();
+        // Clusters spanning several Discord users are the interesting ones.
+        clusters.sort_by(|a, b| {
+            (b.multi_owner, b.members.len()).cmp(&(a.multi_owner, a.members.len()))
+        });
+        ClustersReport {
+            days: max_age.map(|age| age.as_secs() / (24 * 60 * 60)),
+            clusters,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use super::{web, AppState, HttpResponse};
+use crate::{
+    config::Host,
+    database::snapshots::{Alert, ParamChange, Snapshot, SnapshotDiff, SnapshotError},
+    templates,
+    utils::blocking,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct DiffQuery {
+    /// Snapshot times, by default the last two snapshots are compared.
+    from: Option<u64>,
+    to: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct HistoryPage {
+    name: String,
+    id: u32,
+    times: Vec<u64>,
+    from: Option<u64>,
+    to: Option<u64>,
+    /// Both snapshots were found.
+    compared: bool,
+    diff: Vec<DiffGroup>,
+    /// Raised by alert rules, newest first.
+    alerts: Vec<Alert>,
+}
+
+#[derive(Debug, Serialize)]
+struct DiffGroup {
+    title: &'static str,
+    changes: Vec<ParamChange>,
+}
+
+fn diff_groups(diff: SnapshotDiff) -> Vec<DiffGroup> {
+    let mut location = vec![];
+    if let Some((old, new)) = diff.location {
+        let fields = [
+            ("map_id", old.map_id as i32, new.map_id as i32),
+            ("map_pid", old.map_pid.into(), new.map_pid.into()),
+            ("hex_x", old.hex_x.into(), new.hex_x.into()),
+            ("hex_y", old.hex_y.into(), new.hex_y.into()),
+        ];
+        for &(name, old, new) in &fields {
+            if old != new {
+                location.push(ParamChange {
+                    name: name.into(),
+                    old,
+                    new,
+                });
+            }
+        }
+    }
+    if let Some((old, new)) = diff.cond {
+        location.push(ParamChange {
+            name: "cond".into(),
+            old: old.into(),
+            new: new.into(),
+        });
+    }
+    vec![
+        DiffGroup {
+            title: "Location",
+            changes: location,
+        },
+        DiffGroup {
+            title: "Stats",
+            changes: diff.stats,
+        },
+        DiffGroup {
+            title: "Skills",
+            changes: diff.skills,
+        },
+        DiffGroup {
+            title: "Perks",
+            changes: diff.perks,
+        },
+        DiffGroup {
+            title: "Other",
+            changes: diff.other,
+        },
+    ]
+    .into_iter()
+    .filter(|group| !group.changes.is_empty())
+    .collect()
+}
+
+impl HistoryPage {
+    fn render(&self, host: &Host) -> Result<String, templates::TemplatesError> {
+        templates::render(
+            "gm_history.html",
+            self,
+            templates::RenderConfig { host: Some(host) },
+        )
+    }
+}
+
+pub async fn history(
+    data: web::Data<AppState>,
+    name: web::Path<String>,
+    query: web::Query<DiffQuery>,
+) -> actix_web::Result<HttpResponse> {
+    let name = name.into_inner();
+    let id = match data.critters_db.client_info(&name) {
+        Ok(info) => info.id,
+        Err(_) => return Ok(HttpResponse::NotFound().body("Unknown client")),
+    };
+    let characters = data.sled_db.characters.clone();
+    let DiffQuery { from, to } = query.into_inner();
+    let page = blocking(move || {
+        let times = characters.times(id)?;
+        let to = to.or_else(|| times.last().copied());
+        let from = from.or_else(|| {
+            let to = to?;
+            times.iter().rev().find(|&&time| time < to).copied()
+        });
+        let get = |time: Option<u64>| -> Result<Option<Snapshot>, SnapshotError> {
+            match time {
+                Some(time) => characters.get(id, time),
+                None => Ok(None),
+            }
+        };
+        let diff = match (get(from)?, get(to)?) {
+            (Some(from), Some(to)) => Some(diff_groups(SnapshotDiff::new(&from, &to))),
+            _ => None,
+        };
+        let compared = diff.is_some();
+        let alerts = characters.alerts(id)?;
+        Ok::<_, SnapshotError>(HistoryPage {
+            name,
+            id,
+            times,
+            from,
+            to,
+            compared,
+            diff: diff.unwrap_or_default(),
+            alerts,
+        })
+    })
+    .await
+    .map_err(super::internal_error)?;
+    let body = page
+        .render(&data.config.host)
+        .map_err(super::internal_error)?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
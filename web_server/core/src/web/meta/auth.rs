@@ -15,11 +15,12 @@ pub async fn auth(
         "Auth: session: {:?}",
         session.get::<String>(DISCORD_CSRF_COOKIE_NAME)
     );*/
+    let oauth = match data.oauth.as_ref() {
+        Some(oauth) => oauth,
+        None => return Ok(login_disabled()),
+    };
     let res = match session.get::<CsrfToken>(DISCORD_CSRF_COOKIE_NAME) {
-        Ok(Some(csrf)) if csrf.secret() == &params.state => data
-            .oauth
-            .as_ref()
-            .expect("OAuth config")
+        Ok(Some(csrf)) if csrf.secret() == &params.state => oauth
             .exchange_code(AuthorizationCode::new(params.code.clone()))
             .request_async(oauth_reqwest::async_http_client)
             .await
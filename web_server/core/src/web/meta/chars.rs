@@ -0,0 +1,71 @@
+use super::*;
+use crate::{
+    config::Host,
+    database::{ownership::characters_of, VersionedError},
+    templates,
+    utils::blocking,
+};
+use fo_defines::CritterParam;
+use fo_defines_fo4rp::param::Param;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+struct MyChars {
+    chars: Vec<MyChar>,
+}
+
+#[derive(Debug, Serialize)]
+struct MyChar {
+    id: u32,
+    /// `None` if save file wasn't found.
+    name: Option<String>,
+    level: Option<i32>,
+}
+
+impl MyChars {
+    fn render(&self, host: &Host) -> Result<String, templates::TemplatesError> {
+        templates::render(
+            "my_chars.html",
+            self,
+            templates::RenderConfig { host: Some(host) },
+        )
+    }
+}
+
+/// Characters of logged in Discord user, redirects to login first.
+pub async fn my_chars(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    session: Session,
+) -> actix_web::Result<HttpResponse> {
+    let user_id = match get_user_id(&session) {
+        Some(user_id) => user_id,
+        None => {
+            session.insert(LOCATION_AFTER_AUTH, req.path())?;
+            return login(data, session).await;
+        }
+    };
+    let page = {
+        let data = data.clone();
+        blocking(move || {
+            let ids = characters_of(&data.sled_db.root, user_id)?;
+            let clients = data.critters_db.list_clients();
+            let chars = ids
+                .into_iter()
+                .map(|id| {
+                    let name = clients.index().by_id(id).map(str::to_owned);
+                    let level = name
+                        .as_ref()
+                        .and_then(|name| clients.client_info(name).ok())
+                        .map(|info| info.param(Param::ST_LEVEL));
+                    MyChar { id, name, level }
+                })
+                .collect();
+            Ok::<_, VersionedError>(MyChars { chars })
+        })
+        .await
+        .map_err(internal_error)?
+    };
+    let body = page.render(&data.config.host).map_err(internal_error)?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
@@ -16,12 +16,15 @@ const DISCORD_API_URL: &str = "https://discordapp.com/api";
 const LOCATION_AFTER_AUTH: &str = "location_after_auth";
 
 mod auth;
+mod chars;
 mod ownership;
 mod rank;
 pub use ownership::restrict_ownership;
+pub(crate) use rank::role_name_to_rank;
 
 pub use self::{
     auth::auth,
+    chars::my_chars,
     rank::{extract_member, get_ranks, get_user_record, Rank},
 };
 
@@ -77,10 +80,11 @@ pub async fn login(
     data: web::Data<AppState>,
     session: Session,
 ) -> actix_web::Result<HttpResponse> {
-    let (authorize_url, csrf_token) = data
-        .oauth
-        .as_ref()
-        .expect("OAuth config")
+    let oauth = match data.oauth.as_ref() {
+        Some(oauth) => oauth,
+        None => return Ok(login_disabled()),
+    };
+    let (authorize_url, csrf_token) = oauth
         .authorize_url(CsrfToken::new_random)
         .add_scope(Scope::new("identify".to_owned()))
         .url();
@@ -96,6 +100,13 @@ pub async fn login(
         .finish())
 }
 
+/// Without Discord config there is nobody to log in with.
+fn login_disabled() -> HttpResponse {
+    HttpResponse::NotFound()
+        .content_type("text/plain; charset=utf-8")
+        .body("Login is disabled: no Discord config")
+}
+
 pub async fn logout(session: Session) -> actix_web::Result<HttpResponse> {
     session.remove(DISCORD_USER_ID_COOKIE_NAME);
     Ok(HttpResponse::Found()
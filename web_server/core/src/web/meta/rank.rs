@@ -4,36 +4,32 @@ use actix_web::web::Data;
 use std::sync::Arc;
 
 pub async fn get_ranks(data: Arc<AppState>, user_id: u64) -> Result<Vec<Rank>, &'static str> {
-    data.mrhandy
-        .as_ref()
-        .expect("Discord config")
-        .with_guild_member(user_id, |guild, member| {
-            let mut ranks = mrhandy::MrHandy::get_roles(
-                guild,
-                member,
-                role_to_rank(&data.config.discord.as_ref().expect("Discord config").roles),
-            );
-            ranks.sort_by_key(|key| std::cmp::Reverse(*key));
-            ranks
-        })
-        .await
+    let member = data.chat.member(user_id).await.ok_or(NOT_A_MEMBER)?;
+    Ok(member_ranks(&data, &member.roles))
 }
 
 pub async fn get_user_record(data: &AppState, user_id: u64) -> Result<UserRecord, &'static str> {
-    data.mrhandy
-        .as_ref()
-        .expect("Discord config")
-        .with_guild_member(user_id, |guild, member| {
-            let mut ranks = mrhandy::MrHandy::get_roles(
-                guild,
-                member,
-                role_to_rank(&data.config.discord.as_ref().expect("Discord config").roles),
-            );
-            ranks.sort_by_key(|key| std::cmp::Reverse(*key));
-            let (name, nick) = mrhandy::MrHandy::get_name_nick(member);
-            UserRecord { name, nick, ranks }
-        })
-        .await
+    let member = data.chat.member(user_id).await.ok_or(NOT_A_MEMBER)?;
+    Ok(UserRecord {
+        ranks: member_ranks(data, &member.roles),
+        name: member.name,
+        nick: member.nick,
+    })
+}
+
+const NOT_A_MEMBER: &str = "Not a member of the main guild";
+
+/// Ranks given by roles, highest first.
+fn member_ranks(data: &AppState, roles: &[String]) -> Vec<Rank> {
+    let mut ranks: Vec<_> = match &data.config.discord {
+        Some(discord) => roles
+            .iter()
+            .map(|role| role_name_to_rank(&discord.roles, role))
+            .collect(),
+        None => vec![],
+    };
+    ranks.sort_by_key(|key| std::cmp::Reverse(*key));
+    ranks
 }
 
 pub struct UserRecord {
@@ -42,28 +38,19 @@ pub struct UserRecord {
     pub ranks: Vec<Rank>,
 }
 
-#[derive(Debug, Clone, Copy, PartialOrd, PartialEq, Ord, Eq)]
-pub enum Rank {
-    Unknown,
-    Player,
-    GameMaster,
-    Developer,
-    Admin,
-}
+pub use mrhandy::commands::Rank;
 
-fn role_to_rank<'b>(config: &'b crate::config::Roles) -> impl 'b + Fn(&mrhandy::Role) -> Rank {
-    move |role| {
-        if role.name == config.player {
-            Rank::Player
-        } else if role.name == config.gamemaster {
-            Rank::GameMaster
-        } else if role.name == config.developer {
-            Rank::Developer
-        } else if role.name == config.admin {
-            Rank::Admin
-        } else {
-            Rank::Unknown
-        }
+pub(crate) fn role_name_to_rank(config: &crate::config::Roles, name: &str) -> Rank {
+    if name == config.player {
+        Rank::Player
+    } else if name == config.gamemaster {
+        Rank::GameMaster
+    } else if name == config.developer {
+        Rank::Developer
+    } else if name == config.admin {
+        Rank::Admin
+    } else {
+        Rank::Unknown
     }
 }
 
@@ -13,7 +13,14 @@ use futures::{
 use std::time::Duration;
 use tokio::sync::Mutex;
 
-use crate::{bridge, config, critters_db::CrittersDb, database::SledDb};
+use crate::{
+    bridge,
+    chat::{ChatBackend, InMemoryChat},
+    config,
+    critters_db::CrittersDb,
+    database::SledDb,
+    events,
+};
 
 #[allow(unused_imports)]
 use std::{collections::BTreeMap, sync::Arc};
@@ -22,11 +29,16 @@ use std::{collections::BTreeMap, sync::Arc};
 use fo_data::FoRetriever;
 
 mod avatar;
+mod bot;
 mod char_action;
+mod dashboard;
 mod dir;
 mod gm;
+mod history;
 mod meta;
+mod owners;
 mod restrict;
+mod search;
 mod stats;
 
 #[cfg(feature = "fo_data")]
@@ -70,6 +82,9 @@ async fn index(
                     format!(
                         "<h1>Menu:</h1><ul>\
                          <li><a href=\"gm/clients\">clients</a></li>\
+                         <li><a href=\"gm/search\">search</a></li>\
+                         <li><a href=\"gm/clusters?days=30\">shared IPs</a></li>\
+                         <li><a href=\"gm/statistics\">statistics</a></li>\
                          <li><a href=\"private/\">private</a></li>\
                          {}\
                          </ul>",
@@ -125,7 +140,7 @@ fn _info(
 pub struct AppState {
     oauth: Option<oauth2::basic::BasicClient>,
     pub(crate) config: config::Config,
-    pub(crate) mrhandy: Option<mrhandy::MrHandy>,
+    pub(crate) chat: Arc<dyn ChatBackend>,
     pub(crate) sled_db: SledDb,
     critters_db: CrittersDb,
     pub(crate) bridge: bridge::Bridge,
@@ -135,6 +150,7 @@ pub struct AppState {
     items: Arc<BTreeMap<u16, fo_proto_format::ProtoItem>>,
     reqwest: reqwest::Client,
     pub(crate) server_status: Mutex<bridge::Status>,
+    pub(crate) event_feed: Option<parking_lot::Mutex<events::EventFeed>>,
 }
 
 impl AppState {
@@ -144,10 +160,17 @@ impl AppState {
         #[cfg(feature = "fo_data")] fo_data: FoRetriever,
         #[cfg(feature = "fo_proto_format")] items: BTreeMap<u16, fo_proto_format::ProtoItem>,
     ) -> Self {
-        let critters_db = CrittersDb::new(config.paths.save_clients.clone());
+        let critters_db = CrittersDb::new(
+            config.paths.save_clients.clone(),
+            config.paths.watch_clients,
+        );
 
         let sled_db = SledDb::new(db);
         let bridge = bridge::Bridge::new();
+        let event_feed = config
+            .events
+            .as_ref()
+            .map(|events| parking_lot::Mutex::new(events::EventFeed::new(events)));
 
         let redirect = config.host.web_url("/meta/auth");
         let oauth = config
@@ -166,7 +189,7 @@ impl AppState {
         Self {
             oauth,
             config,
-            mrhandy: None,
+            chat: Arc::new(InMemoryChat::new()),
             sled_db,
             critters_db,
             bridge,
@@ -175,6 +198,7 @@ impl AppState {
             #[cfg(feature = "fo_proto_format")]
             items: Arc::new(items),
             reqwest,
+            event_feed,
             server_status: Mutex::new(bridge::Status::new()),
         }
     }
@@ -208,18 +232,24 @@ pub fn run(state: AppState) {
 }
 
 async fn run_async(mut state: AppState) {
-    let mut serenity_client = if let Some(discord) = &state.config.discord {
+    let mut discord = if let Some(discord) = &state.config.discord {
         // TODO: Should we keep or join client fut?
         let (mrhandy, serenity_client) =
             mrhandy::init(&discord.bot.token, discord.main_guild_id).await;
-        state.mrhandy = Some(mrhandy);
-        Some(serenity_client)
+        state.chat = Arc::new(mrhandy.clone());
+        Some((mrhandy, serenity_client))
     } else {
+        println!("No Discord config, chat is kept in memory");
         None
     };
 
     let state = web::Data::new(state);
 
+    if let Some((mrhandy, _)) = &discord {
+        let backend = bot::BotBackend::new(state.clone());
+        mrhandy.set_commands_backend(Arc::new(backend)).await;
+    }
+
     let bridge_server = bridge::Bridge::start(state.clone().into_inner()).await;
 
     let web_server = HttpServer::new({
@@ -244,12 +274,35 @@ async fn run_async(mut state: AppState) {
                         .service(web::resource("/logout").route(web::get().to(meta::logout)))
                         .service(web::resource("/auth").route(web::get().to(meta::auth))),
                 )
+                .service(web::resource("/chars").route(web::get().to(meta::my_chars)))
                 .service(
                     web::scope("/gm")
                         .wrap(restrict(meta::restrict_gm))
                         .service(web::resource("/clients").route(web::get().to(gm::clients)))
+                        .service(web::resource("/search").route(web::get().to(search::search)))
+                        .service(web::resource("/clusters").route(web::get().to(gm::clusters)))
+                        .service(
+                            web::resource("/clusters.json").route(web::get().to(gm::clusters_json)),
+                        )
+                        .service(
+                            web::resource("/statistics")
+                                .route(web::get().to(dashboard::statistics)),
+                        )
+                        .service(
+                            web::resource("/statistics.json")
+                                .route(web::get().to(dashboard::statistics_json)),
+                        )
                         .service(
                             web::resource("/client/{client}").route(web::get().to(stats::gm_stats)),
+                        )
+                        .service(
+                            web::resource("/client/{client}/history")
+                                .route(web::get().to(history::history)),
+                        )
+                        .service(
+                            web::resource("/char/{id}/ownership")
+                                .route(web::get().to(owners::ownership))
+                                .route(web::post().to(owners::transfer)),
                         ),
                 )
                 .service(
@@ -263,6 +316,11 @@ async fn run_async(mut state: AppState) {
                                         .route(web::post().to(avatar::upload)),
                                 ),
                         )
+                        .service(
+                            web::resource("/stats")
+                                .wrap(restrict(meta::restrict_ownership))
+                                .route(web::get().to(stats::char_stats)),
+                        )
                         .service(
                             web::scope("/action")
                                 .wrap(restrict(meta::restrict_ownership))
@@ -352,15 +410,20 @@ async fn run_async(mut state: AppState) {
         web_server.map_err(RuntimeError::Io).boxed(),
         file_server.map_err(RuntimeError::Io).boxed(),
     ];
-    if let Some(serenity_client) = serenity_client.as_mut() {
+    if let Some(snapshots) = state.config.snapshots.clone() {
+        futs.push(snapshotter(state.clone(), snapshots).boxed());
+    }
+    if let Some((_, serenity_client)) = discord.as_mut() {
         futs.push(
             serenity_client
                 .start()
                 .map_err(RuntimeError::Serenity)
                 .boxed(),
         );
-        let status_updater = status_updater(state);
-        futs.push(status_updater.boxed());
+    }
+    futs.push(status_updater(state.clone()).boxed());
+    if let Some(events) = state.config.events.clone() {
+        futs.push(event_sender(state, events).boxed());
     }
     let (res, _, _) = futures::future::select_all(futs).await;
     println!("Stopping... Result: {:?}", res);
@@ -371,12 +434,69 @@ async fn status_updater(state: web::Data<AppState>) -> Result<(), RuntimeError>
     loop {
         interval.tick().await;
         let mut server_status = state.server_status.lock().await;
-        let mrhandy = state.mrhandy.as_ref().expect("MrHandy");
-        server_status.new_status(mrhandy).await;
+        server_status.new_status(&*state.chat).await;
         //.map_err(RuntimeError::Serenity)?;
     }
 }
 
+async fn event_sender(
+    state: web::Data<AppState>,
+    config: config::Events,
+) -> Result<(), RuntimeError> {
+    let mut interval = tokio::time::interval(Duration::from_secs(config.batch_seconds.max(1)));
+    loop {
+        interval.tick().await;
+        let messages = match &state.event_feed {
+            Some(feed) => feed.lock().flush(std::time::Instant::now()),
+            None => continue,
+        };
+        events::deliver(&*state.chat, messages).await;
+    }
+}
+
+async fn snapshotter(
+    state: web::Data<AppState>,
+    config: config::Snapshots,
+) -> Result<(), RuntimeError> {
+    use crate::database::{
+        snapshots::{AlertRule, ParamRate},
+        statistics::unix_time,
+    };
+    use fo_defines_fo4rp::param::Param;
+
+    let mut rules: Vec<Box<dyn AlertRule>> = vec![];
+    if let Some(per_hour) = config.exp_per_hour {
+        rules.push(Box::new(ParamRate {
+            param: Param::ST_EXPERIENCE,
+            per_hour,
+        }));
+    }
+    let rules = Arc::new(rules);
+    let mut interval = tokio::time::interval(Duration::from_secs(config.interval.max(1) * 60));
+    loop {
+        interval.tick().await;
+        let state = state.clone();
+        let rules = Arc::clone(&rules);
+        let res = crate::utils::blocking(move || {
+            let clients = state.critters_db.list_clients();
+            let infos = clients
+                .clients()
+                .values()
+                .filter_map(|record| record.info.as_deref());
+            state.sled_db.characters.record(unix_time(), infos, &rules)
+        })
+        .await;
+        match res {
+            Ok(recorded) => {
+                for alert in recorded.alerts {
+                    log::warn!("Character {} alert: {}", alert.id, alert.message);
+                }
+            }
+            Err(err) => eprintln!("Can't snapshot characters: {:?}", err),
+        }
+    }
+}
+
 #[derive(Debug)]
 enum RuntimeError {
     Io(std::io::Error),
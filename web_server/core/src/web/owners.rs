@@ -0,0 +1,103 @@
+use super::{meta, web, AppState, HttpResponse};
+use crate::{
+    config::Host,
+    database::{
+        ownership::{get_ownership, ownership_log, transfer_ownership, OwnershipChange},
+        VersionedError,
+    },
+    templates,
+    utils::blocking,
+};
+use actix_session::Session;
+use actix_web::http::header;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct TransferForm {
+    /// Discord id of the new owner, empty to revoke ownership.
+    to: String,
+    reason: String,
+}
+
+#[derive(Debug, Serialize)]
+struct OwnershipPage {
+    id: u32,
+    name: Option<String>,
+    owner: Option<u64>,
+    log: Vec<OwnershipChange>,
+}
+
+impl OwnershipPage {
+    fn render(&self, host: &Host) -> Result<String, templates::TemplatesError> {
+        templates::render(
+            "gm_ownership.html",
+            self,
+            templates::RenderConfig { host: Some(host) },
+        )
+    }
+}
+
+pub async fn ownership(
+    data: web::Data<AppState>,
+    id: web::Path<u32>,
+) -> actix_web::Result<HttpResponse> {
+    let id = id.into_inner();
+    let db = data.clone();
+    let page = blocking(move || {
+        let root = &db.sled_db.root;
+        Ok::<_, VersionedError>(OwnershipPage {
+            id,
+            name: db
+                .critters_db
+                .list_clients()
+                .index()
+                .by_id(id)
+                .map(str::to_owned),
+            owner: get_ownership(root, id)?,
+            log: ownership_log(root, Some(id))?,
+        })
+    })
+    .await
+    .map_err(super::internal_error)?;
+    let body = page
+        .render(&data.config.host)
+        .map_err(super::internal_error)?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
+/// Transfers or revokes ownership, GM and reason are written to the audit log.
+pub async fn transfer(
+    data: web::Data<AppState>,
+    id: web::Path<u32>,
+    form: web::Form<TransferForm>,
+    session: Session,
+) -> actix_web::Result<HttpResponse> {
+    let id = id.into_inner();
+    let gm_id = meta::get_user_id(&session).ok_or_else(meta::access_denied("Not logged in"))?;
+    let TransferForm { to, reason } = form.into_inner();
+    let to = match to.trim() {
+        "" => None,
+        to => match to.parse() {
+            Ok(to) => Some(to),
+            Err(_) => return Ok(HttpResponse::BadRequest().body("Invalid Discord id")),
+        },
+    };
+    let reason = reason.trim().to_owned();
+    if reason.is_empty() {
+        return Ok(HttpResponse::BadRequest().body("Reason is required"));
+    }
+    let root = data.sled_db.root.clone();
+    let from = blocking(move || transfer_ownership(&root, id, to, gm_id, &reason))
+        .await
+        .map_err(super::internal_error)?;
+    log::warn!(
+        "Ownership of {} changed by GM {}: {:?} -> {:?}",
+        id,
+        gm_id,
+        from,
+        to
+    );
+    Ok(HttpResponse::SeeOther()
+        .append_header((header::LOCATION, "ownership"))
+        .finish())
+}
@@ -0,0 +1,176 @@
+use super::{web, AppState, HttpResponse};
+use crate::{config::Host, templates};
+use clients_db::{CritterInfo, ParamFilter, Query};
+use fo_defines::CritterParam;
+use fo_defines_fo4rp::param::Param;
+use serde::{Deserialize, Serialize};
+
+/// Search form, fields are kept as strings so empty inputs are just ignored.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct SearchForm {
+    #[serde(default)]
+    id: String,
+    #[serde(default)]
+    map_pid: String,
+    #[serde(default)]
+    ip: String,
+    /// Comma-separated param filters, i.e. `ST_LEVEL>=10, ST_ACCESS_LEVEL>0`.
+    #[serde(default)]
+    params: String,
+    #[serde(default)]
+    format: Option<String>,
+}
+
+impl SearchForm {
+    fn query(&self) -> Result<Query, Vec<String>> {
+        fn field<T: std::str::FromStr>(
+            name: &str,
+            value: &str,
+            errors: &mut Vec<String>,
+        ) -> Option<T> {
+            let value = value.trim();
+            if value.is_empty() {
+                return None;
+            }
+            value
+                .parse()
+                .map_err(|_| errors.push(format!("Invalid {}: {:?}", name, value)))
+                .ok()
+        }
+        let mut errors = vec![];
+        let mut query = Query {
+            id: field("id", &self.id, &mut errors),
+            map_pid: field("map_pid", &self.map_pid, &mut errors),
+            ip: field("ip", &self.ip, &mut errors),
+            params: vec![],
+        };
+        for filter in self.params.split(',').filter(|s| !s.trim().is_empty()) {
+            match filter.parse::<ParamFilter>() {
+                Ok(filter) => query.params.push(filter),
+                Err(err) => errors.push(format!("Invalid param filter: {:?}", err)),
+            }
+        }
+        if errors.is_empty() {
+            Ok(query)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SearchRow<'a> {
+    name: &'a str,
+    id: u32,
+    lvl: i32,
+    hp: i32,
+    map_id: u32,
+    map_pid: u16,
+    cond: &'static str,
+    access_level: i32,
+    ip: String,
+}
+
+impl<'a> SearchRow<'a> {
+    fn new(name: &'a str, info: &CritterInfo) -> Self {
+        SearchRow {
+            name,
+            id: info.id,
+            lvl: info.param(Param::ST_LEVEL),
+            hp: info.param(Param::ST_CURRENT_HP),
+            map_id: info.map_id,
+            map_pid: info.map_pid,
+            cond: info.cond(),
+            access_level: info.param(Param::ST_ACCESS_LEVEL),
+            ip: info
+                .ip
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SearchPage<'a> {
+    form: &'a SearchForm,
+    errors: Vec<String>,
+    rows: Vec<SearchRow<'a>>,
+}
+
+impl<'a> SearchPage<'a> {
+    fn render(&self, host: &Host) -> Result<String, templates::TemplatesError> {
+        templates::render(
+            "gm_search.html",
+            self,
+            templates::RenderConfig { host: Some(host) },
+        )
+    }
+}
+
+fn to_csv(rows: &[SearchRow]) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(vec![]);
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|err| csv::Error::from(err.into_error()))
+}
+
+#[derive(Debug)]
+enum SearchError {
+    Csv(csv::Error),
+    Templates(templates::TemplatesError),
+}
+
+enum Output {
+    Html(String),
+    Csv(Vec<u8>),
+}
+
+pub async fn search(
+    data: web::Data<AppState>,
+    form: web::Query<SearchForm>,
+) -> actix_web::Result<HttpResponse> {
+    let form = form.into_inner();
+    let output = web::block(move || {
+        let clients = data.critters_db.list_clients();
+        let (rows, errors) = match form.query() {
+            Ok(query) => (
+                clients
+                    .query(&query)
+                    .into_iter()
+                    .map(|(name, info)| SearchRow::new(name, info))
+                    .collect(),
+                vec![],
+            ),
+            Err(errors) => (vec![], errors),
+        };
+        if form.format.as_deref() == Some("csv") {
+            return to_csv(&rows).map(Output::Csv).map_err(SearchError::Csv);
+        }
+        let page = SearchPage {
+            form: &form,
+            errors,
+            rows,
+        };
+        page.render(&data.config.host)
+            .map(Output::Html)
+            .map_err(SearchError::Templates)
+    })
+    .await?
+    .map_err(super::internal_error)?;
+
+    Ok(match output {
+        Output::Html(body) => HttpResponse::Ok().content_type("text/html").body(body),
+        Output::Csv(csv) => HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .append_header((
+                actix_http::header::CONTENT_DISPOSITION,
+                "attachment; filename=\"clients.csv\"",
+            ))
+            .body(csv),
+    })
+}
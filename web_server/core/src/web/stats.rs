@@ -41,6 +41,31 @@ pub async fn gm_stats(
     }
 }
 
+/// Character sheet for the owner, access is checked by `restrict_ownership`.
+pub async fn char_stats(
+    data: web::Data<AppState>,
+    id: web::Path<u32>,
+) -> actix_web::Result<HttpResponse> {
+    let id = id.into_inner();
+    let db = data.clone();
+    let cr_info = web::block(move || {
+        let clients = db.critters_db.list_clients();
+        let name = clients.index().by_id(id);
+        name.map(|name| clients.client_info(name)).transpose()
+    })
+    .await
+    .map_err(super::internal_error)?
+    .map_err(super::internal_error)?;
+    let cr_info = match cr_info {
+        Some(cr_info) => cr_info,
+        None => return Ok(HttpResponse::NotFound().body("Unknown character")),
+    };
+    let body = Stats::new(&cr_info)
+        .render(&data.config.host)
+        .map_err(super::internal_error)?;
+    Ok(HttpResponse::Ok().content_type("text/html").body(body))
+}
+
 #[derive(Debug, Serialize)]
 struct Stats<'a> {
     nickname: &'a str,